@@ -0,0 +1,475 @@
+// Events emitted to the frontend after data changes, so panels can refresh without polling.
+// Every note mutation goes through these helpers, which also keep the search index and the backlink
+// index in sync, stamp the `created_at`/`updated_at` metadata of saved notes and count the words they add.
+//
+// vault://note-saved        { vault, title }   a note was created or its content was written
+// vault://note-deleted      { vault, title }   a note was removed, in the app or outside of it
+// vault://note-created      { vault, title }   a note was created outside the app (watched vaults only)
+// vault://note-modified     { vault, title }   a note was changed outside the app (watched vaults only)
+// vault://metadata-changed  { vault, title }   per-note metadata (pins, locks, review stats) changed;
+//                                              `title` is null for vault-wide metadata such as bookmarks
+// vault://index-updated     { vault, titles }  several notes changed at once (bulk edits, undo, generated
+//                                              notes); search, graph and file tree views should reload
+// vault://lint-diagnostics { vault, title, diagnostics }
+//                                              lint results of a saved note, when the vault has lint rules;
+//                                              an empty list means the note is clean
+// vault://publish-status    { vault, state, status, error }
+//                                              a publish run `started`, `finished` or `failed`; `status` is
+//                                              the report of the finished run, which is also kept as the
+//                                              vault's last publish report
+// vault://notes-batch       { vault, offset, total, titles }
+//                                              one batch of the note list streamed by `stream_notes`;
+//                                              the last batch has `offset + titles.len() == total`
+// vault://index-progress    { vault, indexed, total }
+//                                              background indexing of an opened vault committed a batch:
+//                                              `indexed` of the `total` notes changed since the last run
+// vault://index-complete    { vault, indexed, removed, error }
+//                                              background indexing finished, or failed with `error`;
+//                                              search and backlinks are current
+// vault://integrity-report  { vault, orphaned, missing, error }
+//                                              the scan of an opened vault that follows its indexing:
+//                                              notes deleted outside the app whose stored data was dropped,
+//                                              and notes created outside it the stores were missing; both
+//                                              lists are empty for a vault in order
+// vault://link-check-progress { vault, checked, total }
+//                                              the background link check tested another external URL
+// vault://link-check-complete { vault, report, error }
+//                                              the link check finished with its report, or failed with `error`
+// settings-changed          Settings           the application settings changed
+//
+// Errors and notices in payloads are `Message`s: a key and parameters the frontend translates, with the
+// English text as a fallback.
+use serde::Serialize;
+use std::io::{self, ErrorKind};
+use std::thread;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::feature::{backlinks, git_sync, integrity, lint::{self, Diagnostic}, people, view_state, writing_stats};
+use crate::feature::link_rot::{self, LinkChecks, LinkReport, UrlChecker};
+use crate::feature::publish::{self, PublishScheduler, PublishStatus, PublishTrigger};
+use crate::feature::refactor::MoveReport;
+use crate::feature::recovery::SafeMode;
+use crate::feature::search::{IndexChanges, SearchIndexes};
+use crate::feature::settings::{self, Settings};
+use crate::feature::watcher::{ChangeKind, NoteChange, VaultWatchers};
+use crate::feature::write_lock::WriteLocks;
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::message::Message;
+
+pub const NOTE_SAVED: &str = "vault://note-saved";
+pub const NOTE_DELETED: &str = "vault://note-deleted";
+pub const NOTE_CREATED: &str = "vault://note-created";
+pub const NOTE_MODIFIED: &str = "vault://note-modified";
+pub const METADATA_CHANGED: &str = "vault://metadata-changed";
+pub const INDEX_UPDATED: &str = "vault://index-updated";
+pub const NOTES_BATCH: &str = "vault://notes-batch";
+pub const LINT_DIAGNOSTICS: &str = "vault://lint-diagnostics";
+pub const PUBLISH_STATUS: &str = "vault://publish-status";
+pub const INDEX_PROGRESS: &str = "vault://index-progress";
+pub const INDEX_COMPLETE: &str = "vault://index-complete";
+pub const INTEGRITY_REPORT: &str = "vault://integrity-report";
+pub const LINK_CHECK_PROGRESS: &str = "vault://link-check-progress";
+pub const LINK_CHECK_COMPLETE: &str = "vault://link-check-complete";
+
+pub const NOTE_BATCH_SIZE: usize = 500;
+// Notes indexed per commit in the background; search sees each batch as it lands
+pub const INDEX_BATCH_SIZE: usize = 200;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteEvent {
+    pub vault: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetadataEvent {
+    pub vault: String,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexEvent {
+    pub vault: String,
+    pub titles: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LintEvent {
+    pub vault: String,
+    pub title: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PublishState {
+    Started,
+    Finished,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishEvent {
+    pub vault: String,
+    pub state: PublishState,
+    pub status: Option<PublishStatus>,
+    pub error: Option<Message>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotesBatch {
+    pub vault: String,
+    pub offset: usize,
+    pub total: usize,
+    pub titles: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexProgress {
+    pub vault: String,
+    pub indexed: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexComplete {
+    pub vault: String,
+    pub indexed: usize,
+    pub removed: usize,
+    pub error: Option<Message>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityEvent {
+    pub vault: String,
+    pub orphaned: Vec<String>,
+    pub missing: Vec<String>,
+    pub error: Option<Message>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkCheckProgress {
+    pub vault: String,
+    pub checked: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkCheckComplete {
+    pub vault: String,
+    pub report: Option<LinkReport>,
+    pub error: Option<Message>,
+}
+
+fn emit<S: Serialize + Clone>(app: &AppHandle, event: &str, payload: S) {
+    if let Err(e) = app.emit(event, payload) {
+        println!("❌ Failed to emit {}: {}", event, e);
+    }
+}
+
+// Keeps the file watcher from reporting the app's own writes as external changes.
+fn record_writes(app: &AppHandle, vault: &Vault, paths: &[String]) {
+    if let Some(watchers) = app.try_state::<VaultWatchers>() {
+        for path in paths {
+            watchers.record_write(vault, path);
+        }
+    }
+}
+
+// Re-indexes changed notes; notes that no longer exist are removed from the index.
+fn sync_search(app: &AppHandle, vault: &Vault, paths: &[String]) {
+    let (Some(safe_mode), Some(indexes)) = (app.try_state::<SafeMode>(), app.try_state::<SearchIndexes>()) else {
+        return;
+    };
+    if paths.is_empty() || safe_mode.is_safe_mode(vault) {
+        return;
+    }
+    if let Err(e) = safe_mode.search(&indexes, vault).and_then(|search| search.sync(vault, paths)) {
+        println!("❌ Failed to update the search index: {}", e);
+    }
+}
+
+// Refreshes the backlinks contributed by changed notes and the people index, and, for saves, records their save time and the
+// words written.
+fn update_metadata(app: &AppHandle, vault: &Vault, paths: &[String], saved: bool) {
+    let Some(safe_mode) = app.try_state::<SafeMode>() else {
+        return;
+    };
+    if paths.is_empty() || safe_mode.is_safe_mode(vault) {
+        return;
+    }
+    let result = safe_mode.metadata(vault).and_then(|store| {
+        for path in paths {
+            if saved {
+                let metadata = store.touch(path)?;
+                let created = metadata.created_at == metadata.updated_at;
+                writing_stats::record_save(vault, &store, path, created, writing_stats::today())?;
+            }
+            backlinks::index_links(vault, &store, path)?;
+            people::index_person(vault, &store, path)?;
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        println!("❌ Failed to update the metadata of {}: {}", paths.join(", "), e);
+    }
+}
+
+// Lints a saved note against the rules of its vault, if it has any.
+fn lint_saved(app: &AppHandle, vault: &Vault, title: &str) {
+    let result = lint::load_config(vault).and_then(|config| {
+        if !config.is_enabled() {
+            return Ok(None);
+        }
+        Ok(Some(lint::lint_note(&config, title, &Note::read_note(vault, title)?)))
+    });
+    match result {
+        Ok(Some(diagnostics)) => {
+            emit(app, LINT_DIAGNOSTICS, LintEvent { vault: vault.name.clone(), title: title.to_string(), diagnostics });
+        }
+        Ok(None) => {}
+        Err(e) => println!("❌ Failed to lint {}: {}", title, e),
+    }
+}
+
+// Commits a saved note when the vault syncs through git with automatic commits. Like the git commands,
+// the commit runs on a snapshot of the vault, so it waits for the save to release its write lock and never
+// runs alongside another commit or a pull.
+fn commit_saved(app: &AppHandle, vault: &Vault, title: &str) {
+    if !git_sync::is_repository(vault) {
+        return;
+    }
+    let (app, vault, title) = (app.clone(), vault.clone(), title.to_string());
+    thread::spawn(move || {
+        let locks = app.state::<WriteLocks>();
+        let _snapshot = locks.snapshot(&vault);
+        if let Err(e) = git_sync::commit_saved(&vault, &title) {
+            println!("❌ Failed to commit {}: {}", title, e);
+        }
+    });
+}
+
+// Starts a publish run in the background once the vault has seen the configured number of saves.
+fn publish_saved(app: &AppHandle, vault: &Vault) {
+    let Some(scheduler) = app.try_state::<PublishScheduler>() else {
+        return;
+    };
+    let every = match publish::load_config(vault) {
+        Ok(config) => config.auto.every_saves,
+        Err(e) => {
+            println!("❌ Failed to load the publish settings: {}", e);
+            return;
+        }
+    };
+    if every.is_some_and(|every| scheduler.record_save(vault, every)) {
+        let (app, vault) = (app.clone(), vault.clone());
+        // The run waits for the save that triggered it to release its write lock
+        thread::spawn(move || {
+            if let Err(e) = publish(&app, &vault, PublishTrigger::Saves) {
+                println!("❌ Automatic publish failed: {}", e);
+            }
+        });
+    }
+}
+
+// Publishes the vault on a snapshot of its notes, reporting the run as publish status events.
+pub fn publish(app: &AppHandle, vault: &Vault, trigger: PublishTrigger) -> io::Result<PublishStatus> {
+    let scheduler = app.state::<PublishScheduler>();
+    if !scheduler.start_run(vault) {
+        return Err(Message::new("publish.already_running", "The vault is already being published").error(ErrorKind::WouldBlock));
+    }
+    emit(app, PUBLISH_STATUS, PublishEvent { vault: vault.name.clone(), state: PublishState::Started, status: None, error: None });
+
+    let locks = app.state::<WriteLocks>();
+    let result = {
+        let _snapshot = locks.snapshot(vault);
+        publish::publish(vault, trigger)
+    };
+    scheduler.finish_run(vault);
+
+    let event = match &result {
+        Ok(status) => PublishEvent { vault: vault.name.clone(), state: PublishState::Finished, status: Some(status.clone()), error: None },
+        // A failed pipeline still leaves its report behind
+        Err(e) => PublishEvent {
+            vault: vault.name.clone(),
+            state: PublishState::Failed,
+            status: publish::load_status(vault).ok().flatten().filter(|status| !status.success),
+            error: Some(Message::from(e)),
+        },
+    };
+    emit(app, PUBLISH_STATUS, event);
+    result
+}
+
+// Brings the search index and backlinks of a vault up to date: only notes changed since they were last
+// indexed are read, in batches committed one at a time. Returns the changes it indexed.
+fn index_vault(app: &AppHandle, vault: &Vault) -> io::Result<IndexChanges> {
+    let safe_mode = app.state::<SafeMode>();
+    let indexes = app.state::<SearchIndexes>();
+    if safe_mode.is_safe_mode(vault) {
+        return Ok(IndexChanges::default());
+    }
+    let search = safe_mode.search(&indexes, vault)?;
+    let changes = search.changes(vault)?;
+    let total = changes.changed.len();
+    for (number, batch) in changes.changed.chunks(INDEX_BATCH_SIZE).enumerate() {
+        search.update(vault, batch, &[])?;
+        // The store is opened per batch, so commands are not kept from it for the whole run
+        let store = safe_mode.metadata(vault)?;
+        for path in batch {
+            backlinks::index_links(vault, &store, path)?;
+        }
+        drop(store);
+        let indexed = number * INDEX_BATCH_SIZE + batch.len();
+        emit(app, INDEX_PROGRESS, IndexProgress { vault: vault.name.clone(), indexed, total });
+    }
+    if !changes.removed.is_empty() {
+        search.update(vault, &[], &changes.removed)?;
+        let store = safe_mode.metadata(vault)?;
+        for path in &changes.removed {
+            backlinks::index_links(vault, &store, path)?;
+        }
+    }
+    Ok(changes)
+}
+
+// Reconciles the metadata store of an opened vault with its notes, and reports what the stores, search
+// index included, held on to or were missing.
+fn check_integrity(app: &AppHandle, vault: &Vault, changes: &IndexChanges) {
+    let safe_mode = app.state::<SafeMode>();
+    if safe_mode.is_safe_mode(vault) {
+        return;
+    }
+    let event = match safe_mode.metadata(vault).and_then(|store| integrity::reconcile(vault, &store)) {
+        Ok(mut report) => {
+            report.add_search_changes(changes);
+            IntegrityEvent { vault: vault.name.clone(), orphaned: report.orphaned, missing: report.missing, error: None }
+        }
+        Err(e) => {
+            println!("❌ Failed to check the integrity of {}: {}", vault.name, e);
+            let (orphaned, missing) = (changes.removed.clone(), changes.added.clone());
+            IntegrityEvent { vault: vault.name.clone(), orphaned, missing, error: Some(Message::from(e)) }
+        }
+    };
+    emit(app, INTEGRITY_REPORT, event);
+}
+
+// Starts indexing a freshly opened vault in the background, so opening it does not wait for the index,
+// then checks the integrity of its stores. Searches made meanwhile use the index as far as it got.
+pub fn index_in_background(app: &AppHandle, vault: &Vault) {
+    let Some(indexes) = app.try_state::<SearchIndexes>() else {
+        return;
+    };
+    if !indexes.start_indexing(vault) {
+        return;
+    }
+    let (app, vault) = (app.clone(), vault.clone());
+    thread::spawn(move || {
+        let result = index_vault(&app, &vault);
+        app.state::<SearchIndexes>().finish_indexing(&vault);
+        let (event, changes) = match result {
+            Ok(changes) => {
+                let (indexed, removed) = (changes.changed.len(), changes.removed.len());
+                (IndexComplete { vault: vault.name.clone(), indexed, removed, error: None }, changes)
+            }
+            Err(e) => {
+                println!("❌ Failed to index {}: {}", vault.name, e);
+                let event = IndexComplete { vault: vault.name.clone(), indexed: 0, removed: 0, error: Some(Message::from(e)) };
+                (event, IndexChanges::default())
+            }
+        };
+        emit(&app, INDEX_COMPLETE, event);
+        check_integrity(&app, &vault, &changes);
+    });
+}
+
+// Checks the external links of a vault in the background, reporting progress and the final report as
+// events. Fails when the links of the vault are already being checked.
+pub fn check_links_in_background(app: &AppHandle, vault: &Vault, force: bool) -> io::Result<()> {
+    if !app.state::<LinkChecks>().start(vault) {
+        return Err(Message::new("link_check.already_running", "The links of the vault are already being checked").error(ErrorKind::WouldBlock));
+    }
+    let (app, vault) = (app.clone(), vault.clone());
+    thread::spawn(move || {
+        let mut checker = UrlChecker::default();
+        let result = link_rot::check_links(&vault, force, |url| checker.check(url), |checked, total| {
+            emit(&app, LINK_CHECK_PROGRESS, LinkCheckProgress { vault: vault.name.clone(), checked, total });
+        });
+        app.state::<LinkChecks>().finish(&vault);
+        let event = match result {
+            Ok(report) => LinkCheckComplete { vault: vault.name.clone(), report: Some(report), error: None },
+            Err(e) => {
+                println!("❌ Failed to check the links of {}: {}", vault.name, e);
+                LinkCheckComplete { vault: vault.name.clone(), report: None, error: Some(Message::from(e)) }
+            }
+        };
+        emit(&app, LINK_CHECK_COMPLETE, event);
+    });
+    Ok(())
+}
+
+pub fn note_saved(app: &AppHandle, vault: &Vault, title: &str) {
+    record_writes(app, vault, &[title.to_string()]);
+    update_metadata(app, vault, &[title.to_string()], true);
+    sync_search(app, vault, &[title.to_string()]);
+    emit(app, NOTE_SAVED, NoteEvent { vault: vault.name.clone(), title: title.to_string() });
+    lint_saved(app, vault, title);
+    commit_saved(app, vault, title);
+    publish_saved(app, vault);
+}
+
+pub fn note_deleted(app: &AppHandle, vault: &Vault, title: &str) {
+    record_writes(app, vault, &[title.to_string()]);
+    update_metadata(app, vault, &[title.to_string()], false);
+    sync_search(app, vault, &[title.to_string()]);
+    emit(app, NOTE_DELETED, NoteEvent { vault: vault.name.clone(), title: title.to_string() });
+}
+
+pub fn metadata_changed(app: &AppHandle, vault: &Vault, title: Option<&str>) {
+    emit(app, METADATA_CHANGED, MetadataEvent { vault: vault.name.clone(), title: title.map(str::to_string) });
+}
+
+pub fn index_updated(app: &AppHandle, vault: &Vault, titles: Vec<String>) {
+    record_writes(app, vault, &titles);
+    update_metadata(app, vault, &titles, false);
+    sync_search(app, vault, &titles);
+    emit(app, INDEX_UPDATED, IndexEvent { vault: vault.name.clone(), titles });
+}
+
+// Carries the metadata and saved views of a moved or renamed note over to its new path, then reports the changed notes.
+pub fn note_moved(app: &AppHandle, vault: &Vault, report: &MoveReport) {
+    if let Some(safe_mode) = app.try_state::<SafeMode>().filter(|safe_mode| !safe_mode.is_safe_mode(vault)) {
+        let result = safe_mode.metadata(vault).and_then(|store| {
+            store.rename_metadata(&report.from, &report.to)?;
+            view_state::rename(&store, &report.from, &report.to)
+        });
+        if let Err(e) = result {
+            println!("❌ Failed to move the metadata of {}: {}", report.from, e);
+        }
+    }
+    let mut changed = vec![report.from.clone(), report.to.clone()];
+    changed.extend(report.updated.iter().cloned());
+    index_updated(app, vault, changed);
+}
+
+// Brings the indexes up to date with a note changed outside the app and tells the frontend.
+pub fn external_change(app: &AppHandle, vault: &Vault, change: &NoteChange) {
+    let paths = [change.path.clone()];
+    let event = match change.kind {
+        ChangeKind::Created => NOTE_CREATED,
+        ChangeKind::Modified => NOTE_MODIFIED,
+        ChangeKind::Deleted => NOTE_DELETED,
+    };
+    update_metadata(app, vault, &paths, change.kind != ChangeKind::Deleted);
+    sync_search(app, vault, &paths);
+    emit(app, event, NoteEvent { vault: vault.name.clone(), title: change.path.clone() });
+}
+
+pub fn notes_batch(app: &AppHandle, vault: &Vault, offset: usize, total: usize, titles: &[String]) {
+    let batch = NotesBatch { vault: vault.name.clone(), offset, total, titles: titles.to_vec() };
+    emit(app, NOTES_BATCH, batch);
+}
+
+pub fn settings_changed(app: &AppHandle, settings: &Settings) {
+    emit(app, settings::SETTINGS_CHANGED_EVENT, settings.clone());
+}
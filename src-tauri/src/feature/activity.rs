@@ -0,0 +1,81 @@
+// Activity log of automated changes made to a vault (archival moves, ...)
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use std::fs::OpenOptions;
+use std::io::{self, Error, ErrorKind, Write};
+
+use crate::storage::vault::Vault;
+use crate::utils::file_operations;
+
+const LOGS_DIR: &str = ".logs";
+const ACTIVITY_LOG: &str = "activity.jsonl";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    pub at: String,
+    pub action: String,
+    // Vault-relative path of the note the action applied to
+    pub path: String,
+    pub detail: String,
+}
+
+fn log_path(vault: &Vault) -> String {
+    format!("{}/{}/{}", vault.path, LOGS_DIR, ACTIVITY_LOG)
+}
+
+// Appends an entry to the activity log of a vault.
+pub fn record(vault: &Vault, action: &str, path: &str, detail: &str) -> io::Result<ActivityEntry> {
+    let entry = ActivityEntry {
+        at: Utc::now().to_rfc3339(),
+        action: action.to_string(),
+        path: path.to_string(),
+        detail: detail.to_string(),
+    };
+    file_operations::create_directory(&format!("{}/{}", vault.path, LOGS_DIR))?;
+    let line = serde_json::to_string(&entry).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_operations::resolve_path(&log_path(vault)))?;
+    writeln!(file, "{}", line)?;
+    Ok(entry)
+}
+
+// Reads the activity log, newest first; unreadable lines are skipped.
+pub fn read_log(vault: &Vault, limit: Option<usize>) -> io::Result<Vec<ActivityEntry>> {
+    let content = match file_operations::read_from_file(&log_path(vault)) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let entries = content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_record_and_read_log() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        assert!(read_log(&vault, None).unwrap().is_empty());
+
+        record(&vault, "archive", "Meeting", "first").unwrap();
+        record(&vault, "archive", "Standup", "second").unwrap();
+        let entries = read_log(&vault, None).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, "Standup");
+        assert_eq!(read_log(&vault, Some(1)).unwrap()[0].detail, "second");
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,72 @@
+// The vault open in the app. Note commands take an optional vault and fall back to the open one, so the
+// frontend does not have to send the whole vault along with every call.
+use std::io::{self, ErrorKind};
+use std::sync::Mutex;
+
+use crate::storage::vault::Vault;
+use crate::utils::message::Message;
+
+#[derive(Default)]
+pub struct AppState {
+    vault: Mutex<Option<Vault>>,
+}
+
+impl AppState {
+    // Makes a vault the open one, replacing the vault that was open before.
+    pub fn open(&self, vault: Vault) {
+        *self.vault.lock().unwrap() = Some(vault);
+    }
+
+    // Closes the open vault and returns it.
+    pub fn close(&self) -> Option<Vault> {
+        self.vault.lock().unwrap().take()
+    }
+
+    pub fn current(&self) -> Option<Vault> {
+        self.vault.lock().unwrap().clone()
+    }
+
+    // Returns the vault a command was given, or the open vault when it was given none.
+    pub fn vault(&self, vault: Option<Vault>) -> io::Result<Vault> {
+        vault
+            .or_else(|| self.current())
+            .ok_or_else(|| Message::new("vault.none_open", "No vault is open").error(ErrorKind::NotFound))
+    }
+
+    // Forgets the open vault if it is `name`, e.g. after it was deleted.
+    pub fn forget(&self, name: &str) {
+        let mut vault = self.vault.lock().unwrap();
+        if vault.as_ref().is_some_and(|open| open.name == name) {
+            *vault = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_open_vault() {
+        file_operations::set_base_path(None);
+        let state = AppState::default();
+        assert_eq!(state.vault(None).unwrap_err().kind(), ErrorKind::NotFound);
+
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        state.open(Vault::open(&vault.name).unwrap());
+        assert_eq!(state.vault(None).unwrap().path, vault.path);
+        let other = Vault { name: "Other".to_string(), path: "Vaults/Other".to_string() };
+        assert_eq!(state.vault(Some(other)).unwrap().name, "Other");
+
+        state.forget("Other");
+        assert!(state.current().is_some());
+        assert_eq!(state.close().map(|vault| vault.name), Some(vault.name.clone()));
+        assert!(state.current().is_none());
+        assert_eq!(Vault::open("missing vault").unwrap_err().kind(), ErrorKind::NotFound);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
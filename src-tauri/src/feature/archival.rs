@@ -0,0 +1,173 @@
+// Per-vault archival rules, applied periodically by a background task
+use serde::{Serialize, Deserialize};
+use std::io::{self, Error, ErrorKind};
+use std::time::{Duration, SystemTime};
+
+use crate::feature::{activity, refactor};
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, markdown, message::Message};
+
+const CONFIG_DIR: &str = ".config";
+pub const DEFAULT_ARCHIVE_FOLDER: &str = "Archive";
+// How often the background task applies the rules
+pub const ARCHIVAL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const DAY: u64 = 24 * 60 * 60;
+
+fn default_destination() -> String {
+    DEFAULT_ARCHIVE_FOLDER.to_string()
+}
+
+// Moves the notes tagged `tag` that were not updated for `older_than_days` into `destination`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveRule {
+    pub tag: String,
+    pub older_than_days: u64,
+    #[serde(default = "default_destination")]
+    pub destination: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivalMove {
+    pub from: String,
+    pub to: String,
+    // The rule that matched, e.g. "#meeting older than 90 days"
+    pub rule: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArchivalReport {
+    pub dry_run: bool,
+    pub moves: Vec<ArchivalMove>,
+    // Notes whose links were rewritten by the moves
+    pub updated: Vec<String>,
+}
+
+impl ArchiveRule {
+    fn describe(&self) -> String {
+        format!("#{} older than {} days", self.tag, self.older_than_days)
+    }
+}
+
+fn rules_path(vault: &Vault) -> String {
+    format!("{}/{}/archival.json", vault.path, CONFIG_DIR)
+}
+
+pub fn load_rules(vault: &Vault) -> io::Result<Vec<ArchiveRule>> {
+    match file_operations::read_from_file(&rules_path(vault)) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| Error::new(ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+// Replaces the rules of a vault, normalizing tags and destination folders.
+pub fn save_rules(vault: &Vault, mut rules: Vec<ArchiveRule>) -> io::Result<Vec<ArchiveRule>> {
+    for rule in &mut rules {
+        rule.tag = rule.tag.trim().trim_start_matches('#').to_string();
+        rule.destination = refactor::clean_path(&rule.destination);
+        if rule.tag.is_empty() || rule.destination.is_empty() {
+            return Err(Message::new("archival.incomplete_rule", "Archival rules need a tag and a destination folder").error(ErrorKind::InvalidInput));
+        }
+    }
+    file_operations::create_directory(&format!("{}/{}", vault.path, CONFIG_DIR))?;
+    let json = serde_json::to_string_pretty(&rules).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    file_operations::write_to_file(&rules_path(vault), &json)?;
+    Ok(rules)
+}
+
+// Lists the moves the rules would make now; the first matching rule wins for each note.
+pub fn plan(vault: &Vault, rules: &[ArchiveRule]) -> io::Result<Vec<ArchivalMove>> {
+    let now = SystemTime::now();
+    let mut moves = Vec::new();
+    if rules.is_empty() {
+        return Ok(moves);
+    }
+
+    for path in refactor::note_paths(vault)? {
+        let file = format!("{}/{}.md", vault.path, path);
+        let modified = std::fs::metadata(file_operations::resolve_path(&file))?.modified()?;
+        let age = now.duration_since(modified).unwrap_or_default();
+        let tags = markdown::note_tags(&file_operations::read_from_file(&file)?);
+
+        let matching = rules.iter().find(|rule| {
+            let archived = path.starts_with(&format!("{}/", rule.destination));
+            !archived && tags.contains(&rule.tag) && age >= Duration::from_secs(rule.older_than_days * DAY)
+        });
+        if let Some(rule) = matching {
+            let title = path.rsplit('/').next().unwrap_or(&path);
+            moves.push(ArchivalMove {
+                to: format!("{}/{}", rule.destination, title),
+                rule: rule.describe(),
+                from: path,
+            });
+        }
+    }
+    Ok(moves)
+}
+
+// Applies the rules of a vault, or only reports the planned moves when `dry_run` is set.
+// Every move is written to the activity log.
+pub fn apply_rules(vault: &Vault, dry_run: bool) -> io::Result<ArchivalReport> {
+    let rules = load_rules(vault)?;
+    let moves = plan(vault, &rules)?;
+    let mut report = ArchivalReport { dry_run, ..Default::default() };
+    if dry_run {
+        report.moves = moves;
+        return Ok(report);
+    }
+
+    for planned in moves {
+        let folder = planned.to.rsplit_once('/').map(|(folder, _)| folder).unwrap_or_default();
+        let moved = match refactor::move_note(vault, &planned.from, folder) {
+            Ok(moved) => moved,
+            // A note with the same name is already archived; leave this one in place
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        activity::record(vault, "archive", &moved.to, &format!("Moved from {} ({})", moved.from, planned.rule))?;
+        for path in moved.updated {
+            if !report.updated.contains(&path) {
+                report.updated.push(path);
+            }
+        }
+        report.moves.push(planned);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_apply_rules() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Standup.md", vault.path), "#meeting notes").unwrap();
+        file_operations::write_to_file(&format!("{}/Plan.md", vault.path), "#project [standup](Standup.md)").unwrap();
+        assert!(apply_rules(&vault, false).unwrap().moves.is_empty());
+
+        let rule = |tag: &str, days| ArchiveRule { tag: tag.to_string(), older_than_days: days, destination: "Archive".to_string() };
+        assert!(save_rules(&vault, vec![rule("", 1)]).is_err());
+        // Fresh notes are not old enough
+        save_rules(&vault, vec![rule("#project", 30)]).unwrap();
+        assert!(apply_rules(&vault, false).unwrap().moves.is_empty());
+
+        save_rules(&vault, vec![rule("meeting", 0)]).unwrap();
+        let preview = apply_rules(&vault, true).unwrap();
+        assert_eq!(preview.moves[0].to, "Archive/Standup");
+        assert!(file_operations::read_from_file(&format!("{}/Standup.md", vault.path)).is_ok());
+
+        let report = apply_rules(&vault, false).unwrap();
+        assert_eq!(report.moves.len(), 1);
+        assert_eq!(report.updated, vec!["Plan"]);
+        assert!(file_operations::read_from_file(&format!("{}/Archive/Standup.md", vault.path)).is_ok());
+        assert_eq!(activity::read_log(&vault, None).unwrap()[0].path, "Archive/Standup");
+        // Archived notes stay where they are
+        assert!(apply_rules(&vault, false).unwrap().moves.is_empty());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
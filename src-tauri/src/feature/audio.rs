@@ -0,0 +1,136 @@
+// Audio memos and transcription
+use chrono::Local;
+use serde::{Serialize, Deserialize};
+use std::io::{self, Error, ErrorKind, Read};
+use std::process::Command;
+
+use crate::storage::{attachment, note::Note, vault::Vault};
+use crate::utils::{file_operations, message::Message};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TranscriptionHook {
+    // Runs a local binary such as whisper.cpp; `{input}` in the arguments is replaced by the audio file path.
+    Command { binary: String, args: Vec<String> },
+    // Posts the audio to an HTTP endpoint that answers with plain text or `{ "text": "..." }`.
+    Http { url: String, api_key: Option<String> },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AudioMemo {
+    pub path: String,
+    pub transcript: Option<String>,
+}
+
+// Saves a recorded memo as an attachment of a note, linking it and appending the transcript if a hook is configured.
+pub fn save_audio_memo(
+    vault: &Vault,
+    title: &str,
+    data: &[u8],
+    extension: &str,
+    hook: Option<&TranscriptionHook>,
+) -> io::Result<AudioMemo> {
+    let mut content = Note::read_note(vault, title)?;
+
+    let now = Local::now();
+    let extension: String = extension.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    let file_name = format!("memo-{}.{}", now.format("%Y%m%d-%H%M%S"), extension);
+    let path = attachment::save_attachment(vault, &file_name, data)?;
+
+    content.push_str(&format!("\n\n[Audio memo {}]({})", now.format("%Y-%m-%d %H:%M"), path));
+
+    let transcript = match hook {
+        Some(hook) => {
+            let audio_path = file_operations::resolve_path(&format!("{}/{}", vault.path, path));
+            let transcript = transcribe(hook, &audio_path)?;
+            if !transcript.is_empty() {
+                content.push_str(&format!("\n\n> {}", transcript.replace('\n', "\n> ")));
+            }
+            Some(transcript)
+        }
+        None => None,
+    };
+
+    Note::update_note(vault, title, &content)?;
+    Ok(AudioMemo { path, transcript })
+}
+
+// Runs the configured transcription hook on an audio file.
+pub fn transcribe(hook: &TranscriptionHook, audio_path: &str) -> io::Result<String> {
+    match hook {
+        TranscriptionHook::Command { binary, args } => {
+            let output = Command::new(binary)
+                .args(args.iter().map(|arg| arg.replace("{input}", audio_path)))
+                .output()?;
+            if !output.status.success() {
+                return Err(Message::new("audio.transcription_failed", "Transcription failed: {detail}")
+                    .with("detail", String::from_utf8_lossy(&output.stderr).trim())
+                    .error(ErrorKind::Other));
+            }
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        }
+        TranscriptionHook::Http { url, api_key } => {
+            let audio = std::fs::read(audio_path)?;
+            let mut request = ureq::post(url).set("Content-Type", "application/octet-stream");
+            if let Some(api_key) = api_key {
+                request = request.set("Authorization", &format!("Bearer {}", api_key));
+            }
+            let response = request.send_bytes(&audio).map_err(Error::other)?;
+
+            let mut body = String::new();
+            response.into_reader().read_to_string(&mut body)?;
+            let text = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|json| json.get("text").and_then(|text| text.as_str()).map(str::to_string))
+                .unwrap_or(body);
+            if text.trim().is_empty() {
+                return Err(Message::new("audio.empty_transcription", "Transcription service returned no text").error(ErrorKind::InvalidData));
+            }
+            Ok(text.trim().to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::note::OnConflict;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_save_audio_memo_without_transcription() {
+        file_operations::set_base_path(None);
+        let mut vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        Note::new("Meeting-with-Bob", "Meeting with Bob").create_note(&mut vault, OnConflict::Fail).unwrap();
+
+        let memo = save_audio_memo(&vault, "Meeting-with-Bob", b"audio", "webm", None).unwrap();
+        assert!(memo.path.starts_with("attachments/memo-"));
+        assert!(memo.transcript.is_none());
+        let content = Note::read_note(&vault, "Meeting-with-Bob").unwrap();
+        assert!(content.contains(&format!("]({})", memo.path)));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_audio_memo_with_command_hook() {
+        file_operations::set_base_path(None);
+        let mut vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        Note::new("Meeting-with-Alice", "Meeting with Alice").create_note(&mut vault, OnConflict::Fail).unwrap();
+
+        let hook = TranscriptionHook::Command {
+            binary: "echo".to_string(),
+            args: vec!["hello from".to_string(), "{input}".to_string()],
+        };
+        let memo = save_audio_memo(&vault, "Meeting-with-Alice", b"audio", "wav", Some(&hook)).unwrap();
+        let transcript = memo.transcript.unwrap();
+        assert!(transcript.starts_with("hello from ") && transcript.ends_with(".wav"));
+        let content = Note::read_note(&vault, "Meeting-with-Alice").unwrap();
+        assert!(content.ends_with(&format!("> {}", transcript)));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,200 @@
+// Link autocompletion for the `[[` editor popup
+use serde::{Serialize, Deserialize};
+use std::io;
+
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{frontmatter, markdown, outline};
+
+pub const MAX_COMPLETIONS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionKind {
+    Note,
+    Alias,
+    Heading,
+    Block,
+    Person,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Completion {
+    pub kind: CompletionKind,
+    pub label: String,
+    pub insert: String,
+    pub note: String,
+    pub detail: Option<String>,
+    pub score: f32,
+}
+
+// Scores a candidate against a query: exact > prefix > word start > substring > subsequence.
+pub fn match_score(candidate: &str, query: &str) -> Option<f32> {
+    let candidate = candidate.to_lowercase();
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Some(0.1);
+    }
+    if candidate == query {
+        return Some(1.0);
+    }
+    if candidate.starts_with(&query) {
+        return Some(0.8);
+    }
+    if let Some(position) = candidate.find(&query) {
+        let word_start = candidate[..position].ends_with(|c: char| !c.is_alphanumeric());
+        return Some(if word_start { 0.6 } else { 0.5 });
+    }
+
+    let mut chars = candidate.chars();
+    query
+        .chars()
+        .all(|q| chars.any(|c| c == q))
+        .then_some(0.2)
+}
+
+struct NoteEntry {
+    title: String,
+    aliases: Vec<String>,
+    content: String,
+}
+
+fn load_notes(vault: &Vault) -> io::Result<Vec<NoteEntry>> {
+    let mut notes = Vec::new();
+    for title in Note::list_notes(vault)? {
+        let content = Note::read_note(vault, &title)?;
+        let aliases = frontmatter::parse(&content).map(|fm| fm.get_list("aliases")).unwrap_or_default();
+        notes.push(NoteEntry { title, aliases, content });
+    }
+    Ok(notes)
+}
+
+// Completes the text typed after `[[`: `Note`, `Note#Heading` or `Note#^block` (an empty note searches all notes).
+pub fn autocomplete_link(vault: &Vault, prefix: &str) -> io::Result<Vec<Completion>> {
+    let notes = load_notes(vault)?;
+    let mut completions = Vec::new();
+
+    match prefix.split_once('#') {
+        None => {
+            for note in &notes {
+                if let Some(score) = match_score(&note.title, prefix) {
+                    completions.push(Completion {
+                        kind: CompletionKind::Note,
+                        label: note.title.clone(),
+                        insert: note.title.clone(),
+                        note: note.title.clone(),
+                        detail: None,
+                        score,
+                    });
+                }
+                for alias in &note.aliases {
+                    if let Some(score) = match_score(alias, prefix) {
+                        completions.push(Completion {
+                            kind: CompletionKind::Alias,
+                            label: alias.clone(),
+                            insert: format!("{}|{}", note.title, alias),
+                            note: note.title.clone(),
+                            detail: None,
+                            // Aliases rank just below a title with the same score
+                            score: score * 0.95,
+                        });
+                    }
+                }
+            }
+        }
+        Some((note_part, query)) => {
+            let note_part = note_part.trim().to_lowercase();
+            let targets = notes.iter().filter(|note| {
+                note_part.is_empty()
+                    || note.title.to_lowercase() == note_part
+                    || note.aliases.iter().any(|alias| alias.to_lowercase() == note_part)
+            });
+
+            for note in targets {
+                if let Some(block_query) = query.strip_prefix('^') {
+                    for (id, text) in markdown::extract_block_ids(&note.content) {
+                        let score = match (match_score(&id, block_query), match_score(&text, block_query)) {
+                            (Some(a), Some(b)) => Some(a.max(b)),
+                            (a, b) => a.or(b),
+                        };
+                        if let Some(score) = score {
+                            completions.push(Completion {
+                                kind: CompletionKind::Block,
+                                label: id.clone(),
+                                insert: format!("{}#^{}", note.title, id),
+                                note: note.title.clone(),
+                                detail: Some(text),
+                                score,
+                            });
+                        }
+                    }
+                } else {
+                    for heading in outline::headings(&note.content) {
+                        if let Some(score) = match_score(&heading.text, query) {
+                            completions.push(Completion {
+                                kind: CompletionKind::Heading,
+                                insert: format!("{}#{}", note.title, heading.text),
+                                label: heading.text,
+                                note: note.title.clone(),
+                                detail: Some(format!("H{}", heading.level)),
+                                score,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    completions.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.label.cmp(&b.label)));
+    completions.truncate(MAX_COMPLETIONS);
+    Ok(completions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_match_score() {
+        assert_eq!(match_score("Rust", "rust"), Some(1.0));
+        assert_eq!(match_score("Rustacean", "rust"), Some(0.8));
+        assert_eq!(match_score("Learning Rust", "rust"), Some(0.6));
+        assert_eq!(match_score("Trust", "rust"), Some(0.5));
+        assert_eq!(match_score("Road to success", "rts"), Some(0.2));
+        assert_eq!(match_score("Python", "rust"), None);
+    }
+
+    #[test]
+    fn test_autocomplete_link() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(
+            &format!("{}/Rust.md", vault.path),
+            "---\naliases: [Ferris]\n---\n# Ownership\nMoves and borrows ^borrow\n## Lifetimes\n",
+        )
+        .unwrap();
+        file_operations::write_to_file(&format!("{}/Trust.md", vault.path), "# Trust issues\n").unwrap();
+
+        let notes = autocomplete_link(&vault, "rus").unwrap();
+        assert_eq!(notes[0].insert, "Rust");
+        assert_eq!(notes[1].insert, "Trust");
+
+        let aliases = autocomplete_link(&vault, "fer").unwrap();
+        assert_eq!(aliases[0].kind, CompletionKind::Alias);
+        assert_eq!(aliases[0].insert, "Rust|Ferris");
+
+        let headings = autocomplete_link(&vault, "Rust#life").unwrap();
+        assert_eq!(headings.len(), 1);
+        assert_eq!(headings[0].insert, "Rust#Lifetimes");
+
+        let blocks = autocomplete_link(&vault, "#^bor").unwrap();
+        assert_eq!(blocks[0].kind, CompletionKind::Block);
+        assert_eq!(blocks[0].insert, "Rust#^borrow");
+        assert_eq!(blocks[0].detail.as_deref(), Some("Moves and borrows"));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,283 @@
+// Backlink index kept in the metadata store, and the context of each mention grouped by source note
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+
+use crate::feature::metadata::MetadataStore;
+use crate::feature::refactor;
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, frontmatter, markdown, outline};
+
+// Outgoing links of every note, to know which backlinks to drop when a link is removed
+pub const LINKS_TREE: &str = "links";
+// Set once the whole vault was indexed, so notes added while it was partly indexed are still picked up
+const INDEXED_KEY: &str = "\0indexed";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mention {
+    // Zero-based line of the mention in the source note
+    pub line: usize,
+    pub context: String,
+    // Source title followed by the enclosing sections: Note > Section > Subsection
+    pub breadcrumbs: Vec<String>,
+    // Slug of the innermost enclosing heading, to scroll to it
+    pub heading: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacklinkGroup {
+    // Path relative to the vault, without the `.md` extension
+    pub source: String,
+    pub title: String,
+    pub mentions: Vec<Mention>,
+}
+
+// Resolves link targets to notes: a target naming a note path is that note, and any other target is the
+// first note with that name in any case, like the editor follows links.
+pub struct LinkResolver {
+    paths: HashSet<String>,
+    by_title: HashMap<String, String>,
+}
+
+impl LinkResolver {
+    pub fn new(paths: &[String]) -> Self {
+        let mut by_title = HashMap::new();
+        for path in paths {
+            let title = path.rsplit('/').next().unwrap_or(path);
+            by_title.entry(title.to_lowercase()).or_insert_with(|| path.clone());
+        }
+        Self { paths: paths.iter().cloned().collect(), by_title }
+    }
+
+    pub fn resolve(&self, target: &str) -> Option<String> {
+        if self.paths.contains(target) {
+            Some(target.to_string())
+        } else {
+            self.by_title.get(&target.to_lowercase()).cloned()
+        }
+    }
+}
+
+// Notes a content links to; attachment embeds such as `![[photo.png]]` are left out.
+pub fn outgoing_links(source: &str, content: &str) -> Vec<String> {
+    let mut targets: Vec<String> = Vec::new();
+    for link in markdown::extract_links(content) {
+        let target = markdown::link_target(&link).to_string();
+        let is_file = target.rsplit('/').next().is_some_and(|name| name.contains('.'));
+        if !target.is_empty() && !is_file && target != source && !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+    targets
+}
+
+// Refreshes the backlinks contributed by one note from its current content; a missing note, or one in an
+// excluded folder, contributes none.
+pub fn index_links(vault: &Vault, store: &MetadataStore, path: &str) -> io::Result<()> {
+    let tree = store.tree(LINKS_TREE)?;
+    let old: Vec<String> = tree
+        .get(path)?
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    let file = format!("{}/{}.md", vault.path, path);
+    let new = if Path::new(&file_operations::resolve_path(&file)).exists() && !vault.config()?.is_excluded(path) {
+        outgoing_links(path, &file_operations::read_from_file(&file)?)
+    } else {
+        Vec::new()
+    };
+
+    for target in old.iter().filter(|target| !new.contains(target)) {
+        if let Some(mut metadata) = store.get_metadata(target) {
+            metadata.backlinks.retain(|source| source != path);
+            store.update_metadata(target, metadata)?;
+        }
+    }
+    for target in new.iter().filter(|target| !old.contains(target)) {
+        let mut metadata = store.get_metadata(target).unwrap_or_default();
+        if !metadata.backlinks.iter().any(|source| source == path) {
+            metadata.backlinks.push(path.to_string());
+            store.update_metadata(target, metadata)?;
+        }
+    }
+
+    if new.is_empty() {
+        tree.remove(path)?;
+    } else {
+        let bytes = serde_json::to_vec(&new).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        tree.insert(path, bytes)?;
+    }
+    tree.flush()?;
+    Ok(())
+}
+
+// Returns the notes linking to a note, sorted. `title` is resolved like a link, so `Plan` finds the links
+// to `Projects/Plan`, and each indexed link is resolved the same way, whatever case or form it was written
+// in. Links to a note that does not exist are matched by their text. The index is built from every note
+// on first use.
+pub fn get_backlinks(vault: &Vault, store: &MetadataStore, title: &str) -> io::Result<Vec<String>> {
+    let tree = store.tree(LINKS_TREE)?;
+    let paths = refactor::note_paths(vault)?;
+    if !tree.contains_key(INDEXED_KEY)? {
+        for path in &paths {
+            index_links(vault, store, path)?;
+        }
+        tree.insert(INDEXED_KEY, &[])?;
+        tree.flush()?;
+    }
+
+    let resolver = LinkResolver::new(&paths);
+    let target = resolver.resolve(title).unwrap_or_else(|| title.to_string());
+    let mut backlinks = Vec::new();
+    for entry in tree.iter() {
+        let (key, bytes) = entry?;
+        if key.as_ref() == INDEXED_KEY.as_bytes() {
+            continue;
+        }
+        let source = String::from_utf8_lossy(&key).to_string();
+        let links: Vec<String> = serde_json::from_slice(&bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        if source != target && links.iter().any(|link| resolver.resolve(link).as_deref().unwrap_or(link) == target) {
+            backlinks.push(source);
+        }
+    }
+    backlinks.sort();
+    Ok(backlinks)
+}
+
+// Whether a wikilink points at the note at `path`, by full path or by bare title.
+pub fn links_to(link: &str, path: &str) -> bool {
+    let target = markdown::link_target(link);
+    let title = path.rsplit('/').next().unwrap_or(path);
+    target.eq_ignore_ascii_case(path) || (!target.contains('/') && target.eq_ignore_ascii_case(title))
+}
+
+// Lists the mentions of the note at `path` in one source note.
+pub fn mentions(source: &str, content: &str, path: &str) -> Vec<Mention> {
+    let headings = outline::headings(content);
+    let title = source.rsplit('/').next().unwrap_or(source);
+    let body_start = content.lines().count() - frontmatter::strip(content).lines().count();
+
+    content
+        .lines()
+        .enumerate()
+        .skip(body_start)
+        .filter(|(_, text)| markdown::extract_links(text).iter().any(|link| links_to(link, path)))
+        .map(|(line, text)| {
+            let chain = outline::breadcrumbs(&headings, line);
+            let mut breadcrumbs = vec![title.to_string()];
+            breadcrumbs.extend(chain.iter().map(|heading| heading.text.clone()));
+            Mention {
+                line,
+                context: text.trim().to_string(),
+                breadcrumbs,
+                heading: chain.last().map(|heading| heading.slug.clone()),
+            }
+        })
+        .collect()
+}
+
+// Collects the mentions of a note across the vault, grouped by source note and sorted by path.
+pub fn backlink_context(vault: &Vault, path: &str) -> io::Result<Vec<BacklinkGroup>> {
+    let path = refactor::clean_path(path);
+    let mut groups = Vec::new();
+    for source in refactor::note_paths(vault)? {
+        if source == path {
+            continue;
+        }
+        let content = file_operations::read_from_file(&format!("{}/{}.md", vault.path, source))?;
+        let mentions = mentions(&source, &content, &path);
+        if !mentions.is_empty() {
+            groups.push(BacklinkGroup {
+                title: source.rsplit('/').next().unwrap_or(&source).to_string(),
+                source,
+                mentions,
+            });
+        }
+    }
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::vault::VaultConfig;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_backlink_index() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Target.md", vault.path), "# Target").unwrap();
+        file_operations::write_to_file(&format!("{}/A.md", vault.path), "[[Target]] ![[photo.png]] [[A]]").unwrap();
+        file_operations::write_to_file(&format!("{}/B.md", vault.path), "Nothing yet").unwrap();
+        let store = MetadataStore::open(&vault).unwrap();
+
+        // The first query indexes the whole vault, even when some notes were indexed on save already
+        index_links(&vault, &store, "B").unwrap();
+        assert_eq!(get_backlinks(&vault, &store, "Target").unwrap(), vec!["A"]);
+        assert!(store.get_metadata("photo.png").is_none());
+        assert!(get_backlinks(&vault, &store, "A").unwrap().is_empty());
+
+        file_operations::write_to_file(&format!("{}/B.md", vault.path), "See [[Target|it]] and [[Target#Top]]").unwrap();
+        index_links(&vault, &store, "B").unwrap();
+        assert_eq!(get_backlinks(&vault, &store, "Target").unwrap(), vec!["A", "B"]);
+
+        file_operations::write_to_file(&format!("{}/A.md", vault.path), "Unlinked").unwrap();
+        index_links(&vault, &store, "A").unwrap();
+        file_operations::delete_file(&format!("{}/B.md", vault.path)).unwrap();
+        index_links(&vault, &store, "B").unwrap();
+        assert!(get_backlinks(&vault, &store, "Target").unwrap().is_empty());
+
+        // Bare titles and case variants resolve to the note in its folder; links to missing notes match by text
+        file_operations::create_directory(&format!("{}/Projects", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Projects/Plan.md", vault.path), "[[Someday]]").unwrap();
+        file_operations::write_to_file(&format!("{}/C.md", vault.path), "[[plan]] [[someday]]").unwrap();
+        file_operations::write_to_file(&format!("{}/D.md", vault.path), "[[Projects/Plan#Goals]]").unwrap();
+        for path in ["Projects/Plan", "C", "D"] {
+            index_links(&vault, &store, path).unwrap();
+        }
+        assert_eq!(get_backlinks(&vault, &store, "Projects/Plan").unwrap(), vec!["C", "D"]);
+        assert_eq!(get_backlinks(&vault, &store, "Plan").unwrap(), vec!["C", "D"]);
+        assert_eq!(get_backlinks(&vault, &store, "Someday").unwrap(), vec!["Projects/Plan"]);
+
+        // Notes in excluded folders give no backlinks
+        let config = vault.config().unwrap();
+        vault.set_config(&VaultConfig { excluded_dirs: vec!["Projects".to_string()], ..config }).unwrap();
+        index_links(&vault, &store, "Projects/Plan").unwrap();
+        assert!(get_backlinks(&vault, &store, "Someday").unwrap().is_empty());
+
+        // Cleanup
+        drop(store);
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_backlink_context() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Projects", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Projects/Plan.md", vault.path), "# Plan").unwrap();
+        file_operations::write_to_file(
+            &format!("{}/Journal.md", vault.path),
+            "---\nrelated: \"[[Plan]]\"\n---\nSee [[Plan]]\n# Week 1\n## Monday\n  Worked on [[Projects/Plan#Goals|the plan]]\n# Week 2\n[[Other]]",
+        )
+        .unwrap();
+        file_operations::write_to_file(&format!("{}/Other.md", vault.path), "[[plan]] twice [[Plan]]").unwrap();
+
+        let groups = backlink_context(&vault, "Projects/Plan").unwrap();
+        let sources: Vec<&str> = groups.iter().map(|group| group.source.as_str()).collect();
+        assert_eq!(sources, vec!["Journal", "Other"]);
+
+        let journal = &groups[0].mentions;
+        assert_eq!(journal.len(), 2);
+        assert_eq!(journal[0].breadcrumbs, vec!["Journal"]);
+        assert_eq!(journal[1].breadcrumbs, vec!["Journal", "Week 1", "Monday"]);
+        assert_eq!(journal[1].context, "Worked on [[Projects/Plan#Goals|the plan]]");
+        assert_eq!(journal[1].line, 6);
+        assert_eq!(journal[1].heading.as_deref(), Some("monday"));
+        assert_eq!(groups[1].mentions.len(), 1);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,168 @@
+// "Copy link to heading/block": canonical `[[Note#Heading]]` and `[[Note#^id]]` references. Blocks get a
+// `^id` marker at the end of their last line the first time they are referenced.
+use nanoid::nanoid;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+use std::io::{self, ErrorKind};
+
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{frontmatter, markdown, outline, message::Message};
+
+// Block ids stay short and lowercase so they are easy to type
+const BLOCK_ID_ALPHABET: [char; 36] = [
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l',
+    'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
+];
+const BLOCK_ID_LENGTH: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockReference {
+    pub link: String,
+    pub block_id: String,
+    // Whether the id was just added to the note
+    pub created: bool,
+}
+
+// Returns the `[[Note#Heading]]` reference of the heading at `index` in the note's outline. Headings that
+// repeat an earlier one, or contain characters wikilinks cannot hold, are referenced by their slug.
+pub fn heading_reference(note: &str, content: &str, index: usize) -> io::Result<String> {
+    let headings = outline::headings(content);
+    let heading = headings
+        .get(index)
+        .ok_or_else(|| Message::new("heading.not_found", "Heading does not exist").error(ErrorKind::NotFound))?;
+    let repeated = headings[..index].iter().any(|earlier| earlier.text.eq_ignore_ascii_case(&heading.text));
+    let anchor = if repeated || heading.text.contains(['[', ']', '|', '#', '^']) { &heading.slug } else { &heading.text };
+    Ok(format!("[[{}#{}]]", note, anchor))
+}
+
+fn is_list_item(line: &str) -> bool {
+    let re = Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s").unwrap();
+    re.is_match(line)
+}
+
+fn is_heading(line: &str) -> bool {
+    let re = Regex::new(r"^#{1,6}\s").unwrap();
+    re.is_match(line)
+}
+
+fn is_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+// Returns the line range `[start, end)` of the paragraph, list item or quote containing a line.
+fn block_range(lines: &[&str], line: usize, first_line: usize) -> io::Result<(usize, usize)> {
+    let in_code = lines[..=line].iter().filter(|line| is_fence(line)).count() % 2 == 1;
+    let target = lines[line];
+    if in_code || is_fence(target) || target.trim_start().starts_with('|') {
+        return Err(Message::new("block.unreferenceable", "Code blocks and tables cannot be referenced").error(ErrorKind::InvalidInput));
+    }
+    if target.trim().is_empty() {
+        return Err(Message::new("block.not_found", "No block at this line").error(ErrorKind::InvalidInput));
+    }
+    if is_heading(target) {
+        return Err(Message::new("block.heading_reference", "Headings are referenced by their text").error(ErrorKind::InvalidInput));
+    }
+    let part_of_block = |line: &&str| !line.trim().is_empty() && !is_heading(line) && !is_fence(line);
+
+    // A list item is its own block: from its marker to the next item
+    let mut start = line;
+    while !is_list_item(lines[start]) && start > first_line && part_of_block(&lines[start - 1]) {
+        start -= 1;
+    }
+    if !is_list_item(lines[start]) {
+        start = line;
+        while start > first_line && part_of_block(&lines[start - 1]) {
+            start -= 1;
+        }
+    }
+    let mut end = line + 1;
+    while end < lines.len() && part_of_block(&lines[end]) && !is_list_item(lines[end]) {
+        end += 1;
+    }
+    Ok((start, end))
+}
+
+// Adds a block id to the block containing `line` (0-based) unless it has one. Returns the updated content,
+// the id and whether it was added.
+pub fn ensure_block_id(content: &str, line: usize) -> io::Result<(String, String, bool)> {
+    let lines: Vec<&str> = content.lines().collect();
+    let first_line = match frontmatter::split(content) {
+        (Some(_), body) => content[..content.len() - body.len()].lines().count(),
+        (None, _) => 0,
+    };
+    if line < first_line || line >= lines.len() {
+        return Err(Message::new("block.not_found", "No block at this line").error(ErrorKind::InvalidInput));
+    }
+    let (start, end) = block_range(&lines, line, first_line)?;
+    if let Some((id, _)) = markdown::extract_block_ids(&lines[start..end].join("\n")).pop() {
+        return Ok((content.to_string(), id, false));
+    }
+
+    let taken: HashSet<String> = markdown::extract_block_ids(content).into_iter().map(|(id, _)| id).collect();
+    let mut id = nanoid!(BLOCK_ID_LENGTH, &BLOCK_ID_ALPHABET);
+    while taken.contains(&id) {
+        id = nanoid!(BLOCK_ID_LENGTH, &BLOCK_ID_ALPHABET);
+    }
+    let mut updated: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    updated[end - 1] = format!("{} ^{}", updated[end - 1].trim_end(), id);
+    let mut updated = updated.join("\n");
+    if content.ends_with('\n') {
+        updated.push('\n');
+    }
+    Ok((updated, id, true))
+}
+
+// Returns the `[[Note#^id]]` reference of the block containing `line`, writing a new block id into the
+// note when the block has none yet.
+pub fn block_reference(vault: &Vault, note: &str, line: usize) -> io::Result<BlockReference> {
+    let content = Note::read_note(vault, note)?;
+    let (updated, block_id, created) = ensure_block_id(&content, line)?;
+    if created {
+        Note::update_note(vault, note, &updated)?;
+    }
+    Ok(BlockReference { link: format!("[[{}#^{}]]", note, block_id), block_id, created })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+
+    #[test]
+    fn test_heading_reference() {
+        let content = "# Plan\n## Next steps\ntext\n## Next Steps\n## A | B\n";
+        assert_eq!(heading_reference("Projects/Plan", content, 1).unwrap(), "[[Projects/Plan#Next steps]]");
+        assert_eq!(heading_reference("Projects/Plan", content, 2).unwrap(), "[[Projects/Plan#next-steps-1]]");
+        assert_eq!(heading_reference("Projects/Plan", content, 3).unwrap(), "[[Projects/Plan#a--b]]");
+        assert_eq!(heading_reference("Projects/Plan", content, 9).unwrap_err().kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_block_reference() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let content = "---\ntags: [a]\n---\n# Log\nFirst line\nsecond line\n\n- one\n  more of one\n- two ^known\n\n```\ncode\n```\n";
+        file_operations::write_to_file(&format!("{}/Log.md", vault.path), content).unwrap();
+
+        let reference = block_reference(&vault, "Log", 4).unwrap();
+        assert!(reference.created);
+        assert_eq!(reference.link, format!("[[Log#^{}]]", reference.block_id));
+        let content = Note::read_note(&vault, "Log").unwrap();
+        assert!(content.contains(&format!("First line\nsecond line ^{}\n", reference.block_id)));
+        // Referencing the block again reuses its id
+        assert_eq!(block_reference(&vault, "Log", 5).unwrap(), BlockReference { created: false, ..reference });
+
+        let item = block_reference(&vault, "Log", 8).unwrap();
+        assert!(Note::read_note(&vault, "Log").unwrap().contains(&format!("  more of one ^{}\n- two", item.block_id)));
+        assert_eq!(block_reference(&vault, "Log", 9).unwrap().block_id, "known");
+
+        for line in [1, 3, 6, 12, 40] {
+            assert_eq!(block_reference(&vault, "Log", line).unwrap_err().kind(), ErrorKind::InvalidInput);
+        }
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
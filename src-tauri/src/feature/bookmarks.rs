@@ -0,0 +1,124 @@
+// Bookmarks and highlights from clipped pages
+use chrono::Utc;
+use nanoid::nanoid;
+use serde::{Serialize, Deserialize};
+use std::io::{self, Error, ErrorKind};
+
+use crate::feature::metadata::MetadataStore;
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::message::Message;
+
+const BOOKMARKS_TREE: &str = "bookmarks";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub highlights: Vec<String>,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+// Stores a new bookmark with its highlights.
+pub fn add_bookmark(
+    store: &MetadataStore,
+    url: &str,
+    title: &str,
+    highlights: Vec<String>,
+    note: Option<String>,
+) -> io::Result<Bookmark> {
+    if url.trim().is_empty() {
+        return Err(Message::new("bookmark.url_empty", "Bookmark URL is empty").error(ErrorKind::InvalidInput));
+    }
+
+    let bookmark = Bookmark {
+        id: nanoid!(),
+        url: url.trim().to_string(),
+        title: if title.trim().is_empty() { url.trim().to_string() } else { title.trim().to_string() },
+        highlights,
+        note,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    save_bookmark(store, &bookmark)?;
+    Ok(bookmark)
+}
+
+// Lists all bookmarks, newest first.
+pub fn list_bookmarks(store: &MetadataStore) -> io::Result<Vec<Bookmark>> {
+    let mut bookmarks = Vec::new();
+    for entry in store.tree(BOOKMARKS_TREE)?.iter() {
+        let (_, bytes) = entry?;
+        if let Ok(bookmark) = serde_json::from_slice::<Bookmark>(&bytes) {
+            bookmarks.push(bookmark);
+        }
+    }
+    bookmarks.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(bookmarks)
+}
+
+pub fn get_bookmark(store: &MetadataStore, id: &str) -> io::Result<Bookmark> {
+    let bytes = store
+        .tree(BOOKMARKS_TREE)?
+        .get(id)?
+        .ok_or_else(|| Message::new("bookmark.not_found", "Bookmark does not exist").error(ErrorKind::NotFound))?;
+    serde_json::from_slice(&bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+}
+
+fn save_bookmark(store: &MetadataStore, bookmark: &Bookmark) -> io::Result<()> {
+    let bytes = serde_json::to_vec(bookmark).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    let tree = store.tree(BOOKMARKS_TREE)?;
+    tree.insert(bookmark.id.as_str(), bytes)?;
+    tree.flush()?;
+    Ok(())
+}
+
+// Turns a bookmark into a full note quoting its highlights, and links the bookmark to it.
+pub fn convert_to_note(vault: &Vault, store: &MetadataStore, id: &str) -> io::Result<String> {
+    let mut bookmark = get_bookmark(store, id)?;
+
+    let mut content = format!("# {}\n\nSource: <{}>\n", bookmark.title, bookmark.url);
+    for highlight in &bookmark.highlights {
+        content.push_str(&format!("\n> {}\n", highlight.trim().replace('\n', "\n> ")));
+    }
+
+    let title = Note::create_named_note(vault, &bookmark.title, &content)?;
+    bookmark.note = Some(title.clone());
+    save_bookmark(store, &bookmark)?;
+    Ok(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+
+    #[test]
+    fn test_add_list_and_convert_bookmark() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let store = MetadataStore::open(&vault).unwrap();
+
+        let bookmark = add_bookmark(
+            &store,
+            "https://example.com/rust",
+            "Rust Article",
+            vec!["Ownership is key.".to_string()],
+            None,
+        )
+        .unwrap();
+        assert_eq!(list_bookmarks(&store).unwrap().len(), 1);
+        assert!(add_bookmark(&store, " ", "Empty", Vec::new(), None).is_err());
+
+        let title = convert_to_note(&vault, &store, &bookmark.id).unwrap();
+        assert_eq!(title, "Rust Article");
+        let content = Note::read_note(&vault, &title).unwrap();
+        assert!(content.contains("Source: <https://example.com/rust>"));
+        assert!(content.contains("> Ownership is key."));
+        assert_eq!(get_bookmark(&store, &bookmark.id).unwrap().note.as_deref(), Some("Rust Article"));
+
+        // Cleanup
+        drop(store);
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,161 @@
+// Edits applied across all notes of a vault
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use serde_yaml::Value;
+use std::io::{self, ErrorKind};
+
+use crate::feature::history::FileChange;
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{frontmatter, error::AppError, message::Message, plan::Plan};
+
+// Notes a tag was renamed in, and notes left alone because their frontmatter could not be parsed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TagRename {
+    pub renamed: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+// Runs every note through the given function and returns the snapshots of the notes that would change,
+// with the plan writing them. Notes the function fails on are left out of the plan and returned last.
+fn plan_rewrite<F>(vault: &Vault, rewrite: F) -> io::Result<(Vec<FileChange>, Plan, Vec<String>)>
+where
+    F: Fn(&str) -> io::Result<String>,
+{
+    let mut changes = Vec::new();
+    let mut plan = Plan::default();
+    let mut failed = Vec::new();
+    for title in Note::list_notes(vault)? {
+        let before = Note::read_note(vault, &title)?;
+        let Ok(after) = rewrite(&before) else {
+            failed.push(title);
+            continue;
+        };
+        if after != before {
+            plan.write(&format!("{}.md", title), &after);
+            changes.push(FileChange {
+                title,
+                before: Some(before),
+                after: Some(after),
+            });
+        }
+    }
+    Ok((changes, plan, failed))
+}
+
+fn apply((changes, plan): (Vec<FileChange>, Plan), vault: &Vault) -> io::Result<Vec<FileChange>> {
+    plan.apply(vault)?;
+    Ok(changes)
+}
+
+// Plans replacing every occurrence of a literal text in all notes.
+pub fn plan_replace_in_notes(vault: &Vault, find: &str, replace: &str) -> io::Result<(Vec<FileChange>, Plan)> {
+    if find.is_empty() {
+        return Err(Message::new("search.text_empty", "Search text is empty").error(ErrorKind::InvalidInput));
+    }
+    let (changes, plan, _) = plan_rewrite(vault, |content| Ok(content.replace(find, replace)))?;
+    Ok((changes, plan))
+}
+
+// Replaces every occurrence of a literal text in all notes.
+pub fn replace_in_notes(vault: &Vault, find: &str, replace: &str) -> io::Result<Vec<FileChange>> {
+    apply(plan_replace_in_notes(vault, find, replace)?, vault)
+}
+
+// Renames an inline `#tag` (and its `#tag/nested` children) in a note body.
+fn rename_inline_tag(body: &str, old: &str, new: &str) -> String {
+    let tag_re = Regex::new(r"#[\w/-]+").unwrap();
+    let mut renamed = String::new();
+    let mut last = 0;
+    for found in tag_re.find_iter(body) {
+        let at_word_start = body[..found.start()].chars().last().is_none_or(char::is_whitespace);
+        let tag = &found.as_str()[1..];
+        let rest = tag.strip_prefix(old).filter(|rest| rest.is_empty() || rest.starts_with('/'));
+        if let (true, Some(rest)) = (at_word_start, rest) {
+            renamed.push_str(&body[last..found.start()]);
+            renamed.push_str(&format!("#{}{}", new, rest));
+            last = found.end();
+        }
+    }
+    renamed.push_str(&body[last..]);
+    renamed
+}
+
+// Plans renaming a tag in frontmatter `tags` and inline `#tags` across the vault. Notes with malformed
+// frontmatter are skipped and returned last, so one broken note does not stop the rename.
+pub fn plan_rename_tag(vault: &Vault, old: &str, new: &str) -> io::Result<(Vec<FileChange>, Plan, Vec<String>)> {
+    let (old, new) = (old.trim_start_matches('#'), new.trim_start_matches('#'));
+    if old.is_empty() || new.is_empty() || new.contains(char::is_whitespace) {
+        return Err(AppError::InvalidName(Message::new("tag.invalid_name", "Tag names must be single words")).into());
+    }
+
+    plan_rewrite(vault, |content| {
+        let mut content = content.to_string();
+        let mut fields = frontmatter::parse(&content)?;
+        let tags = fields.tags();
+        if tags.iter().any(|tag| tag == old) {
+            let renamed: Vec<Value> = tags
+                .into_iter()
+                .map(|tag| Value::String(if tag == old { new.to_string() } else { tag }))
+                .collect();
+            fields.set("tags", renamed);
+            content = frontmatter::replace(&content, &fields)?;
+        }
+
+        let body = frontmatter::strip(&content);
+        let head = &content[..content.len() - body.len()];
+        Ok(format!("{}{}", head, rename_inline_tag(body, old, new)))
+    })
+}
+
+// Renames a tag in frontmatter `tags` and inline `#tags` across the vault. Returns the changes and the
+// notes skipped for their malformed frontmatter.
+pub fn rename_tag(vault: &Vault, old: &str, new: &str) -> io::Result<(Vec<FileChange>, Vec<String>)> {
+    let (changes, plan, failed) = plan_rename_tag(vault, old, new)?;
+    Ok((apply((changes, plan), vault)?, failed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_rename_inline_tag() {
+        let body = "#work and #work/meeting but not #workshop, a#work or [link](#work)\n#work";
+        assert_eq!(
+            rename_inline_tag(body, "work", "job"),
+            "#job and #job/meeting but not #workshop, a#work or [link](#work)\n#job"
+        );
+    }
+
+    #[test]
+    fn test_bulk_edits() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/One.md", vault.path), "---\ntags: [work, home]\n---\nHello #work").unwrap();
+        file_operations::write_to_file(&format!("{}/Two.md", vault.path), "Hello world").unwrap();
+        file_operations::write_to_file(&format!("{}/Broken.md", vault.path), "---\ntags: [work\n---\nHello #work").unwrap();
+
+        let (planned, plan, failed) = plan_rename_tag(&vault, "#work", "job").unwrap();
+        assert_eq!(plan.changes.iter().map(|change| change.path.as_str()).collect::<Vec<_>>(), vec!["One.md"]);
+        assert_eq!(failed, vec!["Broken".to_string()]);
+        assert!(Note::read_note(&vault, "One").unwrap().contains("#work"));
+
+        // Notes with malformed frontmatter are reported instead of stopping the rename
+        let (changes, failed) = rename_tag(&vault, "#work", "job").unwrap();
+        assert_eq!(changes, planned);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(failed, vec!["Broken".to_string()]);
+        assert_eq!(Note::read_note(&vault, "One").unwrap(), "---\ntags:\n- job\n- home\n---\nHello #job");
+        assert!(Note::read_note(&vault, "Broken").unwrap().contains("#work"));
+
+        let changes = replace_in_notes(&vault, "Hello", "Hi").unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].before.as_deref().map(|before| before.contains("Hello")), Some(true));
+        assert!(replace_in_notes(&vault, "", "x").is_err());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,147 @@
+// Calendar data for daily notes, due tasks and dated notes
+use chrono::{Months, NaiveDate};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind};
+
+use crate::feature::journal;
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{frontmatter, message::Message};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub title: String,
+    pub note: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarDay {
+    pub date: String,
+    pub daily_note: Option<String>,
+    pub tasks_due: usize,
+    pub events: Vec<CalendarEvent>,
+}
+
+// Returns the due dates of the open tasks of a note (`📅 2024-05-03` or `due: 2024-05-03`).
+pub fn due_dates(content: &str) -> Vec<NaiveDate> {
+    let task_re = Regex::new(r"^\s*[-*+]\s+\[ \]\s+(.*)$").unwrap();
+    let due_re = Regex::new(r"(?:📅|\bdue::?)\s*(\d{4}-\d{2}-\d{2})").unwrap();
+    content
+        .lines()
+        .filter_map(|line| task_re.captures(line))
+        .filter_map(|task| due_re.captures(&task[1]).map(|due| due[1].to_string()))
+        .filter_map(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok())
+        .collect()
+}
+
+fn parse_date(value: &Value) -> Option<NaiveDate> {
+    let text = value.as_str()?;
+    NaiveDate::parse_from_str(text.get(..10)?, "%Y-%m-%d").ok()
+}
+
+// Reads the events of a note: its `date` field, plus any `events` entries with a `date` and `title`.
+pub fn frontmatter_events(title: &str, content: &str) -> Vec<(NaiveDate, CalendarEvent)> {
+    let Ok(fields) = frontmatter::parse(content) else {
+        return Vec::new();
+    };
+    let mut events = Vec::new();
+    if let Some(date) = fields.get("date").and_then(parse_date) {
+        let name = fields.get("title").and_then(Value::as_str).unwrap_or(title);
+        events.push((date, CalendarEvent { title: name.to_string(), note: title.to_string() }));
+    }
+    if let Some(Value::Sequence(entries)) = fields.get("events") {
+        for entry in entries {
+            if let Some(date) = entry.get("date").and_then(parse_date) {
+                let name = entry.get("title").and_then(Value::as_str).unwrap_or(title);
+                events.push((date, CalendarEvent { title: name.to_string(), note: title.to_string() }));
+            }
+        }
+    }
+    events
+}
+
+// Builds the calendar of a month (`YYYY-MM`), one entry per day.
+pub fn get_calendar(vault: &Vault, month: &str) -> io::Result<Vec<CalendarDay>> {
+    let first = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| Message::new("calendar.invalid_month", "Month must be formatted as YYYY-MM").error(ErrorKind::InvalidInput))?;
+    let next = first + Months::new(1);
+
+    let mut days: BTreeMap<NaiveDate, CalendarDay> = first
+        .iter_days()
+        .take_while(|day| *day < next)
+        .map(|day| {
+            let entry = CalendarDay {
+                date: day.to_string(),
+                daily_note: None,
+                tasks_due: 0,
+                events: Vec::new(),
+            };
+            (day, entry)
+        })
+        .collect();
+
+    let daily_notes = journal::load_config(vault)?;
+    for title in Note::list_notes(vault)? {
+        if let Some(day) = journal::daily_note_date(&daily_notes, &title) {
+            if let Some(entry) = days.get_mut(&day) {
+                entry.daily_note = Some(title.clone());
+            }
+        }
+
+        let content = Note::read_note(vault, &title)?;
+        for due in due_dates(&content) {
+            if let Some(entry) = days.get_mut(&due) {
+                entry.tasks_due += 1;
+            }
+        }
+        for (date, event) in frontmatter_events(&title, &content) {
+            if let Some(entry) = days.get_mut(&date) {
+                entry.events.push(event);
+            }
+        }
+    }
+
+    Ok(days.into_values().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_due_dates() {
+        let content = "- [ ] Pay rent 📅 2024-05-03\n- [x] Done due: 2024-05-03\n* [ ] Call due:: 2024-05-10\n- [ ] No date";
+        let dates = due_dates(content);
+        assert_eq!(dates, vec![
+            NaiveDate::from_ymd_opt(2024, 5, 3).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 5, 10).unwrap(),
+        ]);
+    }
+
+    #[test]
+    fn test_get_calendar() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/2024-05-03.md", vault.path), "- [ ] Ship 📅 2024-05-04").unwrap();
+        file_operations::write_to_file(
+            &format!("{}/Conference.md", vault.path),
+            "---\ndate: 2024-05-20\nevents:\n  - date: 2024-05-21\n    title: Workshop\n  - date: 2024-06-01\n---\n",
+        )
+        .unwrap();
+
+        let days = get_calendar(&vault, "2024-05").unwrap();
+        assert_eq!(days.len(), 31);
+        assert_eq!(days[2].daily_note.as_deref(), Some("2024-05-03"));
+        assert_eq!(days[3].tasks_due, 1);
+        assert_eq!(days[19].events, vec![CalendarEvent { title: "Conference".to_string(), note: "Conference".to_string() }]);
+        assert_eq!(days[20].events[0].title, "Workshop");
+        assert!(get_calendar(&vault, "May").is_err());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
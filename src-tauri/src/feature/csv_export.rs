@@ -0,0 +1,130 @@
+// CSV export of note lists: one row per note with its title, path, tags, creation and update dates and
+// frontmatter fields, so the notes a search selects can be analyzed in a spreadsheet.
+use serde_yaml::Value;
+use std::collections::BTreeSet;
+use std::io;
+
+use crate::feature::timeline;
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{frontmatter, markdown};
+
+// Columns every export starts with; frontmatter fields of the same name are not repeated after them.
+pub const COLUMNS: [&str; 5] = ["title", "path", "tags", "created", "updated"];
+
+// Quotes a cell when it holds a separator, a quote or a line break, doubling the quotes inside. Cells
+// starting like a formula get a leading `'`, so spreadsheets show them as text instead of evaluating them.
+fn cell(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) { format!("'{}", value) } else { value.to_string() };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+// A frontmatter value as a single cell: lists are joined with commas and maps written as JSON.
+fn field_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        Value::Sequence(items) => items.iter().map(field_text).collect::<Vec<_>>().join(", "),
+        Value::Mapping(_) => serde_json::to_string(value).unwrap_or_default(),
+        Value::Tagged(tagged) => field_text(&tagged.value),
+    }
+}
+
+// Writes the notes at `paths` as CSV, in that order, keeping the ones that have every tag of `tags`.
+// The fixed columns are followed by `fields`, or by every frontmatter field the kept notes use, sorted,
+// when no fields are given. Dates are `YYYY-MM-DD`, from the frontmatter or else the file times.
+pub fn export_csv(vault: &Vault, paths: &[String], tags: &[String], fields: &[String]) -> io::Result<String> {
+    let mut rows = Vec::new();
+    for path in paths {
+        let content = Note::read_note(vault, path)?;
+        let note_tags = markdown::note_tags(&content);
+        if !tags.iter().all(|tag| note_tags.contains(&tag.trim_start_matches('#').to_string())) {
+            continue;
+        }
+        let properties = frontmatter::parse(&content).unwrap_or_default();
+        let created = timeline::note_date(vault, path, &content, "created")?;
+        let updated = timeline::note_date(vault, path, &content, "updated")?;
+        let title = path.rsplit('/').next().unwrap_or(path).to_string();
+        let fixed = vec![
+            title,
+            path.clone(),
+            note_tags.join(", "),
+            created.map(|date| date.to_string()).unwrap_or_default(),
+            updated.map(|date| date.to_string()).unwrap_or_default(),
+        ];
+        rows.push((fixed, properties));
+    }
+
+    let fields: Vec<String> = if fields.is_empty() {
+        let used: BTreeSet<&str> = rows
+            .iter()
+            .flat_map(|(_, properties)| properties.fields.keys().filter_map(Value::as_str))
+            .filter(|key| !COLUMNS.contains(key))
+            .collect();
+        used.into_iter().map(str::to_string).collect()
+    } else {
+        fields.to_vec()
+    };
+
+    let line = |cells: Vec<String>| cells.iter().map(|value| cell(value)).collect::<Vec<_>>().join(",") + "\r\n";
+    let mut csv = line(COLUMNS.iter().map(|column| column.to_string()).chain(fields.iter().cloned()).collect());
+    for (mut row, properties) in rows {
+        row.extend(fields.iter().map(|field| properties.get(field).map(field_text).unwrap_or_default()));
+        csv.push_str(&line(row));
+    }
+    Ok(csv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_export_csv() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Projects", vault.path)).unwrap();
+        file_operations::write_to_file(
+            &format!("{}/Projects/Plan.md", vault.path),
+            "---\ntags: [work]\ncreated: 2024-05-01\nupdated: 2024-06-02\nstatus: \"done, mostly\"\nowners: [Ada, Grace]\n---\n#urgent plan",
+        )
+        .unwrap();
+        file_operations::write_to_file(
+            &format!("{}/Ideas.md", vault.path),
+            "---\ncreated: 2024-01-10\nupdated: 2024-01-11\npriority: 2\n---\nSay \"hi\"",
+        )
+        .unwrap();
+        let paths = vec!["Projects/Plan".to_string(), "Ideas".to_string()];
+
+        let csv = export_csv(&vault, &paths, &[], &[]).unwrap();
+        assert_eq!(
+            csv,
+            "title,path,tags,created,updated,owners,priority,status\r\n\
+             Plan,Projects/Plan,\"work, urgent\",2024-05-01,2024-06-02,\"Ada, Grace\",,\"done, mostly\"\r\n\
+             Ideas,Ideas,,2024-01-10,2024-01-11,,2,\r\n"
+        );
+
+        // Tag filters keep the notes with every tag; chosen fields replace the ones found
+        let csv = export_csv(&vault, &paths, &["#urgent".to_string()], &["status".to_string(), "missing".to_string()]).unwrap();
+        assert_eq!(
+            csv,
+            "title,path,tags,created,updated,status,missing\r\n\
+             Plan,Projects/Plan,\"work, urgent\",2024-05-01,2024-06-02,\"done, mostly\",\r\n"
+        );
+        assert_eq!(cell("Say \"hi\""), "\"Say \"\"hi\"\"\"");
+        assert_eq!(cell("=HYPERLINK(\"http://x\")"), "\"'=HYPERLINK(\"\"http://x\"\")\"");
+        assert_eq!(cell("-1"), "'-1");
+        assert_eq!(cell("@user"), "'@user");
+        assert_eq!(export_csv(&vault, &paths, &["missing".to_string()], &[]).unwrap(), "title,path,tags,created,updated\r\n");
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,170 @@
+// Duplicate and near-duplicate note detection
+use serde::{Serialize, Deserialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io;
+
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::string_utils;
+
+const SHINGLE_SIZE: usize = 3;
+const SIGNATURE_SIZE: usize = 64;
+pub const DEFAULT_SIMILARITY_THRESHOLD: f64 = 0.8;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateCluster {
+    pub notes: Vec<String>,
+    pub similarity: f64,
+    pub exact: bool,
+    pub suggested_keep: String,
+}
+
+// Scans every note of a vault and reports clusters of duplicate notes.
+pub fn find_duplicates(vault: &Vault, threshold: f64) -> io::Result<Vec<DuplicateCluster>> {
+    let notes = Note::list_notes(vault)?
+        .into_iter()
+        .map(|title| Note::read_note(vault, &title).map(|content| (title, content)))
+        .collect::<io::Result<Vec<_>>>()?;
+    Ok(cluster_notes(&notes, threshold))
+}
+
+// Groups `(title, content)` pairs whose estimated similarity reaches the threshold.
+pub fn cluster_notes(notes: &[(String, String)], threshold: f64) -> Vec<DuplicateCluster> {
+    let normalized: Vec<String> = notes
+        .iter()
+        .map(|(_, content)| string_utils::normalize_whitespace(&content.to_lowercase()))
+        .collect();
+    let signatures: Vec<Vec<u64>> = normalized.iter().map(|text| signature(text)).collect();
+
+    let mut parents: Vec<usize> = (0..notes.len()).collect();
+    let mut edges = Vec::new();
+    for i in 0..notes.len() {
+        for j in (i + 1)..notes.len() {
+            let similarity = if normalized[i] == normalized[j] {
+                1.0
+            } else {
+                estimate_similarity(&signatures[i], &signatures[j])
+            };
+            if similarity >= threshold {
+                let (a, b) = (find_root(&mut parents, i), find_root(&mut parents, j));
+                parents[b] = a;
+                edges.push((i, j, similarity));
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..notes.len() {
+        let root = find_root(&mut parents, i);
+        groups.entry(root).or_default().push(i);
+    }
+
+    let mut clusters: Vec<DuplicateCluster> = groups
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(root, members)| {
+            let cluster_edges: Vec<f64> = edges
+                .iter()
+                .filter(|(i, _, _)| find_root(&mut parents, *i) == root)
+                .map(|(_, _, similarity)| *similarity)
+                .collect();
+            let similarity = cluster_edges.iter().sum::<f64>() / cluster_edges.len() as f64;
+            let exact = members.iter().all(|&i| normalized[i] == normalized[members[0]]);
+            // Keep the most complete note and merge the rest into it
+            let keep = members
+                .iter()
+                .copied()
+                .max_by_key(|&i| notes[i].1.len())
+                .unwrap_or(members[0]);
+
+            DuplicateCluster {
+                notes: members.iter().map(|&i| notes[i].0.clone()).collect(),
+                similarity,
+                exact,
+                suggested_keep: notes[keep].0.clone(),
+            }
+        })
+        .collect();
+
+    clusters.sort_by(|a, b| b.similarity.total_cmp(&a.similarity).then_with(|| a.notes.cmp(&b.notes)));
+    clusters
+}
+
+// Computes the MinHash signature of the word shingles of a text.
+fn signature(text: &str) -> Vec<u64> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let shingles: Vec<String> = if words.len() < SHINGLE_SIZE {
+        vec![words.join(" ")]
+    } else {
+        words.windows(SHINGLE_SIZE).map(|window| window.join(" ")).collect()
+    };
+
+    (0..SIGNATURE_SIZE)
+        .map(|seed| {
+            shingles
+                .iter()
+                .map(|shingle| {
+                    let mut hasher = DefaultHasher::new();
+                    seed.hash(&mut hasher);
+                    shingle.hash(&mut hasher);
+                    hasher.finish()
+                })
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+// Estimates the Jaccard similarity of two MinHash signatures.
+fn estimate_similarity(a: &[u64], b: &[u64]) -> f64 {
+    let matching = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    matching as f64 / SIGNATURE_SIZE as f64
+}
+
+fn find_root(parents: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    parents[i] = root;
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(title: &str, content: &str) -> (String, String) {
+        (title.to_string(), content.to_string())
+    }
+
+    #[test]
+    fn test_exact_duplicates() {
+        let notes = vec![
+            note("a", "The quick brown fox jumps over the lazy dog"),
+            note("b", "The quick  brown fox\njumps over the lazy dog"),
+            note("c", "Something entirely different lives in this note"),
+        ];
+        let clusters = cluster_notes(&notes, DEFAULT_SIMILARITY_THRESHOLD);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].notes, vec!["a", "b"]);
+        assert!(clusters[0].exact);
+        assert_eq!(clusters[0].similarity, 1.0);
+    }
+
+    #[test]
+    fn test_near_duplicates() {
+        let base = "one two three four five six seven eight nine ten eleven twelve thirteen fourteen fifteen sixteen seventeen eighteen nineteen twenty";
+        let notes = vec![
+            note("draft", base),
+            note("final", &format!("{} twentyone", base)),
+            note("other", "alpha beta gamma delta epsilon zeta eta theta"),
+        ];
+        let clusters = cluster_notes(&notes, 0.7);
+        assert_eq!(clusters.len(), 1);
+        assert!(!clusters[0].exact);
+        assert!(clusters[0].similarity < 1.0);
+        assert_eq!(clusters[0].suggested_keep, "final");
+    }
+}
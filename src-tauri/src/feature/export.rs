@@ -0,0 +1,315 @@
+// Static HTML site export, laid out with the site templates (built-in or from the vault)
+use ammonia::clean_text;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use tera::{Context, Tera};
+
+use crate::feature::site_templates::{self, INDEX_TEMPLATE, PAGE_TEMPLATE, TAG_TEMPLATE};
+use crate::storage::{attachment::{self, AttachmentKind}, note::Note, vault::Vault};
+use crate::utils::{file_operations, frontmatter, markdown};
+
+const TAGS_DIR: &str = "tags";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportReport {
+    pub output_dir: String,
+    pub pages: Vec<String>,
+    // Attachments copied next to the pages, relative to the output folder
+    pub attachments: Vec<String>,
+    // One page per tag of the exported notes, listing its notes
+    #[serde(default)]
+    pub tag_pages: Vec<String>,
+}
+
+// A link as passed to the site templates; both values are HTML-escaped.
+#[derive(Debug, Clone, Serialize)]
+struct PageLink {
+    title: String,
+    href: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TagLink {
+    name: String,
+    href: String,
+    count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FolderLinks {
+    name: String,
+    pages: Vec<PageLink>,
+}
+
+// Encodes a site-relative path for use in a link.
+fn href(path: &str) -> String {
+    path.replace('%', "%25").replace(' ', "%20").replace('#', "%23")
+}
+
+// Renders the given notes into a static HTML site with an index page.
+// Wikilinks between exported notes become relative `.html` links; links to other notes become plain text.
+// `[[Name]]` resolves to the exported note with that file name when no note has that exact path.
+// Attachments the notes link to or embed are copied into the site with the same layout as in the vault.
+pub fn export_site(vault: &Vault, titles: &[String], output_dir: &str) -> io::Result<ExportReport> {
+    // Broken custom templates fail the export before anything is written
+    let (tera, _) = site_templates::load(vault)?;
+    file_operations::create_directory(output_dir)?;
+    let mut by_name: HashMap<String, &str> = HashMap::new();
+    for title in titles {
+        let name = title.rsplit('/').next().unwrap_or(title).to_lowercase();
+        by_name.entry(name).or_insert(title);
+    }
+    let resolve = |target: &str| -> Option<&str> {
+        let target = markdown::link_target(target);
+        titles.iter().find(|title| *title == target).map(String::as_str).or_else(|| by_name.get(&target.to_lowercase()).copied())
+    };
+    let attachments: HashMap<String, String> = attachment::list_attachments(vault)?
+        .into_iter()
+        .map(|attachment| (attachment.name, attachment.path))
+        .collect();
+
+    let mut pages = Vec::new();
+    let mut copied = BTreeSet::new();
+    let mut tagged: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+    for title in titles {
+        let content = Note::read_note(vault, title)?;
+        let tags = markdown::note_tags(&content);
+        for tag in &tags {
+            tagged.entry(tag.clone()).or_default().push(title);
+        }
+        let content = markdown::resolve_embeds(frontmatter::strip(&content), |target| Note::read_note(vault, target).ok());
+        // Pages of notes in folders are written in matching subfolders of the site
+        let root = "../".repeat(title.matches('/').count());
+        let body = embed_attachments(&content, &attachments, &root);
+        let body = markdown::rewrite_wikilinks(&body, |target, label| match resolve(target) {
+            Some(page) => format!("[{}]({}{}.html)", label, root, href(page)),
+            None => label.to_string(),
+        });
+
+        for target in markdown::extract_local_targets(&body) {
+            let name = target.rsplit('/').next().unwrap_or_default();
+            if let Some(path) = attachments.get(name) {
+                copied.insert(path.clone());
+            }
+        }
+
+        let tags: Vec<TagLink> = tags.iter().map(|tag| tag_link(tag, &root, 0)).collect();
+        let mut context = page_context(title, &root);
+        context.insert("content", &markdown::render_markdown(&body));
+        context.insert("tags", &tags);
+        let html = site_templates::render(&tera, PAGE_TEMPLATE, &context)?;
+        let page = format!("{}.html", title);
+        if let Some((folder, _)) = title.rsplit_once('/') {
+            file_operations::create_directory(&format!("{}/{}", output_dir, folder))?;
+        }
+        file_operations::write_to_file(&format!("{}/{}", output_dir, page), &html)?;
+        pages.push(page);
+    }
+
+    for path in &copied {
+        if let Some((folder, _)) = path.rsplit_once('/') {
+            file_operations::create_directory(&format!("{}/{}", output_dir, folder))?;
+        }
+        fs::copy(
+            file_operations::resolve_path(&format!("{}/{}", vault.path, path)),
+            file_operations::resolve_path(&format!("{}/{}", output_dir, path)),
+        )?;
+    }
+
+    let mut tag_pages = Vec::new();
+    for (tag, notes) in &tagged {
+        let page = format!("{}/{}.html", TAGS_DIR, tag);
+        let root = "../".repeat(page.matches('/').count());
+        let mut context = page_context(&format!("#{}", tag), &root);
+        context.insert("tag", &clean_text(tag));
+        context.insert("pages", &notes.iter().map(|note| page_link(note, &root)).collect::<Vec<_>>());
+        let html = site_templates::render(&tera, TAG_TEMPLATE, &context)?;
+        if let Some((folder, _)) = page.rsplit_once('/') {
+            file_operations::create_directory(&format!("{}/{}", output_dir, folder))?;
+        }
+        file_operations::write_to_file(&format!("{}/{}", output_dir, page), &html)?;
+        tag_pages.push(page);
+    }
+
+    let tags: Vec<TagLink> = tagged.iter().map(|(tag, notes)| tag_link(tag, "", notes.len())).collect();
+    let html = render_index(&tera, titles, &tags)?;
+    file_operations::write_to_file(&format!("{}/index.html", output_dir), &html)?;
+
+    Ok(ExportReport {
+        output_dir: output_dir.to_string(),
+        pages,
+        attachments: copied.into_iter().collect(),
+        tag_pages,
+    })
+}
+
+// Values every site template gets: the page title and the way back to the site root.
+fn page_context(title: &str, root: &str) -> Context {
+    let mut context = Context::new();
+    context.insert("title", &clean_text(title));
+    context.insert("root", root);
+    context
+}
+
+fn page_link(title: &str, root: &str) -> PageLink {
+    let name = title.rsplit('/').next().unwrap_or(title);
+    PageLink { title: clean_text(name), href: format!("{}{}.html", root, href(title)) }
+}
+
+fn tag_link(tag: &str, root: &str, count: usize) -> TagLink {
+    TagLink { name: clean_text(tag), href: format!("{}{}/{}.html", root, TAGS_DIR, href(tag)), count }
+}
+
+// Exports every note of the vault as a static site.
+pub fn export_vault(vault: &Vault, output_dir: &str) -> io::Result<ExportReport> {
+    let titles = Note::list_notes(vault)?;
+    export_site(vault, &titles, output_dir)
+}
+
+// Turns `![[file.png]]` attachment embeds into images (or links for other files) pointing at the copy
+// of the attachment in the site.
+fn embed_attachments(content: &str, attachments: &HashMap<String, String>, root: &str) -> String {
+    let re = Regex::new(r"!\[\[([^\]|#]+\.[^\]|#/]+)(?:\|([^\]]*))?\]\]").unwrap();
+    re.replace_all(content, |caps: &regex::Captures| {
+        let name = caps[1].trim().rsplit('/').next().unwrap_or_default().to_string();
+        let Some(path) = attachments.get(&name) else {
+            return caps[0].to_string();
+        };
+        let label = caps.get(2).map(|label| label.as_str().trim()).filter(|label| !label.is_empty()).unwrap_or(&name);
+        match AttachmentKind::from_name(&name) {
+            AttachmentKind::Image => format!("![{}]({}{})", label, root, href(path)),
+            _ => format!("[{}]({}{})", label, root, href(path)),
+        }
+    })
+    .to_string()
+}
+
+// Renders the index page: the pages grouped under their folders, then the tags.
+fn render_index(tera: &Tera, titles: &[String], tags: &[TagLink]) -> io::Result<String> {
+    let split = |title: &str| title.rsplit_once('/').unwrap_or(("", title)).0.to_string();
+    let mut sorted: Vec<&String> = titles.iter().collect();
+    sorted.sort_by_key(|title| (split(title), title.to_lowercase()));
+
+    let mut folders: Vec<FolderLinks> = Vec::new();
+    for title in sorted {
+        let name = clean_text(&split(title));
+        if folders.last().is_none_or(|folder| folder.name != name) {
+            folders.push(FolderLinks { name, pages: Vec::new() });
+        }
+        if let Some(folder) = folders.last_mut() {
+            folder.pages.push(page_link(title, ""));
+        }
+    }
+
+    let mut context = page_context("Index", "");
+    context.insert("folders", &folders);
+    context.insert("tags", tags);
+    site_templates::render(tera, INDEX_TEMPLATE, &context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_export_site() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/First.md", vault.path), "---\npublish: true\n---\nSee [[Second]] and [[Private]].\n\n![[Second#Details]]").unwrap();
+        file_operations::write_to_file(&format!("{}/Second.md", vault.path), "# Second\nIntro\n## Details\nOnly this").unwrap();
+        let output_dir = format!("{}/site", vault.path);
+
+        let titles = vec!["First".to_string(), "Second".to_string()];
+        let report = export_site(&vault, &titles, &output_dir).unwrap();
+        assert_eq!(report.pages, vec!["First.html", "Second.html"]);
+
+        let first = file_operations::read_from_file(&format!("{}/First.html", output_dir)).unwrap();
+        assert!(first.contains("href=\"Second.html\""));
+        assert!(first.contains("and Private."));
+        assert!(!first.contains("publish: true"));
+        // Section embeds only include the embedded section
+        assert!(first.contains("<h2>Details</h2>\n<p>Only this</p>"));
+        assert!(!first.contains("Intro"));
+        let index = file_operations::read_from_file(&format!("{}/index.html", output_dir)).unwrap();
+        assert!(index.contains("<a href=\"First.html\">First</a>"));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_export_vault() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Projects", vault.path)).unwrap();
+        let photo = attachment::save_attachment(&vault, "my photo.png", b"png").unwrap();
+        attachment::save_attachment(&vault, "unused.pdf", b"pdf").unwrap();
+        file_operations::write_to_file(&format!("{}/Home.md", vault.path), "Working on [[plan#Goals|the plan]].").unwrap();
+        file_operations::write_to_file(
+            &format!("{}/Projects/Plan.md", vault.path),
+            &format!("## Goals\n![[my photo.png]] back [[Home]]\n\n[copy]({})", photo.replace(' ', "%20")),
+        )
+        .unwrap();
+        let output_dir = format!("{}/site", vault.path);
+
+        let report = export_vault(&vault, &output_dir).unwrap();
+        assert_eq!(report.pages, vec!["Home.html", "Projects/Plan.html"]);
+        assert_eq!(report.attachments, vec!["attachments/my photo.png"]);
+        assert!(std::path::Path::new(&format!("{}/attachments/my photo.png", output_dir)).exists());
+        assert!(!std::path::Path::new(&format!("{}/attachments/unused.pdf", output_dir)).exists());
+
+        let home = file_operations::read_from_file(&format!("{}/Home.html", output_dir)).unwrap();
+        assert!(home.contains("<a href=\"Projects/Plan.html\" rel=\"noopener noreferrer\">the plan</a>"));
+        let plan = file_operations::read_from_file(&format!("{}/Projects/Plan.html", output_dir)).unwrap();
+        assert!(plan.contains("<nav><a href=\"../index.html\">Index</a></nav>"));
+        assert!(plan.contains("<img src=\"../attachments/my%20photo.png\" alt=\"my photo.png\""));
+        assert!(plan.contains("href=\"../Home.html\""));
+
+        let index = file_operations::read_from_file(&format!("{}/index.html", output_dir)).unwrap();
+        assert!(index.contains("<h2>Projects</h2>\n<ul>\n<li><a href=\"Projects/Plan.html\">Plan</a></li>"));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_export_tags_and_custom_templates() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Trip.md", vault.path), "Rome #travel #places/italy").unwrap();
+        file_operations::write_to_file(&format!("{}/Food.md", vault.path), "Pasta #travel").unwrap();
+        let templates_dir = format!("{}/.templates/site", vault.path);
+        file_operations::create_directory(&templates_dir).unwrap();
+        file_operations::write_to_file(
+            &format!("{}/tag.html", templates_dir),
+            "{% block content %}{{ tag }}:{% for page in pages %} {{ page.href }}{% endfor %}{% endblock %}",
+        )
+        .unwrap();
+        let output_dir = format!("{}/site", vault.path);
+
+        let report = export_vault(&vault, &output_dir).unwrap();
+        assert_eq!(report.tag_pages, vec!["tags/places/italy.html", "tags/travel.html"]);
+        let travel = file_operations::read_from_file(&format!("{}/tags/travel.html", output_dir)).unwrap();
+        assert_eq!(travel, "travel: ../Food.html ../Trip.html");
+        let italy = file_operations::read_from_file(&format!("{}/tags/places/italy.html", output_dir)).unwrap();
+        assert_eq!(italy, "places&#47;italy: ../../Trip.html");
+
+        // Built-in templates still lay out the other pages
+        let trip = file_operations::read_from_file(&format!("{}/Trip.html", output_dir)).unwrap();
+        assert!(trip.contains("<a href=\"tags/travel.html\">#travel</a>"));
+        let index = file_operations::read_from_file(&format!("{}/index.html", output_dir)).unwrap();
+        assert!(index.contains("<li><a href=\"tags/travel.html\">#travel</a> (2)</li>"));
+
+        // A template missing its content block stops the export
+        file_operations::write_to_file(&format!("{}/page.html", templates_dir), "{{ content }}").unwrap();
+        let error = export_vault(&vault, &format!("{}/site2", vault.path)).unwrap_err();
+        assert!(error.to_string().contains("page.html is missing the `content` block"));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
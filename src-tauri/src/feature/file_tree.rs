@@ -0,0 +1,200 @@
+// Folder hierarchy of a vault with sorted and grouped notes for the sidebar
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, markdown};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortBy {
+    #[default]
+    Name,
+    Created,
+    Updated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    Tag,
+    Letter,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TreeNote {
+    pub title: String,
+    // Path relative to the vault, without the `.md` extension
+    pub path: String,
+    pub created: Option<String>,
+    pub updated: Option<String>,
+    #[serde(skip)]
+    tags: Vec<String>,
+}
+
+// A group of notes within a folder; `label` is `None` for notes without a tag.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteGroup {
+    pub label: Option<String>,
+    pub notes: Vec<TreeNote>,
+}
+
+// When the tree is grouped, the notes of each folder are in `groups` and `notes` is empty.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TreeFolder {
+    pub name: String,
+    pub path: String,
+    pub folders: Vec<TreeFolder>,
+    pub notes: Vec<TreeNote>,
+    pub groups: Vec<NoteGroup>,
+}
+
+fn timestamp(time: io::Result<SystemTime>) -> Option<String> {
+    time.ok().map(|time| DateTime::<Utc>::from(time).to_rfc3339())
+}
+
+fn sort_notes(notes: &mut [TreeNote], sort: SortBy) {
+    match sort {
+        SortBy::Name => notes.sort_by_key(|note| note.title.to_lowercase()),
+        // Newest first
+        SortBy::Created => notes.sort_by(|a, b| b.created.cmp(&a.created)),
+        SortBy::Updated => notes.sort_by(|a, b| b.updated.cmp(&a.updated)),
+    }
+}
+
+fn group_notes(notes: Vec<TreeNote>, group_by: GroupBy) -> Vec<NoteGroup> {
+    let mut groups: BTreeMap<Option<String>, Vec<TreeNote>> = BTreeMap::new();
+    for note in notes {
+        match group_by {
+            GroupBy::Letter => {
+                let letter = note
+                    .title
+                    .chars()
+                    .next()
+                    .filter(|c| c.is_alphanumeric())
+                    .map(|c| c.to_uppercase().to_string())
+                    .unwrap_or_else(|| "#".to_string());
+                groups.entry(Some(letter)).or_default().push(note);
+            }
+            GroupBy::Tag if note.tags.is_empty() => groups.entry(None).or_default().push(note),
+            GroupBy::Tag => {
+                for tag in note.tags.clone() {
+                    groups.entry(Some(tag)).or_default().push(note.clone());
+                }
+            }
+        }
+    }
+
+    // Tagged groups first, untagged notes last
+    let untagged = groups.remove(&None);
+    let mut groups: Vec<NoteGroup> = groups.into_iter().map(|(label, notes)| NoteGroup { label, notes }).collect();
+    groups.extend(untagged.map(|notes| NoteGroup { label: None, notes }));
+    groups
+}
+
+// Only grouping by tag needs the content of the note.
+fn read_note(root: &Path, path: &Path, group_by: Option<GroupBy>) -> io::Result<TreeNote> {
+    let file = std::fs::metadata(path)?;
+    let relative = path.strip_prefix(root).unwrap_or(path).with_extension("");
+    let tags = match group_by {
+        Some(GroupBy::Tag) => markdown::note_tags(&std::fs::read_to_string(path)?),
+        _ => Vec::new(),
+    };
+
+    Ok(TreeNote {
+        title: path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+        path: relative.to_string_lossy().replace('\\', "/"),
+        created: timestamp(file.created()),
+        updated: timestamp(file.modified()),
+        tags,
+    })
+}
+
+fn build_folder(root: &Path, dir: &Path, sort: SortBy, group_by: Option<GroupBy>) -> io::Result<TreeFolder> {
+    let mut folders = Vec::new();
+    let mut notes = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        // Hidden folders hold app data (metadata, templates, config)
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            folders.push(build_folder(root, &path, sort, group_by)?);
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            notes.push(read_note(root, &path, group_by)?);
+        }
+    }
+
+    folders.sort_by_key(|folder: &TreeFolder| folder.name.to_lowercase());
+    sort_notes(&mut notes, sort);
+    let groups = match group_by {
+        Some(group_by) => group_notes(std::mem::take(&mut notes), group_by),
+        None => Vec::new(),
+    };
+
+    Ok(TreeFolder {
+        name: dir.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        path: dir.strip_prefix(root).unwrap_or(dir).to_string_lossy().replace('\\', "/"),
+        folders,
+        notes,
+        groups,
+    })
+}
+
+// Builds the folder hierarchy of a vault, with the notes of each folder sorted and optionally grouped.
+pub fn get_file_tree(vault: &Vault, sort: SortBy, group_by: Option<GroupBy>) -> io::Result<TreeFolder> {
+    let root = file_operations::resolve_path(&vault.path);
+    let root = Path::new(&root);
+    let mut tree = build_folder(root, root, sort, group_by)?;
+    tree.name = vault.name.clone();
+    Ok(tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_get_file_tree() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/beta.md", vault.path), "#rust #web").unwrap();
+        file_operations::write_to_file(&format!("{}/Alpha.md", vault.path), "---\ntags: [rust]\n---\n").unwrap();
+        file_operations::write_to_file(&format!("{}/2024.md", vault.path), "No tags").unwrap();
+        file_operations::create_directory(&format!("{}/Projects/Web", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Projects/Web/Site.md", vault.path), "").unwrap();
+        file_operations::create_directory(&format!("{}/.templates", vault.path)).unwrap();
+
+        let tree = get_file_tree(&vault, SortBy::Name, None).unwrap();
+        let titles: Vec<&str> = tree.notes.iter().map(|note| note.title.as_str()).collect();
+        assert_eq!(titles, vec!["2024", "Alpha", "beta"]);
+        assert_eq!(tree.folders.len(), 1);
+        assert_eq!(tree.folders[0].folders[0].path, "Projects/Web");
+        assert_eq!(tree.folders[0].folders[0].notes[0].path, "Projects/Web/Site");
+
+        let tree = get_file_tree(&vault, SortBy::Name, Some(GroupBy::Tag)).unwrap();
+        assert!(tree.notes.is_empty());
+        let labels: Vec<Option<&str>> = tree.groups.iter().map(|group| group.label.as_deref()).collect();
+        assert_eq!(labels, vec![Some("rust"), Some("web"), None]);
+        assert_eq!(tree.groups[0].notes.len(), 2);
+
+        let tree = get_file_tree(&vault, SortBy::Name, Some(GroupBy::Letter)).unwrap();
+        let labels: Vec<Option<&str>> = tree.groups.iter().map(|group| group.label.as_deref()).collect();
+        assert_eq!(labels, vec![Some("2"), Some("A"), Some("B")]);
+
+        // Contents are only read to group by tag, so a note that is not UTF-8 is still listed
+        std::fs::write(file_operations::resolve_path(&format!("{}/Binary.md", vault.path)), [0xff, 0xfe]).unwrap();
+        assert_eq!(get_file_tree(&vault, SortBy::Name, None).unwrap().notes.len(), 4);
+        assert!(get_file_tree(&vault, SortBy::Name, Some(GroupBy::Tag)).is_err());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
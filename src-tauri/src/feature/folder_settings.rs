@@ -0,0 +1,187 @@
+// Folder-level overrides for new notes: the template they start from and their default tags
+use chrono::Local;
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Error, ErrorKind};
+
+use crate::feature::{refactor, snippets, templates::TEMPLATES_DIR};
+use crate::storage::{note::{Note, OnConflict}, vault::Vault};
+use crate::utils::{file_operations, frontmatter, error::AppError, message::Message};
+
+const CONFIG_DIR: &str = ".config";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FolderSettings {
+    // Template file name within `.templates/`, used when the new note has no content
+    pub template: Option<String>,
+    // Tags added to the frontmatter of every note created in the folder
+    pub tags: Vec<String>,
+}
+
+impl FolderSettings {
+    fn is_empty(&self) -> bool {
+        self.template.is_none() && self.tags.is_empty()
+    }
+}
+
+fn folders_path(vault: &Vault) -> String {
+    format!("{}/{}/folders.json", vault.path, CONFIG_DIR)
+}
+
+// Loads the folder overrides of a vault, keyed by vault-relative folder path ("" for the root).
+pub fn load_folder_settings(vault: &Vault) -> io::Result<BTreeMap<String, FolderSettings>> {
+    match file_operations::read_from_file(&folders_path(vault)) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| Error::new(ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e),
+    }
+}
+
+// Sets the overrides of a folder; empty settings remove them.
+pub fn set_folder_settings(vault: &Vault, folder: &str, settings: FolderSettings) -> io::Result<()> {
+    if let Some(template) = &settings.template {
+        if template.is_empty() || template.contains(['/', '\\']) || template.starts_with('.') {
+            return Err(AppError::InvalidName(Message::new("template.invalid_name", "Template must be a file name in .templates")).into());
+        }
+    }
+
+    let mut folders = load_folder_settings(vault)?;
+    let folder = refactor::clean_path(folder);
+    if settings.is_empty() {
+        folders.remove(&folder);
+    } else {
+        folders.insert(folder, settings);
+    }
+    file_operations::create_directory(&format!("{}/{}", vault.path, CONFIG_DIR))?;
+    let json = serde_json::to_string_pretty(&folders).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    file_operations::write_to_file(&folders_path(vault), &json)
+}
+
+// Resolves the settings of a folder; subfolders inherit every field they do not set from their parents,
+// and folders without a template use the vault's default template.
+pub fn settings_for(vault: &Vault, folder: &str) -> io::Result<FolderSettings> {
+    let folders = load_folder_settings(vault)?;
+    let folder = refactor::clean_path(folder);
+    let mut resolved = FolderSettings::default();
+    let mut current = Some(folder.as_str());
+    while let Some(path) = current {
+        if let Some(settings) = folders.get(path) {
+            if resolved.template.is_none() {
+                resolved.template = settings.template.clone();
+            }
+            for tag in &settings.tags {
+                if !resolved.tags.contains(tag) {
+                    resolved.tags.push(tag.clone());
+                }
+            }
+        }
+        current = match path {
+            "" => None,
+            _ => Some(path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("")),
+        };
+    }
+    if resolved.template.is_none() {
+        resolved.template = vault.config()?.default_template;
+    }
+    Ok(resolved)
+}
+
+// Fills empty content from the folder template and merges the default tags into the frontmatter.
+pub fn apply(
+    vault: &Vault,
+    settings: &FolderSettings,
+    title: &str,
+    folder: &str,
+    content: &str,
+) -> io::Result<String> {
+    let mut content = content.to_string();
+    if let (Some(template), true) = (&settings.template, content.trim().is_empty()) {
+        let template = file_operations::read_from_file(&format!("{}/{}/{}", vault.path, TEMPLATES_DIR, template))?;
+        let variables = HashMap::from([
+            ("title".to_string(), title.to_string()),
+            ("folder".to_string(), folder.to_string()),
+        ]);
+        content = snippets::render(&template, &variables, Local::now()).text;
+    }
+    if settings.tags.is_empty() {
+        return Ok(content);
+    }
+
+    let mut fields = frontmatter::parse(&content)?;
+    let mut tags = fields.tags();
+    for tag in &settings.tags {
+        let tag = tag.trim_start_matches('#');
+        if !tags.iter().any(|existing| existing == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    fields.set("tags", tags);
+    frontmatter::replace(&content, &fields)
+}
+
+// Creates a note inside a folder with the overrides of that folder applied. The file is named after
+// the title, or gets an `untitled_` id when there is none; it is never named after the content, so the
+// folder template or the vault's default template only fill the note and never decide its name.
+// Returns the vault-relative path.
+pub fn create_note(vault: &Vault, folder: &str, note: &Note, on_conflict: OnConflict) -> io::Result<String> {
+    let folder = refactor::clean_path(folder);
+    let file_name = Note::file_name_for(&note.title);
+    let path = if folder.is_empty() { file_name } else { format!("{}/{}", folder, file_name) };
+    let path = Note::free_path(vault, &path, on_conflict)?;
+    let note_path = format!("{}/{}.md", vault.path, path);
+
+    let settings = settings_for(vault, &folder)?;
+    let content = apply(vault, &settings, &note.title, &folder, &note.content)?;
+    file_operations::create_directory(&format!("{}/{}", vault.path, folder))?;
+    file_operations::write_to_file(&note_path, &content)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_create_note_with_folder_settings() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/{}", vault.path, TEMPLATES_DIR)).unwrap();
+        file_operations::write_to_file(&format!("{}/{}/meeting.md", vault.path, TEMPLATES_DIR), "# {{title}}\n## Attendees\n").unwrap();
+
+        let meetings = FolderSettings { template: Some("meeting.md".to_string()), tags: vec!["meeting".to_string()] };
+        set_folder_settings(&vault, "Work/Meetings", meetings).unwrap();
+        set_folder_settings(&vault, "Work", FolderSettings { template: None, tags: vec!["work".to_string()] }).unwrap();
+        assert!(set_folder_settings(&vault, "Work", FolderSettings { template: Some("../x".to_string()), tags: Vec::new() }).is_err());
+
+        let resolved = settings_for(&vault, "Work/Meetings/2024").unwrap();
+        assert_eq!(resolved.template.as_deref(), Some("meeting.md"));
+        assert_eq!(resolved.tags, vec!["meeting", "work"]);
+
+        let path = create_note(&vault, "Work/Meetings", &Note::new("Standup", ""), OnConflict::Fail).unwrap();
+        assert_eq!(path, "Work/Meetings/Standup");
+        let content = file_operations::read_from_file(&format!("{}/{}.md", vault.path, path)).unwrap();
+        assert_eq!(content, "---\ntags:\n- meeting\n- work\n---\n# Standup\n## Attendees\n");
+        let standup = Note::new("Standup", "");
+        assert_eq!(create_note(&vault, "Work/Meetings", &standup, OnConflict::Fail).unwrap_err().kind(), ErrorKind::AlreadyExists);
+        assert_eq!(create_note(&vault, "Work/Meetings", &standup, OnConflict::Suffix).unwrap(), "Work/Meetings/Standup-2");
+
+        // The title names the note, not the first words of its content
+        let retro = Note::new("Retro", "Went well: shipping");
+        assert_eq!(create_note(&vault, "Work/Meetings", &retro, OnConflict::Fail).unwrap(), "Work/Meetings/Retro");
+
+        // Written content wins over the template, tags are still merged
+        let path = create_note(&vault, "Work", &Note::new("", "---\ntags: [work, plan]\n---\nQ3 plan"), OnConflict::Fail).unwrap();
+        assert!(path.starts_with("Work/untitled_"));
+        let content = file_operations::read_from_file(&format!("{}/{}.md", vault.path, path)).unwrap();
+        assert_eq!(frontmatter::parse(&content).unwrap().tags(), vec!["work", "plan"]);
+        assert!(content.ends_with("Q3 plan"));
+
+        set_folder_settings(&vault, "Work", FolderSettings::default()).unwrap();
+        assert_eq!(load_folder_settings(&vault).unwrap().len(), 1);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,399 @@
+// Syncing a vault between machines through a git remote: the vault folder is the work tree, every
+// save can be committed, and pull/push exchange commits with the `origin` remote.
+use git2::{
+    build::CheckoutBuilder, Cred, CredentialType, ErrorClass, ErrorCode, FetchOptions, IndexAddOption, MergeAnalysis,
+    Oid, PushOptions, RemoteCallbacks, Repository, RepositoryInitOptions, Signature,
+};
+use serde::{Serialize, Deserialize};
+use std::cell::{Cell, RefCell};
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, message::Message};
+
+const CONFIG_DIR: &str = ".config";
+pub const REMOTE: &str = "origin";
+// Local indexes and caches are rebuilt on every machine and stay out of the repository
+const GITIGNORE: &str = ".meta/\n.cache/\n.trash/\n";
+// Authentication is retried by libgit2 until it gives up; stop asking after a few attempts
+const MAX_CREDENTIAL_ATTEMPTS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    pub branch: String,
+    // Commit the vault every time a note is saved
+    pub auto_commit: bool,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self { branch: "main".to_string(), auto_commit: false }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PullOutcome {
+    UpToDate,
+    FastForward,
+    Merged,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PullReport {
+    pub outcome: PullOutcome,
+    // Notes added, changed or removed by the pull, as vault-relative paths without extension
+    pub changed: Vec<String>,
+}
+
+fn config_path(vault: &Vault) -> String {
+    format!("{}/{}/git.json", vault.path, CONFIG_DIR)
+}
+
+pub fn load_config(vault: &Vault) -> io::Result<GitConfig> {
+    match file_operations::read_from_file(&config_path(vault)) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| Error::new(ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(GitConfig::default()),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn save_config(vault: &Vault, config: &GitConfig) -> io::Result<()> {
+    if !git2::Reference::is_valid_name(&format!("refs/heads/{}", config.branch)) {
+        return Err(Message::new("git.invalid_branch", "Invalid branch name").error(ErrorKind::InvalidInput));
+    }
+    file_operations::create_directory(&format!("{}/{}", vault.path, CONFIG_DIR))?;
+    let json = serde_json::to_string_pretty(config).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    file_operations::write_to_file(&config_path(vault), &json)
+}
+
+fn git_error(e: git2::Error) -> Error {
+    let kind = match (e.code(), e.class()) {
+        (ErrorCode::NotFound, _) => ErrorKind::NotFound,
+        (ErrorCode::Auth, _) | (_, ErrorClass::Ssh) => ErrorKind::PermissionDenied,
+        (ErrorCode::Conflict | ErrorCode::MergeConflict | ErrorCode::NotFastForward, _) => ErrorKind::AlreadyExists,
+        (_, ErrorClass::Net | ErrorClass::Http) => ErrorKind::ConnectionRefused,
+        _ => ErrorKind::Other,
+    };
+    Message::new("git.failed", "Git: {detail}").with("detail", e.message()).error(kind)
+}
+
+fn work_tree(vault: &Vault) -> String {
+    file_operations::resolve_path(&vault.path)
+}
+
+// Whether the vault folder is the root of a git repository.
+pub fn is_repository(vault: &Vault) -> bool {
+    Path::new(&work_tree(vault)).join(".git").exists()
+}
+
+fn open(vault: &Vault) -> io::Result<Repository> {
+    if !is_repository(vault) {
+        return Err(Message::new("git.not_a_repository", "The vault is not a git repository").error(ErrorKind::NotFound));
+    }
+    Repository::open(work_tree(vault)).map_err(git_error)
+}
+
+fn signature(repo: &Repository) -> io::Result<Signature<'static>> {
+    repo.signature()
+        .or_else(|_| Signature::now("Markdown Notes", "notes@localhost"))
+        .map_err(git_error)
+}
+
+// Credentials from the SSH agent or the user's git credential helper.
+fn callbacks<'a>() -> RemoteCallbacks<'a> {
+    let attempts = Cell::new(0);
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| {
+        attempts.set(attempts.get() + 1);
+        if attempts.get() > MAX_CREDENTIAL_ATTEMPTS {
+            return Err(git2::Error::new(ErrorCode::Auth, ErrorClass::Ssh, "no working credentials for the remote"));
+        }
+        if allowed.contains(CredentialType::SSH_KEY) {
+            Cred::ssh_key_from_agent(username.unwrap_or("git"))
+        } else if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            Cred::credential_helper(&git2::Config::open_default()?, url, username)
+        } else {
+            Cred::default()
+        }
+    });
+    callbacks
+}
+
+// Makes the vault a git repository (keeping an existing one), points `origin` at `remote` when given
+// and commits the current notes. Returns the id of that commit, if anything was committed.
+pub fn init(vault: &Vault, remote: Option<&str>) -> io::Result<Option<String>> {
+    let config = load_config(vault)?;
+    let repo = if is_repository(vault) {
+        open(vault)?
+    } else {
+        let mut options = RepositoryInitOptions::new();
+        options.initial_head(&config.branch);
+        Repository::init_opts(work_tree(vault), &options).map_err(git_error)?
+    };
+
+    let gitignore = format!("{}/.gitignore", vault.path);
+    if !Path::new(&file_operations::resolve_path(&gitignore)).exists() {
+        file_operations::write_to_file(&gitignore, GITIGNORE)?;
+    }
+    if let Some(url) = remote.map(str::trim).filter(|url| !url.is_empty()) {
+        if repo.find_remote(REMOTE).is_ok() {
+            repo.remote_set_url(REMOTE, url).map_err(git_error)?;
+        } else {
+            repo.remote(REMOTE, url).map_err(git_error)?;
+        }
+    }
+    commit(vault, "Initial commit")
+}
+
+fn head_commit(repo: &Repository) -> io::Result<Option<git2::Commit<'_>>> {
+    match repo.head() {
+        Ok(head) => Ok(Some(head.peel_to_commit().map_err(git_error)?)),
+        Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
+        Err(e) => Err(git_error(e)),
+    }
+}
+
+// Stages every change of the vault and commits it. Returns the commit id, or `None` when there was
+// nothing to commit.
+pub fn commit(vault: &Vault, message: &str) -> io::Result<Option<String>> {
+    let message = message.trim();
+    if message.is_empty() {
+        return Err(Message::new("git.commit_message_empty", "Commit message is empty").error(ErrorKind::InvalidInput));
+    }
+    let repo = open(vault)?;
+    let mut index = repo.index().map_err(git_error)?;
+    index.add_all(["*"], IndexAddOption::DEFAULT, None).map_err(git_error)?;
+    index.update_all(["*"], None).map_err(git_error)?;
+    index.write().map_err(git_error)?;
+    let tree = repo.find_tree(index.write_tree().map_err(git_error)?).map_err(git_error)?;
+
+    let parent = head_commit(&repo)?;
+    let unchanged = match &parent {
+        Some(parent) => parent.tree_id() == tree.id(),
+        None => tree.is_empty(),
+    };
+    if unchanged {
+        return Ok(None);
+    }
+    let signature = signature(&repo)?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let id = repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents).map_err(git_error)?;
+    Ok(Some(id.to_string()))
+}
+
+// Commits a saved note when the vault has automatic commits turned on.
+pub fn commit_saved(vault: &Vault, title: &str) -> io::Result<Option<String>> {
+    if !is_repository(vault) || !load_config(vault)?.auto_commit {
+        return Ok(None);
+    }
+    commit(vault, &format!("Update {}", title))
+}
+
+// Notes that differ between two commits.
+fn changed_notes(repo: &Repository, before: Option<Oid>, after: Oid) -> io::Result<Vec<String>> {
+    let tree = |id: Oid| repo.find_commit(id).and_then(|commit| commit.tree()).map_err(git_error);
+    let old_tree = before.map(tree).transpose()?;
+    let new_tree = tree(after)?;
+    let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None).map_err(git_error)?;
+    let mut changed: Vec<String> = diff
+        .deltas()
+        .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+        .flatten()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .map(|path| path.with_extension("").to_string_lossy().replace('\\', "/"))
+        .collect();
+    changed.sort();
+    changed.dedup();
+    Ok(changed)
+}
+
+// Fetches the branch from `origin` and brings the vault up to date with it, committing local changes
+// first. Diverged histories are merged; conflicting changes abort the pull and leave the vault as it was.
+pub fn pull(vault: &Vault) -> io::Result<PullReport> {
+    let config = load_config(vault)?;
+    let repo = open(vault)?;
+    commit(vault, "Commit local changes before pull")?;
+
+    let mut remote = repo.find_remote(REMOTE).map_err(git_error)?;
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(callbacks());
+    let refspec = format!("+refs/heads/{0}:refs/remotes/{1}/{0}", config.branch, REMOTE);
+    match remote.fetch(&[&refspec], Some(&mut options), None) {
+        Ok(()) => {}
+        // An empty remote has nothing to pull yet
+        Err(e) if e.code() == ErrorCode::NotFound || e.message().contains("couldn't find remote ref") => {
+            return Ok(PullReport { outcome: PullOutcome::UpToDate, changed: Vec::new() });
+        }
+        Err(e) => return Err(git_error(e)),
+    }
+    let remote_ref = match repo.find_reference(&format!("refs/remotes/{}/{}", REMOTE, config.branch)) {
+        Ok(reference) => reference,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(PullReport { outcome: PullOutcome::UpToDate, changed: Vec::new() }),
+        Err(e) => return Err(git_error(e)),
+    };
+    let theirs = repo.reference_to_annotated_commit(&remote_ref).map_err(git_error)?;
+    let before = head_commit(&repo)?.map(|commit| commit.id());
+    let (analysis, _) = repo.merge_analysis(&[&theirs]).map_err(git_error)?;
+    let branch_ref = format!("refs/heads/{}", config.branch);
+
+    let outcome = if analysis.contains(MergeAnalysis::ANALYSIS_UP_TO_DATE) {
+        PullOutcome::UpToDate
+    } else if analysis.intersects(MergeAnalysis::ANALYSIS_FASTFORWARD | MergeAnalysis::ANALYSIS_UNBORN) {
+        repo.reference(&branch_ref, theirs.id(), true, "pull: fast-forward").map_err(git_error)?;
+        repo.set_head(&branch_ref).map_err(git_error)?;
+        repo.checkout_head(Some(CheckoutBuilder::new().force())).map_err(git_error)?;
+        PullOutcome::FastForward
+    } else {
+        let ours = head_commit(&repo)?.ok_or_else(|| Message::new("git.no_commits", "The vault has no commits").error(ErrorKind::NotFound))?;
+        let theirs = repo.find_commit(theirs.id()).map_err(git_error)?;
+        let mut index = repo.merge_commits(&ours, &theirs, None).map_err(git_error)?;
+        if index.has_conflicts() {
+            let conflicts: Vec<String> = index
+                .conflicts()
+                .map_err(git_error)?
+                .flatten()
+                .filter_map(|conflict| conflict.our.or(conflict.their))
+                .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+                .collect();
+            return Err(Message::new("git.pull_conflicts", "Pull conflicts with local changes in: {paths}")
+                .with("paths", conflicts.join(", "))
+                .error(ErrorKind::AlreadyExists));
+        }
+        let tree = repo.find_tree(index.write_tree_to(&repo).map_err(git_error)?).map_err(git_error)?;
+        let signature = signature(&repo)?;
+        let message = format!("Merge {}/{}", REMOTE, config.branch);
+        repo.commit(Some("HEAD"), &signature, &signature, &message, &tree, &[&ours, &theirs]).map_err(git_error)?;
+        repo.checkout_head(Some(CheckoutBuilder::new().force())).map_err(git_error)?;
+        PullOutcome::Merged
+    };
+
+    let after = head_commit(&repo)?.map(|commit| commit.id());
+    let changed = match after.filter(|after| Some(*after) != before) {
+        Some(after) => changed_notes(&repo, before, after)?,
+        None => Vec::new(),
+    };
+    Ok(PullReport { outcome, changed })
+}
+
+// Pushes the branch to `origin`. A remote that moved on rejects the push until the vault is pulled.
+pub fn push(vault: &Vault) -> io::Result<()> {
+    let config = load_config(vault)?;
+    let repo = open(vault)?;
+    if head_commit(&repo)?.is_none() {
+        return Err(Message::new("git.nothing_to_push", "The vault has no commits to push").error(ErrorKind::NotFound));
+    }
+    let mut remote = repo.find_remote(REMOTE).map_err(git_error)?;
+    let rejected = RefCell::new(None);
+    let mut callbacks = callbacks();
+    callbacks.push_update_reference(|_, status| {
+        if let Some(status) = status {
+            *rejected.borrow_mut() = Some(status.to_string());
+        }
+        Ok(())
+    });
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+    let refspec = format!("refs/heads/{0}:refs/heads/{0}", config.branch);
+    remote.push(&[&refspec], Some(&mut options)).map_err(git_error)?;
+    drop(options);
+
+    match rejected.into_inner() {
+        Some(reason) => Err(Message::new("git.push_rejected", "Push rejected ({reason}), pull first")
+            .with("reason", reason)
+            .error(ErrorKind::AlreadyExists)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::note::Note;
+    use nanoid::nanoid;
+
+    fn write(vault: &Vault, path: &str, content: &str) {
+        file_operations::write_to_file(&format!("{}/{}.md", vault.path, path), content).unwrap();
+    }
+
+    #[test]
+    fn test_commit() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        assert_eq!(commit(&vault, "Nothing").unwrap_err().kind(), ErrorKind::NotFound);
+        write(&vault, "Plan", "v1");
+
+        assert!(init(&vault, None).unwrap().is_some());
+        assert!(file_operations::read_from_file(&format!("{}/.gitignore", vault.path)).unwrap().contains(".meta/"));
+        assert!(commit(&vault, "Nothing changed").unwrap().is_none());
+        assert_eq!(commit(&vault, " ").unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        // Saves are only committed with automatic commits turned on
+        write(&vault, "Plan", "v2");
+        assert!(commit_saved(&vault, "Plan").unwrap().is_none());
+        save_config(&vault, &GitConfig { auto_commit: true, ..Default::default() }).unwrap();
+        assert!(commit_saved(&vault, "Plan").unwrap().is_some());
+        let repo = open(&vault).unwrap();
+        assert_eq!(head_commit(&repo).unwrap().unwrap().message(), Some("Update Plan"));
+
+        // Deleted notes are committed too
+        file_operations::delete_file(&format!("{}/Plan.md", vault.path)).unwrap();
+        assert!(commit(&vault, "Remove plan").unwrap().is_some());
+        assert!(head_commit(&repo).unwrap().unwrap().tree().unwrap().get_name("Plan.md").is_none());
+
+        assert_eq!(save_config(&vault, &GitConfig { branch: "a b".to_string(), ..Default::default() }).unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_pull_and_push() {
+        file_operations::set_base_path(None);
+        let remote = std::env::temp_dir().join(format!("remote_{}.git", nanoid!()));
+        Repository::init_bare(&remote).unwrap();
+        let url = remote.to_string_lossy().to_string();
+        let laptop = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let desktop = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+
+        write(&laptop, "Plan", "v1\n");
+        init(&laptop, Some(&url)).unwrap();
+        assert_eq!(pull(&laptop).unwrap().outcome, PullOutcome::UpToDate);
+        push(&laptop).unwrap();
+
+        // The desktop's own initial commit (its `.gitignore`) is merged with the laptop's notes
+        init(&desktop, Some(&url)).unwrap();
+        let report = pull(&desktop).unwrap();
+        assert_eq!(report, PullReport { outcome: PullOutcome::Merged, changed: vec!["Plan".to_string()] });
+        push(&desktop).unwrap();
+        assert_eq!(pull(&laptop).unwrap(), PullReport { outcome: PullOutcome::FastForward, changed: Vec::new() });
+        assert_eq!(Note::read_note(&desktop, "Plan").unwrap(), "v1\n");
+
+        // Changes to different notes on both machines are merged
+        write(&desktop, "Ideas", "idea\n");
+        commit(&desktop, "Add ideas").unwrap();
+        push(&desktop).unwrap();
+        write(&laptop, "Todo", "todo\n");
+        commit(&laptop, "Add todo").unwrap();
+        assert_eq!(push(&laptop).unwrap_err().kind(), ErrorKind::AlreadyExists);
+        let report = pull(&laptop).unwrap();
+        assert_eq!(report, PullReport { outcome: PullOutcome::Merged, changed: vec!["Ideas".to_string()] });
+        push(&laptop).unwrap();
+
+        // Both machines changing the same line is a conflict that leaves the local note alone
+        pull(&desktop).unwrap();
+        write(&desktop, "Plan", "desktop\n");
+        commit(&desktop, "Desktop plan").unwrap();
+        push(&desktop).unwrap();
+        write(&laptop, "Plan", "laptop\n");
+        assert_eq!(pull(&laptop).unwrap_err().kind(), ErrorKind::AlreadyExists);
+        assert_eq!(Note::read_note(&laptop, "Plan").unwrap(), "laptop\n");
+
+        // Cleanup
+        laptop.delete_vault().expect("Failed to delete vault");
+        desktop.delete_vault().expect("Failed to delete vault");
+        std::fs::remove_dir_all(remote).unwrap();
+    }
+}
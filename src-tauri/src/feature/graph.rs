@@ -1,25 +1,438 @@
-// Graph structure and visualization
+// Graph structure and visualization, analytics for the vault health panel, and export to Graphviz (DOT)
+// and Gephi (GraphML)
 use petgraph::graph::{Graph, NodeIndex};
+use petgraph::unionfind::UnionFind;
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, ErrorKind};
 
+use crate::feature::{backlinks::LinkResolver, lookup, refactor};
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, markdown, message::Message};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    // Path relative to the vault, without the `.md` extension
+    pub id: String,
+    // Stable id from the frontmatter, which stays the same when the note is renamed
+    #[serde(default)]
+    pub note_id: Option<String>,
+    pub title: String,
+    pub links: usize,
+    pub backlinks: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphData {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+// Hops around the note a local graph shows when the caller does not ask for another
+pub const LOCAL_GRAPH_DEPTH: usize = 1;
+// Number of most linked notes in a graph report when the caller does not ask for another
+pub const MOST_LINKED: usize = 10;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphStats {
+    pub notes: usize,
+    pub links: usize,
+    // Notes with neither links nor backlinks, sorted by path
+    pub orphans: Vec<String>,
+    // Most backlinked notes first
+    pub most_linked: Vec<GraphNode>,
+    // Groups of notes connected by links in either direction, largest first; orphans are left out
+    pub clusters: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    Dot,
+    GraphMl,
+}
+
+// Attributes of a note exported with its node
+#[derive(Debug, Clone, Default)]
+struct NodeAttributes {
+    tags: Vec<String>,
+    words: usize,
+}
+
+#[derive(Default)]
 pub struct NoteGraph {
     graph: Graph<String, ()>,
-    node_indices: std::collections::HashMap<String, NodeIndex>,
+    node_indices: HashMap<String, NodeIndex>,
+    note_ids: HashMap<String, String>,
+    attributes: HashMap<String, NodeAttributes>,
+}
+
+fn title_of(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
 }
 
 impl NoteGraph {
     pub fn new() -> Self {
-        todo!("Initialize a new NoteGraph");
+        Self::default()
+    }
+
+    // Builds the graph of a vault from the wikilinks of its notes; links to missing notes are left out.
+    pub fn from_vault(vault: &Vault) -> io::Result<Self> {
+        let paths = refactor::note_paths(vault)?;
+        let mut graph = Self::new();
+        // `[[Title]]` resolves to the first note with that name, like the editor does
+        let resolver = LinkResolver::new(&paths);
+        for path in &paths {
+            graph.add_note(path.clone());
+        }
+
+        for path in &paths {
+            let content = file_operations::read_from_file(&format!("{}/{}.md", vault.path, path))?;
+            if let Some(id) = lookup::note_id(&content) {
+                graph.note_ids.insert(path.clone(), id);
+            }
+            let attributes = NodeAttributes { tags: markdown::note_tags(&content), words: markdown::word_count(&content) };
+            graph.attributes.insert(path.clone(), attributes);
+            for link in markdown::extract_links(&content) {
+                let resolved = resolver.resolve(markdown::link_target(&link));
+                if let Some(resolved) = resolved.filter(|resolved| resolved != path) {
+                    graph.add_link(path.clone(), resolved);
+                }
+            }
+        }
+        Ok(graph)
     }
 
     pub fn add_note(&mut self, note: String) {
-        todo!("Add a note to the graph");
+        if !self.node_indices.contains_key(&note) {
+            let index = self.graph.add_node(note.clone());
+            self.node_indices.insert(note, index);
+        }
     }
 
+    // Adds a link once, creating the notes it connects when needed.
     pub fn add_link(&mut self, from: String, to: String) {
-        todo!("Add a link between two notes");
+        self.add_note(from.clone());
+        self.add_note(to.clone());
+        self.graph.update_edge(self.node_indices[&from], self.node_indices[&to], ());
+    }
+
+    fn node(&self, index: NodeIndex) -> GraphNode {
+        let id = self.graph[index].clone();
+        GraphNode {
+            note_id: self.note_ids.get(&id).cloned(),
+            title: title_of(&id).to_string(),
+            links: self.graph.edges_directed(index, Direction::Outgoing).count(),
+            backlinks: self.graph.edges_directed(index, Direction::Incoming).count(),
+            id,
+        }
+    }
+
+    // Serializable nodes and edges for the graph view, nodes sorted by path.
+    pub fn data(&self) -> GraphData {
+        let mut nodes: Vec<GraphNode> = self.graph.node_indices().map(|index| self.node(index)).collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let edges = self
+            .graph
+            .edge_references()
+            .map(|edge| GraphEdge {
+                source: self.graph[edge.source()].clone(),
+                target: self.graph[edge.target()].clone(),
+            })
+            .collect();
+        GraphData { nodes, edges }
+    }
+
+    // The part of the graph within `depth` links of a note, following links in either direction, with
+    // nodes sorted by path. Link counts of the nodes stay those of the whole graph. None if the note is
+    // not in the graph.
+    pub fn neighborhood(&self, center: &str, depth: usize) -> Option<GraphData> {
+        let start = *self.node_indices.get(center)?;
+        let mut included = HashSet::from([start]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((index, distance)) = queue.pop_front() {
+            if distance == depth {
+                continue;
+            }
+            for neighbor in self.graph.neighbors_undirected(index) {
+                if included.insert(neighbor) {
+                    queue.push_back((neighbor, distance + 1));
+                }
+            }
+        }
+
+        let mut nodes: Vec<GraphNode> = included.iter().map(|index| self.node(*index)).collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        let edges = self
+            .graph
+            .edge_references()
+            .filter(|edge| included.contains(&edge.source()) && included.contains(&edge.target()))
+            .map(|edge| GraphEdge {
+                source: self.graph[edge.source()].clone(),
+                target: self.graph[edge.target()].clone(),
+            })
+            .collect();
+        Some(GraphData { nodes, edges })
+    }
+
+    // Notes with neither links nor backlinks, sorted by path.
+    pub fn find_orphan_notes(&self) -> Vec<String> {
+        let mut orphans: Vec<String> = self
+            .graph
+            .node_indices()
+            .filter(|index| self.graph.neighbors_undirected(*index).next().is_none())
+            .map(|index| self.graph[index].clone())
+            .collect();
+        orphans.sort();
+        orphans
+    }
+
+    // The `n` notes with the most backlinks, ties by path; notes nothing links to are left out.
+    pub fn most_linked_notes(&self, n: usize) -> Vec<GraphNode> {
+        let mut nodes: Vec<GraphNode> = self.graph.node_indices().map(|index| self.node(index)).filter(|node| node.backlinks > 0).collect();
+        nodes.sort_by(|a, b| b.backlinks.cmp(&a.backlinks).then_with(|| a.id.cmp(&b.id)));
+        nodes.truncate(n);
+        nodes
+    }
+
+    // Groups of notes reachable from each other through links in either direction, largest first, then by
+    // their first path. Each group is sorted; single notes are groups of their own.
+    pub fn connected_components(&self) -> Vec<Vec<String>> {
+        let mut sets = UnionFind::new(self.graph.node_count());
+        for edge in self.graph.edge_references() {
+            sets.union(edge.source().index(), edge.target().index());
+        }
+        let mut groups: HashMap<usize, Vec<String>> = HashMap::new();
+        for index in self.graph.node_indices() {
+            groups.entry(sets.find(index.index())).or_default().push(self.graph[index].clone());
+        }
+        let mut groups: Vec<Vec<String>> = groups.into_values().collect();
+        for group in &mut groups {
+            group.sort();
+        }
+        groups.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        groups
+    }
+
+    // Report for the vault health panel: orphans, the `top` most linked notes and clusters.
+    pub fn stats(&self, top: usize) -> GraphStats {
+        GraphStats {
+            notes: self.graph.node_count(),
+            links: self.graph.edge_count(),
+            orphans: self.find_orphan_notes(),
+            most_linked: self.most_linked_notes(top),
+            clusters: self.connected_components().into_iter().filter(|group| group.len() > 1).collect(),
+        }
+    }
+
+    // Renders the graph as a file Graphviz (DOT) or Gephi (GraphML) can open. Nodes are identified by path
+    // and carry the title, tags (comma-separated) and word count of their note.
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.render_dot(),
+            GraphFormat::GraphMl => self.render_graphml(),
+        }
+    }
+
+    fn attributes_of(&self, index: NodeIndex) -> (String, usize) {
+        let attributes = self.attributes.get(&self.graph[index]).cloned().unwrap_or_default();
+        (attributes.tags.join(","), attributes.words)
+    }
+
+    fn render_dot(&self) -> String {
+        let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut dot = String::from("digraph notes {\n");
+        for index in self.graph.node_indices() {
+            let path = &self.graph[index];
+            let (tags, words) = self.attributes_of(index);
+            dot.push_str(&format!(
+                "    {} [label={}, tags={}, words={}];\n",
+                quote(path),
+                quote(title_of(path)),
+                quote(&tags),
+                words
+            ));
+        }
+        for edge in self.graph.edge_references() {
+            dot.push_str(&format!(
+                "    {} -> {};\n",
+                quote(&self.graph[edge.source()]),
+                quote(&self.graph[edge.target()])
+            ));
+        }
+        dot.push('}');
+        dot
+    }
+
+    fn render_graphml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"tags\" for=\"node\" attr.name=\"tags\" attr.type=\"string\"/>\n",
+            "  <key id=\"words\" for=\"node\" attr.name=\"words\" attr.type=\"int\"/>\n",
+            "  <graph id=\"notes\" edgedefault=\"directed\">\n",
+        ));
+        for index in self.graph.node_indices() {
+            let path = &self.graph[index];
+            let (tags, words) = self.attributes_of(index);
+            xml.push_str(&format!(
+                "    <node id=\"{}\">\n      <data key=\"label\">{}</data>\n      <data key=\"tags\">{}</data>\n      <data key=\"words\">{}</data>\n    </node>\n",
+                escape_xml(path),
+                escape_xml(title_of(path)),
+                escape_xml(&tags),
+                words
+            ));
+        }
+        for edge in self.graph.edge_references() {
+            xml.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\"/>\n",
+                escape_xml(&self.graph[edge.source()]),
+                escape_xml(&self.graph[edge.target()])
+            ));
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+// The notes within `depth` links of a note, given by its path or id, for a focused graph view.
+pub fn local_graph(vault: &Vault, path_or_id: &str, depth: usize) -> io::Result<GraphData> {
+    let path = lookup::resolve_note(vault, path_or_id)?;
+    NoteGraph::from_vault(vault)?
+        .neighborhood(&path, depth)
+        .ok_or_else(|| Message::new("note.not_found", "Note file does not exist").error(ErrorKind::NotFound))
+}
+
+// Analyses the note graph of a vault.
+pub fn graph_stats(vault: &Vault, top: usize) -> io::Result<GraphStats> {
+    Ok(NoteGraph::from_vault(vault)?.stats(top))
+}
+
+// Renders the note graph of a vault in an export format.
+pub fn export_graph(vault: &Vault, format: GraphFormat) -> io::Result<String> {
+    Ok(NoteGraph::from_vault(vault)?.render(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_note_graph_from_vault() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Projects", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Index.md", vault.path), "[[Plan]] [[Projects/Plan|again]] [[Missing]] [[Index]]").unwrap();
+        file_operations::write_to_file(&format!("{}/Projects/Plan.md", vault.path), "---\nid: p1\n---\nBack to [[index#Top]]").unwrap();
+        file_operations::write_to_file(&format!("{}/Lonely.md", vault.path), "![[photo.png]]").unwrap();
+
+        let data = NoteGraph::from_vault(&vault).unwrap().data();
+        let ids: Vec<&str> = data.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(ids, vec!["Index", "Lonely", "Projects/Plan"]);
+        assert_eq!(data.edges.len(), 2);
+        assert!(data.edges.contains(&GraphEdge { source: "Index".to_string(), target: "Projects/Plan".to_string() }));
+        assert_eq!((data.nodes[2].title.as_str(), data.nodes[2].links, data.nodes[2].backlinks), ("Plan", 1, 1));
+        assert_eq!((data.nodes[0].note_id.as_deref(), data.nodes[2].note_id.as_deref()), (None, Some("p1")));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_local_graph() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        for (title, content) in [("A", "[[B]]"), ("B", "[[C]]"), ("C", ""), ("D", "[[C]]"), ("E", "[[D]]"), ("Far", "")] {
+            file_operations::write_to_file(&format!("{}/{}.md", vault.path, title), content).unwrap();
+        }
+        let ids = |data: &GraphData| data.nodes.iter().map(|node| node.id.clone()).collect::<Vec<_>>();
+
+        let near = local_graph(&vault, "C", 1).unwrap();
+        assert_eq!(ids(&near), vec!["B", "C", "D"]);
+        assert_eq!(near.edges.len(), 2);
+        let wider = local_graph(&vault, "C.md", 2).unwrap();
+        assert_eq!(ids(&wider), vec!["A", "B", "C", "D", "E"]);
+        assert_eq!(wider.edges.len(), 4);
+        // Counts are those of the whole graph
+        assert_eq!(near.nodes[0].backlinks, 1);
+
+        assert_eq!(ids(&local_graph(&vault, "Far", 3).unwrap()), vec!["Far"]);
+        assert_eq!(ids(&local_graph(&vault, "A", 0).unwrap()), vec!["A"]);
+        assert_eq!(local_graph(&vault, "Missing", 1).unwrap_err().kind(), ErrorKind::NotFound);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_graph_stats() {
+        let mut graph = NoteGraph::new();
+        graph.add_link("Index".to_string(), "Plan".to_string());
+        graph.add_link("Ideas".to_string(), "Plan".to_string());
+        graph.add_link("Plan".to_string(), "Index".to_string());
+        graph.add_link("Recipes/Soup".to_string(), "Recipes/Bread".to_string());
+        graph.add_note("Lonely".to_string());
+        graph.add_note("Another".to_string());
+
+        assert_eq!(graph.find_orphan_notes(), vec!["Another", "Lonely"]);
+        let most_linked: Vec<(String, usize)> = graph.most_linked_notes(2).into_iter().map(|node| (node.id, node.backlinks)).collect();
+        assert_eq!(most_linked, vec![("Plan".to_string(), 2), ("Index".to_string(), 1)]);
+        assert_eq!(graph.connected_components().len(), 4);
+
+        let stats = graph.stats(MOST_LINKED);
+        assert_eq!((stats.notes, stats.links, stats.most_linked.len()), (7, 4, 3));
+        assert_eq!(stats.clusters, vec![vec!["Ideas", "Index", "Plan"], vec!["Recipes/Bread", "Recipes/Soup"]]);
     }
 
-    pub fn render(&self) -> String {
-        todo!("Render the graph as a string (e.g., DOT format)");
+    #[test]
+    fn test_render_dot() {
+        let mut graph = NoteGraph::new();
+        graph.add_link("A".to_string(), "B \"quoted\"".to_string());
+        graph.add_link("A".to_string(), "B \"quoted\"".to_string());
+        assert_eq!(
+            graph.render(GraphFormat::Dot),
+            "digraph notes {\n    \"A\" [label=\"A\", tags=\"\", words=0];\n    \"B \\\"quoted\\\"\" [label=\"B \\\"quoted\\\"\", tags=\"\", words=0];\n    \"A\" -> \"B \\\"quoted\\\"\";\n}"
+        );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_export_graph() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/R&D.md", vault.path), "Ideas for [[Plan]] #work #rust").unwrap();
+        file_operations::write_to_file(&format!("{}/Plan.md", vault.path), "Three short words").unwrap();
+
+        let dot = export_graph(&vault, GraphFormat::Dot).unwrap();
+        assert!(dot.contains("    \"R&D\" [label=\"R&D\", tags=\"work,rust\", words=5];\n"));
+        assert!(dot.contains("    \"Plan\" [label=\"Plan\", tags=\"\", words=3];\n"));
+        assert!(dot.contains("    \"R&D\" -> \"Plan\";\n"));
+
+        let graphml = export_graph(&vault, GraphFormat::GraphMl).unwrap();
+        assert!(graphml.starts_with("<?xml"));
+        assert!(graphml.contains("<node id=\"R&amp;D\">\n      <data key=\"label\">R&amp;D</data>\n      <data key=\"tags\">work,rust</data>\n      <data key=\"words\">5</data>\n"));
+        assert!(graphml.contains("<edge source=\"R&amp;D\" target=\"Plan\"/>"));
+        assert!(graphml.ends_with("</graphml>\n"));
+        assert_eq!(serde_json::from_str::<GraphFormat>("\"graphml\"").unwrap(), GraphFormat::GraphMl);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
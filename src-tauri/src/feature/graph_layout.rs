@@ -0,0 +1,244 @@
+// Force-directed layout of the note graph, computed in the backend so large vaults open with a
+// precomputed layout. Layouts are cached per graph revision (a hash of its nodes and edges), and a new
+// revision starts from the cached positions so the graph does not jump around after an edit.
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+
+use crate::feature::graph::{GraphData, NoteGraph};
+use crate::storage::vault::Vault;
+use crate::utils::file_operations;
+
+const LAYOUT_CACHE: &str = ".cache/graph_layout.json";
+// Ideal distance between linked notes
+const SPACING: f64 = 100.0;
+const ITERATIONS: usize = 200;
+// Starting from a previous layout only needs to settle the new notes
+const WARM_ITERATIONS: usize = 60;
+// Pull towards the center, which keeps unlinked notes and separate clusters from drifting apart
+const GRAVITY: f64 = 0.05;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodePosition {
+    pub id: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphLayout {
+    pub revision: String,
+    pub graph: GraphData,
+    // Positions of the nodes, in the order of `graph.nodes`
+    pub positions: Vec<NodePosition>,
+    // Whether the layout came from the cache
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LayoutCache {
+    revision: String,
+    positions: Vec<NodePosition>,
+}
+
+// Hash of the nodes and edges of a graph, which changes whenever its layout would.
+pub fn revision(data: &GraphData) -> String {
+    let mut edges: Vec<(&str, &str)> = data.edges.iter().map(|edge| (edge.source.as_str(), edge.target.as_str())).collect();
+    edges.sort();
+    let mut hasher = Sha256::new();
+    for node in &data.nodes {
+        hasher.update(format!("{}\n", node.id));
+    }
+    for (source, target) in edges {
+        hasher.update(format!("{} -> {}\n", source, target));
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+// Spreads nodes over a sunflower spiral, which fills a disc evenly and depends only on the node order.
+fn initial_position(index: usize) -> (f64, f64) {
+    let golden_angle = PI * (3.0 - 5f64.sqrt());
+    let radius = SPACING * (index as f64 + 0.5).sqrt();
+    let angle = index as f64 * golden_angle;
+    (radius * angle.cos(), radius * angle.sin())
+}
+
+// Computes a Fruchterman-Reingold layout. Repulsion only acts between nodes in neighbouring grid cells,
+// which keeps each iteration close to linear in the number of nodes. Nodes found in `previous` start
+// from their old positions.
+pub fn compute_layout(data: &GraphData, previous: &[NodePosition]) -> Vec<NodePosition> {
+    let previous: HashMap<&str, (f64, f64)> = previous.iter().map(|position| (position.id.as_str(), (position.x, position.y))).collect();
+    let index: HashMap<&str, usize> = data.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+    let mut positions: Vec<(f64, f64)> = data
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| previous.get(node.id.as_str()).copied().unwrap_or_else(|| initial_position(i)))
+        .collect();
+    let edges: Vec<(usize, usize)> = data
+        .edges
+        .iter()
+        .filter_map(|edge| Some((*index.get(edge.source.as_str())?, *index.get(edge.target.as_str())?)))
+        .collect();
+
+    let reused = data.nodes.iter().filter(|node| previous.contains_key(node.id.as_str())).count();
+    let warm = reused > 0 && reused * 2 >= data.nodes.len();
+    let iterations = if warm { WARM_ITERATIONS } else { ITERATIONS };
+    let start_temperature = SPACING * if warm { 0.5 } else { (data.nodes.len() as f64).sqrt().max(1.0) };
+    let cell_size = 2.0 * SPACING;
+
+    for iteration in 0..iterations {
+        let temperature = start_temperature * (1.0 - iteration as f64 / iterations as f64);
+        let mut displacement = vec![(0.0, 0.0); positions.len()];
+
+        let cell = |(x, y): (f64, f64)| ((x / cell_size).floor() as i64, (y / cell_size).floor() as i64);
+        let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+        for (i, position) in positions.iter().enumerate() {
+            grid.entry(cell(*position)).or_default().push(i);
+        }
+        for (i, &(x, y)) in positions.iter().enumerate() {
+            let (cx, cy) = cell((x, y));
+            for neighbour in (cx - 1..=cx + 1).flat_map(|nx| (cy - 1..=cy + 1).map(move |ny| (nx, ny))) {
+                for &j in grid.get(&neighbour).into_iter().flatten() {
+                    if i == j {
+                        continue;
+                    }
+                    let (mut dx, mut dy) = (x - positions[j].0, y - positions[j].1);
+                    let mut distance = (dx * dx + dy * dy).sqrt();
+                    if distance < 0.01 {
+                        // Nodes on the same spot are pushed apart in a direction that depends on their order
+                        let angle = (i * 7 + j * 13) as f64;
+                        (dx, dy, distance) = (angle.cos(), angle.sin(), 1.0);
+                    }
+                    if distance < cell_size {
+                        let force = SPACING * SPACING / distance;
+                        displacement[i].0 += dx / distance * force;
+                        displacement[i].1 += dy / distance * force;
+                    }
+                }
+            }
+        }
+        for &(source, target) in &edges {
+            let (dx, dy) = (positions[source].0 - positions[target].0, positions[source].1 - positions[target].1);
+            let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+            let force = distance * distance / SPACING;
+            displacement[source].0 -= dx / distance * force;
+            displacement[source].1 -= dy / distance * force;
+            displacement[target].0 += dx / distance * force;
+            displacement[target].1 += dy / distance * force;
+        }
+
+        for (position, (dx, dy)) in positions.iter_mut().zip(displacement) {
+            let (dx, dy) = (dx - position.0 * GRAVITY, dy - position.1 * GRAVITY);
+            let length = (dx * dx + dy * dy).sqrt();
+            if length > 0.0 {
+                let step = length.min(temperature);
+                position.0 += dx / length * step;
+                position.1 += dy / length * step;
+            }
+        }
+    }
+
+    // Centered and rounded, so unchanged layouts serialize the same way
+    let count = positions.len().max(1) as f64;
+    let center_x = positions.iter().map(|position| position.0).sum::<f64>() / count;
+    let center_y = positions.iter().map(|position| position.1).sum::<f64>() / count;
+    let round = |value: f64| (value * 10.0).round() / 10.0;
+    data.nodes
+        .iter()
+        .zip(positions)
+        .map(|(node, (x, y))| NodePosition { id: node.id.clone(), x: round(x - center_x), y: round(y - center_y) })
+        .collect()
+}
+
+fn load_cache(vault: &Vault) -> io::Result<LayoutCache> {
+    let path = format!("{}/{}", vault.path, LAYOUT_CACHE);
+    if !Path::new(&file_operations::resolve_path(&path)).exists() {
+        return Ok(LayoutCache::default());
+    }
+    let json = file_operations::read_from_file(&path)?;
+    // A damaged cache is only a cache: lay the graph out from scratch
+    Ok(serde_json::from_str(&json).unwrap_or_default())
+}
+
+// Returns the graph of a vault with its layout, from the cache when the graph did not change since it
+// was last laid out.
+pub fn graph_layout(vault: &Vault) -> io::Result<GraphLayout> {
+    let graph = NoteGraph::from_vault(vault)?.data();
+    let revision = revision(&graph);
+    let cache = load_cache(vault)?;
+    if cache.revision == revision {
+        return Ok(GraphLayout { revision, graph, positions: cache.positions, cached: true });
+    }
+
+    let positions = compute_layout(&graph, &cache.positions);
+    let json = serde_json::to_string(&LayoutCache { revision: revision.clone(), positions: positions.clone() })
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    file_operations::create_directory(&format!("{}/.cache", vault.path))?;
+    file_operations::write_to_file(&format!("{}/{}", vault.path, LAYOUT_CACHE), &json)?;
+    Ok(GraphLayout { revision, graph, positions, cached: false })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::graph::{GraphEdge, GraphNode};
+    use nanoid::nanoid;
+
+    fn distance(a: &NodePosition, b: &NodePosition) -> f64 {
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn test_compute_layout() {
+        let node = |id: &str| GraphNode { id: id.to_string(), note_id: None, title: id.to_string(), links: 0, backlinks: 0 };
+        let edge = |source: &str, target: &str| GraphEdge { source: source.to_string(), target: target.to_string() };
+        let data = GraphData {
+            nodes: ["A", "B", "C", "D", "E", "F"].map(node).to_vec(),
+            edges: vec![edge("A", "B"), edge("B", "C"), edge("C", "A"), edge("D", "E"), edge("E", "F"), edge("F", "D")],
+        };
+
+        let layout = compute_layout(&data, &[]);
+        assert_eq!(layout, compute_layout(&data, &[]));
+        assert!(layout.iter().all(|position| position.x.is_finite() && position.y.is_finite()));
+        // Linked notes end up closer together than notes of the other triangle
+        assert!(distance(&layout[0], &layout[1]) < distance(&layout[0], &layout[4]));
+        assert!(layout.iter().enumerate().all(|(i, a)| layout[i + 1..].iter().all(|b| distance(a, b) > 1.0)));
+
+        // A changed graph keeps the positions of its existing notes close to where they were
+        let mut grown = data.clone();
+        grown.nodes.push(node("G"));
+        grown.edges.push(edge("G", "A"));
+        let relaid = compute_layout(&grown, &layout);
+        assert!(distance(&relaid[1], &layout[1]) < SPACING);
+        assert_ne!(revision(&grown), revision(&data));
+    }
+
+    #[test]
+    fn test_graph_layout_cache() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Index.md", vault.path), "[[Plan]]").unwrap();
+        file_operations::write_to_file(&format!("{}/Plan.md", vault.path), "Back to [[Index]]").unwrap();
+
+        let first = graph_layout(&vault).unwrap();
+        assert!(!first.cached);
+        assert_eq!(first.positions.len(), 2);
+        let second = graph_layout(&vault).unwrap();
+        assert!(second.cached);
+        assert_eq!(second.positions, first.positions);
+
+        file_operations::write_to_file(&format!("{}/Ideas.md", vault.path), "[[Plan]]").unwrap();
+        let third = graph_layout(&vault).unwrap();
+        assert!(!third.cached);
+        assert_ne!(third.revision, first.revision);
+        let ids: Vec<&str> = third.positions.iter().map(|position| position.id.as_str()).collect();
+        assert_eq!(ids, vec!["Ideas", "Index", "Plan"]);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
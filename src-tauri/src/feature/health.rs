@@ -0,0 +1,186 @@
+// Vault health report: broken links, missing attachments, invalid frontmatter, duplicate titles and
+// oversized files, as one checklist the UI can jump from
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
+
+use crate::storage::{attachment, note::Note, vault::Vault};
+use crate::utils::{file_operations, frontmatter, markdown, message::Message};
+
+// Notes and attachments above these sizes slow down indexing, sync and rendering.
+pub const MAX_NOTE_SIZE: u64 = 1024 * 1024;
+pub const MAX_ATTACHMENT_SIZE: u64 = 25 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    BrokenLink,
+    MissingAttachment,
+    InvalidFrontmatter,
+    DuplicateTitle,
+    OversizedFile,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthIssue {
+    pub kind: IssueKind,
+    // Note (without `.md`) or attachment the issue is in, relative to the vault
+    pub path: String,
+    // 1-based line to jump to, when the issue is at a specific place in a note
+    pub line: Option<usize>,
+    pub detail: Message,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub notes_checked: usize,
+    pub attachments_checked: usize,
+    // Sorted by kind, then path and line
+    pub issues: Vec<HealthIssue>,
+}
+
+fn title_of(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn folder_of(path: &str) -> &str {
+    path.rsplit_once('/').map(|(folder, _)| folder).unwrap_or("")
+}
+
+// Line of the first occurrence of `needle` in the content.
+fn line_of(content: &str, needle: &str) -> Option<usize> {
+    content.find(needle).map(|offset| content[..offset].matches('\n').count() + 1)
+}
+
+fn exists(vault: &Vault, path: &str) -> bool {
+    Path::new(&file_operations::resolve_path(&format!("{}/{}", vault.path, path))).exists()
+}
+
+// Checks every note and attachment of the vault.
+pub fn health_report(vault: &Vault) -> io::Result<HealthReport> {
+    let paths = Note::list_notes(vault)?;
+    let attachments = attachment::list_attachments(vault)?;
+    let note_paths: HashSet<String> = paths.iter().map(|path| path.to_lowercase()).collect();
+    let mut by_title: HashMap<String, Vec<&String>> = HashMap::new();
+    for path in &paths {
+        by_title.entry(title_of(path).to_lowercase()).or_default().push(path);
+    }
+    let attachment_names: HashSet<&str> = attachments.iter().map(|attachment| attachment.name.as_str()).collect();
+
+    let mut issues = Vec::new();
+    let mut issue = |kind, path: &str, line, detail: Message| {
+        issues.push(HealthIssue { kind, path: path.to_string(), line, detail });
+    };
+
+    for path in &paths {
+        let file = format!("{}/{}.md", vault.path, path);
+        let size = std::fs::metadata(file_operations::resolve_path(&file))?.len();
+        if size > MAX_NOTE_SIZE {
+            issue(IssueKind::OversizedFile, path, None, Message::new("health.large_note", "Note is {size} KB").with("size", size / 1024));
+        }
+        let content = file_operations::read_from_file(&file)?;
+        if let Err(e) = frontmatter::parse(&content) {
+            issue(IssueKind::InvalidFrontmatter, path, Some(1), Message::from(e));
+        }
+
+        let folder = folder_of(path);
+        for link in markdown::extract_links(&content) {
+            let target = markdown::link_target(link.trim_start_matches('!'));
+            // Note titles never contain a dot, so anything with an extension is an attachment
+            if target.is_empty() || title_of(target).contains('.') {
+                continue;
+            }
+            let resolved = note_paths.contains(&target.to_lowercase()) || by_title.contains_key(&title_of(target).to_lowercase());
+            if !resolved {
+                let line = line_of(&content, &format!("[[{}", link.trim_start_matches('!')));
+                let detail = Message::new("health.broken_wikilink", "[[{target}]] points at a missing note").with("target", target);
+                issue(IssueKind::BrokenLink, path, line, detail);
+            }
+        }
+
+        for target in markdown::extract_local_targets(&content) {
+            let relative = markdown::normalize_path(&format!("{}/{}", folder, target));
+            if target.ends_with(".md") {
+                if !exists(vault, &relative) && !exists(vault, &markdown::normalize_path(&target)) {
+                    let line = line_of(&content, &target.replace(' ', "%20")).or_else(|| line_of(&content, &target));
+                    let detail = Message::new("health.broken_link", "{target} points at a missing note").with("target", &target);
+                    issue(IssueKind::BrokenLink, path, line, detail);
+                }
+                continue;
+            }
+            let name = target.rsplit('/').next().unwrap_or_default();
+            let found = attachment_names.contains(name) || exists(vault, &relative) || exists(vault, &markdown::normalize_path(&target));
+            if !found {
+                let line = line_of(&content, &target.replace(' ', "%20")).or_else(|| line_of(&content, &target));
+                let detail = Message::new("health.missing_attachment", "{target} does not exist").with("target", &target);
+                issue(IssueKind::MissingAttachment, path, line, detail);
+            }
+        }
+    }
+
+    for notes in by_title.values().filter(|notes| notes.len() > 1) {
+        for path in notes {
+            let others: Vec<&str> = notes.iter().filter(|other| other != &path).map(|other| other.as_str()).collect();
+            let detail = Message::new("health.duplicate_title", "Same title as {notes}").with("notes", others.join(", "));
+            issue(IssueKind::DuplicateTitle, path, None, detail);
+        }
+    }
+
+    for attachment in attachments.iter().filter(|attachment| attachment.size > MAX_ATTACHMENT_SIZE) {
+        let detail = Message::new("health.large_attachment", "Attachment is {size} MB").with("size", attachment.size / (1024 * 1024));
+        issue(IssueKind::OversizedFile, &attachment.path, None, detail);
+    }
+
+    issues.sort_by(|a, b| (a.kind, &a.path, a.line).cmp(&(b.kind, &b.path, b.line)));
+    Ok(HealthReport {
+        notes_checked: paths.len(),
+        attachments_checked: attachments.len(),
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_health_report() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        vault.create_folder("Work").unwrap();
+        attachment::save_attachment(&vault, "chart.png", b"png").unwrap();
+        file_operations::write_to_file(
+            &format!("{}/Index.md", vault.path),
+            "# Index\n[[Plan]] [[work/plan#Goals]]\n[[Gone|alias]] ![[chart.png]]\n![lost](images/lost%20photo.png) [old](Old.md)",
+        )
+        .unwrap();
+        file_operations::write_to_file(&format!("{}/Plan.md", vault.path), "---\ntags: [\n---\nText").unwrap();
+        file_operations::write_to_file(&format!("{}/Work/plan.md", vault.path), "[up](../Index.md)").unwrap();
+        file_operations::write_to_file(&format!("{}/Big.md", vault.path), &"x".repeat(MAX_NOTE_SIZE as usize + 1)).unwrap();
+
+        let report = health_report(&vault).unwrap();
+        assert_eq!((report.notes_checked, report.attachments_checked), (4, 1));
+        let found: Vec<(IssueKind, &str, Option<usize>)> =
+            report.issues.iter().map(|issue| (issue.kind, issue.path.as_str(), issue.line)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (IssueKind::BrokenLink, "Index", Some(3)),
+                (IssueKind::BrokenLink, "Index", Some(4)),
+                (IssueKind::MissingAttachment, "Index", Some(4)),
+                (IssueKind::InvalidFrontmatter, "Plan", Some(1)),
+                (IssueKind::DuplicateTitle, "Plan", None),
+                (IssueKind::DuplicateTitle, "Work/plan", None),
+                (IssueKind::OversizedFile, "Big", None),
+            ]
+        );
+        assert_eq!(report.issues[0].detail.to_string(), "[[Gone]] points at a missing note");
+        assert_eq!(report.issues[2].detail.to_string(), "images/lost photo.png does not exist");
+        assert_eq!(report.issues[4].detail.to_string(), "Same title as Work/plan");
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,196 @@
+// Session-scoped undo/redo of destructive operations
+use chrono::Utc;
+use nanoid::nanoid;
+use serde::{Serialize, Deserialize};
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::storage::vault::Vault;
+use crate::utils::file_operations;
+
+pub const MAX_HISTORY: usize = 50;
+
+// Snapshot of a note before and after an operation; `None` means the note did not exist.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub title: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct Operation {
+    pub id: String,
+    pub label: String,
+    pub vault: Vault,
+    pub changes: Vec<FileChange>,
+    pub created_at: String,
+    // Bulk operations are recorded as snapshots, which `rollback_to` returns the vault to
+    pub snapshot: bool,
+}
+
+#[derive(Default)]
+struct Stacks {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+}
+
+#[derive(Default)]
+pub struct History {
+    stacks: Mutex<Stacks>,
+}
+
+impl Operation {
+    pub fn titles(&self) -> Vec<String> {
+        self.changes.iter().map(|change| change.title.clone()).collect()
+    }
+}
+
+impl History {
+    // Records an operation that was just applied; a new operation clears the redo stack.
+    pub fn record(&self, label: &str, vault: &Vault, changes: Vec<FileChange>) {
+        self.push(label, vault, changes, false);
+    }
+
+    // Records a bulk operation that was just applied as a labelled snapshot.
+    pub fn record_snapshot(&self, label: &str, vault: &Vault, changes: Vec<FileChange>) {
+        self.push(label, vault, changes, true);
+    }
+
+    fn push(&self, label: &str, vault: &Vault, changes: Vec<FileChange>, snapshot: bool) {
+        if changes.is_empty() {
+            return;
+        }
+        let mut stacks = self.stacks.lock().unwrap();
+        stacks.undo.push(Operation {
+            id: nanoid!(),
+            label: label.to_string(),
+            vault: vault.clone(),
+            changes,
+            created_at: Utc::now().to_rfc3339(),
+            snapshot,
+        });
+        if stacks.undo.len() > MAX_HISTORY {
+            stacks.undo.remove(0);
+        }
+        stacks.redo.clear();
+    }
+
+    // Restores the snapshots taken before the last operation and returns it.
+    pub fn undo_last(&self) -> io::Result<Option<Operation>> {
+        let mut stacks = self.stacks.lock().unwrap();
+        let Some(operation) = stacks.undo.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = apply(&operation, false) {
+            stacks.undo.push(operation);
+            return Err(e);
+        }
+        stacks.redo.push(operation.clone());
+        Ok(Some(operation))
+    }
+
+    // Re-applies the last undone operation and returns it.
+    pub fn redo_last(&self) -> io::Result<Option<Operation>> {
+        let mut stacks = self.stacks.lock().unwrap();
+        let Some(operation) = stacks.redo.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = apply(&operation, true) {
+            stacks.redo.push(operation);
+            return Err(e);
+        }
+        stacks.undo.push(operation.clone());
+        Ok(Some(operation))
+    }
+
+    // Snapshots of a vault that can still be rolled back to, most recent first.
+    pub fn snapshots(&self, vault: &Vault) -> Vec<Operation> {
+        let stacks = self.stacks.lock().unwrap();
+        stacks.undo.iter().rev().filter(|op| op.snapshot && op.vault.path == vault.path).cloned().collect()
+    }
+
+    // Undoes the operations of a vault down to and including a snapshot, the most recent one when no id
+    // is given, and returns them in the order they were undone. Operations of other vaults are kept.
+    // `None` when the vault has no such snapshot.
+    pub fn rollback_to(&self, vault: &Vault, id: Option<&str>) -> io::Result<Option<Vec<Operation>>> {
+        let mut stacks = self.stacks.lock().unwrap();
+        let Some(position) = stacks
+            .undo
+            .iter()
+            .rposition(|op| op.snapshot && op.vault.path == vault.path && id.is_none_or(|id| op.id == id))
+        else {
+            return Ok(None);
+        };
+        let mut undone = Vec::new();
+        for index in (position..stacks.undo.len()).rev() {
+            if stacks.undo[index].vault.path != vault.path {
+                continue;
+            }
+            let operation = stacks.undo.remove(index);
+            if let Err(e) = apply(&operation, false) {
+                stacks.undo.insert(index, operation);
+                return Err(e);
+            }
+            stacks.redo.push(operation.clone());
+            undone.push(operation);
+        }
+        Ok(Some(undone))
+    }
+}
+
+fn apply(operation: &Operation, forward: bool) -> io::Result<()> {
+    for change in &operation.changes {
+        let note_path = format!("{}/{}.md", operation.vault.path, change.title);
+        let target = if forward { &change.after } else { &change.before };
+        match target {
+            Some(content) => file_operations::write_to_file(&note_path, content)?,
+            None if Path::new(&file_operations::resolve_path(&note_path)).exists() => {
+                file_operations::delete_file(&note_path)?
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::note::Note;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_undo_and_redo() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let history = History::default();
+        file_operations::write_to_file(&format!("{}/Kept.md", vault.path), "new").unwrap();
+
+        history.record(
+            "Delete note",
+            &vault,
+            vec![FileChange { title: "Gone".to_string(), before: Some("old".to_string()), after: None }],
+        );
+        history.record(
+            "Replace",
+            &vault,
+            vec![FileChange { title: "Kept".to_string(), before: Some("old".to_string()), after: Some("new".to_string()) }],
+        );
+
+        assert_eq!(history.undo_last().unwrap().map(|op| op.label).as_deref(), Some("Replace"));
+        assert_eq!(Note::read_note(&vault, "Kept").unwrap(), "old");
+        assert_eq!(history.undo_last().unwrap().map(|op| op.label).as_deref(), Some("Delete note"));
+        assert_eq!(Note::read_note(&vault, "Gone").unwrap(), "old");
+        assert!(history.undo_last().unwrap().is_none());
+
+        assert_eq!(history.redo_last().unwrap().map(|op| op.label).as_deref(), Some("Delete note"));
+        assert!(Note::read_note(&vault, "Gone").is_err());
+        history.record("Other", &vault, vec![FileChange { title: "X".to_string(), before: None, after: Some("x".to_string()) }]);
+        assert!(history.redo_last().unwrap().is_none());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
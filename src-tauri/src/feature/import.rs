@@ -0,0 +1,469 @@
+// Importing notes exported from other apps (Standard Notes backups, Simplenote exports, Obsidian vaults)
+use nanoid::nanoid;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::feature::{history::FileChange, refactor};
+use crate::storage::{attachment, note::{Note, OnConflict}, vault::Vault};
+use crate::utils::{file_operations, frontmatter::{self, Frontmatter}, markdown, string_utils};
+use crate::utils::{error::AppError, message::Message};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    // Decrypted backup file of Standard Notes
+    StandardNotes,
+    // `notes.json` of a Simplenote export
+    Simplenote,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VaultImportReport {
+    // Name of the created vault
+    pub vault: String,
+    // Imported notes, as vault-relative paths
+    pub notes: Vec<String>,
+    // Files stored as attachments, as vault-relative paths
+    pub attachments: Vec<String>,
+    // Notes whose file name had to change to a valid note name; links to them were updated
+    pub renamed: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportReport {
+    // Created notes, as vault-relative paths
+    pub imported: Vec<String>,
+    // Trashed or deleted notes that were left out
+    pub skipped: usize,
+}
+
+// A note read from an export, before it is written to the vault.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct ImportedNote {
+    title: String,
+    text: String,
+    tags: Vec<String>,
+    created: Option<String>,
+    updated: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StandardNotesBackup {
+    #[serde(default)]
+    items: Vec<StandardNotesItem>,
+}
+
+#[derive(Deserialize)]
+struct StandardNotesItem {
+    uuid: String,
+    content_type: String,
+    #[serde(default)]
+    deleted: bool,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    #[serde(default)]
+    content: StandardNotesContent,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct StandardNotesContent {
+    title: Option<String>,
+    text: Option<String>,
+    trashed: bool,
+    references: Vec<StandardNotesReference>,
+}
+
+#[derive(Deserialize)]
+struct StandardNotesReference {
+    uuid: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimplenoteExport {
+    #[serde(default)]
+    active_notes: Vec<SimplenoteNote>,
+    #[serde(default)]
+    trashed_notes: Vec<SimplenoteNote>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimplenoteNote {
+    #[serde(default)]
+    content: String,
+    creation_date: Option<String>,
+    last_modified: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+fn invalid(e: serde_json::Error) -> Error {
+    Message::new("import.unrecognized_file", "Unrecognized export file: {detail}").with("detail", e).error(ErrorKind::InvalidData)
+}
+
+// Reads the notes of a Standard Notes backup; tags are items that reference the notes they are on.
+fn parse_standard_notes(json: &str) -> io::Result<(Vec<ImportedNote>, usize)> {
+    let backup: StandardNotesBackup = serde_json::from_str(json).map_err(invalid)?;
+    let mut tags: HashMap<&str, Vec<String>> = HashMap::new();
+    for item in backup.items.iter().filter(|item| item.content_type == "Tag" && !item.deleted) {
+        let Some(tag) = item.content.title.as_deref().map(str::trim).filter(|tag| !tag.is_empty()) else {
+            continue;
+        };
+        for reference in &item.content.references {
+            tags.entry(reference.uuid.as_str()).or_default().push(tag.to_string());
+        }
+    }
+
+    let mut notes = Vec::new();
+    let mut skipped = 0;
+    for item in backup.items.iter().filter(|item| item.content_type == "Note") {
+        if item.deleted || item.content.trashed {
+            skipped += 1;
+            continue;
+        }
+        notes.push(ImportedNote {
+            title: item.content.title.clone().unwrap_or_default(),
+            text: item.content.text.clone().unwrap_or_default(),
+            tags: tags.remove(item.uuid.as_str()).unwrap_or_default(),
+            created: item.created_at.clone(),
+            updated: item.updated_at.clone(),
+        });
+    }
+    Ok((notes, skipped))
+}
+
+// Reads the notes of a Simplenote export; the first line of a note is its title.
+fn parse_simplenote(json: &str) -> io::Result<(Vec<ImportedNote>, usize)> {
+    let export: SimplenoteExport = serde_json::from_str(json).map_err(invalid)?;
+    let notes = export
+        .active_notes
+        .into_iter()
+        .map(|note| {
+            let content = note.content.replace("\r\n", "\n");
+            let (title, text) = content.split_once('\n').unwrap_or((&content, ""));
+            ImportedNote {
+                title: title.trim().trim_start_matches('#').trim().to_string(),
+                text: text.trim_start_matches('\n').to_string(),
+                tags: note.tags,
+                created: note.creation_date,
+                updated: note.last_modified,
+            }
+        })
+        .collect();
+    Ok((notes, export.trashed_notes.len()))
+}
+
+// Builds the note file: tags and timestamps go to the frontmatter, on top of any the note already had.
+fn note_content(note: &ImportedNote) -> io::Result<String> {
+    let mut fields = frontmatter::parse(&note.text).unwrap_or_else(|_| Frontmatter::default());
+    let mut tags = fields.tags();
+    for tag in &note.tags {
+        let tag = tag.trim().trim_start_matches('#');
+        if !tag.is_empty() && !tags.iter().any(|existing| existing == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    if !tags.is_empty() {
+        fields.set("tags", tags);
+    }
+    if let Some(created) = &note.created {
+        fields.set("created", created.clone());
+    }
+    if let Some(updated) = &note.updated {
+        fields.set("updated", updated.clone());
+    }
+    frontmatter::replace(&note.text, &fields)
+}
+
+// Picks a file name that is free in the folder, adding `-2`, `-3`… to repeated titles.
+fn free_path(vault: &Vault, folder: &str, note: &ImportedNote) -> io::Result<String> {
+    let name = Note::file_name_for(&note.title);
+    let path = if folder.is_empty() { name } else { format!("{}/{}", folder, name) };
+    Note::free_path(vault, &path, OnConflict::Suffix)
+}
+
+// Imports the notes of an export file into a folder of the vault (empty for the root). Returns the notes
+// created as changes too, so the import can be recorded as a snapshot and rolled back.
+pub fn import_notes(vault: &Vault, source: ImportSource, input: &Path, folder: &str) -> io::Result<(ImportReport, Vec<FileChange>)> {
+    let json = fs::read_to_string(input)?;
+    let (notes, skipped) = match source {
+        ImportSource::StandardNotes => parse_standard_notes(&json)?,
+        ImportSource::Simplenote => parse_simplenote(&json)?,
+    };
+
+    let folder = refactor::clean_path(folder);
+    file_operations::create_directory(&format!("{}/{}", vault.path, folder))?;
+    let mut report = ImportReport { imported: Vec::new(), skipped };
+    let mut changes = Vec::new();
+    for note in notes {
+        let path = free_path(vault, &folder, &note)?;
+        let content = note_content(&note)?;
+        file_operations::write_to_file(&format!("{}/{}.md", vault.path, path), &content)?;
+        report.imported.push(path.clone());
+        changes.push(FileChange { title: path, before: None, after: Some(content) });
+    }
+    Ok((report, changes))
+}
+
+// Files of an Obsidian vault, keyed by their path in the source folder.
+struct ObsidianFiles {
+    // Original path without `.md` -> note path in the new vault
+    notes: HashMap<String, String>,
+    // Original path -> attachment path in the new vault
+    attachments: HashMap<String, String>,
+}
+
+impl ObsidianFiles {
+    // Resolves a link target like Obsidian does: the exact path first, then the shortest path by name.
+    fn resolve<'a>(map: &'a HashMap<String, String>, target: &str) -> Option<(&'a String, &'a String)> {
+        let target = target.trim().trim_start_matches('/').to_lowercase();
+        let name_of = |path: &str| path.rsplit('/').next().unwrap_or(path).to_string();
+        map.iter()
+            .find(|(original, _)| original.to_lowercase() == target)
+            .or_else(|| {
+                map.iter()
+                    .filter(|(original, _)| name_of(original).to_lowercase() == name_of(&target))
+                    .min_by_key(|(original, _)| (original.matches('/').count(), original.to_string()))
+            })
+    }
+
+    // New path of a linked note, when importing changed its name.
+    fn renamed_note(&self, target: &str) -> Option<&String> {
+        let name_of = |path: &str| path.rsplit('/').next().unwrap_or(path).to_string();
+        Self::resolve(&self.notes, markdown::link_target(target))
+            .filter(|(original, path)| name_of(original) != name_of(path))
+            .map(|(_, path)| path)
+    }
+
+    fn attachment(&self, target: &str) -> Option<&String> {
+        Self::resolve(&self.attachments, target).map(|(_, path)| path)
+    }
+}
+
+// Rewrites the links of an imported note: wikilinks and note embeds follow renamed notes, attachment embeds
+// become Markdown images or links, and Markdown links to files point at their new place.
+fn convert_obsidian_note(content: &str, source_folder: &str, folder: &str, files: &ObsidianFiles) -> String {
+    let link_re = Regex::new(r"(!?)\[([^\]]*)\]\(([^)\s]+)\)").unwrap();
+    let content = link_re
+        .replace_all(content, |caps: &regex::Captures| {
+            let dest = caps[3].replace("%20", " ");
+            if dest.contains("://") || dest.starts_with('#') || dest.starts_with("mailto:") || dest.ends_with(".md") {
+                return caps[0].to_string();
+            }
+            let relative = markdown::normalize_path(&format!("{}/{}", source_folder, dest));
+            let found = files.attachments.get(&relative).or_else(|| files.attachments.get(&markdown::normalize_path(&dest)));
+            match found {
+                Some(path) => attachment::markdown_link(folder, path, &caps[2]),
+                None => caps[0].to_string(),
+            }
+        })
+        .to_string();
+
+    let embed_re = Regex::new(r"!\[\[([^\]|#]+)(#[^\]|]*)?(?:\|([^\]]*))?\]\]").unwrap();
+    let content = embed_re.replace_all(&content, |caps: &regex::Captures| {
+        let target = caps[1].trim();
+        let anchor = caps.get(2).map(|anchor| anchor.as_str()).unwrap_or("");
+        if target.rsplit('/').next().is_some_and(|name| name.contains('.') && !name.ends_with(".md")) {
+            return match files.attachment(target) {
+                Some(path) => {
+                    // `![[photo.png|300]]` sets a display width, which Markdown images have no place for
+                    let name = target.rsplit('/').next().unwrap_or(target);
+                    attachment::markdown_link(folder, path, name)
+                }
+                None => caps[0].to_string(),
+            };
+        }
+        match files.renamed_note(target) {
+            Some(path) => format!("![[{}{}]]", path, anchor),
+            None => caps[0].to_string(),
+        }
+    });
+
+    markdown::rewrite_wikilinks(&content, |target, label| {
+        let (path, anchor) = target.split_once('#').map(|(path, anchor)| (path, format!("#{}", anchor))).unwrap_or((target, String::new()));
+        match files.renamed_note(path) {
+            Some(new_path) => format!("[[{}{}|{}]]", new_path, anchor, label),
+            None if label != target => format!("[[{}|{}]]", target, label),
+            None => format!("[[{}]]", target),
+        }
+    })
+}
+
+// Creates a vault from an Obsidian vault folder. Notes keep their folders and frontmatter; other files
+// become attachments of the new vault and the embeds and links pointing at them are converted. Obsidian's
+// own settings (`.obsidian`) and other hidden folders are left out.
+pub fn import_obsidian_vault(source: &Path, vault_name: &str) -> io::Result<VaultImportReport> {
+    if !source.is_dir() {
+        return Err(Message::new("import.obsidian_folder_not_found", "Obsidian vault folder does not exist").error(ErrorKind::NotFound));
+    }
+    let name = string_utils::sanitize_filename(vault_name);
+    if name.is_empty() {
+        return Err(AppError::InvalidName(Message::new("vault.name_empty", "Vault name is empty")).into());
+    }
+    let target = file_operations::resolve_path(&format!("Vaults/{}", name));
+    if fs::read_dir(&target).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(Message::new("vault.already_exists", "A vault with this name already exists").error(ErrorKind::AlreadyExists));
+    }
+    let vault = Vault::create_vault(&name)?;
+
+    let mut sources = Vec::new();
+    let walker = WalkDir::new(source)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.'));
+    for entry in walker {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            let relative = entry.path().strip_prefix(source).unwrap_or(entry.path()).to_string_lossy().replace('\\', "/");
+            sources.push(relative);
+        }
+    }
+
+    let mut report = VaultImportReport { vault: vault.name.clone(), ..VaultImportReport::default() };
+    let mut files = ObsidianFiles { notes: HashMap::new(), attachments: HashMap::new() };
+    let mut taken = HashSet::new();
+    for relative in &sources {
+        if let Some(original) = relative.strip_suffix(".md") {
+            let (folder, name) = original.rsplit_once('/').unwrap_or(("", original));
+            let folder = string_utils::sanitize_path(folder);
+            let mut name = string_utils::sanitize_filename(name);
+            if name.is_empty() {
+                name = format!("untitled_{}", nanoid!());
+            }
+            let base = if folder.is_empty() { name } else { format!("{}/{}", folder, name) };
+            let mut path = base.clone();
+            let mut suffix = 2;
+            while !taken.insert(path.to_lowercase()) {
+                path = format!("{}-{}", base, suffix);
+                suffix += 1;
+            }
+            if path != original {
+                report.renamed += 1;
+            }
+            files.notes.insert(original.to_string(), path);
+        } else {
+            let file_name = relative.rsplit('/').next().unwrap_or(relative);
+            let path = attachment::save_attachment(&vault, file_name, &fs::read(source.join(relative))?)?;
+            if !report.attachments.contains(&path) {
+                report.attachments.push(path.clone());
+            }
+            files.attachments.insert(relative.clone(), path);
+        }
+    }
+
+    for relative in &sources {
+        let Some(original) = relative.strip_suffix(".md") else {
+            continue;
+        };
+        let path = &files.notes[original];
+        let source_folder = original.rsplit_once('/').map(|(folder, _)| folder).unwrap_or("");
+        let folder = path.rsplit_once('/').map(|(folder, _)| folder).unwrap_or("");
+        let content = fs::read_to_string(source.join(relative))?;
+        let content = convert_obsidian_note(&content, source_folder, folder, &files);
+        file_operations::create_directory(&format!("{}/{}", vault.path, folder))?;
+        file_operations::write_to_file(&format!("{}/{}.md", vault.path, path), &content)?;
+        report.notes.push(path.clone());
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_import_standard_notes() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let backup = r#"{"version": "004", "items": [
+            {"uuid": "n1", "content_type": "Note", "created_at": "2021-03-01T10:00:00.000Z", "updated_at": "2021-03-02T10:00:00.000Z",
+             "content": {"title": "Meeting notes", "text": "Agenda", "references": []}},
+            {"uuid": "n2", "content_type": "Note", "content": {"title": "Old", "text": "x", "trashed": true}},
+            {"uuid": "n3", "content_type": "Note", "content": {"title": "Meeting notes", "text": "Second"}},
+            {"uuid": "t1", "content_type": "Tag", "content": {"title": "work", "references": [{"uuid": "n1", "content_type": "Note"}]}},
+            {"uuid": "c1", "content_type": "SN|Component", "content": {}}
+        ]}"#;
+        let input = std::env::temp_dir().join(format!("standard_notes_{}.json", nanoid!()));
+        fs::write(&input, backup).unwrap();
+
+        let (report, changes) = import_notes(&vault, ImportSource::StandardNotes, &input, "Imported").unwrap();
+        assert_eq!(changes.iter().map(|change| change.title.as_str()).collect::<Vec<_>>(), report.imported);
+        assert_eq!(report.imported, vec!["Imported/Meeting notes", "Imported/Meeting notes-2"]);
+        assert_eq!(report.skipped, 1);
+        let content = Note::read_note(&vault, "Imported/Meeting notes").unwrap();
+        assert_eq!(
+            content,
+            "---\ntags:\n- work\ncreated: 2021-03-01T10:00:00.000Z\nupdated: 2021-03-02T10:00:00.000Z\n---\nAgenda"
+        );
+        assert!(import_notes(&vault, ImportSource::Simplenote, Path::new("missing.json"), "").is_err());
+
+        // Cleanup
+        fs::remove_file(input).unwrap();
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_import_obsidian_vault() {
+        file_operations::set_base_path(None);
+        let source = std::env::temp_dir().join(format!("obsidian_{}", nanoid!()));
+        for dir in [".obsidian", "Projects", "assets"] {
+            fs::create_dir_all(source.join(dir)).unwrap();
+        }
+        fs::write(source.join(".obsidian/app.json"), "{}").unwrap();
+        fs::write(
+            source.join("Home.md"),
+            "---\ntags: [start]\n---\nSee [[My Plan#Goals]] and [[Projects/My Plan|plan]], ![[diagram.png|300]] ![[spec.pdf]]\n\n![[My Plan]] ![local](assets/local%20pic.jpg) [[Missing|x]]",
+        )
+        .unwrap();
+        fs::write(source.join("Projects/My Plan.md"), "## Goals\n![[diagram.png]]").unwrap();
+        fs::write(source.join("assets/diagram.png"), "png").unwrap();
+        fs::write(source.join("assets/local pic.jpg"), "jpg").unwrap();
+        fs::write(source.join("spec.pdf"), "pdf").unwrap();
+
+        let name = format!("obsidian{}", nanoid!(8, &['a', 'b', 'c', 'd']));
+        let report = import_obsidian_vault(&source, &name).unwrap();
+        let vault = Vault::create_vault(&report.vault).unwrap();
+        assert_eq!(report.notes, vec!["Home", "Projects/MyPlan"]);
+        assert_eq!(report.attachments, vec!["attachments/diagram.png", "attachments/local pic.jpg", "attachments/spec.pdf"]);
+        assert_eq!(report.renamed, 1);
+
+        assert_eq!(
+            Note::read_note(&vault, "Home").unwrap(),
+            "---\ntags: [start]\n---\nSee [[Projects/MyPlan#Goals|My Plan#Goals]] and [[Projects/MyPlan|plan]], \
+             ![diagram.png](attachments/diagram.png) [spec.pdf](attachments/spec.pdf)\n\n\
+             ![[Projects/MyPlan]] ![local](attachments/local%20pic.jpg) [[Missing|x]]"
+        );
+        assert_eq!(Note::read_note(&vault, "Projects/MyPlan").unwrap(), "## Goals\n![diagram.png](../attachments/diagram.png)");
+        assert!(!Path::new(&file_operations::resolve_path(&format!("{}/.obsidian", vault.path))).exists());
+
+        assert_eq!(import_obsidian_vault(&source, &name).unwrap_err().kind(), ErrorKind::AlreadyExists);
+        assert!(import_obsidian_vault(&source.join("missing"), "other").is_err());
+
+        // Cleanup
+        fs::remove_dir_all(&source).unwrap();
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_parse_simplenote() {
+        let export = r##"{"activeNotes": [
+            {"id": "a", "content": "# Groceries\r\n\r\n- milk", "creationDate": "2020-01-01T00:00:00.000Z", "tags": ["home"]},
+            {"id": "b", "content": "single line"}
+        ], "trashedNotes": [{"id": "c", "content": "gone"}]}"##;
+        let (notes, skipped) = parse_simplenote(export).unwrap();
+        assert_eq!(skipped, 1);
+        assert_eq!(notes[0].title, "Groceries");
+        assert_eq!(notes[0].text, "- milk");
+        assert_eq!(notes[0].tags, vec!["home"]);
+        assert_eq!((notes[1].title.as_str(), notes[1].text.as_str()), ("single line", ""));
+        assert!(parse_simplenote("\"notes\"").is_err());
+    }
+}
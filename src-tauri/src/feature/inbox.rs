@@ -0,0 +1,123 @@
+// Inbox triage of new notes
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use serde_yaml::Value;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use crate::feature::refactor::{self, MoveReport};
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{file_operations, frontmatter, string_utils, message::Message};
+
+pub const INBOX_DIR: &str = "Inbox";
+const TRIAGED_KEY: &str = "triaged";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InboxItem {
+    pub title: String,
+    pub in_inbox_folder: bool,
+}
+
+fn is_untriaged(content: &str) -> bool {
+    let fields = frontmatter::parse(content).unwrap_or_default();
+    fields.get(TRIAGED_KEY).is_none() && fields.tags().is_empty()
+}
+
+// Lists notes waiting for triage: everything in the Inbox folder plus untagged, unprocessed notes.
+pub fn list_untriaged(vault: &Vault) -> io::Result<Vec<InboxItem>> {
+    let mut items = Vec::new();
+
+    let inbox = file_operations::resolve_path(&format!("{}/{}", vault.path, INBOX_DIR));
+    if Path::new(&inbox).is_dir() {
+        for entry in std::fs::read_dir(&inbox)? {
+            let name = entry?.file_name().to_string_lossy().to_string();
+            if let Some(title) = name.strip_suffix(".md") {
+                items.push(InboxItem { title: title.to_string(), in_inbox_folder: true });
+            }
+        }
+    }
+
+    // Notes filed in folders are considered sorted already
+    for title in Note::list_notes(vault)?.into_iter().filter(|path| !path.contains('/')) {
+        if is_untriaged(&Note::read_note(vault, &title)?) {
+            items.push(InboxItem { title, in_inbox_folder: false });
+        }
+    }
+    items.sort_by(|a, b| b.in_inbox_folder.cmp(&a.in_inbox_folder).then_with(|| a.title.cmp(&b.title)));
+    Ok(items)
+}
+
+// Moves a note out of the inbox into a folder (empty for the vault root), adds tags and marks it processed.
+pub fn triage(vault: &Vault, item: &InboxItem, folder: &str, tags: &[String]) -> io::Result<MoveReport> {
+    let title = string_utils::title_filename(&item.title);
+    let from = if item.in_inbox_folder { format!("{}/{}", INBOX_DIR, title) } else { title.clone() };
+    let source = format!("{}/{}.md", vault.path, from);
+    let content = file_operations::read_from_file(&source)?;
+
+    let folder = refactor::clean_path(folder);
+    let to = if folder.is_empty() { title } else { format!("{}/{}", folder, title) };
+    if to != from {
+        match Note::taken_by(vault, &to)? {
+            Some(existing) if existing == to => {
+                return Err(Message::new("note.already_exists_in_folder", "A note with this name already exists in the folder").error(ErrorKind::AlreadyExists));
+            }
+            Some(existing) => return Err(Note::conflict_error(&to, &existing)),
+            None => {}
+        }
+    }
+
+    let mut fields = frontmatter::parse(&content)?;
+    let mut all_tags = fields.tags();
+    for tag in tags.iter().map(|tag| tag.trim().trim_start_matches('#')) {
+        if !tag.is_empty() && !all_tags.iter().any(|existing| existing == tag) {
+            all_tags.push(tag.to_string());
+        }
+    }
+    if !all_tags.is_empty() {
+        fields.set("tags", all_tags.into_iter().map(Value::String).collect::<Vec<_>>());
+    }
+    fields.set(TRIAGED_KEY, Utc::now().to_rfc3339());
+    file_operations::write_to_file(&source, &frontmatter::replace(&content, &fields)?)?;
+
+    // Links to the note follow it out of the inbox
+    refactor::move_note(vault, &from, &folder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_list_and_triage() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/{}", vault.path, INBOX_DIR)).unwrap();
+        file_operations::write_to_file(&format!("{}/{}/Clip.md", vault.path, INBOX_DIR), "Clipped text").unwrap();
+        file_operations::write_to_file(&format!("{}/Loose.md", vault.path), "No tags yet").unwrap();
+        file_operations::write_to_file(&format!("{}/Sorted.md", vault.path), "---\ntags: [done]\n---\n").unwrap();
+
+        let items = list_untriaged(&vault).unwrap();
+        assert_eq!(
+            items,
+            vec![
+                InboxItem { title: "Clip".to_string(), in_inbox_folder: true },
+                InboxItem { title: "Loose".to_string(), in_inbox_folder: false },
+            ]
+        );
+
+        file_operations::write_to_file(&format!("{}/Sorted.md", vault.path), "---\ntags: [done]\n---\n[[Inbox/Clip]]").unwrap();
+        let moved = triage(&vault, &items[0], "Projects/Web", &["#research".to_string()]).unwrap();
+        assert_eq!(moved.to, "Projects/Web/Clip");
+        assert_eq!(moved.updated, vec!["Sorted"]);
+        let content = file_operations::read_from_file(&format!("{}/Projects/Web/Clip.md", vault.path)).unwrap();
+        assert!(content.starts_with("---\ntags:\n- research\ntriaged: "));
+        assert!(content.ends_with("---\nClipped text"));
+
+        triage(&vault, &items[1], "", &[]).unwrap();
+        assert!(list_untriaged(&vault).unwrap().is_empty());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,173 @@
+// Auto-generated "Map of Content" index notes per tag and folder
+use serde::{Serialize, Deserialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, frontmatter, markdown, string_utils};
+
+const GENERATED_KEY: &str = "generated";
+const GENERATED_VALUE: &str = "index";
+const LIST_START: &str = "<!-- index:start -->";
+const LIST_END: &str = "<!-- index:end -->";
+
+type Members = BTreeMap<String, Vec<String>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexKind {
+    Tag,
+    Folder,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IndexReport {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub unchanged: Vec<String>,
+    // Notes with the index name that are not generated, or whose generated list markers were removed
+    pub skipped: Vec<String>,
+}
+
+fn is_generated(content: &str) -> bool {
+    frontmatter::parse(content)
+        .ok()
+        .and_then(|fields| fields.get(GENERATED_KEY).and_then(Value::as_str).map(|value| value == GENERATED_VALUE))
+        .unwrap_or(false)
+}
+
+// Groups the notes of the vault (as `folder/title` paths without extension) by tag and by folder.
+fn collect_members(vault: &Vault) -> io::Result<(Members, Members)> {
+    let root = file_operations::resolve_path(&vault.path);
+    let mut by_tag = Members::new();
+    let mut by_folder = Members::new();
+
+    let walker = WalkDir::new(&root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.'));
+    for entry in walker {
+        let entry = entry?;
+        let path = entry.path();
+        if !entry.file_type().is_file() || path.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+        let content = std::fs::read_to_string(path)?;
+        if is_generated(&content) {
+            continue;
+        }
+
+        let relative = path.strip_prefix(&root).unwrap_or(path).with_extension("");
+        let id = relative.to_string_lossy().replace('\\', "/");
+        for tag in markdown::note_tags(&content) {
+            by_tag.entry(tag).or_default().push(id.clone());
+        }
+        if let Some((folder, _)) = id.rsplit_once('/') {
+            by_folder.entry(folder.to_string()).or_default().push(id.clone());
+        }
+    }
+    Ok((by_tag, by_folder))
+}
+
+fn render_list(members: &[String]) -> String {
+    members.iter().map(|member| format!("- [[{}]]\n", member)).collect()
+}
+
+// Creates or refreshes one index note, only rewriting the generated list between its markers.
+fn write_index(
+    vault: &Vault,
+    title: &str,
+    heading: &str,
+    source: &str,
+    members: &[String],
+    report: &mut IndexReport,
+) -> io::Result<()> {
+    let path = format!("{}/{}.md", vault.path, title);
+    let list = render_list(members);
+
+    if !Path::new(&file_operations::resolve_path(&path)).exists() {
+        let content = format!(
+            "---\n{}: {}\nsource: \"{}\"\n---\n# {}\n\n{}\n{}{}\n",
+            GENERATED_KEY, GENERATED_VALUE, source, heading, LIST_START, list, LIST_END
+        );
+        file_operations::write_to_file(&path, &content)?;
+        report.created.push(title.to_string());
+        return Ok(());
+    }
+
+    let content = file_operations::read_from_file(&path)?;
+    let markers = content
+        .find(LIST_START)
+        .and_then(|start| content[start..].find(LIST_END).map(|end| (start + LIST_START.len(), start + end)));
+    let Some((start, end)) = markers.filter(|_| is_generated(&content)) else {
+        report.skipped.push(title.to_string());
+        return Ok(());
+    };
+
+    let refreshed = format!("{}\n{}{}", &content[..start], list, &content[end..]);
+    if refreshed == content {
+        report.unchanged.push(title.to_string());
+    } else {
+        file_operations::write_to_file(&path, &refreshed)?;
+        report.updated.push(title.to_string());
+    }
+    Ok(())
+}
+
+// Generates or refreshes the index notes of every tag and/or folder (`None` for both).
+pub fn generate_index_notes(vault: &Vault, kind: Option<IndexKind>) -> io::Result<IndexReport> {
+    let (by_tag, by_folder) = collect_members(vault)?;
+    let mut report = IndexReport::default();
+
+    if kind != Some(IndexKind::Folder) {
+        for (tag, members) in &by_tag {
+            let title = format!("Tag-{}", string_utils::sanitize_filename(&tag.replace('/', "-")));
+            write_index(vault, &title, &format!("#{}", tag), &format!("tag:{}", tag), members, &mut report)?;
+        }
+    }
+    if kind != Some(IndexKind::Tag) {
+        for (folder, members) in &by_folder {
+            let title = format!("Folder-{}", string_utils::sanitize_filename(&folder.replace('/', "-")));
+            write_index(vault, &title, folder, &format!("folder:{}", folder), members, &mut report)?;
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_generate_index_notes() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/A.md", vault.path), "---\ntags: [rust]\n---\nBody").unwrap();
+        file_operations::create_directory(&format!("{}/Projects", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Projects/B.md", vault.path), "About #rust").unwrap();
+        file_operations::write_to_file(&format!("{}/Tag-manual.md", vault.path), "#manual hand written").unwrap();
+
+        let report = generate_index_notes(&vault, None).unwrap();
+        assert_eq!(report.created, vec!["Tag-rust", "Folder-Projects"]);
+        assert_eq!(report.skipped, vec!["Tag-manual"]);
+        let index = file_operations::read_from_file(&format!("{}/Tag-rust.md", vault.path)).unwrap();
+        assert!(index.contains("# #rust\n\n<!-- index:start -->\n- [[A]]\n- [[Projects/B]]\n<!-- index:end -->"));
+
+        // Manual edits outside the generated list survive a refresh
+        let edited = index.replace("# #rust\n", "# #rust\nMy intro\n");
+        file_operations::write_to_file(&format!("{}/Tag-rust.md", vault.path), &edited).unwrap();
+        file_operations::write_to_file(&format!("{}/C.md", vault.path), "#rust too").unwrap();
+        let report = generate_index_notes(&vault, Some(IndexKind::Tag)).unwrap();
+        assert_eq!(report.updated, vec!["Tag-rust"]);
+        let index = file_operations::read_from_file(&format!("{}/Tag-rust.md", vault.path)).unwrap();
+        assert!(index.contains("My intro\n"));
+        assert!(index.contains("- [[C]]\n"));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
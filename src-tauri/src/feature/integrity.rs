@@ -0,0 +1,185 @@
+// Integrity of the data derived from notes. Notes deleted outside the app while it was closed leave their
+// metadata, outgoing links, people entries, word counts, saved views and locks behind, and notes created
+// outside it have none of them. The scan run when a vault is opened reconciles the metadata store with
+// the notes on disk; the search index is reconciled by the indexing pass it follows.
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeSet, HashSet};
+use std::io;
+
+use crate::feature::metadata::{MetadataStore, NoteMetadata};
+use crate::feature::search::IndexChanges;
+use crate::feature::{backlinks, locks, people, recovery, refactor, view_state, writing_stats};
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::file_operations;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    // Notes no longer on disk whose stored data was dropped, sorted
+    pub orphaned: Vec<String>,
+    // Notes on disk the stores did not know, now added to them, sorted
+    pub missing: Vec<String>,
+}
+
+impl IntegrityReport {
+    // Adds the notes the search index held on to or did not have.
+    pub fn add_search_changes(&mut self, changes: &IndexChanges) {
+        self.orphaned.extend(changes.removed.iter().cloned());
+        self.missing.extend(changes.added.iter().cloned());
+        for paths in [&mut self.orphaned, &mut self.missing] {
+            paths.sort();
+            paths.dedup();
+        }
+    }
+}
+
+// The note a key of a per-note tree belongs to: window keys end with the note path, and keys starting
+// with a NUL are markers rather than notes.
+fn note_of(key: &str) -> Option<&str> {
+    if key.starts_with('\0') {
+        return None;
+    }
+    key.rsplit('\0').next()
+}
+
+// The keys of a per-note tree whose note is not on disk, with that note.
+fn orphaned_keys(store: &MetadataStore, tree: &str, notes: &HashSet<String>) -> io::Result<Vec<(String, String)>> {
+    let mut orphaned = Vec::new();
+    for entry in store.tree(tree)?.iter() {
+        let (key, _) = entry?;
+        let key = String::from_utf8_lossy(&key).to_string();
+        if let Some(path) = note_of(&key).filter(|path| !notes.contains(*path)) {
+            orphaned.push((key.clone(), path.to_string()));
+        }
+    }
+    Ok(orphaned)
+}
+
+// Drops the stored data of notes that are no longer on disk and adds the notes the store does not know.
+// Metadata kept for a missing note only holds its backlinks, since links to a note that does not exist
+// (yet) are still listed. Notes without a creation time get the times of their file.
+pub fn reconcile(vault: &Vault, store: &MetadataStore) -> io::Result<IntegrityReport> {
+    // Notes in excluded folders keep their data, they are only left out of indexing
+    let on_disk: HashSet<String> = Note::list_all_notes(vault)?.into_iter().collect();
+    let notes: HashSet<String> = refactor::note_paths(vault)?.into_iter().collect();
+    let mut orphaned = BTreeSet::new();
+
+    // Re-indexing a deleted note drops its entry, and its links also drop the backlinks they gave
+    for (_, path) in orphaned_keys(store, backlinks::LINKS_TREE, &on_disk)? {
+        backlinks::index_links(vault, store, &path)?;
+        orphaned.insert(path);
+    }
+    for (_, path) in orphaned_keys(store, people::PEOPLE_TREE, &on_disk)? {
+        people::index_person(vault, store, &path)?;
+        orphaned.insert(path);
+    }
+    for name in [writing_stats::WORD_COUNTS_TREE, view_state::VIEW_STATE_TREE, locks::LOCKS_TREE] {
+        let tree = store.tree(name)?;
+        for (key, path) in orphaned_keys(store, name, &on_disk)? {
+            tree.remove(key.as_str())?;
+            orphaned.insert(path);
+        }
+        tree.flush()?;
+    }
+
+    let mut known = HashSet::new();
+    for (key, metadata) in store.all_metadata()? {
+        let mut kept = metadata.clone();
+        kept.backlinks.retain(|source| on_disk.contains(source));
+        orphaned.extend(metadata.backlinks.iter().filter(|source| !on_disk.contains(*source)).cloned());
+        if on_disk.contains(&key) {
+            if !kept.created_at.is_empty() {
+                known.insert(key.clone());
+            }
+        } else {
+            let stripped = NoteMetadata { backlinks: kept.backlinks.clone(), ..Default::default() };
+            if stripped != kept {
+                kept = stripped;
+                orphaned.insert(key.clone());
+            }
+            if kept.backlinks.is_empty() {
+                store.remove_metadata(&key)?;
+                continue;
+            }
+        }
+        if kept != metadata {
+            store.update_metadata(&key, kept)?;
+        }
+    }
+
+    let mut missing: Vec<String> = notes.into_iter().filter(|path| !known.contains(path)).collect();
+    missing.sort();
+    for path in &missing {
+        let file = std::fs::metadata(file_operations::resolve_path(&format!("{}/{}.md", vault.path, path)))?;
+        let mut metadata = store.get_metadata(path).unwrap_or_default();
+        metadata.created_at = recovery::file_timestamp(file.created());
+        metadata.updated_at = recovery::file_timestamp(file.modified());
+        store.update_metadata(path, metadata)?;
+        backlinks::index_links(vault, store, path)?;
+        people::index_person(vault, store, path)?;
+    }
+
+    Ok(IntegrityReport { orphaned: orphaned.into_iter().collect(), missing })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::view_state::ViewState;
+    use crate::storage::vault::VaultConfig;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_reconcile() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let store = MetadataStore::open(&vault).unwrap();
+        file_operations::write_to_file(&format!("{}/Plan.md", vault.path), "See [[Ideas]] and [[Someday]]").unwrap();
+        file_operations::write_to_file(&format!("{}/Ideas.md", vault.path), "---\ntags: [person]\n---\n# Ada").unwrap();
+        for path in ["Plan", "Ideas"] {
+            store.touch(path).unwrap();
+            backlinks::index_links(&vault, &store, path).unwrap();
+            people::index_person(&vault, &store, path).unwrap();
+        }
+        store.record_open("Plan").unwrap();
+        view_state::set_view_state(&store, "Plan", Some("main"), &ViewState::default()).unwrap();
+        assert_eq!(reconcile(&vault, &store).unwrap(), IntegrityReport::default());
+
+        // Plan is deleted and Draft created while the app is closed
+        file_operations::delete_file(&format!("{}/Plan.md", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Draft.md", vault.path), "Links to [[Ideas]]").unwrap();
+        let report = reconcile(&vault, &store).unwrap();
+        assert_eq!(report, IntegrityReport { orphaned: vec!["Plan".to_string()], missing: vec!["Draft".to_string()] });
+
+        assert!(store.get_metadata("Plan").is_none());
+        assert!(store.get_metadata("Someday").is_none());
+        assert_eq!(store.get_metadata("Ideas").unwrap().backlinks, vec!["Draft"]);
+        assert!(!store.get_metadata("Draft").unwrap().created_at.is_empty());
+        assert!(store.tree(backlinks::LINKS_TREE).unwrap().get("Plan").unwrap().is_none());
+        assert!(view_state::get_view_state(&store, "Plan", Some("main")).unwrap().is_none());
+        assert!(store.tree(people::PEOPLE_TREE).unwrap().get("Ideas").unwrap().is_some());
+        assert_eq!(reconcile(&vault, &store).unwrap(), IntegrityReport::default());
+
+        // Excluding a folder keeps the data of its notes
+        file_operations::create_directory(&format!("{}/Archive", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Archive/Old.md", vault.path), "[[Ideas]]").unwrap();
+        store.touch("Archive/Old").unwrap();
+        backlinks::index_links(&vault, &store, "Archive/Old").unwrap();
+        view_state::set_view_state(&store, "Archive/Old", Some("main"), &ViewState::default()).unwrap();
+        let config = vault.config().unwrap();
+        vault.set_config(&VaultConfig { excluded_dirs: vec!["Archive".to_string()], ..config }).unwrap();
+        assert_eq!(reconcile(&vault, &store).unwrap(), IntegrityReport::default());
+        assert!(store.get_metadata("Archive/Old").is_some());
+        assert!(view_state::get_view_state(&store, "Archive/Old", Some("main")).unwrap().is_some());
+        assert_eq!(store.get_metadata("Ideas").unwrap().backlinks, vec!["Draft", "Archive/Old"]);
+
+        // Search index changes join the report
+        let mut report = IntegrityReport { orphaned: vec!["Plan".to_string()], missing: Vec::new() };
+        let changes = IndexChanges { changed: vec!["New".to_string()], added: vec!["New".to_string()], removed: vec!["Plan".to_string()] };
+        report.add_search_changes(&changes);
+        assert_eq!(report, IntegrityReport { orphaned: vec!["Plan".to_string()], missing: vec!["New".to_string()] });
+
+        // Cleanup
+        drop(store);
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
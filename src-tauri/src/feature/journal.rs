@@ -0,0 +1,159 @@
+// Daily notes, named after their date, and the append-only journal kept in them
+use chrono::{Local, NaiveDate};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use crate::feature::templates;
+use crate::storage::{note::Note, vault::{DailyNotesConfig, Vault}};
+use crate::utils::{file_operations, message::Message};
+
+pub const JOURNAL_TEMPLATE: &str = ".templates/journal.md";
+const DEFAULT_TEMPLATE: &str = "# {{date}}\n\n";
+
+// Today's daily note and whether opening it created it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyNote {
+    pub path: String,
+    pub created: bool,
+}
+
+// The daily note settings, kept in the vault config.
+pub fn load_config(vault: &Vault) -> io::Result<DailyNotesConfig> {
+    Ok(vault.config()?.daily_notes)
+}
+
+// Returns the title of the daily note for a date.
+pub fn daily_note_title(config: &DailyNotesConfig, date: NaiveDate) -> String {
+    date.format(&config.date_format).to_string()
+}
+
+// Returns the vault-relative path of the daily note for a date.
+pub fn daily_note_path(config: &DailyNotesConfig, date: NaiveDate) -> String {
+    match config.folder.as_str() {
+        "" => daily_note_title(config, date),
+        folder => format!("{}/{}", folder, daily_note_title(config, date)),
+    }
+}
+
+// Returns the date of a daily note from its vault-relative path, if it is one.
+pub fn daily_note_date(config: &DailyNotesConfig, path: &str) -> Option<NaiveDate> {
+    let name = match config.folder.as_str() {
+        "" if path.contains('/') => return None,
+        "" => path,
+        folder => path.strip_prefix(folder)?.strip_prefix('/')?,
+    };
+    NaiveDate::parse_from_str(name, &config.date_format).ok()
+}
+
+// Returns the daily note of a date, creating it from the daily template (or the journal template) if missing.
+pub fn open_daily_note(vault: &Vault, date: NaiveDate) -> io::Result<DailyNote> {
+    let config = load_config(vault)?;
+    let path = daily_note_path(&config, date);
+    let file = format!("{}/{}.md", vault.path, path);
+    if Path::new(&file_operations::resolve_path(&file)).exists() {
+        return Ok(DailyNote { path, created: false });
+    }
+
+    let title = daily_note_title(&config, date);
+    let template = match &config.template {
+        Some(name) => match templates::read_template(vault, name) {
+            Ok(template) => Some(template),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        },
+        None => None,
+    };
+    let content = match template {
+        Some(template) => templates::render_template(&template, &title, &HashMap::new())?,
+        None => render_template(vault, date),
+    };
+    file_operations::create_directory(&format!("{}/{}", vault.path, config.folder))?;
+    file_operations::write_to_file(&file, &content)?;
+    Ok(DailyNote { path, created: true })
+}
+
+// Renders the journal template of the vault (or the default one) for a date.
+fn render_template(vault: &Vault, date: NaiveDate) -> String {
+    let template = file_operations::read_from_file(&format!("{}/{}", vault.path, JOURNAL_TEMPLATE))
+        .unwrap_or_else(|_| DEFAULT_TEMPLATE.to_string());
+    template
+        .replace("{{date}}", &date.format("%Y-%m-%d").to_string())
+        .replace("{{weekday}}", &date.format("%A").to_string())
+}
+
+// Appends a timestamped bullet to today's daily note, creating it from the template if absent.
+pub fn log(vault: &Vault, text: &str) -> io::Result<String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(Message::new("journal.entry_empty", "Journal entry is empty").error(ErrorKind::InvalidInput));
+    }
+
+    let now = Local::now();
+    let title = open_daily_note(vault, now.date_naive())?.path;
+    let mut content = Note::read_note(vault, &title)?;
+
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("- {} {}\n", now.format("%H:%M"), text.replace('\n', "\n  ")));
+    Note::update_note(vault, &title, &content)?;
+    Ok(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::vault::VaultConfig;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_log_appends_to_daily_note() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/.templates", vault.path)).unwrap();
+        file_operations::write_to_file(
+            &format!("{}/{}", vault.path, JOURNAL_TEMPLATE),
+            "# Journal {{date}}\n",
+        )
+        .unwrap();
+
+        let title = log(&vault, "First thought").unwrap();
+        log(&vault, "Second\nthought").unwrap();
+        assert!(log(&vault, "  ").is_err());
+
+        let content = Note::read_note(&vault, &title).unwrap();
+        assert!(content.starts_with(&format!("# Journal {}\n- ", title)));
+        assert_eq!(content.lines().count(), 4);
+        assert!(content.ends_with(" Second\n  thought\n"));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_open_daily_note() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 5, 17).unwrap();
+        assert_eq!(open_daily_note(&vault, date).unwrap(), DailyNote { path: "2024-05-17".to_string(), created: true });
+        assert_eq!(Note::read_note(&vault, "2024-05-17").unwrap(), "# 2024-05-17\n\n");
+        assert!(!open_daily_note(&vault, date).unwrap().created);
+
+        let daily_notes = DailyNotesConfig { date_format: "%Y%m%d".to_string(), folder: "Journal/".to_string(), template: Some("daily.md".to_string()) };
+        vault.set_config(&VaultConfig { daily_notes, ..VaultConfig::default() }).unwrap();
+        file_operations::create_directory(&format!("{}/.templates", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/.templates/daily.md", vault.path), "# {{title}}\n## Tasks\n").unwrap();
+
+        let daily = open_daily_note(&vault, date).unwrap();
+        assert_eq!(daily.path, "Journal/20240517");
+        assert_eq!(Note::read_note(&vault, &daily.path).unwrap(), "# 20240517\n## Tasks\n");
+        let config = load_config(&vault).unwrap();
+        assert_eq!(daily_note_date(&config, "Journal/20240517"), Some(date));
+        assert_eq!(daily_note_date(&config, "20240517"), None);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
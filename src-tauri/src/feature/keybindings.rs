@@ -0,0 +1,154 @@
+// Keyboard shortcut configuration
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind};
+
+use crate::feature::settings::Settings;
+use crate::utils::message::Message;
+
+// `Mod` is Cmd on macOS and Ctrl elsewhere.
+pub const DEFAULT_KEYMAP: &[(&str, &str)] = &[
+    ("note.new", "Mod+N"),
+    ("note.save", "Mod+S"),
+    ("note.daily", "Mod+D"),
+    ("note.rename", "F2"),
+    ("search.open", "Mod+Shift+F"),
+    ("palette.open", "Mod+P"),
+    ("editor.bold", "Mod+B"),
+    ("editor.italic", "Mod+I"),
+    ("editor.link", "Mod+K"),
+    ("view.toggle_preview", "Mod+E"),
+    ("view.toggle_sidebar", "Mod+\\"),
+];
+
+// Combos owned by the OS or the webview's clipboard and undo handling.
+pub const RESERVED_COMBOS: &[&str] = &["Mod+Q", "Mod+W", "Mod+C", "Mod+V", "Mod+X", "Mod+A", "Mod+Z", "Mod+Shift+Z"];
+
+const MODIFIERS: &[&str] = &["Mod", "Ctrl", "Alt", "Shift"];
+const NAMED_KEYS: &[&str] = &[
+    "Enter", "Tab", "Space", "Escape", "Backspace", "Delete", "Home", "End", "PageUp", "PageDown", "Up", "Down",
+    "Left", "Right",
+];
+
+// Normalizes a combo like `shift+cmd+f` into `Mod+Shift+F`.
+pub fn normalize_combo(combo: &str) -> io::Result<String> {
+    let invalid = || Message::new("keybinding.invalid_combo", "Invalid key combination: {combo}")
+        .with("combo", combo)
+        .error(ErrorKind::InvalidInput);
+    let parts: Vec<&str> = combo.split('+').map(str::trim).collect();
+    let (key, modifiers) = parts.split_last().ok_or_else(invalid)?;
+
+    let mut found = Vec::new();
+    for modifier in modifiers {
+        let canonical = match modifier.to_lowercase().as_str() {
+            "mod" | "cmd" | "command" | "meta" | "cmdorctrl" | "commandorcontrol" => "Mod",
+            "ctrl" | "control" => "Ctrl",
+            "alt" | "option" => "Alt",
+            "shift" => "Shift",
+            _ => return Err(invalid()),
+        };
+        if !found.contains(&canonical) {
+            found.push(canonical);
+        }
+    }
+    found.sort_by_key(|modifier| MODIFIERS.iter().position(|m| m == modifier));
+
+    let is_function_key = key.len() > 1
+        && key.to_uppercase().starts_with('F')
+        && key[1..].parse::<u8>().is_ok_and(|n| (1..=12).contains(&n));
+    let key = if key.chars().count() == 1 || is_function_key {
+        key.to_uppercase()
+    } else {
+        NAMED_KEYS
+            .iter()
+            .find(|named| named.eq_ignore_ascii_case(key))
+            .map(|named| named.to_string())
+            .ok_or_else(invalid)?
+    };
+    if found.is_empty() && !is_function_key {
+        return Err(Message::new("keybinding.missing_modifier", "Shortcuts need a modifier key").error(ErrorKind::InvalidInput));
+    }
+
+    found.push(&key);
+    Ok(found.join("+"))
+}
+
+// Returns the keymap in effect: defaults overridden by the user's bindings (an empty binding unbinds).
+pub fn effective_keymap(settings: &Settings) -> BTreeMap<String, String> {
+    let mut keymap: BTreeMap<String, String> = DEFAULT_KEYMAP
+        .iter()
+        .map(|(action, combo)| (action.to_string(), combo.to_string()))
+        .collect();
+    for (action, combo) in &settings.hotkeys {
+        if combo.is_empty() {
+            keymap.remove(action);
+        } else if keymap.contains_key(action) {
+            keymap.insert(action.clone(), combo.clone());
+        }
+    }
+    keymap
+}
+
+// Binds an action to a combo (`None` restores the default, an empty combo unbinds it).
+pub fn set_keybinding(settings: &mut Settings, action: &str, combo: Option<&str>) -> io::Result<()> {
+    if !DEFAULT_KEYMAP.iter().any(|(known, _)| *known == action) {
+        return Err(Message::new("keybinding.unknown_action", "Unknown action: {action}").with("action", action).error(ErrorKind::NotFound));
+    }
+
+    let combo = match combo.map(str::trim) {
+        None => {
+            settings.hotkeys.remove(action);
+            return Ok(());
+        }
+        Some("") => String::new(),
+        Some(combo) => normalize_combo(combo)?,
+    };
+    if RESERVED_COMBOS.contains(&combo.as_str()) {
+        return Err(Message::new("keybinding.reserved", "{combo} is reserved").with("combo", &combo).error(ErrorKind::InvalidInput));
+    }
+    if !combo.is_empty() {
+        if let Some((other, _)) = effective_keymap(settings)
+            .into_iter()
+            .find(|(other, bound)| other != action && *bound == combo)
+        {
+            return Err(Message::new("keybinding.already_bound", "{combo} is already bound to {action}")
+                .with("combo", &combo)
+                .with("action", other)
+                .error(ErrorKind::AlreadyExists));
+        }
+    }
+
+    settings.hotkeys.insert(action.to_string(), combo);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_combo() {
+        assert_eq!(normalize_combo("shift+cmd+f").unwrap(), "Mod+Shift+F");
+        assert_eq!(normalize_combo("Ctrl + Alt + enter").unwrap(), "Ctrl+Alt+Enter");
+        assert_eq!(normalize_combo("f5").unwrap(), "F5");
+        assert!(normalize_combo("K").is_err());
+        assert!(normalize_combo("Hyper+K").is_err());
+        assert!(normalize_combo("Mod+Banana").is_err());
+    }
+
+    #[test]
+    fn test_set_keybinding() {
+        let mut settings = Settings::default();
+        set_keybinding(&mut settings, "note.new", Some("mod+shift+n")).unwrap();
+        assert_eq!(effective_keymap(&settings)["note.new"], "Mod+Shift+N");
+
+        assert!(set_keybinding(&mut settings, "note.save", Some("Mod+Shift+N")).is_err());
+        assert!(set_keybinding(&mut settings, "note.save", Some("Mod+Q")).is_err());
+        assert!(set_keybinding(&mut settings, "unknown.action", Some("Mod+J")).is_err());
+
+        set_keybinding(&mut settings, "note.daily", Some("")).unwrap();
+        assert!(!effective_keymap(&settings).contains_key("note.daily"));
+
+        set_keybinding(&mut settings, "note.new", None).unwrap();
+        assert_eq!(effective_keymap(&settings)["note.new"], "Mod+N");
+    }
+}
@@ -0,0 +1,305 @@
+// Link rot checker: the external URLs of a vault's notes are checked with HEAD requests (GET when a server
+// refuses HEAD), one at a time and at most one request per host every `HOST_INTERVAL`, so a vault with
+// many links to one site does not hammer it. Results are cached in `.cache/link_status.json` and reused
+// until they are `RECHECK_AFTER_HOURS` old; reports list the broken and redirected URLs of each note.
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{file_operations, markdown};
+
+const LINK_CACHE: &str = ".cache/link_status.json";
+pub const RECHECK_AFTER_HOURS: i64 = 24;
+const HOST_INTERVAL: Duration = Duration::from_secs(1);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const USER_AGENT: &str = "markdown-note-app link checker";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkStatus {
+    Ok,
+    Redirected,
+    // The server answered with an error status
+    Broken,
+    // No answer: DNS, connection or TLS failure, timeout, or rate limiting
+    Unreachable,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UrlCheck {
+    pub url: String,
+    pub status: LinkStatus,
+    // HTTP status code, when the server answered
+    pub code: Option<u16>,
+    // Where a redirected URL points
+    pub location: Option<String>,
+    pub error: Option<String>,
+    pub checked_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteLinks {
+    pub path: String,
+    // Broken, unreachable and redirected URLs of the note
+    pub links: Vec<UrlCheck>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LinkReport {
+    // External URLs found in the vault, each counted once
+    pub urls: usize,
+    // URLs that have been checked at least once
+    pub checked: usize,
+    pub broken: usize,
+    pub redirected: usize,
+    // Notes with links needing attention, sorted by path
+    pub notes: Vec<NoteLinks>,
+}
+
+// Vaults whose links are being checked; checks of one vault never overlap.
+#[derive(Default)]
+pub struct LinkChecks {
+    running: Mutex<HashSet<String>>,
+}
+
+impl LinkChecks {
+    // Marks a check of the vault as started; false if one is already going.
+    pub fn start(&self, vault: &Vault) -> bool {
+        self.running.lock().unwrap().insert(vault.path.clone())
+    }
+
+    pub fn finish(&self, vault: &Vault) {
+        self.running.lock().unwrap().remove(&vault.path);
+    }
+}
+
+// Checks URLs over the network, waiting between requests to the same host.
+pub struct UrlChecker {
+    agent: ureq::Agent,
+    last_request: HashMap<String, Instant>,
+}
+
+fn host_of(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    rest.split(['/', '?', '#']).next().unwrap_or(rest)
+}
+
+// The status of an HTTP answer: 2xx is fine, 3xx a redirect, 429 a refusal to answer now, anything else broken.
+fn classify(code: u16) -> LinkStatus {
+    match code {
+        200..=299 => LinkStatus::Ok,
+        300..=399 => LinkStatus::Redirected,
+        429 => LinkStatus::Unreachable,
+        _ => LinkStatus::Broken,
+    }
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339()
+}
+
+impl Default for UrlChecker {
+    fn default() -> Self {
+        let agent = ureq::AgentBuilder::new().redirects(0).timeout(REQUEST_TIMEOUT).user_agent(USER_AGENT).build();
+        Self { agent, last_request: HashMap::new() }
+    }
+}
+
+impl UrlChecker {
+    fn request(&self, method: &str, url: &str) -> Result<(u16, Option<String>), String> {
+        match self.agent.request(method, url).call() {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => {
+                Ok((response.status(), response.header("Location").map(str::to_string)))
+            }
+            Err(ureq::Error::Transport(e)) => Err(e.to_string()),
+        }
+    }
+
+    pub fn check(&mut self, url: &str) -> UrlCheck {
+        let host = host_of(url).to_lowercase();
+        if let Some(wait) = self.last_request.get(&host).and_then(|last| HOST_INTERVAL.checked_sub(last.elapsed())) {
+            thread::sleep(wait);
+        }
+        let mut result = self.request("HEAD", url);
+        // Some servers only answer GET
+        if matches!(result, Ok((405 | 501, _))) {
+            thread::sleep(HOST_INTERVAL);
+            result = self.request("GET", url);
+        }
+        self.last_request.insert(host, Instant::now());
+
+        match result {
+            Ok((code, location)) => {
+                let status = classify(code);
+                let location = location.filter(|_| status == LinkStatus::Redirected);
+                UrlCheck { url: url.to_string(), status, code: Some(code), location, error: None, checked_at: now() }
+            }
+            Err(error) => UrlCheck {
+                url: url.to_string(),
+                status: LinkStatus::Unreachable,
+                code: None,
+                location: None,
+                error: Some(error),
+                checked_at: now(),
+            },
+        }
+    }
+}
+
+fn load_cache(vault: &Vault) -> io::Result<HashMap<String, UrlCheck>> {
+    let path = format!("{}/{}", vault.path, LINK_CACHE);
+    if !Path::new(&file_operations::resolve_path(&path)).exists() {
+        return Ok(HashMap::new());
+    }
+    // A damaged cache only means checking again
+    Ok(serde_json::from_str(&file_operations::read_from_file(&path)?).unwrap_or_default())
+}
+
+fn save_cache(vault: &Vault, cache: &HashMap<String, UrlCheck>) -> io::Result<()> {
+    let sorted: BTreeMap<&String, &UrlCheck> = cache.iter().collect();
+    let json = serde_json::to_string_pretty(&sorted).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    file_operations::create_directory(&format!("{}/.cache", vault.path))?;
+    file_operations::write_to_file(&format!("{}/{}", vault.path, LINK_CACHE), &json)
+}
+
+// The external URLs of every note that has some.
+fn note_urls(vault: &Vault) -> io::Result<Vec<(String, Vec<String>)>> {
+    let mut notes = Vec::new();
+    for path in Note::list_notes(vault)? {
+        let urls = markdown::extract_external_urls(&Note::read_note(vault, &path)?);
+        if !urls.is_empty() {
+            notes.push((path, urls));
+        }
+    }
+    Ok(notes)
+}
+
+fn is_stale(check: &UrlCheck) -> bool {
+    DateTime::parse_from_rfc3339(&check.checked_at)
+        .map(|checked| Utc::now() - checked.with_timezone(&Utc) > ChronoDuration::hours(RECHECK_AFTER_HOURS))
+        .unwrap_or(true)
+}
+
+fn build_report(notes: &[(String, Vec<String>)], cache: &HashMap<String, UrlCheck>) -> LinkReport {
+    let urls: HashSet<&String> = notes.iter().flat_map(|(_, urls)| urls).collect();
+    let count = |status: LinkStatus| urls.iter().filter(|url| cache.get(**url).is_some_and(|check| check.status == status)).count();
+    let mut report = LinkReport {
+        urls: urls.len(),
+        checked: urls.iter().filter(|url| cache.contains_key(**url)).count(),
+        broken: count(LinkStatus::Broken),
+        redirected: count(LinkStatus::Redirected),
+        notes: Vec::new(),
+    };
+    for (path, urls) in notes {
+        let links: Vec<UrlCheck> = urls
+            .iter()
+            .filter_map(|url| cache.get(url))
+            .filter(|check| check.status != LinkStatus::Ok)
+            .cloned()
+            .collect();
+        if !links.is_empty() {
+            report.notes.push(NoteLinks { path: path.clone(), links });
+        }
+    }
+    report
+}
+
+// Checks the external URLs of a vault that were not checked in the last `RECHECK_AFTER_HOURS` (all of
+// them when `force` is set) with `check`, calling `progress` with the checked and total counts after each
+// one. URLs no longer linked from any note are dropped from the cache.
+pub fn check_links<C, P>(vault: &Vault, force: bool, mut check: C, mut progress: P) -> io::Result<LinkReport>
+where
+    C: FnMut(&str) -> UrlCheck,
+    P: FnMut(usize, usize),
+{
+    let notes = note_urls(vault)?;
+    let mut cache = load_cache(vault)?;
+    let linked: HashSet<&String> = notes.iter().flat_map(|(_, urls)| urls).collect();
+    cache.retain(|url, _| linked.contains(url));
+
+    let mut due: Vec<&String> = linked.into_iter().filter(|url| force || cache.get(*url).is_none_or(is_stale)).collect();
+    due.sort();
+    for (number, url) in due.iter().enumerate() {
+        cache.insert(url.to_string(), check(url));
+        progress(number + 1, due.len());
+    }
+    save_cache(vault, &cache)?;
+    Ok(build_report(&notes, &cache))
+}
+
+// The report of the last checks, without checking anything. URLs never checked are only counted.
+pub fn link_report(vault: &Vault) -> io::Result<LinkReport> {
+    Ok(build_report(&note_urls(vault)?, &load_cache(vault)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    fn fake_check(url: &str) -> UrlCheck {
+        let (status, code) = match url {
+            url if url.contains("gone") => (LinkStatus::Broken, Some(404)),
+            url if url.contains("moved") => (LinkStatus::Redirected, Some(301)),
+            _ => (LinkStatus::Ok, Some(200)),
+        };
+        let location = (status == LinkStatus::Redirected).then(|| "https://new.example".to_string());
+        UrlCheck { url: url.to_string(), status, code, location, error: None, checked_at: now() }
+    }
+
+    #[test]
+    fn test_check_links() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let content = "[a](https://ok.example) [b](https://gone.example/page) https://moved.example";
+        file_operations::write_to_file(&format!("{}/Links.md", vault.path), content).unwrap();
+        file_operations::write_to_file(&format!("{}/Other.md", vault.path), "Same https://ok.example").unwrap();
+        assert_eq!(link_report(&vault).unwrap(), LinkReport { urls: 3, ..Default::default() });
+
+        let mut checked = Vec::new();
+        let mut steps = Vec::new();
+        let check = |url: &str| {
+            checked.push(url.to_string());
+            fake_check(url)
+        };
+        let report = check_links(&vault, false, check, |done, total| steps.push((done, total))).unwrap();
+        assert_eq!(checked.len(), 3);
+        assert_eq!(steps.last(), Some(&(3, 3)));
+        assert_eq!((report.urls, report.checked, report.broken, report.redirected), (3, 3, 1, 1));
+        assert_eq!(report.notes.len(), 1);
+        let statuses: Vec<(&str, LinkStatus)> = report.notes[0].links.iter().map(|check| (check.url.as_str(), check.status)).collect();
+        assert_eq!(statuses, vec![("https://gone.example/page", LinkStatus::Broken), ("https://moved.example", LinkStatus::Redirected)]);
+        assert_eq!(report.notes[0].links[1].location.as_deref(), Some("https://new.example"));
+
+        // Recent results come from the cache unless forced
+        let mut calls = 0;
+        assert_eq!(check_links(&vault, false, |url| { calls += 1; fake_check(url) }, |_, _| {}).unwrap(), report);
+        assert_eq!(calls, 0);
+        assert_eq!(link_report(&vault).unwrap(), report);
+        check_links(&vault, true, |url| { calls += 1; fake_check(url) }, |_, _| {}).unwrap();
+        assert_eq!(calls, 3);
+
+        // Unlinked URLs leave the cache
+        file_operations::write_to_file(&format!("{}/Links.md", vault.path), "No links left").unwrap();
+        let report = check_links(&vault, false, fake_check, |_, _| {}).unwrap();
+        assert_eq!((report.urls, report.checked, report.notes.len()), (1, 1, 0));
+        assert_eq!(load_cache(&vault).unwrap().len(), 1);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(host_of("https://Example.com:8080/a?b#c"), "Example.com:8080");
+        let statuses: Vec<LinkStatus> = [200, 204, 301, 308, 404, 410, 429, 500].into_iter().map(classify).collect();
+        use LinkStatus::*;
+        assert_eq!(statuses, vec![Ok, Ok, Redirected, Redirected, Broken, Broken, Unreachable, Broken]);
+    }
+}
@@ -0,0 +1,321 @@
+// Vault conventions checked on every save and on demand: required frontmatter keys, title casing, the
+// deepest heading level and bare URLs. Rules are configured per vault and are all off by default.
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::io::{self, Error, ErrorKind};
+
+use crate::feature::untitled;
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{file_operations, frontmatter, message::Message, outline};
+
+const CONFIG_DIR: &str = ".config";
+// Words kept lowercase inside titles in title case
+const SMALL_WORDS: [&str; 14] = ["a", "an", "and", "as", "at", "by", "for", "in", "of", "on", "or", "the", "to", "with"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintRule {
+    RequiredFrontmatter,
+    TitleCase,
+    HeadingDepth,
+    BareUrl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl LintRule {
+    fn default_severity(self) -> Severity {
+        match self {
+            LintRule::RequiredFrontmatter => Severity::Error,
+            LintRule::TitleCase | LintRule::HeadingDepth => Severity::Warning,
+            LintRule::BareUrl => Severity::Info,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleCase {
+    // First letter uppercase: "Meeting notes"
+    Sentence,
+    // Every word capitalized except small words: "Notes on the Roadmap"
+    Title,
+    // No uppercase letters: "meeting notes"
+    Lower,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintConfig {
+    // Frontmatter keys every note must set
+    pub required_keys: Vec<String>,
+    pub title_case: Option<TitleCase>,
+    // Deepest heading level allowed, 1 to 6
+    pub max_heading_depth: Option<u8>,
+    pub no_bare_urls: bool,
+    // Severity overrides; rules not listed use their default severity
+    pub severities: BTreeMap<LintRule, Severity>,
+}
+
+impl LintConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.required_keys.is_empty() || self.title_case.is_some() || self.max_heading_depth.is_some() || self.no_bare_urls
+    }
+
+    fn severity(&self, rule: LintRule) -> Severity {
+        self.severities.get(&rule).copied().unwrap_or_else(|| rule.default_severity())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub rule: LintRule,
+    pub severity: Severity,
+    // 1-based line the diagnostic points at, frontmatter included
+    pub line: Option<usize>,
+    pub message: Message,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteDiagnostics {
+    pub path: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LintReport {
+    pub notes_checked: usize,
+    // Only notes with diagnostics, sorted by path
+    pub notes: Vec<NoteDiagnostics>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+fn config_path(vault: &Vault) -> String {
+    format!("{}/{}/lint.json", vault.path, CONFIG_DIR)
+}
+
+pub fn load_config(vault: &Vault) -> io::Result<LintConfig> {
+    match file_operations::read_from_file(&config_path(vault)) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| Error::new(ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(LintConfig::default()),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn save_config(vault: &Vault, config: &LintConfig) -> io::Result<()> {
+    if config.max_heading_depth.is_some_and(|depth| !(1..=6).contains(&depth)) {
+        return Err(Message::new("lint.heading_depth_out_of_range", "Maximum heading depth must be between {min} and {max}")
+            .with("min", 1)
+            .with("max", 6)
+            .error(ErrorKind::InvalidInput));
+    }
+    if config.required_keys.iter().any(|key| key.trim().is_empty()) {
+        return Err(Message::new("lint.empty_required_key", "Required frontmatter keys cannot be empty").error(ErrorKind::InvalidInput));
+    }
+    file_operations::create_directory(&format!("{}/{}", vault.path, CONFIG_DIR))?;
+    let json = serde_json::to_string_pretty(config).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    file_operations::write_to_file(&config_path(vault), &json)
+}
+
+// Whether a title follows the casing convention.
+fn matches_case(title: &str, case: TitleCase) -> bool {
+    let starts_upper = |word: &str| word.chars().find(|c| c.is_alphabetic()).is_none_or(char::is_uppercase);
+    match case {
+        TitleCase::Sentence => starts_upper(title),
+        TitleCase::Title => title
+            .split_whitespace()
+            .enumerate()
+            .all(|(i, word)| starts_upper(word) || (i > 0 && SMALL_WORDS.contains(&word))),
+        TitleCase::Lower => !title.chars().any(char::is_uppercase),
+    }
+}
+
+// The title the casing rule checks, with its line: the frontmatter `title`, else the first `# Heading`.
+// File names are not checked, since sanitizing them drops spaces and punctuation.
+fn note_title(content: &str) -> Option<(String, usize)> {
+    let fields = frontmatter::parse(content).unwrap_or_default();
+    if let Some(title) = fields.get("title").and_then(serde_yaml::Value::as_str).map(str::trim).filter(|title| !title.is_empty()) {
+        return Some((title.to_string(), 1));
+    }
+    outline::headings(content)
+        .into_iter()
+        .find(|heading| heading.level == 1)
+        .map(|heading| (heading.text, heading.line + 1))
+}
+
+fn heading_depth(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+// Checks a note against the configured rules; `path` is relative to the vault, without `.md`.
+pub fn lint_note(config: &LintConfig, path: &str, content: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut report = |rule, line, message: Message| {
+        diagnostics.push(Diagnostic { rule, severity: config.severity(rule), line, message });
+    };
+
+    if !config.required_keys.is_empty() {
+        // Unparseable frontmatter is reported by the health check; here it counts as missing keys
+        let fields = frontmatter::parse(content).unwrap_or_default();
+        for key in &config.required_keys {
+            if fields.get(key).is_none_or(|value| value.is_null()) {
+                let message = Message::new("lint.missing_frontmatter_key", "Missing frontmatter key \"{key}\"").with("key", key);
+                report(LintRule::RequiredFrontmatter, Some(1), message);
+            }
+        }
+    }
+
+    // Untitled notes are checked once they are named
+    if let Some(case) = config.title_case.filter(|_| !untitled::is_untitled(path)) {
+        let title = note_title(content).filter(|(title, _)| !matches_case(title, case));
+        if let Some((title, line)) = title {
+            let (key, text) = match case {
+                TitleCase::Sentence => ("lint.title_not_sentence_case", "Title \"{title}\" is not in sentence case"),
+                TitleCase::Title => ("lint.title_not_title_case", "Title \"{title}\" is not in title case"),
+                TitleCase::Lower => ("lint.title_not_lowercase", "Title \"{title}\" is not in lowercase"),
+            };
+            report(LintRule::TitleCase, Some(line), Message::new(key, text).with("title", title));
+        }
+    }
+
+    if config.max_heading_depth.is_none() && !config.no_bare_urls {
+        return diagnostics;
+    }
+    // Lines are counted from the top of the file, frontmatter included
+    let body = frontmatter::strip(content);
+    let offset = content.len() - body.len();
+    let line_at = |position: usize| content[..offset + position].matches('\n').count() + 1;
+    let url_re = Regex::new(r"https?://[^\s<>()\[\]]+").unwrap();
+    let mut in_link = 0;
+    let mut in_code = false;
+    for (event, range) in Parser::new(body).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                let depth = heading_depth(level);
+                if let Some(max) = config.max_heading_depth.filter(|max| depth > *max) {
+                    let message = Message::new("lint.heading_too_deep", "Heading level {level} is deeper than {max}")
+                        .with("level", depth)
+                        .with("max", max);
+                    report(LintRule::HeadingDepth, Some(line_at(range.start)), message);
+                }
+            }
+            Event::Start(Tag::Link { .. } | Tag::Image { .. }) => in_link += 1,
+            Event::End(TagEnd::Link | TagEnd::Image) => in_link -= 1,
+            Event::Start(Tag::CodeBlock(_)) => in_code = true,
+            Event::End(TagEnd::CodeBlock) => in_code = false,
+            Event::Text(text) if config.no_bare_urls && in_link == 0 && !in_code => {
+                for url in url_re.find_iter(&text) {
+                    let url = url.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+                    let message = Message::new("lint.bare_url", "Bare URL {url}; use a Markdown link").with("url", url);
+                    report(LintRule::BareUrl, Some(line_at(range.start)), message);
+                }
+            }
+            _ => {}
+        }
+    }
+    diagnostics
+}
+
+// Lints every note of the vault with its configuration.
+pub fn lint_vault(vault: &Vault) -> io::Result<LintReport> {
+    let config = load_config(vault)?;
+    let paths = Note::list_notes(vault)?;
+    let mut report = LintReport { notes_checked: paths.len(), ..LintReport::default() };
+    if !config.is_enabled() {
+        return Ok(report);
+    }
+    for path in paths {
+        let content = file_operations::read_from_file(&format!("{}/{}.md", vault.path, path))?;
+        let diagnostics = lint_note(&config, &path, &content);
+        if diagnostics.is_empty() {
+            continue;
+        }
+        report.errors += diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+        report.warnings += diagnostics.iter().filter(|d| d.severity == Severity::Warning).count();
+        report.notes.push(NoteDiagnostics { path, diagnostics });
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_lint_note() {
+        let config = LintConfig {
+            required_keys: vec!["tags".to_string(), "status".to_string()],
+            title_case: Some(TitleCase::Title),
+            max_heading_depth: Some(2),
+            no_bare_urls: true,
+            severities: BTreeMap::from([(LintRule::BareUrl, Severity::Warning)]),
+        };
+        let content = "---\ntags: [a]\n---\n# notes on the roadmap\n### Too deep\nSee https://example.com.\n\n[ok](https://rust-lang.org) <https://auto.link>\n\n```\nhttps://in.code\n```";
+        let diagnostics = lint_note(&config, "Projects/notes on the roadmap", content);
+        let found: Vec<(LintRule, Severity, Option<usize>)> = diagnostics.iter().map(|d| (d.rule, d.severity, d.line)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (LintRule::RequiredFrontmatter, Severity::Error, Some(1)),
+                (LintRule::TitleCase, Severity::Warning, Some(4)),
+                (LintRule::HeadingDepth, Severity::Warning, Some(5)),
+                (LintRule::BareUrl, Severity::Warning, Some(6)),
+            ]
+        );
+        assert_eq!(diagnostics[0].message.to_string(), "Missing frontmatter key \"status\"");
+        assert_eq!(diagnostics[0].message.key, "lint.missing_frontmatter_key");
+        assert!(diagnostics[3].message.to_string().contains("https://example.com;"));
+
+        assert!(lint_note(&config, "Notes on the Roadmap", "---\ntags: []\nstatus: done\n---\n## Fine").is_empty());
+        // The frontmatter title wins over the heading
+        let titled = "---\ntags: []\nstatus: done\ntitle: Notes on the Roadmap\n---\n# lowercase heading";
+        assert!(lint_note(&config, "Notesontheroadmap", titled).is_empty());
+        let titled = "---\ntags: []\nstatus: done\ntitle: notes on the roadmap\n---\n";
+        let diagnostics = lint_note(&config, "Notesontheroadmap", titled);
+        assert_eq!(diagnostics.iter().map(|d| (d.rule, d.line)).collect::<Vec<_>>(), vec![(LintRule::TitleCase, Some(1))]);
+        assert_eq!(diagnostics[0].message.to_string(), "Title \"notes on the roadmap\" is not in title case");
+        assert!(matches_case("Meeting notes", TitleCase::Sentence));
+        assert!(!matches_case("Meeting Notes", TitleCase::Lower));
+        assert!(lint_note(&LintConfig::default(), "any title", "#### https://x.y").is_empty());
+        assert!(lint_note(&config, "untitled_abc", "---\ntags: []\nstatus: done\n---\n").is_empty());
+    }
+
+    #[test]
+    fn test_lint_vault() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Good.md", vault.path), "Fine").unwrap();
+        file_operations::write_to_file(&format!("{}/bad.md", vault.path), "# a\n#### B").unwrap();
+        assert_eq!(lint_vault(&vault).unwrap().notes, Vec::new());
+
+        let config = LintConfig { title_case: Some(TitleCase::Sentence), max_heading_depth: Some(3), ..LintConfig::default() };
+        save_config(&vault, &config).unwrap();
+        assert_eq!(load_config(&vault).unwrap(), config);
+        assert!(save_config(&vault, &LintConfig { max_heading_depth: Some(7), ..LintConfig::default() }).is_err());
+
+        let report = lint_vault(&vault).unwrap();
+        assert_eq!((report.notes_checked, report.errors, report.warnings), (2, 0, 2));
+        assert_eq!(report.notes.len(), 1);
+        assert_eq!(report.notes[0].path, "bad");
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,140 @@
+// Advisory edit locks shared between windows and devices
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+use std::io::{self, Error, ErrorKind};
+
+use crate::feature::metadata::MetadataStore;
+use crate::utils::message::Message;
+
+pub const LOCKS_TREE: &str = "locks";
+
+// Locks that are not refreshed within this many seconds are considered abandoned.
+pub const LOCK_TTL_SECONDS: i64 = 5 * 60;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteLock {
+    pub holder: String,
+    pub acquired_at: String,
+    pub refreshed_at: String,
+}
+
+impl NoteLock {
+    fn is_stale(&self) -> bool {
+        DateTime::parse_from_rfc3339(&self.refreshed_at)
+            .map(|refreshed| Utc::now() - refreshed.with_timezone(&Utc) > Duration::seconds(LOCK_TTL_SECONDS))
+            .unwrap_or(true)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockStatus {
+    pub acquired: bool,
+    pub lock: NoteLock,
+}
+
+fn encode(lock: &NoteLock) -> io::Result<Vec<u8>> {
+    serde_json::to_vec(lock).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+}
+
+// Returns the live lock on a note, if any.
+pub fn who_has_lock(store: &MetadataStore, title: &str) -> io::Result<Option<NoteLock>> {
+    let Some(bytes) = store.tree(LOCKS_TREE)?.get(title)? else {
+        return Ok(None);
+    };
+    let lock: Option<NoteLock> = serde_json::from_slice(&bytes).ok();
+    Ok(lock.filter(|lock| !lock.is_stale()))
+}
+
+// Takes (or refreshes) the edit lock of a note; when another holder has it, returns their lock instead.
+pub fn acquire_lock(store: &MetadataStore, title: &str, holder: &str) -> io::Result<LockStatus> {
+    if holder.trim().is_empty() {
+        return Err(Message::new("lock.holder_empty", "Lock holder id is empty").error(ErrorKind::InvalidInput));
+    }
+    let tree = store.tree(LOCKS_TREE)?;
+    let now = Utc::now().to_rfc3339();
+
+    loop {
+        let current = tree.get(title)?;
+        let existing: Option<NoteLock> = current.as_ref().and_then(|bytes| serde_json::from_slice(bytes).ok());
+        let acquired_at = match &existing {
+            Some(lock) if lock.holder != holder && !lock.is_stale() => {
+                return Ok(LockStatus { acquired: false, lock: lock.clone() });
+            }
+            Some(lock) if lock.holder == holder && !lock.is_stale() => lock.acquired_at.clone(),
+            _ => now.clone(),
+        };
+
+        let lock = NoteLock {
+            holder: holder.to_string(),
+            acquired_at,
+            refreshed_at: now.clone(),
+        };
+        // Retry when another window changed the lock between the read and the swap
+        if tree.compare_and_swap(title, current, Some(encode(&lock)?))?.is_ok() {
+            tree.flush()?;
+            return Ok(LockStatus { acquired: true, lock });
+        }
+    }
+}
+
+// Releases the lock of a note if it is held by the given holder.
+pub fn release_lock(store: &MetadataStore, title: &str, holder: &str) -> io::Result<bool> {
+    let tree = store.tree(LOCKS_TREE)?;
+    let Some(current) = tree.get(title)? else {
+        return Ok(false);
+    };
+    let held = serde_json::from_slice::<NoteLock>(&current).is_ok_and(|lock| lock.holder == holder);
+    if !held {
+        return Ok(false);
+    }
+    let released = tree.compare_and_swap(title, Some(current), None as Option<Vec<u8>>)?.is_ok();
+    tree.flush()?;
+    Ok(released)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_acquire_and_release() {
+        file_operations::set_base_path(None);
+        let path = format!("test_meta_{}", nanoid!());
+        let store = MetadataStore::new(&path).unwrap();
+
+        assert!(acquire_lock(&store, "Note", "window-1").unwrap().acquired);
+        let second = acquire_lock(&store, "Note", "laptop:window-2").unwrap();
+        assert!(!second.acquired);
+        assert_eq!(second.lock.holder, "window-1");
+        assert!(acquire_lock(&store, "Note", "window-1").unwrap().acquired);
+        assert_eq!(who_has_lock(&store, "Note").unwrap().unwrap().holder, "window-1");
+
+        assert!(!release_lock(&store, "Note", "laptop:window-2").unwrap());
+        assert!(release_lock(&store, "Note", "window-1").unwrap());
+        assert!(who_has_lock(&store, "Note").unwrap().is_none());
+        assert!(acquire_lock(&store, "Note", "laptop:window-2").unwrap().acquired);
+
+        // Cleanup
+        drop(store);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_stale_lock_is_taken_over() {
+        file_operations::set_base_path(None);
+        let path = format!("test_meta_{}", nanoid!());
+        let store = MetadataStore::new(&path).unwrap();
+        let old = (Utc::now() - Duration::seconds(LOCK_TTL_SECONDS + 1)).to_rfc3339();
+        let stale = NoteLock { holder: "crashed".to_string(), acquired_at: old.clone(), refreshed_at: old };
+        store.tree(LOCKS_TREE).unwrap().insert("Note", encode(&stale).unwrap()).unwrap();
+
+        assert!(who_has_lock(&store, "Note").unwrap().is_none());
+        assert!(acquire_lock(&store, "Note", "window-1").unwrap().acquired);
+
+        // Cleanup
+        drop(store);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
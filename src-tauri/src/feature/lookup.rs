@@ -0,0 +1,193 @@
+// Resolving notes by id, title or alias, so the frontend never has to guess file names. Ids live in the
+// `id` frontmatter field and stay the same when a note is renamed or moved.
+use nanoid::nanoid;
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+
+use crate::feature::{refactor, untitled::{ID_ALPHABET, ID_LENGTH}};
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{file_operations, frontmatter, error::AppError, message::Message};
+
+// Frontmatter field holding the stable id of a note
+pub const ID_KEY: &str = "id";
+
+fn title_of(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn ambiguous(query: &str, candidates: &[String]) -> Error {
+    Message::new("lookup.ambiguous", "\"{query}\" matches several notes: {candidates}")
+        .with("query", query)
+        .with("candidates", candidates.join(", "))
+        .error(ErrorKind::InvalidInput)
+}
+
+// Returns the id of a note, if it has one.
+pub fn note_id(content: &str) -> Option<String> {
+    let fields = frontmatter::parse(content).ok()?;
+    let id = match fields.get(ID_KEY)? {
+        serde_yaml::Value::String(value) => value.trim().to_string(),
+        serde_yaml::Value::Number(value) => value.to_string(),
+        _ => return None,
+    };
+    (!id.is_empty()).then_some(id)
+}
+
+// Returns the id of a note, giving it a new one first when it has none.
+pub fn ensure_id(vault: &Vault, path: &str) -> io::Result<String> {
+    let content = Note::read_note(vault, path)?;
+    if let Some(id) = note_id(&content) {
+        return Ok(id);
+    }
+    let id = nanoid!(ID_LENGTH, &ID_ALPHABET);
+    let mut fields = frontmatter::parse(&content)?;
+    fields.set(ID_KEY, id.clone());
+    Note::update_note(vault, path, &frontmatter::replace(&content, &fields)?)?;
+    Ok(id)
+}
+
+// Gives an id to every note of the vault that has none, e.g. notes written before ids existed. Returns
+// the paths of the notes that got one; notes with invalid frontmatter are left alone.
+pub fn assign_ids(vault: &Vault) -> io::Result<Vec<String>> {
+    let mut assigned = Vec::new();
+    for path in Note::list_notes(vault)? {
+        let content = Note::read_note(vault, &path)?;
+        if note_id(&content).is_none() && frontmatter::parse(&content).is_ok() {
+            ensure_id(vault, &path)?;
+            assigned.push(path);
+        }
+    }
+    Ok(assigned)
+}
+
+// Returns the path of the note whose frontmatter `id` matches.
+pub fn resolve_note_by_id(vault: &Vault, id: &str) -> io::Result<String> {
+    let id = id.trim();
+    if id.is_empty() {
+        return Err(Message::new("note.id_empty", "Note id is empty").error(ErrorKind::InvalidInput));
+    }
+    let mut matches = Vec::new();
+    for path in Note::list_notes(vault)? {
+        let content = Note::read_note(vault, &path)?;
+        if note_id(&content).as_deref() == Some(id) {
+            matches.push(path);
+        }
+    }
+    match matches.len() {
+        0 => Err(Message::new("note.id_not_found", "No note has this id").error(ErrorKind::NotFound)),
+        1 => Ok(matches.remove(0)),
+        _ => Err(ambiguous(id, &matches)),
+    }
+}
+
+// Resolves what the frontend knows a note by, its path or its id, to the note path.
+pub fn resolve_note(vault: &Vault, path_or_id: &str) -> io::Result<String> {
+    let path = refactor::clean_path(path_or_id.trim().trim_end_matches(".md"));
+    if !path.is_empty() && Path::new(&file_operations::resolve_path(&format!("{}/{}.md", vault.path, path))).exists() {
+        return Ok(path);
+    }
+    match resolve_note_by_id(vault, path_or_id) {
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            Err(Message::new("note.not_found", "Note file does not exist").error(ErrorKind::NotFound))
+        }
+        result => result,
+    }
+}
+
+// Resolves a title to a note path: an existing path wins, then notes named exactly like the title
+// (ignoring case), then notes listing it in their `aliases`. Several matches at one level are an error
+// that lists the candidates.
+pub fn resolve_title(vault: &Vault, title: &str) -> io::Result<String> {
+    let query = title.trim().trim_end_matches(".md");
+    let path = refactor::clean_path(query);
+    if path.is_empty() {
+        return Err(AppError::InvalidName(Message::new("note.title_empty", "Note title is empty")).into());
+    }
+    if Path::new(&file_operations::resolve_path(&format!("{}/{}.md", vault.path, path))).exists() {
+        return Ok(path);
+    }
+
+    let paths = Note::list_notes(vault)?;
+    let mut by_title: Vec<String> = paths
+        .iter()
+        .filter(|path| title_of(path).eq_ignore_ascii_case(title_of(query)) && !query.contains('/'))
+        .cloned()
+        .collect();
+    match by_title.len() {
+        0 => {}
+        1 => return Ok(by_title.remove(0)),
+        _ => return Err(ambiguous(query, &by_title)),
+    }
+
+    let mut by_alias = Vec::new();
+    for path in paths {
+        let content = Note::read_note(vault, &path)?;
+        let aliases = frontmatter::parse(&content).map(|fields| fields.aliases()).unwrap_or_default();
+        if aliases.iter().any(|alias| alias.eq_ignore_ascii_case(query)) {
+            by_alias.push(path);
+        }
+    }
+    match by_alias.len() {
+        0 => Err(Message::new("lookup.not_found", "No note is titled \"{query}\"").with("query", query).error(ErrorKind::NotFound)),
+        1 => Ok(by_alias.remove(0)),
+        _ => Err(ambiguous(query, &by_alias)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_resolve_notes() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        vault.create_folder("Work").unwrap();
+        vault.create_folder("Home").unwrap();
+        file_operations::write_to_file(&format!("{}/Work/Plan.md", vault.path), "---\nid: a1b2\naliases: [Roadmap]\n---\n").unwrap();
+        file_operations::write_to_file(&format!("{}/Home/plan.md", vault.path), "---\nid: 42\naliases: Launch plan\n---\n").unwrap();
+        file_operations::write_to_file(&format!("{}/Ideas.md", vault.path), "---\naliases: [roadmap]\n---\n").unwrap();
+
+        assert_eq!(resolve_note_by_id(&vault, "a1b2").unwrap(), "Work/Plan");
+        assert_eq!(resolve_note_by_id(&vault, "42").unwrap(), "Home/plan");
+        assert_eq!(resolve_note_by_id(&vault, "missing").unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(resolve_note(&vault, "Work/Plan").unwrap(), "Work/Plan");
+        assert_eq!(resolve_note(&vault, "a1b2").unwrap(), "Work/Plan");
+        assert_eq!(resolve_note(&vault, "Plan").unwrap_err().kind(), ErrorKind::NotFound);
+
+        assert_eq!(resolve_title(&vault, "Work/Plan.md").unwrap(), "Work/Plan");
+        assert_eq!(resolve_title(&vault, "ideas").unwrap(), "Ideas");
+        assert_eq!(resolve_title(&vault, "launch plan").unwrap(), "Home/plan");
+        let error = resolve_title(&vault, "Plan").unwrap_err();
+        assert!(error.to_string().contains("Home/plan, Work/Plan"));
+        assert!(resolve_title(&vault, "Roadmap").unwrap_err().to_string().contains("Ideas, Work/Plan"));
+        assert_eq!(resolve_title(&vault, "Nothing").unwrap_err().kind(), ErrorKind::NotFound);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_assign_ids() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Kept.md", vault.path), "---\nid: keep-me\n---\nBody").unwrap();
+        file_operations::write_to_file(&format!("{}/Plain.md", vault.path), "# Plain\n").unwrap();
+        file_operations::write_to_file(&format!("{}/Broken.md", vault.path), "---\n: [\n---\n").unwrap();
+
+        assert_eq!(assign_ids(&vault).unwrap(), vec!["Plain"]);
+        let id = note_id(&Note::read_note(&vault, "Plain").unwrap()).unwrap();
+        assert_eq!(id.len(), ID_LENGTH);
+        assert!(Note::read_note(&vault, "Plain").unwrap().ends_with("---\n# Plain\n"));
+        assert_eq!(ensure_id(&vault, "Plain").unwrap(), id);
+        assert_eq!(ensure_id(&vault, "Kept").unwrap(), "keep-me");
+
+        // The id still finds the note after a rename
+        refactor::rename_note(&vault, "Plain", "Renamed").unwrap();
+        assert_eq!(resolve_note(&vault, &id).unwrap(), "Renamed");
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,146 @@
+// Meeting notes: created from the meeting template with every attendee linked to a person note, so a
+// person's backlinks list the meetings they attended.
+use chrono::Local;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+
+use crate::feature::{folder_settings, templates};
+use crate::storage::{note::{Note, OnConflict}, vault::Vault};
+use crate::utils::{string_utils, message::Message};
+
+pub const MEETINGS_FOLDER: &str = "Meetings";
+pub const PEOPLE_FOLDER: &str = "People";
+pub const MEETING_TEMPLATE: &str = "meeting.md";
+const DEFAULT_MEETING_TEMPLATE: &str =
+    "---\ntype: meeting\ndate: {{date}}\n---\n# {{topic}}\n\n## Attendees\n{{attendee_list}}\n\n## Agenda\n\n## Notes\n\n## Action items\n";
+const PERSON_NOTE: &str = "---\ntype: person\n---\n# {{name}}\n";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeetingNote {
+    pub path: String,
+    // Person notes of the attendees, in the order given
+    pub people: Vec<String>,
+    // Person notes that did not exist before
+    pub created_people: Vec<String>,
+}
+
+// Finds the person note of an attendee: a note named after them in the people folder, or anywhere else.
+fn find_person(notes: &[String], file_name: &str) -> Option<String> {
+    let named = |path: &&String| path.rsplit('/').next().is_some_and(|name| name.eq_ignore_ascii_case(file_name));
+    let in_people = |path: &&String| path.starts_with(&format!("{}/", PEOPLE_FOLDER));
+    notes
+        .iter()
+        .filter(named)
+        .find(in_people)
+        .or_else(|| notes.iter().find(named))
+        .cloned()
+}
+
+fn person_link(path: &str, name: &str) -> String {
+    if path.rsplit('/').next() == Some(name) {
+        format!("[[{}]]", path)
+    } else {
+        format!("[[{}|{}]]", path, name)
+    }
+}
+
+// Creates today's meeting note on a topic in the meetings folder, creating a person note for every
+// attendee that has none. The note starts from `.templates/meeting.md` when the vault has one, which can
+// use `{{topic}}`, `{{attendees}}` (comma-separated links) and `{{attendee_list}}` (one link per line).
+pub fn create_meeting_note(vault: &Vault, topic: &str, attendees: &[String]) -> io::Result<MeetingNote> {
+    let topic = topic.trim();
+    if topic.is_empty() {
+        return Err(Message::new("meeting.topic_empty", "Meeting topic is empty").error(ErrorKind::InvalidInput));
+    }
+    let mut names: Vec<&str> = Vec::new();
+    for name in attendees.iter().map(|name| name.trim()).filter(|name| !name.is_empty()) {
+        if string_utils::sanitize_filename(name).is_empty() {
+            return Err(Message::new("meeting.invalid_attendee", "Invalid attendee name: {name}")
+                .with("name", name)
+                .error(ErrorKind::InvalidInput));
+        }
+        if !names.iter().any(|known| known.eq_ignore_ascii_case(name)) {
+            names.push(name);
+        }
+    }
+
+    let template = match templates::read_template(vault, MEETING_TEMPLATE) {
+        Ok(template) => template,
+        Err(e) if e.kind() == ErrorKind::NotFound => DEFAULT_MEETING_TEMPLATE.to_string(),
+        Err(e) => return Err(e),
+    };
+    let title = format!("{} {}", Local::now().format("%Y-%m-%d"), topic);
+    let mut notes = Note::list_notes(vault)?;
+    if notes.contains(&format!("{}/{}", MEETINGS_FOLDER, string_utils::title_filename(&title))) {
+        return Err(Message::new("meeting.already_exists", "A meeting on this topic already exists today").error(ErrorKind::AlreadyExists));
+    }
+
+    let mut people = Vec::new();
+    let mut created_people = Vec::new();
+    let mut links = Vec::new();
+    for name in names {
+        let path = match find_person(&notes, &string_utils::title_filename(name)) {
+            Some(path) => path,
+            None => {
+                let content = PERSON_NOTE.replace("{{name}}", name);
+                let path = folder_settings::create_note(vault, PEOPLE_FOLDER, &Note::new(name, &content), OnConflict::Fail)?;
+                created_people.push(path.clone());
+                notes.push(path.clone());
+                path
+            }
+        };
+        links.push(person_link(&path, name));
+        people.push(path);
+    }
+
+    let values = HashMap::from([
+        ("topic".to_string(), topic.to_string()),
+        ("attendees".to_string(), links.join(", ")),
+        ("attendee_list".to_string(), links.iter().map(|link| format!("- {}", link)).collect::<Vec<_>>().join("\n")),
+    ]);
+    let content = templates::render_template(&template, &title, &values)?;
+    let path = folder_settings::create_note(vault, MEETINGS_FOLDER, &Note::new(&title, &content), OnConflict::Fail)?;
+    Ok(MeetingNote { path, people, created_people })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_create_meeting_note() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Team", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Team/Grace.md", vault.path), "# Grace").unwrap();
+        let attendees = ["Ada Lovelace", "grace", " ", "ada lovelace"].map(String::from);
+
+        let meeting = create_meeting_note(&vault, "Roadmap review", &attendees).unwrap();
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        assert_eq!(meeting.path, format!("Meetings/{} Roadmap review", today));
+        assert_eq!(meeting.people, vec!["People/Ada Lovelace", "Team/Grace"]);
+        assert_eq!(meeting.created_people, vec!["People/Ada Lovelace"]);
+
+        let content = Note::read_note(&vault, &meeting.path).unwrap();
+        assert!(content.starts_with(&format!("---\ntype: meeting\ndate: {}\n---\n# Roadmap review\n", today)));
+        assert!(content.contains("## Attendees\n- [[People/Ada Lovelace]]\n- [[Team/Grace|grace]]\n"));
+        assert_eq!(Note::read_note(&vault, "People/Ada Lovelace").unwrap(), "---\ntype: person\n---\n# Ada Lovelace\n");
+
+        assert_eq!(create_meeting_note(&vault, "Roadmap review", &[]).unwrap_err().kind(), ErrorKind::AlreadyExists);
+        assert_eq!(create_meeting_note(&vault, " ", &[]).unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(create_meeting_note(&vault, "Sync", &["!!".to_string()]).unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        // A vault template replaces the default layout
+        file_operations::create_directory(&format!("{}/.templates", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/.templates/meeting.md", vault.path), "{{topic}} with {{attendees}}").unwrap();
+        let meeting = create_meeting_note(&vault, "Sync", &["Ada Lovelace".to_string()]).unwrap();
+        assert!(meeting.created_people.is_empty());
+        assert_eq!(Note::read_note(&vault, &meeting.path).unwrap(), "Sync with [[People/Ada Lovelace]]");
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -1,13 +1,24 @@
 // Metadata handling
 use sled::Db;
 use serde::{Serialize, Deserialize};
+use std::io::{self, Error, ErrorKind};
+use chrono::Utc;
 
-#[derive(Serialize, Deserialize)]
+use crate::storage::vault::Vault;
+use crate::utils::file_operations;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct NoteMetadata {
     pub tags: Vec<String>,
     pub backlinks: Vec<String>,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub open_count: u64,
+    #[serde(default)]
+    pub last_opened: Option<String>,
+    #[serde(default)]
+    pub pinned: Option<u32>,
 }
 
 pub struct MetadataStore {
@@ -15,15 +26,146 @@ pub struct MetadataStore {
 }
 
 impl MetadataStore {
-    pub fn new(path: &str) -> Self {
-        todo!("Initialize a new MetadataStore");
+    pub fn new(path: &str) -> io::Result<Self> {
+        let db = sled::open(path)?;
+        Ok(Self { db })
+    }
+
+    // Opens the metadata store kept under `<vault>/.meta`.
+    pub fn open(vault: &Vault) -> io::Result<Self> {
+        Self::new(&file_operations::resolve_path(&format!("{}/.meta", vault.path)))
+    }
+
+    // Opens a named keyspace for data that is not per-note metadata.
+    pub fn tree(&self, name: &str) -> io::Result<sled::Tree> {
+        Ok(self.db.open_tree(name)?)
     }
 
     pub fn get_metadata(&self, note_id: &str) -> Option<NoteMetadata> {
-        todo!("Retrieve metadata for a note");
+        let bytes = self.db.get(note_id).ok()??;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    // Lists the metadata of every note that has some.
+    pub fn all_metadata(&self) -> io::Result<Vec<(String, NoteMetadata)>> {
+        let mut all = Vec::new();
+        for entry in self.db.iter() {
+            let (key, bytes) = entry?;
+            if let Ok(metadata) = serde_json::from_slice(&bytes) {
+                all.push((String::from_utf8_lossy(&key).to_string(), metadata));
+            }
+        }
+        Ok(all)
+    }
+
+    pub fn update_metadata(&self, note_id: &str, metadata: NoteMetadata) -> io::Result<()> {
+        let bytes = serde_json::to_vec(&metadata)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        self.db.insert(note_id, bytes)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    pub fn remove_metadata(&self, note_id: &str) -> io::Result<()> {
+        self.db.remove(note_id)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    // Moves the metadata of a renamed or moved note to its new id.
+    pub fn rename_metadata(&self, old_id: &str, new_id: &str) -> io::Result<()> {
+        if old_id == new_id {
+            return Ok(());
+        }
+        if let Some(metadata) = self.get_metadata(old_id) {
+            self.update_metadata(new_id, metadata)?;
+            self.db.remove(old_id)?;
+            self.db.flush()?;
+        }
+        Ok(())
+    }
+
+    // Stamps a saved note: `created_at` on its first save, `updated_at` on every save.
+    pub fn touch(&self, note_id: &str) -> io::Result<NoteMetadata> {
+        let mut metadata = self.get_metadata(note_id).unwrap_or_default();
+        let now = Utc::now().to_rfc3339();
+        if metadata.created_at.is_empty() {
+            metadata.created_at = now.clone();
+        }
+        metadata.updated_at = now;
+        self.update_metadata(note_id, metadata.clone())?;
+        Ok(metadata)
     }
 
-    pub fn update_metadata(&self, note_id: &str, metadata: NoteMetadata) {
-        todo!("Update metadata for a note");
+    // Bumps the open counter and last-opened timestamp of a note.
+    pub fn record_open(&self, note_id: &str) -> io::Result<NoteMetadata> {
+        let mut metadata = self.get_metadata(note_id).unwrap_or_default();
+        metadata.open_count += 1;
+        metadata.last_opened = Some(Utc::now().to_rfc3339());
+        self.update_metadata(note_id, metadata.clone())?;
+        Ok(metadata)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_update_and_get_metadata() {
+        file_operations::set_base_path(None);
+        let path = format!("test_meta_{}", nanoid!());
+        let store = MetadataStore::new(&path).unwrap();
+        assert!(store.get_metadata("note").is_none());
+
+        let metadata = NoteMetadata {
+            tags: vec!["rust".to_string()],
+            ..Default::default()
+        };
+        store.update_metadata("note", metadata).unwrap();
+        assert_eq!(store.get_metadata("note").unwrap().tags, vec!["rust"]);
+
+        store.rename_metadata("note", "Folder/renamed").unwrap();
+        assert!(store.get_metadata("note").is_none());
+        assert_eq!(store.get_metadata("Folder/renamed").unwrap().tags, vec!["rust"]);
+
+        // Cleanup
+        drop(store);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_touch() {
+        file_operations::set_base_path(None);
+        let path = format!("test_meta_{}", nanoid!());
+        let store = MetadataStore::new(&path).unwrap();
+        let created = store.touch("note").unwrap();
+        assert!(!created.created_at.is_empty());
+        assert_eq!(created.created_at, created.updated_at);
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let saved = store.touch("note").unwrap();
+        assert_eq!(saved.created_at, created.created_at);
+        assert!(saved.updated_at > created.updated_at);
+
+        // Cleanup
+        drop(store);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_record_open() {
+        file_operations::set_base_path(None);
+        let path = format!("test_meta_{}", nanoid!());
+        let store = MetadataStore::new(&path).unwrap();
+        store.record_open("note").unwrap();
+        let metadata = store.record_open("note").unwrap();
+        assert_eq!(metadata.open_count, 2);
+        assert!(metadata.last_opened.is_some());
+
+        // Cleanup
+        drop(store);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -1,7 +1,64 @@
+pub mod activity;
+pub mod app_state;
+pub mod archival;
+pub mod audio;
+pub mod autocomplete;
+pub mod backlinks;
+pub mod block_refs;
+pub mod bookmarks;
+pub mod bulk_edit;
+pub mod calendar;
+pub mod csv_export;
+pub mod duplicates;
+pub mod export;
+pub mod file_tree;
+pub mod folder_settings;
+pub mod git_sync;
 pub mod graph;
-pub mod search;
+pub mod graph_layout;
+pub mod health;
+pub mod history;
+pub mod import;
+pub mod inbox;
+pub mod index_notes;
+pub mod integrity;
+pub mod journal;
+pub mod keybindings;
+pub mod link_rot;
+pub mod lint;
+pub mod locks;
+pub mod lookup;
+pub mod meetings;
 pub mod metadata;
-
-pub use graph::*;
-pub use search::*;
-pub use metadata::*;
\ No newline at end of file
+pub mod note_image;
+pub mod note_stats;
+pub mod ocr;
+pub mod paste_guard;
+pub mod pdf;
+pub mod people;
+pub mod pinned;
+pub mod previews;
+pub mod publish;
+pub mod recovery;
+pub mod refactor;
+pub mod review;
+pub mod search;
+pub mod settings;
+pub mod settings_archive;
+pub mod share;
+pub mod site_templates;
+pub mod snapshots;
+pub mod snippets;
+pub mod tag_suggestions;
+pub mod templates;
+pub mod thumbnails;
+pub mod time_tracking;
+pub mod timeline;
+pub mod title_sync;
+pub mod unresolved_links;
+pub mod untitled;
+pub mod view_state;
+pub mod watcher;
+pub mod weekly_review;
+pub mod write_lock;
+pub mod writing_stats;
\ No newline at end of file
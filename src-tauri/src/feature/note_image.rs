@@ -0,0 +1,475 @@
+// Notes rendered to styled PNG cards for sharing
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use resvg::{tiny_skia, usvg};
+use serde::{Serialize, Deserialize};
+use std::fs;
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{frontmatter, markdown, message::Message};
+
+pub const CARD_WIDTH: u32 = 1080;
+const PADDING: f32 = 72.0;
+const MAX_LINES: usize = 36;
+const FONT_FAMILY: &str = "Inter, 'Helvetica Neue', Arial, 'DejaVu Sans', sans-serif";
+const MONO_FAMILY: &str = "'JetBrains Mono', Menlo, Consolas, 'DejaVu Sans Mono', monospace";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CardTheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+struct Palette {
+    background: &'static str,
+    text: &'static str,
+    muted: &'static str,
+    accent: &'static str,
+    code: &'static str,
+}
+
+impl CardTheme {
+    fn palette(self) -> Palette {
+        match self {
+            CardTheme::Light => Palette {
+                background: "#fbfaf7",
+                text: "#1f2328",
+                muted: "#6e7781",
+                accent: "#5b5bd6",
+                code: "#eeece6",
+            },
+            CardTheme::Dark => Palette {
+                background: "#1e1f24",
+                text: "#e6e6e6",
+                muted: "#9198a1",
+                accent: "#9d9dff",
+                code: "#2b2d34",
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BlockKind {
+    Heading(u8),
+    Paragraph,
+    Item,
+    Quote,
+    Code,
+}
+
+// A line of the card after wrapping, with the block it belongs to.
+struct Line {
+    kind: BlockKind,
+    text: String,
+    // First line of its block, which gets the bullet and the spacing above
+    first: bool,
+}
+
+impl BlockKind {
+    fn font_size(self) -> f32 {
+        match self {
+            BlockKind::Heading(1) => 52.0,
+            BlockKind::Heading(2) => 40.0,
+            BlockKind::Heading(_) => 34.0,
+            BlockKind::Code => 24.0,
+            _ => 30.0,
+        }
+    }
+
+    fn indent(self) -> f32 {
+        match self {
+            BlockKind::Item | BlockKind::Quote => 36.0,
+            BlockKind::Code => 20.0,
+            _ => 0.0,
+        }
+    }
+}
+
+// Whether an embed target is a file, such as an image, rather than a note.
+pub(crate) fn is_file_embed(target: &str) -> bool {
+    let name = target.rsplit('/').next().unwrap_or(target);
+    name.contains('.') && !name.ends_with(".md")
+}
+
+// Lays table rows out as aligned columns of plain text, with a rule under the header.
+fn table_text(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| rows.iter().filter_map(|row| row.get(column)).map(|cell| cell.chars().count()).max().unwrap_or(0))
+        .collect();
+    let mut lines = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(column, width)| format!("{:<width$}", row.get(column).map(String::as_str).unwrap_or(""), width = width))
+            .collect();
+        lines.push(cells.join(" | ").trim_end().to_string());
+        if i == 0 {
+            lines.push(widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<_>>().join("-+-"));
+        }
+    }
+    lines.join("\n")
+}
+
+// Splits the Markdown into styled blocks of plain text. Tables become code blocks of aligned columns.
+// For print, external link targets follow their text and images are shown as `[Image: alt]`, since
+// neither can be followed or shown on paper.
+pub(crate) fn blocks(content: &str, print: bool) -> Vec<(BlockKind, String)> {
+    let mut content = markdown::rewrite_wikilinks(frontmatter::strip(content), |_, label| label.to_string());
+    if print {
+        let embed_re = Regex::new(r"!\[\[([^\]|#]+)[^\]]*\]\]").unwrap();
+        content = embed_re
+            .replace_all(&content, |caps: &regex::Captures| {
+                let target = caps[1].trim();
+                if is_file_embed(target) { format!("[Image: {}]", target) } else { target.to_string() }
+            })
+            .to_string();
+    }
+    let mut blocks = Vec::new();
+    let mut current: Option<(BlockKind, String)> = None;
+    let mut quote_depth = 0;
+    let mut item_depth = 0;
+    // Rows of the table being read, and the cell being read
+    let mut table: Option<(Vec<Vec<String>>, String)> = None;
+    let mut link: Option<String> = None;
+
+    let flush = |current: &mut Option<(BlockKind, String)>, blocks: &mut Vec<(BlockKind, String)>| {
+        if let Some((kind, text)) = current.take() {
+            let text = if kind == BlockKind::Code { text.trim_end().to_string() } else { text.trim().to_string() };
+            if !text.is_empty() {
+                blocks.push((kind, text));
+            }
+        }
+    };
+
+    for event in Parser::new_ext(&content, Options::ENABLE_TABLES) {
+        match event {
+            Event::Start(Tag::Table(_)) => {
+                flush(&mut current, &mut blocks);
+                table = Some((vec![Vec::new()], String::new()));
+            }
+            Event::End(TagEnd::TableCell) => {
+                if let Some((rows, cell)) = table.as_mut() {
+                    let text = std::mem::take(cell).trim().to_string();
+                    rows.last_mut().unwrap().push(text);
+                }
+            }
+            Event::End(TagEnd::TableHead | TagEnd::TableRow) => {
+                if let Some((rows, _)) = table.as_mut() {
+                    rows.push(Vec::new());
+                }
+            }
+            Event::End(TagEnd::Table) => {
+                if let Some((mut rows, _)) = table.take() {
+                    rows.retain(|row| !row.is_empty());
+                    blocks.push((BlockKind::Code, table_text(&rows)));
+                }
+            }
+            Event::Text(text) | Event::Code(text) if table.is_some() => {
+                table.as_mut().unwrap().1.push_str(&text);
+            }
+            Event::Start(Tag::Link { dest_url, .. }) if print && (dest_url.contains("://") || dest_url.starts_with("mailto:")) => {
+                link = Some(dest_url.to_string());
+            }
+            Event::End(TagEnd::Link) => {
+                if let (Some(url), Some((_, block))) = (link.take(), current.as_mut()) {
+                    if !block.ends_with(&url) {
+                        block.push_str(&format!(" ({})", url));
+                    }
+                }
+            }
+            Event::Start(Tag::Image { .. }) if print => {
+                if let Some((_, block)) = current.as_mut() {
+                    block.push_str("[Image: ");
+                }
+            }
+            Event::End(TagEnd::Image) if print => {
+                if let Some((_, block)) = current.as_mut() {
+                    block.push(']');
+                }
+            }
+            Event::Start(Tag::Heading { level, .. }) => {
+                flush(&mut current, &mut blocks);
+                let level = match level {
+                    HeadingLevel::H1 => 1,
+                    HeadingLevel::H2 => 2,
+                    _ => 3,
+                };
+                current = Some((BlockKind::Heading(level), String::new()));
+            }
+            Event::Start(Tag::Paragraph) if current.is_none() => {
+                let kind = match (item_depth, quote_depth) {
+                    (0, 0) => BlockKind::Paragraph,
+                    (0, _) => BlockKind::Quote,
+                    _ => BlockKind::Item,
+                };
+                current = Some((kind, String::new()));
+            }
+            Event::Start(Tag::Item) => {
+                flush(&mut current, &mut blocks);
+                item_depth += 1;
+                current = Some((BlockKind::Item, String::new()));
+            }
+            Event::End(TagEnd::Item) => {
+                flush(&mut current, &mut blocks);
+                item_depth -= 1;
+            }
+            Event::Start(Tag::BlockQuote(_)) => quote_depth += 1,
+            Event::End(TagEnd::BlockQuote(_)) => quote_depth -= 1,
+            Event::Start(Tag::CodeBlock(_)) => {
+                flush(&mut current, &mut blocks);
+                current = Some((BlockKind::Code, String::new()));
+            }
+            // Paragraphs of a list item stay in the item
+            Event::End(TagEnd::Heading(_) | TagEnd::Paragraph | TagEnd::CodeBlock)
+                if item_depth == 0 || current.as_ref().is_some_and(|(kind, _)| *kind != BlockKind::Item) =>
+            {
+                flush(&mut current, &mut blocks);
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, block)) = current.as_mut() {
+                    block.push_str(&text);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some((_, block)) = current.as_mut() {
+                    block.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    flush(&mut current, &mut blocks);
+    blocks
+}
+
+// Wraps text on word boundaries to an approximate number of characters per line.
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > columns {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn layout(content: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    for (kind, text) in blocks(content, false) {
+        let width = CARD_WIDTH as f32 - 2.0 * PADDING - kind.indent();
+        // Average glyph width is a bit over half the font size (0.6 for monospace)
+        let ratio = if kind == BlockKind::Code { 0.6 } else { 0.52 };
+        let columns = (width / (kind.font_size() * ratio)) as usize;
+        let wrapped = match kind {
+            BlockKind::Code => text.lines().flat_map(|line| wrap_code(line, columns)).collect(),
+            _ => wrap(&text, columns),
+        };
+        for (i, text) in wrapped.into_iter().enumerate() {
+            lines.push(Line { kind, text, first: i == 0 });
+        }
+    }
+
+    if lines.len() > MAX_LINES {
+        lines.truncate(MAX_LINES);
+        if let Some(last) = lines.last_mut() {
+            last.text.push_str(" …");
+        }
+    }
+    lines
+}
+
+// Code keeps its indentation and is cut instead of wrapped on words.
+fn wrap_code(line: &str, columns: usize) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars.chunks(columns.max(1)).map(|chunk| chunk.iter().collect()).collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Builds the SVG of the card: title, body lines and the vault name as footer.
+fn card_svg(title: &str, footer: &str, content: &str, theme: CardTheme) -> String {
+    let palette = theme.palette();
+    let mut body = String::new();
+    let mut y = PADDING;
+
+    for line in layout(content) {
+        let size = line.kind.font_size();
+        if line.first {
+            y += match line.kind {
+                BlockKind::Heading(_) => size * 0.8,
+                BlockKind::Item => size * 0.25,
+                _ => size * 0.5,
+            };
+        }
+        let line_height = if line.kind == BlockKind::Code { size * 1.5 } else { size * 1.35 };
+        let x = PADDING + line.kind.indent();
+        let baseline = y + size;
+
+        match line.kind {
+            BlockKind::Code => body.push_str(&format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                PADDING,
+                y,
+                CARD_WIDTH as f32 - 2.0 * PADDING,
+                line_height,
+                palette.code
+            )),
+            BlockKind::Quote => body.push_str(&format!(
+                r#"<rect x="{}" y="{}" width="6" height="{}" fill="{}"/>"#,
+                PADDING,
+                y,
+                line_height,
+                palette.accent
+            )),
+            BlockKind::Item if line.first => body.push_str(&format!(
+                r#"<circle cx="{}" cy="{}" r="5" fill="{}"/>"#,
+                PADDING + 12.0,
+                baseline - size * 0.35,
+                palette.accent
+            )),
+            _ => {}
+        }
+
+        let (family, weight, fill) = match line.kind {
+            BlockKind::Heading(_) => (FONT_FAMILY, "700", palette.text),
+            BlockKind::Code => (MONO_FAMILY, "400", palette.text),
+            BlockKind::Quote => (FONT_FAMILY, "400", palette.muted),
+            _ => (FONT_FAMILY, "400", palette.text),
+        };
+        body.push_str(&format!(
+            r#"<text x="{}" y="{}" font-family="{}" font-size="{}" font-weight="{}" fill="{}" xml:space="preserve">{}</text>"#,
+            x,
+            baseline,
+            escape(family),
+            size,
+            weight,
+            fill,
+            escape(&line.text)
+        ));
+        y += line_height;
+    }
+
+    let height = (y + PADDING * 2.0).ceil();
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#,
+            r#"<rect width="100%" height="100%" fill="{background}"/>"#,
+            r#"<rect width="100%" height="10" fill="{accent}"/>"#,
+            "{body}",
+            r#"<text x="{padding}" y="{footer_y}" font-family="{family}" font-size="24" fill="{muted}">{footer}</text>"#,
+            r#"<text x="{right}" y="{footer_y}" font-family="{family}" font-size="24" fill="{muted}" text-anchor="end">{title}</text>"#,
+            "</svg>"
+        ),
+        width = CARD_WIDTH,
+        height = height,
+        background = palette.background,
+        accent = palette.accent,
+        body = body,
+        padding = PADDING,
+        right = CARD_WIDTH as f32 - PADDING,
+        footer_y = height - PADDING * 0.75,
+        family = escape(FONT_FAMILY),
+        muted = palette.muted,
+        footer = escape(footer),
+        title = escape(title),
+    )
+}
+
+// Renders Markdown to a PNG card with the system fonts.
+pub fn render_card(title: &str, footer: &str, content: &str, theme: CardTheme) -> io::Result<Vec<u8>> {
+    let svg = card_svg(title, footer, content, theme);
+    let mut options = usvg::Options::default();
+    Arc::make_mut(&mut options.fontdb).load_system_fonts();
+    let tree = usvg::Tree::from_str(&svg, &options).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| Message::new("note_image.too_large", "Note image is too large").error(ErrorKind::Other))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(Error::other)
+}
+
+// Renders a note, or only the selected Markdown of it, to a PNG card at `output`.
+pub fn export_note_image(
+    vault: &Vault,
+    title: &str,
+    selection: Option<&str>,
+    theme: CardTheme,
+    output: &Path,
+) -> io::Result<()> {
+    let content = match selection {
+        Some(selection) if !selection.trim().is_empty() => selection.to_string(),
+        _ => Note::read_note(vault, title)?,
+    };
+    let png = render_card(title, &vault.name, &content, theme)?;
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(output, png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_layout() {
+        let lines = layout("---\ntags: [a]\n---\n# Title\n\nSome [[Other|text]] & more.\n\n- one\n- two\n\n```\nfn main() {}\n```");
+        let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts, vec!["Title", "Some text & more.", "one", "two", "fn main() {}"]);
+        assert_eq!(lines[0].kind, BlockKind::Heading(1));
+        assert_eq!(lines[2].kind, BlockKind::Item);
+        assert_eq!(lines[4].kind, BlockKind::Code);
+
+        let long = "word ".repeat(1000);
+        assert_eq!(layout(&long).len(), MAX_LINES);
+        assert!(card_svg("A <b>", "Vault", "x", CardTheme::Dark).contains("A &lt;b&gt;"));
+    }
+
+    #[test]
+    fn test_export_note_image() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Idea.md", vault.path), "# Idea\n\nShip it.\n\n> Quote").unwrap();
+
+        let output = std::env::temp_dir().join(format!("test_card_{}.png", nanoid!()));
+        export_note_image(&vault, "Idea", None, CardTheme::Light, &output).unwrap();
+        let image = image::open(&output).unwrap();
+        assert_eq!(image.width(), CARD_WIDTH);
+        assert!(image.height() > 2 * PADDING as u32);
+
+        export_note_image(&vault, "Idea", Some("Only this"), CardTheme::Dark, &output).unwrap();
+        assert!(export_note_image(&vault, "Missing", None, CardTheme::Light, &output).is_err());
+
+        // Cleanup
+        fs::remove_file(&output).unwrap();
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,89 @@
+// Statistics shown as badges next to notes in result lists
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::io;
+
+use crate::feature::metadata::MetadataStore;
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{file_operations, markdown};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteStats {
+    pub title: String,
+    pub word_count: usize,
+    pub backlink_count: usize,
+    pub modified: Option<String>,
+}
+
+// Computes the badges of a note; backlinks and the modification time come from the metadata when available.
+pub fn note_stats(vault: &Vault, store: Option<&MetadataStore>, title: &str, content: &str) -> NoteStats {
+    let metadata = store.and_then(|store| store.get_metadata(title));
+    let modified = metadata
+        .as_ref()
+        .map(|metadata| metadata.updated_at.clone())
+        .filter(|updated_at| !updated_at.is_empty())
+        .or_else(|| {
+            let path = file_operations::resolve_path(&format!("{}/{}.md", vault.path, title));
+            let modified = std::fs::metadata(path).and_then(|file| file.modified()).ok()?;
+            Some(DateTime::<Utc>::from(modified).to_rfc3339())
+        });
+
+    NoteStats {
+        title: title.to_string(),
+        word_count: markdown::word_count(content),
+        backlink_count: metadata.map(|metadata| metadata.backlinks.len()).unwrap_or(0),
+        modified,
+    }
+}
+
+// Returns the badges of several notes in one call, skipping notes that no longer exist.
+pub fn get_note_stats(vault: &Vault, store: Option<&MetadataStore>, titles: &[String]) -> io::Result<Vec<NoteStats>> {
+    let mut stats = Vec::new();
+    for title in titles {
+        match Note::read_note(vault, title) {
+            Ok(content) => stats.push(note_stats(vault, store, title, &content)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::metadata::NoteMetadata;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_get_note_stats() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/A.md", vault.path), "# A\n\nThree more words").unwrap();
+        let store = MetadataStore::open(&vault).unwrap();
+        let metadata = NoteMetadata {
+            backlinks: vec!["B".to_string(), "C".to_string()],
+            updated_at: "2024-05-03T10:00:00+00:00".to_string(),
+            ..Default::default()
+        };
+        store.update_metadata("A", metadata).unwrap();
+
+        let titles = vec!["A".to_string(), "Missing".to_string()];
+        let stats = get_note_stats(&vault, Some(&store), &titles).unwrap();
+        assert_eq!(stats, vec![NoteStats {
+            title: "A".to_string(),
+            word_count: 4,
+            backlink_count: 2,
+            modified: Some("2024-05-03T10:00:00+00:00".to_string()),
+        }]);
+
+        // Without metadata (safe mode) the file time is used
+        let stats = get_note_stats(&vault, None, &titles).unwrap();
+        assert_eq!(stats[0].backlink_count, 0);
+        assert!(stats[0].modified.is_some());
+
+        // Cleanup
+        drop(store);
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,88 @@
+// OCR of image attachments
+use std::io::{self, ErrorKind};
+use std::path::Path;
+use std::process::Command;
+
+use crate::storage::{attachment::AttachmentKind, note::Note, vault::Vault};
+use crate::utils::{file_operations, message::Message};
+
+pub const OCR_DIR: &str = ".ocr";
+
+// Returns the sidecar file holding the recognized text of an attachment.
+pub fn sidecar_path(vault: &Vault, attachment_path: &str) -> String {
+    format!("{}/{}/{}.txt", vault.path, OCR_DIR, attachment_path.replace(['/', '\\'], "_"))
+}
+
+// Reads the stored OCR text of an attachment, if any.
+pub fn read_sidecar(vault: &Vault, attachment_path: &str) -> Option<String> {
+    file_operations::read_from_file(&sidecar_path(vault, attachment_path)).ok()
+}
+
+// Recognizes the text of an image attachment with tesseract, caching it in a sidecar file.
+pub fn extract_text(vault: &Vault, attachment_path: &str, language: Option<&str>) -> io::Result<String> {
+    if AttachmentKind::from_name(attachment_path) != AttachmentKind::Image {
+        return Err(Message::new("ocr.not_an_image", "OCR is only supported for images").error(ErrorKind::InvalidInput));
+    }
+    let source = format!("{}/{}", vault.path, attachment_path);
+    if !Path::new(&file_operations::resolve_path(&source)).exists() {
+        return Err(Message::new("attachment.not_found", "Attachment does not exist").error(ErrorKind::NotFound));
+    }
+
+    let sidecar = sidecar_path(vault, attachment_path);
+    if file_operations::is_up_to_date(&source, &sidecar) {
+        return file_operations::read_from_file(&sidecar);
+    }
+
+    let mut command = Command::new("tesseract");
+    command.arg(file_operations::resolve_path(&source)).arg("stdout");
+    if let Some(language) = language {
+        command.args(["-l", language]);
+    }
+    let output = command.output().map_err(|e| match e.kind() {
+        ErrorKind::NotFound => Message::new("ocr.tesseract_missing", "tesseract is not installed").error(ErrorKind::NotFound),
+        _ => e,
+    })?;
+    if !output.status.success() {
+        return Err(Message::new("ocr.failed", "OCR failed: {detail}")
+            .with("detail", String::from_utf8_lossy(&output.stderr).trim())
+            .error(ErrorKind::Other));
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    file_operations::create_directory(&format!("{}/{}", vault.path, OCR_DIR))?;
+    file_operations::write_to_file(&sidecar, &text)?;
+    Ok(text)
+}
+
+// Appends the recognized text of an attachment to a note.
+pub fn insert_into_note(vault: &Vault, title: &str, text: &str) -> io::Result<()> {
+    let mut content = Note::read_note(vault, title)?;
+    content.push_str(&format!("\n\n{}", text));
+    Note::update_note(vault, title, &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::attachment;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_extract_text_uses_sidecar() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        attachment::save_attachment(&vault, "scan.png", b"png").unwrap();
+        assert!(read_sidecar(&vault, "attachments/scan.png").is_none());
+
+        file_operations::create_directory(&format!("{}/{}", vault.path, OCR_DIR)).unwrap();
+        file_operations::write_to_file(&sidecar_path(&vault, "attachments/scan.png"), "Recognized text").unwrap();
+        assert_eq!(extract_text(&vault, "attachments/scan.png", None).unwrap(), "Recognized text");
+
+        attachment::save_attachment(&vault, "notes.pdf", b"pdf").unwrap();
+        let error = extract_text(&vault, "attachments/notes.pdf", None).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,236 @@
+// Guard against pasted blobs: when a saved note grows past the size limit, base64 data (data URIs or bare)
+// and binary-looking text are moved into attachments and replaced with links, so a stray paste does not
+// turn a note into a multi-megabyte file that slows down indexing and rendering.
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use regex::{Captures, Regex};
+use serde::{Serialize, Deserialize};
+use std::io;
+
+use crate::storage::{attachment, vault::Vault};
+use crate::utils::message::Message;
+
+// Shorter runs are left alone, so ordinary long words and tokens are never touched
+pub const MIN_BLOB_LEN: usize = 4096;
+// Share of control or replacement characters that makes a line look binary
+const BINARY_RATIO: f32 = 0.1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractionNotice {
+    // Attachments created from the blobs, relative to the vault
+    pub attachments: Vec<String>,
+    pub bytes_removed: usize,
+    // The note as saved, with links in place of the blobs
+    pub content: String,
+    pub message: Message,
+}
+
+// Picks a file extension from the leading bytes of a file, then from its MIME type.
+fn extension(bytes: &[u8], mime: Option<&str>) -> String {
+    let sniffed = if bytes.starts_with(b"\x89PNG") {
+        Some("png")
+    } else if bytes.starts_with(b"\xFF\xD8\xFF") {
+        Some("jpg")
+    } else if bytes.starts_with(b"GIF8") {
+        Some("gif")
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        Some("webp")
+    } else if bytes.starts_with(b"%PDF") {
+        Some("pdf")
+    } else if bytes.starts_with(b"PK\x03\x04") {
+        Some("zip")
+    } else {
+        None
+    };
+    let from_mime = mime
+        .and_then(|mime| mime.split_once('/'))
+        .map(|(_, subtype)| subtype.split('+').next().unwrap_or(subtype))
+        .filter(|subtype| !subtype.is_empty() && subtype.chars().all(|c| c.is_ascii_alphanumeric()));
+    sniffed.or(from_mime).unwrap_or("bin").to_string()
+}
+
+fn looks_binary(line: &str) -> bool {
+    let total = line.chars().count();
+    let odd = line.chars().filter(|c| (c.is_control() && *c != '\t') || *c == '\u{FFFD}').count();
+    total > 0 && odd as f32 >= total as f32 * BINARY_RATIO
+}
+
+struct Extractor<'a> {
+    vault: &'a Vault,
+    folder: &'a str,
+    stem: &'a str,
+    attachments: Vec<String>,
+    bytes_removed: usize,
+    error: Option<io::Error>,
+}
+
+impl Extractor<'_> {
+    // Stores a blob as an attachment and returns the link replacing it, or `None` to keep the text.
+    fn store(&mut self, original: &str, bytes: &[u8], mime: Option<&str>, label: Option<&str>) -> Option<String> {
+        if self.error.is_some() {
+            return None;
+        }
+        let name = format!("{}-pasted.{}", self.stem, extension(bytes, mime));
+        match attachment::save_attachment(self.vault, &name, bytes) {
+            Ok(path) => {
+                let link = attachment::markdown_link(self.folder, &path, label.unwrap_or(&name));
+                self.bytes_removed += original.len();
+                if !self.attachments.contains(&path) {
+                    self.attachments.push(path);
+                }
+                Some(link)
+            }
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+
+    fn decode(text: &str) -> Option<Vec<u8>> {
+        let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+        STANDARD.decode(compact).ok()
+    }
+
+    fn data_uris(&mut self, content: &str) -> String {
+        let image_re = Regex::new(r"!\[([^\]]*)\]\(\s*data:([\w.+-]+/[\w.+-]+);base64,([A-Za-z0-9+/=\s]+)\)").unwrap();
+        let bare_re = Regex::new(r"data:([\w.+-]+/[\w.+-]+);base64,([A-Za-z0-9+/=]+)").unwrap();
+        let content = image_re.replace_all(content, |caps: &Captures| {
+            Self::decode(&caps[3])
+                .and_then(|bytes| self.store(&caps[0], &bytes, Some(&caps[2]), Some(&caps[1])))
+                .unwrap_or_else(|| caps[0].to_string())
+        });
+        bare_re
+            .replace_all(&content, |caps: &Captures| {
+                if caps[0].len() < MIN_BLOB_LEN {
+                    return caps[0].to_string();
+                }
+                Self::decode(&caps[2])
+                    .and_then(|bytes| self.store(&caps[0], &bytes, Some(&caps[1]), None))
+                    .unwrap_or_else(|| caps[0].to_string())
+            })
+            .to_string()
+    }
+
+    // Base64 without a data URI, possibly wrapped over several lines as in mail sources.
+    fn bare_base64(&mut self, content: &str) -> String {
+        let blob_re = Regex::new(r"[A-Za-z0-9+/]{60,}(?:\r?\n[A-Za-z0-9+/]{60,})*(?:\r?\n[A-Za-z0-9+/]{1,59})?={0,2}").unwrap();
+        blob_re
+            .replace_all(content, |caps: &Captures| {
+                let text = &caps[0];
+                // A short last line may be the end of the blob or a word on the next line
+                let candidates = [Some((text, "")), text.rsplit_once('\n')];
+                for (blob, rest) in candidates.into_iter().flatten() {
+                    if blob.len() < MIN_BLOB_LEN {
+                        continue;
+                    }
+                    if let Some(link) = Self::decode(blob).and_then(|bytes| self.store(blob, &bytes, None, None)) {
+                        return if rest.is_empty() { link } else { format!("{}\n{}", link, rest) };
+                    }
+                }
+                text.to_string()
+            })
+            .to_string()
+    }
+
+    // Runs of binary-looking lines, stored as they are.
+    fn binary_lines(&mut self, content: &str) -> String {
+        let mut output = String::with_capacity(content.len());
+        let mut run = String::new();
+        let flush = |run: &mut String, output: &mut String, this: &mut Self| {
+            let link = if run.len() >= MIN_BLOB_LEN { this.store(run, run.as_bytes(), None, None) } else { None };
+            match link {
+                Some(link) => output.push_str(&format!("{}\n", link)),
+                None => output.push_str(run),
+            }
+            run.clear();
+        };
+        for line in content.split_inclusive('\n') {
+            if looks_binary(line.trim_end_matches(['\r', '\n'])) {
+                run.push_str(line);
+            } else {
+                flush(&mut run, &mut output, self);
+                output.push_str(line);
+            }
+        }
+        flush(&mut run, &mut output, self);
+        output
+    }
+}
+
+// Moves the blobs of a note's content into attachments when the content is larger than `limit` bytes.
+// Returns the content to save and, when anything was moved, a notice for the editor. A limit of 0
+// turns the guard off.
+pub fn extract_blobs(vault: &Vault, path: &str, content: &str, limit: usize) -> io::Result<(String, Option<ExtractionNotice>)> {
+    if limit == 0 || content.len() <= limit {
+        return Ok((content.to_string(), None));
+    }
+    let (folder, stem) = path.rsplit_once('/').unwrap_or(("", path));
+    let mut extractor = Extractor { vault, folder, stem, attachments: Vec::new(), bytes_removed: 0, error: None };
+    let updated = extractor.data_uris(content);
+    let updated = extractor.bare_base64(&updated);
+    let updated = extractor.binary_lines(&updated);
+    if let Some(e) = extractor.error {
+        return Err(e);
+    }
+    if extractor.attachments.is_empty() {
+        return Ok((content.to_string(), None));
+    }
+
+    let message = Message::new("paste_guard.extracted", "Moved {count} pasted file(s) ({size} KB) out of the note into attachments")
+        .with("count", extractor.attachments.len())
+        .with("size", extractor.bytes_removed.div_ceil(1024));
+    let notice = ExtractionNotice {
+        attachments: extractor.attachments,
+        bytes_removed: extractor.bytes_removed,
+        content: updated.clone(),
+        message,
+    };
+    Ok((updated, Some(notice)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_extract_blobs() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.resize(6000, 7);
+        let encoded = STANDARD.encode(&png);
+        let wrapped: Vec<&str> = encoded.as_bytes().chunks(76).map(|line| std::str::from_utf8(line).unwrap()).collect();
+        let binary = "\u{1}\u{2}\u{FFFD}abc\n".repeat(1000);
+        let content = format!(
+            "# Trip\n![Beach](data:image/png;base64,{})\nRaw:\n{}\n{}Done\n",
+            encoded,
+            wrapped.join("\n"),
+            binary
+        );
+
+        // Small notes and a disabled guard keep their content
+        assert!(extract_blobs(&vault, "Trips/Rome", "short", 1024).unwrap().1.is_none());
+        assert!(extract_blobs(&vault, "Trips/Rome", &content, 0).unwrap().1.is_none());
+
+        let (updated, notice) = extract_blobs(&vault, "Trips/Rome", &content, 1024).unwrap();
+        let notice = notice.unwrap();
+        // The same image pasted twice is stored once
+        assert_eq!(notice.attachments, vec!["attachments/Rome-pasted.png", "attachments/Rome-pasted.bin"]);
+        assert_eq!(
+            updated,
+            "# Trip\n![Beach](../attachments/Rome-pasted.png)\nRaw:\n![Rome-pasted.png](../attachments/Rome-pasted.png)\n\
+             [Rome-pasted.bin](../attachments/Rome-pasted.bin)\nDone\n"
+        );
+        assert_eq!(notice.content, updated);
+        let image = format!("![Beach](data:image/png;base64,{})", encoded);
+        assert_eq!(notice.bytes_removed, image.len() + wrapped.join("\n").len() + binary.len());
+        let stored = std::fs::read(file_operations::resolve_path(&format!("{}/attachments/Rome-pasted.png", vault.path))).unwrap();
+        assert_eq!(stored, png);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,491 @@
+// Notes exported to PDF for printing. The Markdown is laid out straight into pages set in the standard
+// PDF fonts, so no browser or system library is involved; the print style picks the page size, the
+// margins and the type. The standard fonts only cover Western European text, so the export reports the
+// characters it could not print, and the images it printed as their names.
+use pulldown_cmark::{Event, Parser, Tag};
+use serde::{Serialize, Deserialize};
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use crate::feature::note_image::{self, BlockKind};
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{markdown, message::Message};
+
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageSize {
+    #[default]
+    A4,
+    Letter,
+}
+
+impl PageSize {
+    // Width and height in points
+    fn dimensions(self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => (595.28, 841.89),
+            PageSize::Letter => (612.0, 792.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrintFont {
+    #[default]
+    Sans,
+    Serif,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrintStyle {
+    pub page_size: PageSize,
+    pub font: PrintFont,
+    // Body text size in points; headings and code are sized relative to it
+    pub font_size: f32,
+    // Line height as a multiple of the font size
+    pub line_height: f32,
+    // Page margins in millimetres
+    pub margin: f32,
+    pub page_numbers: bool,
+}
+
+impl Default for PrintStyle {
+    fn default() -> Self {
+        Self {
+            page_size: PageSize::A4,
+            font: PrintFont::Sans,
+            font_size: 11.0,
+            line_height: 1.4,
+            margin: 20.0,
+            page_numbers: true,
+        }
+    }
+}
+
+// What an export could not print as written.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PdfReport {
+    pub pages: usize,
+    // Characters outside the encoding of the standard fonts, printed as `?`, in order of appearance
+    pub unsupported: Vec<String>,
+    // Images and embedded files, printed as `[Image: name]`
+    pub images: usize,
+}
+
+impl PrintStyle {
+    fn validate(&self) -> io::Result<()> {
+        if !(6.0..=36.0).contains(&self.font_size) {
+            return Err(Message::new("pdf.font_size_out_of_range", "Font size must be between {min} and {max} points")
+                .with("min", 6)
+                .with("max", 36)
+                .error(ErrorKind::InvalidInput));
+        }
+        if !(1.0..=3.0).contains(&self.line_height) {
+            return Err(Message::new("pdf.line_height_out_of_range", "Line height must be between {min} and {max}")
+                .with("min", 1)
+                .with("max", 3)
+                .error(ErrorKind::InvalidInput));
+        }
+        if !(5.0..=60.0).contains(&self.margin) {
+            return Err(Message::new("pdf.margins_out_of_range", "Margins must be between {min} and {max} mm")
+                .with("min", 5)
+                .with("max", 60)
+                .error(ErrorKind::InvalidInput));
+        }
+        Ok(())
+    }
+
+    fn font_size(&self, kind: BlockKind) -> f32 {
+        match kind {
+            BlockKind::Heading(1) => self.font_size * 1.8,
+            BlockKind::Heading(2) => self.font_size * 1.45,
+            BlockKind::Heading(_) => self.font_size * 1.2,
+            BlockKind::Code => self.font_size * 0.9,
+            _ => self.font_size,
+        }
+    }
+
+    fn indent(&self, kind: BlockKind) -> f32 {
+        match kind {
+            BlockKind::Item | BlockKind::Quote => self.font_size * 1.5,
+            BlockKind::Code => self.font_size * 0.6,
+            _ => 0.0,
+        }
+    }
+}
+
+// The four fonts of a document, registered as /F1 to /F4
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Face {
+    Regular,
+    Bold,
+    Italic,
+    Mono,
+}
+
+const FACES: [Face; 4] = [Face::Regular, Face::Bold, Face::Italic, Face::Mono];
+
+impl Face {
+    fn of(kind: BlockKind) -> Self {
+        match kind {
+            BlockKind::Heading(_) => Face::Bold,
+            BlockKind::Quote => Face::Italic,
+            BlockKind::Code => Face::Mono,
+            _ => Face::Regular,
+        }
+    }
+
+    fn resource(self) -> &'static str {
+        match self {
+            Face::Regular => "F1",
+            Face::Bold => "F2",
+            Face::Italic => "F3",
+            Face::Mono => "F4",
+        }
+    }
+
+    fn base_font(self, font: PrintFont) -> &'static str {
+        match (self, font) {
+            (Face::Regular, PrintFont::Sans) => "Helvetica",
+            (Face::Bold, PrintFont::Sans) => "Helvetica-Bold",
+            (Face::Italic, PrintFont::Sans) => "Helvetica-Oblique",
+            (Face::Regular, PrintFont::Serif) => "Times-Roman",
+            (Face::Bold, PrintFont::Serif) => "Times-Bold",
+            (Face::Italic, PrintFont::Serif) => "Times-Italic",
+            (Face::Mono, _) => "Courier",
+        }
+    }
+}
+
+// Glyph widths of Helvetica for ASCII 32..=126, in thousandths of the font size
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556, 556, 556,
+    556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667, 611, 778, 722, 278,
+    500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469,
+    556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556, 556, 556, 333, 500,
+    278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+// Width of text in points. Other faces are scaled from Helvetica, which is close enough for wrapping.
+fn text_width(text: &str, face: Face, font: PrintFont, size: f32) -> f32 {
+    if face == Face::Mono {
+        return text.chars().count() as f32 * 0.6 * size;
+    }
+    let units: f32 = text
+        .chars()
+        .map(|c| match c {
+            ' '..='~' => HELVETICA_WIDTHS[c as usize - 32] as f32,
+            _ => 556.0,
+        })
+        .sum();
+    let scale = match (face, font) {
+        (Face::Bold, _) => 1.06,
+        (_, PrintFont::Serif) => 0.92,
+        _ => 1.0,
+    };
+    units * scale * size / 1000.0
+}
+
+// Wraps text on word boundaries to a width; words wider than a line are split.
+fn wrap(text: &str, width: f32, measure: impl Fn(&str) -> f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+        if measure(&candidate) <= width {
+            line = candidate;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            if !line.is_empty() && measure(&format!("{}{}", line, c)) > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push(c);
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// Maps a character to WinAnsiEncoding, the encoding of the standard fonts.
+fn win_ansi(c: char) -> u8 {
+    match c {
+        ' '..='~' => c as u8,
+        '\u{a0}'..='\u{ff}' => c as u32 as u8,
+        '€' => 0x80,
+        '…' => 0x85,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '™' => 0x99,
+        '\t' => b' ',
+        _ => b'?',
+    }
+}
+
+// The characters of a text `win_ansi` cannot encode, once each.
+fn unsupported_chars(text: &str, found: &mut Vec<String>) {
+    for c in text.chars().filter(|c| *c != '?' && *c != '\n' && win_ansi(*c) == b'?') {
+        if !found.iter().any(|other| other.starts_with(c)) {
+            found.push(c.to_string());
+        }
+    }
+}
+
+// Encodes text as a PDF string literal.
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut bytes = vec![b'('];
+    for byte in text.chars().map(win_ansi) {
+        if matches!(byte, b'(' | b')' | b'\\') {
+            bytes.push(b'\\');
+        }
+        bytes.push(byte);
+    }
+    bytes.push(b')');
+    bytes
+}
+
+fn show_text(stream: &mut Vec<u8>, face: Face, size: f32, x: f32, y: f32, text: &str) {
+    stream.extend_from_slice(format!("BT /{} {:.2} Tf {:.2} {:.2} Td ", face.resource(), size, x, y).as_bytes());
+    stream.extend_from_slice(&pdf_string(text));
+    stream.extend_from_slice(b" Tj ET\n");
+}
+
+fn fill_rect(stream: &mut Vec<u8>, gray: f32, x: f32, y: f32, width: f32, height: f32) {
+    stream.extend_from_slice(format!("{:.2} g {:.2} {:.2} {:.2} {:.2} re f 0 g\n", gray, x, y, width, height).as_bytes());
+}
+
+// Lays the Markdown out into the content streams of the pages.
+fn layout(blocks: Vec<(BlockKind, String)>, style: &PrintStyle) -> Vec<Vec<u8>> {
+    let (width, height) = style.page_size.dimensions();
+    let margin = style.margin * POINTS_PER_MM;
+    let column = width - 2.0 * margin;
+    let mut pages = vec![Vec::new()];
+    // Distance of the next line from the top of the page
+    let mut y = margin;
+
+    for (kind, text) in blocks {
+        let size = style.font_size(kind);
+        let face = Face::of(kind);
+        let line_height = size * style.line_height;
+        let indent = style.indent(kind);
+        let lines = match kind {
+            BlockKind::Code => {
+                // Code keeps its indentation and is cut instead of wrapped on words
+                let columns = ((column - 2.0 * indent) / text_width(" ", face, style.font, size)) as usize;
+                text.lines()
+                    .flat_map(|line| {
+                        let chars: Vec<char> = line.trim_end().chars().collect();
+                        if chars.is_empty() {
+                            return vec![String::new()];
+                        }
+                        chars.chunks(columns.max(1)).map(|chunk| chunk.iter().collect()).collect()
+                    })
+                    .collect()
+            }
+            _ => wrap(&text, column - indent, |line| text_width(line, face, style.font, size)),
+        };
+
+        if y > margin {
+            y += match kind {
+                BlockKind::Heading(_) => size * 0.9,
+                BlockKind::Item => size * 0.2,
+                _ => size * 0.6,
+            };
+        }
+        for (i, line) in lines.iter().enumerate() {
+            if y + line_height > height - margin {
+                pages.push(Vec::new());
+                y = margin;
+            }
+            let stream = pages.last_mut().unwrap();
+            let bottom = height - y - line_height;
+            let baseline = bottom + (line_height - size) / 2.0 + size * 0.22;
+            match kind {
+                BlockKind::Code => fill_rect(stream, 0.94, margin, bottom, column, line_height),
+                BlockKind::Quote => fill_rect(stream, 0.7, margin, bottom, 2.0, line_height),
+                BlockKind::Item if i == 0 => show_text(stream, Face::Regular, size, margin + indent * 0.3, baseline, "•"),
+                _ => {}
+            }
+            if kind == BlockKind::Quote {
+                stream.extend_from_slice(b"0.35 g\n");
+            }
+            show_text(stream, face, size, margin + indent, baseline, line);
+            if kind == BlockKind::Quote {
+                stream.extend_from_slice(b"0 g\n");
+            }
+            y += line_height;
+        }
+    }
+
+    if style.page_numbers {
+        let total = pages.len();
+        let size = style.font_size * 0.8;
+        for (i, stream) in pages.iter_mut().enumerate() {
+            let number = format!("{} / {}", i + 1, total);
+            let x = (width - text_width(&number, Face::Regular, style.font, size)) / 2.0;
+            stream.extend_from_slice(b"0.45 g\n");
+            show_text(stream, Face::Regular, size, x, margin / 2.0, &number);
+            stream.extend_from_slice(b"0 g\n");
+        }
+    }
+    pages
+}
+
+// Serializes the pages into a PDF document with one uncompressed content stream per page.
+fn write_pdf(title: &str, pages: &[Vec<u8>], style: &PrintStyle) -> Vec<u8> {
+    let (width, height) = style.page_size.dimensions();
+    // Objects 1-3 are the catalog, the page tree and the document info, 4-7 the fonts,
+    // then every page is followed by its content stream
+    let first_page = 4 + FACES.len();
+    let kids: Vec<String> = (0..pages.len()).map(|i| format!("{} 0 R", first_page + 2 * i)).collect();
+    let fonts: Vec<String> = FACES.iter().enumerate().map(|(i, face)| format!("/{} {} 0 R", face.resource(), 4 + i)).collect();
+
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).into_bytes(),
+        [b"<< /Title ".as_slice(), &pdf_string(title), b" /Producer (Markdown Note App) >>"].concat(),
+    ];
+    for face in FACES {
+        objects.push(
+            format!("<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>", face.base_font(style.font))
+                .into_bytes(),
+        );
+    }
+    for (i, stream) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /Font << {} >> >> /Contents {} 0 R >>",
+                width,
+                height,
+                fonts.join(" "),
+                first_page + 2 * i + 1
+            )
+            .into_bytes(),
+        );
+        objects.push([format!("<< /Length {} >>\nstream\n", stream.len()).as_bytes(), stream, b"\nendstream"].concat());
+    }
+
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!("trailer\n<< /Size {} /Root 1 0 R /Info 3 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).as_bytes(),
+    );
+    pdf
+}
+
+// Renders Markdown to a PDF document, with the report of what it could not print as written.
+pub fn render_pdf(title: &str, content: &str, style: &PrintStyle) -> io::Result<(Vec<u8>, PdfReport)> {
+    style.validate()?;
+    let blocks = note_image::blocks(content, true);
+    let mut unsupported = Vec::new();
+    unsupported_chars(title, &mut unsupported);
+    for (_, text) in &blocks {
+        unsupported_chars(text, &mut unsupported);
+    }
+    let images = Parser::new(content).filter(|event| matches!(event, Event::Start(Tag::Image { .. }))).count()
+        + markdown::embed_targets(content).iter().filter(|target| note_image::is_file_embed(target)).count();
+
+    let pages = layout(blocks, style);
+    let report = PdfReport { pages: pages.len(), unsupported, images };
+    Ok((write_pdf(title, &pages, style), report))
+}
+
+// Exports a note to a PDF file at `output`.
+pub fn export_note_pdf(vault: &Vault, title: &str, style: &PrintStyle, output: &Path) -> io::Result<PdfReport> {
+    let content = Note::read_note(vault, title)?;
+    let name = title.rsplit('/').next().unwrap_or(title);
+    let (pdf, report) = render_pdf(name, &content, style)?;
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(output, pdf)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_wrap_and_encode() {
+        let measure = |text: &str| text.chars().count() as f32;
+        assert_eq!(wrap("one two three", 7.0, measure), vec!["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4.0, measure), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(pdf_string("a (b) \\ – ✓"), b"(a \\(b\\) \\\\ \x96 ?)".to_vec());
+        assert!(text_width("Wide", Face::Bold, PrintFont::Sans, 10.0) > text_width("Wide", Face::Regular, PrintFont::Sans, 10.0));
+    }
+
+    #[test]
+    fn test_export_note_pdf() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let long = format!("# Report (draft)\n\n{}\n\n> Quoted\n\n- item\n\n```\nfn main() {{}}\n```", "word ".repeat(2000));
+        file_operations::write_to_file(&format!("{}/Report.md", vault.path), &long).unwrap();
+
+        let output = std::env::temp_dir().join(format!("test_note_{}.pdf", nanoid!()));
+        export_note_pdf(&vault, "Report", &PrintStyle::default(), &output).unwrap();
+        let pdf = fs::read(&output).unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(pdf.starts_with(b"%PDF-1.4") && text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Title (Report)") && text.contains("(Report \\(draft\\)) Tj"));
+        assert!(text.contains("/BaseFont /Helvetica ") && text.contains("/BaseFont /Courier "));
+
+        // The cross-reference table points at the objects
+        let start: usize = text.rsplit("startxref\n").next().unwrap().trim_end_matches("\n%%EOF\n").parse().unwrap();
+        assert!(pdf[start..].starts_with(b"xref"));
+        let pages: usize = text.split("/Count ").nth(1).unwrap().split(' ').next().unwrap().parse().unwrap();
+        assert!(pages > 1);
+        assert!(text.contains(&format!("(1 / {}) Tj", pages)));
+
+        let style = PrintStyle { page_size: PageSize::Letter, font: PrintFont::Serif, page_numbers: false, ..PrintStyle::default() };
+        export_note_pdf(&vault, "Report", &style, &output).unwrap();
+        let text = String::from_utf8_lossy(&fs::read(&output).unwrap()).to_string();
+        assert!(text.contains("/MediaBox [0 0 612.00 792.00]") && text.contains("/BaseFont /Times-Roman"));
+        assert!(!text.contains("(1 / "));
+
+        assert!(export_note_pdf(&vault, "Report", &PrintStyle { font_size: 2.0, ..PrintStyle::default() }, &output).is_err());
+        assert!(export_note_pdf(&vault, "Missing", &PrintStyle::default(), &output).is_err());
+
+        // Tables, link targets and images are printed as text; what cannot be printed is reported
+        let content = "日本 “quoted” ✓ 日\n\n| Name | Qty |\n|---|---|\n| Apple | 3 |\n\nSee [the site](https://example.com) ![a cat](cat.png) ![[photo.png]] ![[Other]]";
+        file_operations::write_to_file(&format!("{}/Mixed.md", vault.path), content).unwrap();
+        let report = export_note_pdf(&vault, "Mixed", &PrintStyle::default(), &output).unwrap();
+        assert_eq!(report, PdfReport { pages: 1, unsupported: vec!["日".to_string(), "本".to_string(), "✓".to_string()], images: 2 });
+        let text = String::from_utf8_lossy(&fs::read(&output).unwrap()).to_string();
+        assert!(text.contains("(Name  | Qty) Tj") && text.contains("(Apple | 3) Tj"));
+        assert!(text.contains("(See the site \\(https://example.com\\) [Image: a cat] [Image: photo.png] Other) Tj"));
+
+        // Cleanup
+        fs::remove_file(&output).unwrap();
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,190 @@
+// Index of person notes (tagged `#person` or kept in the people folder) in the metadata store, for
+// looking people up by name or alias and for `@` mention completion.
+use serde::{Serialize, Deserialize};
+use std::cmp::Ordering;
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+
+use crate::feature::autocomplete::{self, Completion, CompletionKind, MAX_COMPLETIONS};
+use crate::feature::backlinks;
+use crate::feature::meetings::PEOPLE_FOLDER;
+use crate::feature::metadata::MetadataStore;
+use crate::feature::refactor;
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, frontmatter, markdown, outline};
+
+pub const PEOPLE_TREE: &str = "people";
+// Set once the whole vault was scanned, so a vault without people is not scanned on every query
+const INDEXED_KEY: &str = "\0indexed";
+pub const PERSON_TAG: &str = "person";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PersonEntry {
+    name: String,
+    aliases: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Person {
+    pub path: String,
+    pub name: String,
+    pub aliases: Vec<String>,
+    // Notes linking to the person, sorted
+    pub mentioned_by: Vec<String>,
+}
+
+// Whether a note describes a person.
+pub fn is_person(path: &str, content: &str) -> bool {
+    path.starts_with(&format!("{}/", PEOPLE_FOLDER))
+        || markdown::note_tags(content).iter().any(|tag| tag.eq_ignore_ascii_case(PERSON_TAG))
+}
+
+// The name of a person: the first top-level heading, or the file name.
+fn person_name(path: &str, content: &str) -> String {
+    outline::headings(content)
+        .into_iter()
+        .find(|heading| heading.level == 1)
+        .map(|heading| heading.text)
+        .unwrap_or_else(|| path.rsplit('/').next().unwrap_or(path).to_string())
+}
+
+// Adds, updates or drops the entry of one note from its current content.
+pub fn index_person(vault: &Vault, store: &MetadataStore, path: &str) -> io::Result<()> {
+    let tree = store.tree(PEOPLE_TREE)?;
+    let file = format!("{}/{}.md", vault.path, path);
+    let content = if Path::new(&file_operations::resolve_path(&file)).exists() {
+        Some(file_operations::read_from_file(&file)?)
+    } else {
+        None
+    };
+
+    match content.filter(|content| is_person(path, content)) {
+        Some(content) => {
+            let aliases = frontmatter::parse(&content).map(|fm| fm.aliases()).unwrap_or_default();
+            let entry = PersonEntry { name: person_name(path, &content), aliases };
+            let bytes = serde_json::to_vec(&entry).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            tree.insert(path, bytes)?;
+        }
+        None => {
+            tree.remove(path)?;
+        }
+    }
+    tree.flush()?;
+    Ok(())
+}
+
+fn ensure_indexed(vault: &Vault, store: &MetadataStore) -> io::Result<sled::Tree> {
+    let tree = store.tree(PEOPLE_TREE)?;
+    if !tree.contains_key(INDEXED_KEY)? {
+        for path in refactor::note_paths(vault)? {
+            index_person(vault, store, &path)?;
+        }
+        tree.insert(INDEXED_KEY, &[])?;
+        tree.flush()?;
+    }
+    Ok(tree)
+}
+
+// Finds the people whose name or alias matches a query, best match first, then most mentioned.
+pub fn find_people(vault: &Vault, store: &MetadataStore, query: &str) -> io::Result<Vec<(Person, f32)>> {
+    let tree = ensure_indexed(vault, store)?;
+    let mut found = Vec::new();
+    for entry in tree.iter() {
+        let (key, bytes) = entry?;
+        if key.as_ref() == INDEXED_KEY.as_bytes() {
+            continue;
+        }
+        let entry: PersonEntry = serde_json::from_slice(&bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        let score = std::iter::once(&entry.name)
+            .chain(&entry.aliases)
+            .filter_map(|name| autocomplete::match_score(name, query))
+            .fold(None, |best: Option<f32>, score| Some(best.map_or(score, |best| best.max(score))));
+        if let Some(score) = score {
+            let path = String::from_utf8_lossy(&key).to_string();
+            let mentioned_by = backlinks::get_backlinks(vault, store, &path)?;
+            found.push((Person { path, name: entry.name, aliases: entry.aliases, mentioned_by }, score));
+        }
+    }
+    found.sort_by(|(a, a_score), (b, b_score)| {
+        b_score
+            .partial_cmp(a_score)
+            .unwrap_or(Ordering::Equal)
+            .then(b.mentioned_by.len().cmp(&a.mentioned_by.len()))
+            .then(a.name.cmp(&b.name))
+    });
+    Ok(found)
+}
+
+// Completes the text typed after `@` with links to person notes.
+pub fn autocomplete_mention(vault: &Vault, store: &MetadataStore, query: &str) -> io::Result<Vec<Completion>> {
+    let completions = find_people(vault, store, query)?
+        .into_iter()
+        .take(MAX_COMPLETIONS)
+        .map(|(person, score)| {
+            let title = person.path.rsplit('/').next().unwrap_or(&person.path);
+            let insert = if title == person.name {
+                format!("[[{}]]", person.path)
+            } else {
+                format!("[[{}|{}]]", person.path, person.name)
+            };
+            Completion {
+                kind: CompletionKind::Person,
+                label: person.name,
+                insert,
+                detail: Some(match person.mentioned_by.len() {
+                    1 => "1 mention".to_string(),
+                    count => format!("{} mentions", count),
+                }),
+                note: person.path,
+                score,
+            }
+        })
+        .collect();
+    Ok(completions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_people_index() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/People", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/People/AdaLovelace.md", vault.path), "---\naliases: [Countess]\n---\n# Ada Lovelace\n").unwrap();
+        file_operations::write_to_file(&format!("{}/Grace.md", vault.path), "Admiral #person").unwrap();
+        file_operations::write_to_file(&format!("{}/Adapter.md", vault.path), "Not a person").unwrap();
+        file_operations::write_to_file(&format!("{}/Meeting.md", vault.path), "[[People/AdaLovelace|Ada]] and [[Grace]]").unwrap();
+        file_operations::write_to_file(&format!("{}/Notes.md", vault.path), "Ask [[AdaLovelace]]").unwrap();
+        let store = MetadataStore::open(&vault).unwrap();
+
+        let people = find_people(&vault, &store, "ada").unwrap();
+        assert_eq!(people.len(), 1);
+        assert_eq!(
+            people[0].0,
+            Person {
+                path: "People/AdaLovelace".to_string(),
+                name: "Ada Lovelace".to_string(),
+                aliases: vec!["Countess".to_string()],
+                mentioned_by: vec!["Meeting".to_string(), "Notes".to_string()],
+            }
+        );
+        assert_eq!(find_people(&vault, &store, "countess").unwrap()[0].0.name, "Ada Lovelace");
+        assert_eq!(find_people(&vault, &store, "").unwrap().len(), 2);
+
+        let completions = autocomplete_mention(&vault, &store, "gra").unwrap();
+        assert_eq!(completions[0].insert, "[[Grace]]");
+        assert_eq!(completions[0].detail.as_deref(), Some("1 mention"));
+
+        // Untagging drops the note from the index
+        file_operations::write_to_file(&format!("{}/Grace.md", vault.path), "Admiral").unwrap();
+        index_person(&vault, &store, "Grace").unwrap();
+        assert!(find_people(&vault, &store, "grace").unwrap().is_empty());
+
+        // Cleanup
+        drop(store);
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,91 @@
+// Pinned notes with a manual order
+use std::io::{self, ErrorKind};
+
+use crate::feature::metadata::MetadataStore;
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::message::Message;
+
+// Lists pinned notes that still exist, in their manual order.
+pub fn list_pinned(vault: &Vault, store: &MetadataStore) -> io::Result<Vec<String>> {
+    let existing = Note::list_notes(vault)?;
+    let mut pinned: Vec<(u32, String)> = store
+        .all_metadata()?
+        .into_iter()
+        .filter(|(title, _)| existing.contains(title))
+        .filter_map(|(title, metadata)| metadata.pinned.map(|index| (index, title)))
+        .collect();
+    pinned.sort();
+    Ok(pinned.into_iter().map(|(_, title)| title).collect())
+}
+
+// Pins a note at the end of the pinned section.
+pub fn pin_note(vault: &Vault, store: &MetadataStore, title: &str) -> io::Result<Vec<String>> {
+    Note::read_note(vault, title)?;
+    let mut metadata = store.get_metadata(title).unwrap_or_default();
+    if metadata.pinned.is_none() {
+        let last = store.all_metadata()?.into_iter().filter_map(|(_, m)| m.pinned).max();
+        metadata.pinned = Some(last.map_or(0, |last| last + 1));
+        store.update_metadata(title, metadata)?;
+    }
+    list_pinned(vault, store)
+}
+
+pub fn unpin_note(vault: &Vault, store: &MetadataStore, title: &str) -> io::Result<Vec<String>> {
+    if let Some(mut metadata) = store.get_metadata(title) {
+        metadata.pinned = None;
+        store.update_metadata(title, metadata)?;
+    }
+    list_pinned(vault, store)
+}
+
+// Stores a new order for the pinned notes; pinned notes missing from `titles` keep their relative order after them.
+pub fn reorder_pinned(vault: &Vault, store: &MetadataStore, titles: &[String]) -> io::Result<Vec<String>> {
+    let current = list_pinned(vault, store)?;
+    if let Some(unpinned) = titles.iter().find(|title| !current.contains(title)) {
+        return Err(Message::new("pinned.not_pinned", "Note is not pinned: {title}").with("title", unpinned).error(ErrorKind::InvalidInput));
+    }
+
+    let mut order: Vec<&String> = Vec::new();
+    for title in titles.iter().chain(&current) {
+        if !order.contains(&title) {
+            order.push(title);
+        }
+    }
+    for (index, title) in order.into_iter().enumerate() {
+        let mut metadata = store.get_metadata(title).unwrap_or_default();
+        metadata.pinned = Some(index as u32);
+        store.update_metadata(title, metadata)?;
+    }
+    list_pinned(vault, store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_pin_and_reorder() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let store = MetadataStore::open(&vault).unwrap();
+        for title in ["A", "B", "C"] {
+            file_operations::write_to_file(&format!("{}/{}.md", vault.path, title), title).unwrap();
+        }
+
+        pin_note(&vault, &store, "A").unwrap();
+        pin_note(&vault, &store, "B").unwrap();
+        assert_eq!(pin_note(&vault, &store, "C").unwrap(), vec!["A", "B", "C"]);
+        assert!(pin_note(&vault, &store, "Missing").is_err());
+
+        let reordered = reorder_pinned(&vault, &store, &["C".to_string(), "A".to_string()]).unwrap();
+        assert_eq!(reordered, vec!["C", "A", "B"]);
+        assert_eq!(unpin_note(&vault, &store, "A").unwrap(), vec!["C", "B"]);
+        assert!(reorder_pinned(&vault, &store, &["A".to_string()]).is_err());
+
+        // Cleanup
+        drop(store);
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,61 @@
+// Card previews for the note list
+use serde::{Serialize, Deserialize};
+use std::io;
+
+use crate::storage::{attachment::AttachmentKind, note::Note, vault::Vault};
+use crate::utils::{excerpt, frontmatter, markdown};
+
+pub const PREVIEW_LENGTH: usize = 280;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotePreview {
+    pub title: String,
+    pub excerpt: Option<String>,
+    pub image: Option<String>,
+}
+
+// Builds the preview of a note: its first paragraph (shortened) and first embedded image.
+pub fn preview(title: &str, content: &str) -> NotePreview {
+    let body = frontmatter::strip(content);
+    let excerpt = markdown::first_paragraph(body).map(|text| excerpt::truncate(&text, PREVIEW_LENGTH));
+    let image = markdown::extract_local_targets(body)
+        .into_iter()
+        .find(|target| AttachmentKind::from_name(target) == AttachmentKind::Image);
+
+    NotePreview {
+        title: title.to_string(),
+        excerpt,
+        image,
+    }
+}
+
+// Returns the previews of the given notes, skipping notes that no longer exist.
+pub fn get_previews(vault: &Vault, titles: &[String]) -> io::Result<Vec<NotePreview>> {
+    let mut previews = Vec::new();
+    for title in titles {
+        match Note::read_note(vault, title) {
+            Ok(content) => previews.push(preview(title, &content)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(previews)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview() {
+        let content = "---\ntitle: Trip\n---\n# Trip\n\n[site](https://example.com)\n\n![[photos/beach.jpg]]\n";
+        let preview = preview("Trip", content);
+        assert_eq!(preview.excerpt.as_deref(), Some("site"));
+        assert_eq!(preview.image.as_deref(), Some("photos/beach.jpg"));
+
+        let long = "word ".repeat(100);
+        let excerpt = super::preview("Long", &long).excerpt.unwrap();
+        assert!(excerpt.ends_with('…'));
+        assert!(excerpt.chars().count() <= PREVIEW_LENGTH);
+    }
+}
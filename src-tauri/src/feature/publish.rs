@@ -0,0 +1,343 @@
+// Publishing selected notes as a static site, by hand or automatically after a number of saves or on a
+// fixed interval.
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::feature::export::{self, ExportReport};
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{file_operations, frontmatter, message::Message};
+
+const PUBLISH_DIR: &str = ".publish";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishConfig {
+    pub git_remote: Option<String>,
+    #[serde(default = "default_branch")]
+    pub git_branch: String,
+    pub netlify_hook: Option<String>,
+    #[serde(default)]
+    pub auto: AutoPublish,
+}
+
+// When publishing runs without being asked. Both triggers can be combined; neither is set by default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoPublish {
+    // Publish after this many note saves
+    pub every_saves: Option<u32>,
+    // Publish every this many minutes while the vault is open
+    pub interval_minutes: Option<u64>,
+    // Also export the whole vault as HTML into this folder on every run
+    pub export_dir: Option<String>,
+}
+
+impl Default for PublishConfig {
+    fn default() -> Self {
+        Self {
+            git_remote: None,
+            git_branch: default_branch(),
+            netlify_hook: None,
+            auto: AutoPublish::default(),
+        }
+    }
+}
+
+fn default_branch() -> String {
+    "gh-pages".to_string()
+}
+
+// What started a publish run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PublishTrigger {
+    #[default]
+    Manual,
+    Saves,
+    Schedule,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishStatus {
+    pub published_at: String,
+    pub notes: Vec<String>,
+    pub pushed: bool,
+    pub deploy_triggered: bool,
+    pub success: bool,
+    pub message: Message,
+    #[serde(default)]
+    pub trigger: PublishTrigger,
+    #[serde(default)]
+    pub duration_ms: u64,
+    // The full vault export, when `auto.export_dir` is set
+    #[serde(default)]
+    pub exported: Option<ExportReport>,
+}
+
+// Save counts and timers of automatic publishing, per vault. Runs of one vault never overlap.
+#[derive(Default)]
+pub struct PublishScheduler {
+    saves: Mutex<HashMap<String, u32>>,
+    running: Mutex<HashSet<String>>,
+    timers: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl PublishScheduler {
+    // Counts a save of the vault. Returns true when it is the `every`th save since the last run.
+    pub fn record_save(&self, vault: &Vault, every: u32) -> bool {
+        let mut saves = self.saves.lock().unwrap();
+        let count = saves.entry(vault.path.clone()).or_default();
+        *count += 1;
+        *count >= every
+    }
+
+    // Marks a run of the vault as started and resets its save count; false if a run is already going.
+    pub fn start_run(&self, vault: &Vault) -> bool {
+        if !self.running.lock().unwrap().insert(vault.path.clone()) {
+            return false;
+        }
+        self.saves.lock().unwrap().remove(&vault.path);
+        true
+    }
+
+    pub fn finish_run(&self, vault: &Vault) {
+        self.running.lock().unwrap().remove(&vault.path);
+    }
+
+    // Calls `run` every `interval` until the vault is unscheduled, replacing an earlier schedule.
+    pub fn schedule<F>(&self, vault: &Vault, interval: Duration, run: F)
+    where
+        F: Fn() + Send + 'static,
+    {
+        let stopped = Arc::new(AtomicBool::new(false));
+        if let Some(previous) = self.timers.lock().unwrap().insert(vault.path.clone(), Arc::clone(&stopped)) {
+            previous.store(true, Ordering::Relaxed);
+        }
+        thread::spawn(move || loop {
+            let started = Instant::now();
+            // Short sleeps so unscheduling takes effect promptly
+            while started.elapsed() < interval {
+                if stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(200).min(interval));
+            }
+            if stopped.load(Ordering::Relaxed) {
+                return;
+            }
+            run();
+        });
+    }
+
+    // Stops the timer of the vault. Returns false if none was running.
+    pub fn unschedule(&self, vault: &Vault) -> bool {
+        match self.timers.lock().unwrap().remove(&vault.path) {
+            Some(stopped) => {
+                stopped.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn config_path(vault: &Vault) -> String {
+    format!("{}/{}/config.json", vault.path, PUBLISH_DIR)
+}
+
+fn status_path(vault: &Vault) -> String {
+    format!("{}/{}/status.json", vault.path, PUBLISH_DIR)
+}
+
+// Loads the publish configuration of a vault, falling back to defaults.
+pub fn load_config(vault: &Vault) -> io::Result<PublishConfig> {
+    match file_operations::read_from_file(&config_path(vault)) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| Error::new(ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(PublishConfig::default()),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn save_config(vault: &Vault, config: &PublishConfig) -> io::Result<()> {
+    if config.auto.every_saves == Some(0) || config.auto.interval_minutes == Some(0) {
+        return Err(Message::new("publish.invalid_schedule", "Automatic publishing needs at least one save or minute").error(ErrorKind::InvalidInput));
+    }
+    if config.auto.export_dir.as_deref().is_some_and(|dir| dir.trim().is_empty()) {
+        return Err(Message::new("export.folder_empty", "Export folder is empty").error(ErrorKind::InvalidInput));
+    }
+    file_operations::create_directory(&format!("{}/{}", vault.path, PUBLISH_DIR))?;
+    let json = serde_json::to_string_pretty(config).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    file_operations::write_to_file(&config_path(vault), &json)
+}
+
+// Returns the status of the last publish run, if any.
+pub fn load_status(vault: &Vault) -> io::Result<Option<PublishStatus>> {
+    match file_operations::read_from_file(&status_path(vault)) {
+        Ok(json) => serde_json::from_str(&json).map(Some).map_err(|e| Error::new(ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// Lists notes whose frontmatter sets `publish: true`.
+pub fn publishable_notes(vault: &Vault) -> io::Result<Vec<String>> {
+    let mut notes = Vec::new();
+    for title in Note::list_notes(vault)? {
+        let content = Note::read_note(vault, &title)?;
+        if frontmatter::parse(&content).ok().and_then(|fm| fm.get_bool("publish")) == Some(true) {
+            notes.push(title);
+        }
+    }
+    notes.sort();
+    Ok(notes)
+}
+
+// Generates the site from publishable notes, pushes it to the configured branch and triggers the deploy hook.
+// The outcome is kept as the vault's last publish report, also when the run fails.
+pub fn publish(vault: &Vault, trigger: PublishTrigger) -> io::Result<PublishStatus> {
+    let started = Instant::now();
+    let config = load_config(vault)?;
+    let notes = publishable_notes(vault)?;
+    let mut status = PublishStatus {
+        published_at: Utc::now().to_rfc3339(),
+        notes,
+        pushed: false,
+        deploy_triggered: false,
+        success: false,
+        message: Message::new("publish.running", "Publishing"),
+        trigger,
+        duration_ms: 0,
+        exported: None,
+    };
+
+    let result = run_pipeline(vault, &config, &mut status);
+    status.duration_ms = started.elapsed().as_millis() as u64;
+    status.success = result.is_ok();
+    status.message = match &result {
+        Ok(()) => Message::new("publish.finished", "Published {count} notes").with("count", status.notes.len()),
+        Err(e) => Message::from(e),
+    };
+
+    file_operations::create_directory(&format!("{}/{}", vault.path, PUBLISH_DIR))?;
+    let json = serde_json::to_string_pretty(&status).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    file_operations::write_to_file(&status_path(vault), &json)?;
+
+    result.map(|_| status)
+}
+
+fn run_pipeline(vault: &Vault, config: &PublishConfig, status: &mut PublishStatus) -> io::Result<()> {
+    let site_dir = format!("{}/{}/site", vault.path, PUBLISH_DIR);
+    file_operations::delete_directory(&site_dir)?;
+    export::export_site(vault, &status.notes, &site_dir)?;
+
+    if let Some(remote) = &config.git_remote {
+        let site_dir = file_operations::resolve_path(&site_dir);
+        git(&site_dir, &["init", "--quiet"])?;
+        git(&site_dir, &["checkout", "--quiet", "-B", &config.git_branch])?;
+        git(&site_dir, &["add", "--all"])?;
+        git(&site_dir, &["commit", "--quiet", "--allow-empty", "-m", &format!("Publish {}", status.published_at)])?;
+        git(&site_dir, &["push", "--quiet", "--force", remote, &config.git_branch])?;
+        status.pushed = true;
+    }
+
+    if let Some(hook) = &config.netlify_hook {
+        ureq::post(hook).send_string("").map_err(Error::other)?;
+        status.deploy_triggered = true;
+    }
+
+    if let Some(export_dir) = &config.auto.export_dir {
+        status.exported = Some(export::export_vault(vault, export_dir)?);
+    }
+    Ok(())
+}
+
+fn git(dir: &str, args: &[&str]) -> io::Result<()> {
+    let output = Command::new("git").current_dir(Path::new(dir)).args(args).output()?;
+    if !output.status.success() {
+        return Err(Message::new("publish.git_failed", "git {command} failed: {detail}")
+            .with("command", args[0])
+            .with("detail", String::from_utf8_lossy(&output.stderr).trim())
+            .error(ErrorKind::Other));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_publish_without_targets() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Public.md", vault.path), "---\npublish: true\n---\nHello").unwrap();
+        file_operations::write_to_file(&format!("{}/Private.md", vault.path), "Secret").unwrap();
+
+        assert!(load_status(&vault).unwrap().is_none());
+        assert_eq!(load_config(&vault).unwrap().git_branch, "gh-pages");
+
+        let status = publish(&vault, PublishTrigger::Manual).unwrap();
+        assert!(status.success);
+        assert_eq!(status.notes, vec!["Public"]);
+        assert!(!status.pushed && !status.deploy_triggered);
+        assert!(Path::new(&format!("{}/.publish/site/Public.html", vault.path)).exists());
+        assert!(!Path::new(&format!("{}/.publish/site/Private.html", vault.path)).exists());
+        assert_eq!(load_status(&vault).unwrap().unwrap().notes, vec!["Public"]);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_auto_publish() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Public.md", vault.path), "---\npublish: true\n---\nHello").unwrap();
+        file_operations::write_to_file(&format!("{}/Private.md", vault.path), "Secret").unwrap();
+
+        let mut config = PublishConfig::default();
+        config.auto.every_saves = Some(0);
+        assert_eq!(save_config(&vault, &config).unwrap_err().kind(), ErrorKind::InvalidInput);
+        let export_dir = format!("{}/.publish/wiki", vault.path);
+        config.auto = AutoPublish { every_saves: Some(2), interval_minutes: None, export_dir: Some(export_dir.clone()) };
+        save_config(&vault, &config).unwrap();
+        assert_eq!(load_config(&vault).unwrap().auto, config.auto);
+
+        let scheduler = PublishScheduler::default();
+        assert!(!scheduler.record_save(&vault, 2));
+        assert!(scheduler.record_save(&vault, 2));
+        assert!(scheduler.start_run(&vault));
+        assert!(!scheduler.start_run(&vault));
+        // Saves count again from the start of the run
+        assert!(!scheduler.record_save(&vault, 2));
+        scheduler.finish_run(&vault);
+
+        let status = publish(&vault, PublishTrigger::Saves).unwrap();
+        assert_eq!(status.trigger, PublishTrigger::Saves);
+        assert_eq!(status.exported.unwrap().pages.len(), 2);
+        assert!(Path::new(&format!("{}/Private.html", export_dir)).exists());
+        assert_eq!(load_status(&vault).unwrap().unwrap().trigger, PublishTrigger::Saves);
+
+        let runs = Arc::new(Mutex::new(0));
+        let counted = Arc::clone(&runs);
+        scheduler.schedule(&vault, Duration::from_millis(50), move || *counted.lock().unwrap() += 1);
+        thread::sleep(Duration::from_millis(400));
+        assert!(scheduler.unschedule(&vault));
+        let seen = *runs.lock().unwrap();
+        assert!(seen >= 1);
+        thread::sleep(Duration::from_millis(150));
+        assert_eq!(*runs.lock().unwrap(), seen);
+        assert!(!scheduler.unschedule(&vault));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
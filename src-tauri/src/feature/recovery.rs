@@ -0,0 +1,226 @@
+// Safe mode for vaults whose stores fail to open, and recovery of those stores
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::feature::metadata::{MetadataStore, NoteMetadata};
+use crate::feature::search::{self, NoteSearch, SearchIndexes};
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{file_operations, markdown, message::Message};
+
+const METADATA_STORE: &str = "metadata";
+const METADATA_DIR: &str = ".meta";
+const SEARCH_STORE: &str = "search";
+const BACKUP_DIR: &str = ".recovery";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoreFailure {
+    pub store: String,
+    pub error: Message,
+}
+
+// In safe mode only the Markdown files are used; stores that failed to open are skipped.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SafeModeStatus {
+    pub safe_mode: bool,
+    pub failures: Vec<StoreFailure>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    pub backups: Vec<String>,
+    pub rebuilt_notes: usize,
+    pub indexed_notes: usize,
+}
+
+// Vaults currently running in safe mode, keyed by vault name.
+#[derive(Default)]
+pub struct SafeMode {
+    vaults: Mutex<HashMap<String, Vec<StoreFailure>>>,
+}
+
+// Sled reports corrupted or truncated store files as invalid or unexpectedly ending data. Other errors,
+// like a store held open by another window or a missing permission, leave the store intact.
+fn is_corruption(error: &Error) -> bool {
+    matches!(error.kind(), ErrorKind::InvalidData | ErrorKind::UnexpectedEof)
+}
+
+fn safe_mode_error(vault: &Vault, store: &str) -> Error {
+    Message::new("recovery.safe_mode", "Vault {vault} is in safe mode ({store} unavailable); run recover_vault to rebuild it")
+        .with("vault", &vault.name)
+        .with("store", store)
+        .error(ErrorKind::Other)
+}
+
+impl SafeMode {
+    // Opens the metadata store of a vault, switching the vault to safe mode when the store is corrupted.
+    pub fn metadata(&self, vault: &Vault) -> io::Result<MetadataStore> {
+        match MetadataStore::open(vault) {
+            Ok(store) => Ok(store),
+            Err(e) if is_corruption(&e) => {
+                println!("❌ Metadata store of {} failed to open, entering safe mode: {}", vault.name, e);
+                self.enter(vault, METADATA_STORE, &e);
+                Err(safe_mode_error(vault, METADATA_STORE))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // Like `metadata`, but yields `None` in safe mode so read-only views can degrade to empty results.
+    pub fn optional_metadata(&self, vault: &Vault) -> io::Result<Option<MetadataStore>> {
+        match self.metadata(vault) {
+            Ok(store) => Ok(Some(store)),
+            Err(_) if self.is_safe_mode(vault) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Opens the search index of a vault, switching the vault to safe mode when the index is corrupted.
+    pub fn search(&self, indexes: &SearchIndexes, vault: &Vault) -> io::Result<Arc<NoteSearch>> {
+        indexes.get(vault).map_err(|e| {
+            println!("❌ Search index of {} failed to open, entering safe mode: {}", vault.name, e);
+            self.enter(vault, SEARCH_STORE, &e);
+            safe_mode_error(vault, SEARCH_STORE)
+        })
+    }
+
+    fn enter(&self, vault: &Vault, store: &str, error: &Error) {
+        let mut vaults = self.vaults.lock().unwrap();
+        let failures = vaults.entry(vault.name.clone()).or_default();
+        failures.retain(|failure| failure.store != store);
+        failures.push(StoreFailure { store: store.to_string(), error: Message::from(error) });
+    }
+
+    pub fn is_safe_mode(&self, vault: &Vault) -> bool {
+        self.vaults.lock().unwrap().contains_key(&vault.name)
+    }
+
+    // Checks every store of a vault on open, so a corrupted vault starts in safe mode instead of failing later.
+    pub fn check(&self, indexes: &SearchIndexes, vault: &Vault) -> SafeModeStatus {
+        self.vaults.lock().unwrap().remove(&vault.name);
+        // The metadata store is dropped right away; commands open it on demand
+        let _ = self.metadata(vault);
+        let _ = self.search(indexes, vault);
+        self.status(vault)
+    }
+
+    pub fn status(&self, vault: &Vault) -> SafeModeStatus {
+        let failures = self.vaults.lock().unwrap().get(&vault.name).cloned().unwrap_or_default();
+        SafeModeStatus { safe_mode: !failures.is_empty(), failures }
+    }
+
+    // Backs up and rebuilds the stores of a vault, then leaves safe mode.
+    pub fn recover(&self, indexes: &SearchIndexes, vault: &Vault) -> io::Result<RecoveryReport> {
+        indexes.forget(vault);
+        let report = recover_vault(vault)?;
+        self.vaults.lock().unwrap().remove(&vault.name);
+        Ok(report)
+    }
+}
+
+// RFC 3339 time of a file time, or now when the file system does not record it.
+pub fn file_timestamp(time: io::Result<std::time::SystemTime>) -> String {
+    time.map(|time| DateTime::<Utc>::from(time).to_rfc3339())
+        .unwrap_or_else(|_| Utc::now().to_rfc3339())
+}
+
+// Recomputes the metadata that can be derived from the notes themselves: tags, backlinks and file times.
+fn rebuild_metadata(vault: &Vault, store: &MetadataStore) -> io::Result<usize> {
+    let titles = Note::list_notes(vault)?;
+    let mut contents = BTreeMap::new();
+    for title in &titles {
+        contents.insert(title.clone(), Note::read_note(vault, title)?);
+    }
+
+    let mut backlinks: HashMap<String, Vec<String>> = HashMap::new();
+    for (title, content) in &contents {
+        for link in markdown::extract_links(content) {
+            let target = link.split(['|', '#']).next().unwrap_or_default().trim().to_string();
+            let sources = backlinks.entry(target).or_default();
+            if !sources.contains(title) {
+                sources.push(title.clone());
+            }
+        }
+    }
+
+    for (title, content) in &contents {
+        let file = std::fs::metadata(file_operations::resolve_path(&format!("{}/{}.md", vault.path, title)))?;
+        let metadata = NoteMetadata {
+            tags: markdown::note_tags(content),
+            backlinks: backlinks.remove(title).unwrap_or_default(),
+            created_at: file_timestamp(file.created()),
+            updated_at: file_timestamp(file.modified()),
+            ..Default::default()
+        };
+        store.update_metadata(title, metadata)?;
+    }
+    Ok(contents.len())
+}
+
+// Moves the metadata store and search index of a vault to `.recovery/` and rebuilds them from the notes.
+pub fn recover_vault(vault: &Vault) -> io::Result<RecoveryReport> {
+    let mut report = RecoveryReport::default();
+    let stamp = Utc::now().format("%Y%m%d%H%M%S");
+    for (store, dir) in [(METADATA_STORE, METADATA_DIR), (SEARCH_STORE, search::SEARCH_DIR)] {
+        let store_path = format!("{}/{}", vault.path, dir);
+        if Path::new(&file_operations::resolve_path(&store_path)).exists() {
+            file_operations::create_directory(&format!("{}/{}", vault.path, BACKUP_DIR))?;
+            let backup = format!("{}/{}/{}-{}", vault.path, BACKUP_DIR, store, stamp);
+            file_operations::rename_file(&store_path, &backup)?;
+            report.backups.push(backup);
+        }
+    }
+
+    let store = MetadataStore::open(vault)?;
+    report.rebuilt_notes = rebuild_metadata(vault, &store)?;
+    report.indexed_notes = NoteSearch::new(vault)?.reindex(vault)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_safe_mode_and_recovery() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/A.md", vault.path), "---\ntags: [rust]\n---\nSee [[B|the other]]").unwrap();
+        file_operations::write_to_file(&format!("{}/B.md", vault.path), "#draft").unwrap();
+        // A damaged store file makes the store fail to open
+        file_operations::create_directory(&format!("{}/{}", vault.path, METADATA_DIR)).unwrap();
+        file_operations::write_to_file(&format!("{}/{}/conf", vault.path, METADATA_DIR), "not a sled config").unwrap();
+
+        let safe_mode = SafeMode::default();
+        let indexes = SearchIndexes::default();
+        let status = safe_mode.check(&indexes, &vault);
+        assert!(status.safe_mode);
+        assert_eq!(status.failures[0].store, METADATA_STORE);
+        assert!(safe_mode.metadata(&vault).is_err());
+        assert!(safe_mode.optional_metadata(&vault).unwrap().is_none());
+        assert!(!is_corruption(&Error::from(ErrorKind::WouldBlock)));
+        // Files stay readable in safe mode
+        assert_eq!(Note::read_note(&vault, "B").unwrap(), "#draft");
+
+        let report = safe_mode.recover(&indexes, &vault).unwrap();
+        assert_eq!(report.backups.len(), 2);
+        assert_eq!(report.rebuilt_notes, 2);
+        assert_eq!(report.indexed_notes, 2);
+        assert!(!safe_mode.is_safe_mode(&vault));
+        assert!(!safe_mode.check(&indexes, &vault).safe_mode);
+
+        let store = safe_mode.metadata(&vault).unwrap();
+        assert_eq!(store.get_metadata("A").unwrap().tags, vec!["rust"]);
+        assert_eq!(store.get_metadata("B").unwrap().tags, vec!["draft"]);
+        assert_eq!(store.get_metadata("B").unwrap().backlinks, vec!["A"]);
+
+        // Cleanup
+        drop(store);
+        indexes.forget(&vault);
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,186 @@
+// Note moves with link refactoring across the vault
+use serde::{Serialize, Deserialize};
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{file_operations, filename_policy, markdown, string_utils, error::AppError, message::Message};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MoveReport {
+    pub from: String,
+    pub to: String,
+    // Notes whose links were rewritten (vault-relative paths without extension)
+    pub updated: Vec<String>,
+}
+
+// Lists every note of the vault, in folders too, as vault-relative paths without extension.
+pub fn note_paths(vault: &Vault) -> io::Result<Vec<String>> {
+    Note::list_notes(vault)
+}
+
+// Sanitizes every segment of a vault-relative folder or note path.
+pub fn clean_path(path: &str) -> String {
+    string_utils::sanitize_path(path)
+}
+
+fn folder_of(path: &str) -> &str {
+    path.rsplit_once('/').map(|(folder, _)| folder).unwrap_or("")
+}
+
+// Whether a bare `[[Title]]` link to the title of `path` resolves to that note, like the graph resolves it:
+// a note at the vault root with that exact name wins, then the first note with that name in any case.
+fn bare_links_resolve(paths: &[String], path: &str) -> bool {
+    let title = path.rsplit('/').next().unwrap_or(path);
+    if paths.iter().any(|other| other == title) {
+        return path == title;
+    }
+    paths
+        .iter()
+        .find(|other| other.rsplit('/').next().unwrap_or(other).eq_ignore_ascii_case(title))
+        .is_some_and(|first| first == path)
+}
+
+// Rewrites the links of every note that point at `old_path` so they point at `new_path`. `bare` tells
+// whether bare `[[Title]]` links pointed at the note before it moved.
+pub fn rewrite_references(vault: &Vault, old_path: &str, new_path: &str, bare: bool) -> io::Result<Vec<String>> {
+    let mut updated = Vec::new();
+    for path in note_paths(vault)? {
+        let file = format!("{}/{}.md", vault.path, path);
+        let content = file_operations::read_from_file(&file)?;
+        let rewritten = markdown::retarget_note_links(&content, folder_of(&path), old_path, new_path, bare);
+        if rewritten != content {
+            file_operations::write_to_file(&file, &rewritten)?;
+            updated.push(path);
+        }
+    }
+    Ok(updated)
+}
+
+// Moves a note into a folder (empty for the vault root) and updates the links that referenced it.
+pub fn move_note(vault: &Vault, path: &str, folder: &str) -> io::Result<MoveReport> {
+    let from = clean_path(path);
+    let folder = clean_path(folder);
+    let title = from.rsplit('/').next().unwrap_or_default().to_string();
+    let to = if folder.is_empty() { title } else { format!("{}/{}", folder, title) };
+    relocate(vault, from, to)
+}
+
+// Renames a note within its folder and updates the links that referenced it, bare `[[Title]]` links included.
+pub fn rename_note(vault: &Vault, path: &str, new_title: &str) -> io::Result<MoveReport> {
+    let from = clean_path(path);
+    let title = string_utils::title_filename(new_title);
+    if title.is_empty() {
+        return Err(AppError::InvalidName(Message::new("note.title_empty", "Note title is empty")).into());
+    }
+    let to = match folder_of(&from) {
+        "" => title,
+        folder => format!("{}/{}", folder, title),
+    };
+    relocate(vault, from, to)
+}
+
+fn relocate(vault: &Vault, from: String, to: String) -> io::Result<MoveReport> {
+    let folder = folder_of(&to).to_string();
+    let source = format!("{}/{}.md", vault.path, from);
+    if from.is_empty() || !Path::new(&file_operations::resolve_path(&source)).exists() {
+        return Err(Message::new("note.not_found", "Note file does not exist").error(ErrorKind::NotFound));
+    }
+    if to == from {
+        return Ok(MoveReport { from, to, updated: Vec::new() });
+    }
+    filename_policy::check_path(&to)?;
+    // A rename that only changes the case of the note finds the note itself
+    match Note::taken_by(vault, &to)? {
+        Some(existing) if existing == to => {
+            return Err(Message::new("note.already_exists_in_folder", "A note with this name already exists in the folder").error(ErrorKind::AlreadyExists));
+        }
+        Some(existing) if existing != from => return Err(Note::conflict_error(&to, &existing)),
+        _ => {}
+    }
+    let destination = format!("{}/{}.md", vault.path, to);
+    let bare = bare_links_resolve(&note_paths(vault)?, &from);
+
+    if !folder.is_empty() {
+        file_operations::create_directory(&format!("{}/{}", vault.path, folder))?;
+    }
+    file_operations::rename_file(&source, &destination)?;
+
+    // Relative links of the moved note itself now start from another folder
+    let content = file_operations::read_from_file(&destination)?;
+    let rebased = markdown::rebase_relative_links(&content, folder_of(&from), &folder);
+    let mut updated = Vec::new();
+    if rebased != content {
+        file_operations::write_to_file(&destination, &rebased)?;
+        updated.push(to.clone());
+    }
+    for path in rewrite_references(vault, &from, &to, bare)? {
+        if !updated.contains(&path) {
+            updated.push(path);
+        }
+    }
+    Ok(MoveReport { from, to, updated })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_move_note() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Inbox", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Inbox/Clip.md", vault.path), "![img](img.png) [[Index]]").unwrap();
+        file_operations::write_to_file(&format!("{}/Index.md", vault.path), "[[Inbox/Clip]] and [[Clip]]").unwrap();
+        file_operations::write_to_file(&format!("{}/Other.md", vault.path), "Unrelated").unwrap();
+
+        let report = move_note(&vault, "Inbox/Clip", "Projects/Web").unwrap();
+        assert_eq!(report.to, "Projects/Web/Clip");
+        assert_eq!(report.updated, vec!["Projects/Web/Clip", "Index"]);
+        let index = file_operations::read_from_file(&format!("{}/Index.md", vault.path)).unwrap();
+        assert_eq!(index, "[[Projects/Web/Clip]] and [[Clip]]");
+        let moved = file_operations::read_from_file(&format!("{}/Projects/Web/Clip.md", vault.path)).unwrap();
+        assert_eq!(moved, "![img](../../Inbox/img.png) [[Index]]");
+
+        assert!(move_note(&vault, "Other", "").unwrap().updated.is_empty());
+        assert_eq!(move_note(&vault, "Missing", "").unwrap_err().kind(), ErrorKind::NotFound);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_rename_note() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Projects", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Projects/Draft.md", vault.path), "# Draft").unwrap();
+        file_operations::write_to_file(&format!("{}/Index.md", vault.path), "[[Draft|the plan]] [[Draft#Goals]] [p](Projects/Draft.md)").unwrap();
+
+        let report = rename_note(&vault, "Projects/Draft", "Launch Plan").unwrap();
+        assert_eq!(report.to, "Projects/Launch Plan");
+        assert_eq!(report.updated, vec!["Index"]);
+        let index = file_operations::read_from_file(&format!("{}/Index.md", vault.path)).unwrap();
+        assert_eq!(index, "[[Launch Plan|the plan]] [[Launch Plan#Goals]] [p](Projects/Launch%20Plan.md)");
+        file_operations::write_to_file(&format!("{}/Projects/Other.md", vault.path), "").unwrap();
+        assert_eq!(rename_note(&vault, "Projects/Launch Plan", "Other").unwrap_err().kind(), ErrorKind::AlreadyExists);
+        assert_eq!(rename_note(&vault, "Projects/Other", "!!").unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(rename_note(&vault, "Projects/Launch Plan", "other").unwrap_err().kind(), ErrorKind::AlreadyExists);
+        assert_eq!(rename_note(&vault, "Projects/Launch Plan", "con").unwrap_err().kind(), ErrorKind::InvalidInput);
+        // Changing only the case of a title renames the note itself
+        assert_eq!(rename_note(&vault, "Projects/Other", "OTHER").unwrap().to, "Projects/OTHER");
+        assert!(Path::new(&format!("{}/Projects/OTHER.md", vault.path)).exists());
+
+        // Bare links to a note at the root with the same name still point there
+        file_operations::write_to_file(&format!("{}/OTHER.md", vault.path), "").unwrap();
+        file_operations::write_to_file(&format!("{}/Log.md", vault.path), "[[OTHER]] [[Projects/OTHER]]").unwrap();
+        assert_eq!(rename_note(&vault, "Projects/OTHER", "Done").unwrap().updated, vec!["Log"]);
+        let log = file_operations::read_from_file(&format!("{}/Log.md", vault.path)).unwrap();
+        assert_eq!(log, "[[OTHER]] [[Projects/Done]]");
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,120 @@
+// Random notes and resurfacing queue
+use chrono::{DateTime, Utc};
+use rand::seq::SliceRandom;
+use serde::{Serialize, Deserialize};
+use std::cmp::Ordering;
+use std::io;
+
+use crate::feature::metadata::MetadataStore;
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::file_operations;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReviewItem {
+    pub title: String,
+    pub open_count: u64,
+    pub last_seen: Option<String>,
+    pub score: f64,
+}
+
+// Picks a random note, optionally limited to notes whose title or content contains the filter.
+pub fn random_note(vault: &Vault, filter: Option<&str>) -> io::Result<Option<String>> {
+    let mut candidates = Note::list_notes(vault)?;
+    if let Some(filter) = filter.map(str::to_lowercase).filter(|f| !f.trim().is_empty()) {
+        candidates.retain(|title| {
+            title.to_lowercase().contains(&filter)
+                || Note::read_note(vault, title)
+                    .map(|content| content.to_lowercase().contains(&filter))
+                    .unwrap_or(false)
+        });
+    }
+    Ok(candidates.choose(&mut rand::thread_rng()).cloned())
+}
+
+// Builds a queue of old, rarely opened notes, the most overdue first.
+pub fn review_queue(vault: &Vault, store: &MetadataStore, limit: usize) -> io::Result<Vec<ReviewItem>> {
+    let now = Utc::now();
+    let mut queue: Vec<ReviewItem> = Note::list_notes(vault)?
+        .into_iter()
+        .map(|title| {
+            let metadata = store.get_metadata(&title).unwrap_or_default();
+            let last_seen = metadata
+                .last_opened
+                .and_then(|date| DateTime::parse_from_rfc3339(&date).ok())
+                .map(|date| date.with_timezone(&Utc))
+                .or_else(|| last_modified(vault, &title));
+            let days = last_seen
+                .map(|date| (now - date).num_seconds().max(0) as f64 / 86_400.0)
+                .unwrap_or(0.0);
+
+            ReviewItem {
+                score: days / (metadata.open_count + 1) as f64,
+                open_count: metadata.open_count,
+                last_seen: last_seen.map(|date| date.to_rfc3339()),
+                title,
+            }
+        })
+        .collect();
+
+    queue.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    queue.truncate(limit);
+    Ok(queue)
+}
+
+// Returns the modification time of a note file.
+fn last_modified(vault: &Vault, title: &str) -> Option<DateTime<Utc>> {
+    let note_path = file_operations::resolve_path(&format!("{}/{}.md", vault.path, title));
+    let modified = std::fs::metadata(note_path).ok()?.modified().ok()?;
+    Some(modified.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::note::OnConflict;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_random_note_with_filter() {
+        file_operations::set_base_path(None);
+        let mut vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        Note::new("Alpha-note-content", "Alpha note content").create_note(&mut vault, OnConflict::Fail).unwrap();
+        Note::new("Beta-note-content", "Beta note content").create_note(&mut vault, OnConflict::Fail).unwrap();
+
+        let picked = random_note(&vault, Some("beta")).unwrap();
+        assert_eq!(picked.as_deref(), Some("Beta-note-content"));
+        assert!(random_note(&vault, None).unwrap().is_some());
+        assert!(random_note(&vault, Some("gamma")).unwrap().is_none());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_review_queue_prefers_rarely_opened_notes() {
+        file_operations::set_base_path(None);
+        let mut vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        Note::new("Alpha-note-content", "Alpha note content").create_note(&mut vault, OnConflict::Fail).unwrap();
+        Note::new("Beta-note-content", "Beta note content").create_note(&mut vault, OnConflict::Fail).unwrap();
+
+        let store = MetadataStore::open(&vault).unwrap();
+        let old = (Utc::now() - chrono::Duration::days(30)).to_rfc3339();
+        for title in ["Alpha-note-content", "Beta-note-content"] {
+            let mut metadata = store.get_metadata(title).unwrap_or_default();
+            metadata.last_opened = Some(old.clone());
+            store.update_metadata(title, metadata).unwrap();
+        }
+        let mut metadata = store.get_metadata("Alpha-note-content").unwrap();
+        metadata.open_count = 5;
+        store.update_metadata("Alpha-note-content", metadata).unwrap();
+
+        let queue = review_queue(&vault, &store, 10).unwrap();
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue[0].title, "Beta-note-content");
+        assert!(queue[0].score > queue[1].score);
+
+        // Cleanup
+        drop(store);
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
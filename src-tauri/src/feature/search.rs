@@ -1,20 +1,547 @@
-// Full-text search
-use tantivy::collector::TopDocs;
-use tantivy::query::QueryParser;
-use tantivy::schema::{Schema, TEXT, STORED};
-use tantivy::{Index, IndexWriter, DocAddress};
+// Full-text search. Archived notes and notes in the trash are indexed too, under their own `scope` facet,
+// and only searched when asked for. Each document keeps the file time of its note, so an index can be
+// brought up to date by reading only the notes changed since.
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+use tantivy::collector::{DocSetCollector, TopDocs};
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{AllQuery, BooleanQuery, Occur, Query, QueryParser, TermQuery};
+use tantivy::schema::{Facet, FacetOptions, Field, IndexRecordOption, Schema, Value, STORED, STRING, TEXT};
+use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, SnippetGenerator, TantivyDocument, Term};
+
+use crate::feature::archival::{self, DEFAULT_ARCHIVE_FOLDER};
+use crate::feature::lookup;
+use crate::feature::metadata::MetadataStore;
+use crate::feature::note_stats::{self, NoteStats};
+use crate::feature::refactor;
+use crate::storage::{trash::{self, TRASHED_NOTES_DIR}, vault::Vault};
+use crate::utils::{file_operations, markdown, error::AppError, message::Message, outline::{self, MatchLocation}};
+
+pub const SEARCH_DIR: &str = ".search";
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+const WRITER_MEMORY: usize = 20_000_000;
+const SNIPPET_LENGTH: usize = 160;
+
+// Where a note lives: among the notes, in an archive folder, or in the trash.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    #[default]
+    Active,
+    Archived,
+    Trash,
+}
+
+impl Scope {
+    fn facet(self) -> Facet {
+        Facet::from(match self {
+            Scope::Active => "/active",
+            Scope::Archived => "/archived",
+            Scope::Trash => "/trash",
+        })
+    }
+}
+
+// Which notes besides the active ones a search includes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchFilter {
+    pub include_archived: bool,
+    pub include_trash: bool,
+}
+
+// The archive folders of a vault: the default one and the destinations of its archival rules.
+fn archive_folders(vault: &Vault) -> io::Result<Vec<String>> {
+    let mut folders = vec![DEFAULT_ARCHIVE_FOLDER.to_string()];
+    for rule in archival::load_rules(vault)? {
+        let folder = refactor::clean_path(&rule.destination);
+        if !folder.is_empty() && !folders.contains(&folder) {
+            folders.push(folder);
+        }
+    }
+    Ok(folders)
+}
+
+// Modification time of a note file in milliseconds, 0 when it cannot be read.
+fn modified_at(vault: &Vault, path: &str) -> u64 {
+    std::fs::metadata(file_operations::resolve_path(&format!("{}/{}.md", vault.path, path)))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |age| age.as_millis() as u64)
+}
+
+fn scope_of(path: &str, archive_folders: &[String]) -> Scope {
+    if path.starts_with(&format!("{}/", TRASHED_NOTES_DIR)) {
+        Scope::Trash
+    } else if archive_folders.iter().any(|folder| path.starts_with(&format!("{}/", folder))) {
+        Scope::Archived
+    } else {
+        Scope::Active
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub title: String,
+    // Path relative to the vault, without the `.md` extension; for notes in the trash the path of the
+    // trashed file, whose part after `.trash/notes/` is the trash id
+    pub path: String,
+    #[serde(default)]
+    pub scope: Scope,
+    // Stable id of the note, when it has one
+    #[serde(default)]
+    pub id: Option<String>,
+    // Relevance, higher is better
+    pub score: f32,
+    // HTML-escaped excerpt of the body with the matching terms wrapped in `<mark>` tags
+    pub snippet: String,
+    // Badges for the results list
+    pub stats: NoteStats,
+    // First match in the note, to scroll straight to its section
+    pub location: Option<MatchLocation>,
+}
+
+struct Fields {
+    path: Field,
+    title: Field,
+    body: Field,
+    tags: Field,
+    scope: Field,
+    // Modification time of the note file in milliseconds; 0 when unknown
+    modified: Field,
+}
+
+// Notes that differ between the disk and the index.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexChanges {
+    // New notes and notes modified since they were indexed
+    pub changed: Vec<String>,
+    // The notes of `changed` that were not indexed at all
+    pub added: Vec<String>,
+    // Indexed notes that are no longer on disk
+    pub removed: Vec<String>,
+}
 
 pub struct NoteSearch {
-    schema: Schema,
     index: Index,
+    fields: Fields,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+}
+
+fn index_error<E: Display>(e: E) -> Error {
+    AppError::IndexError(Message::new("search.index_error", "Search index error: {detail}").with("detail", e)).into()
+}
+
+fn schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let fields = Fields {
+        path: builder.add_text_field("path", STRING | STORED),
+        title: builder.add_text_field("title", TEXT | STORED),
+        body: builder.add_text_field("body", TEXT | STORED),
+        tags: builder.add_text_field("tags", TEXT | STORED),
+        scope: builder.add_facet_field("scope", FacetOptions::default()),
+        modified: builder.add_u64_field("modified", STORED),
+    };
+    (builder.build(), fields)
 }
 
 impl NoteSearch {
-    pub fn new() -> Self {
-        todo!("Initialize a new NoteSearch instance");
+    // Opens (or creates) the persistent index kept under `<vault>/.search`.
+    pub fn new(vault: &Vault) -> io::Result<Self> {
+        let dir = file_operations::resolve_path(&format!("{}/{}", vault.path, SEARCH_DIR));
+        std::fs::create_dir_all(&dir)?;
+        let (schema, fields) = schema();
+        let directory = MmapDirectory::open(&dir).map_err(index_error)?;
+        let index = match Index::open_or_create(directory, schema.clone()) {
+            Ok(index) => index,
+            // Indexes written by older versions have other fields; they are rebuilt from the notes
+            Err(tantivy::TantivyError::SchemaError(_)) => {
+                std::fs::remove_dir_all(&dir)?;
+                std::fs::create_dir_all(&dir)?;
+                Index::open_or_create(MmapDirectory::open(&dir).map_err(index_error)?, schema).map_err(index_error)?
+            }
+            Err(e) => return Err(index_error(e)),
+        };
+        let writer = index.writer_with_num_threads(1, WRITER_MEMORY).map_err(index_error)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(index_error)?;
+        Ok(Self {
+            index,
+            fields,
+            reader,
+            writer: Mutex::new(writer),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reader.searcher().num_docs() == 0
+    }
+
+    fn add_document(&self, writer: &IndexWriter, path: &str, title: &str, content: &str, scope: Scope, modified: u64) -> io::Result<()> {
+        let document = doc!(
+            self.fields.path => path,
+            self.fields.title => title,
+            self.fields.body => content,
+            self.fields.tags => markdown::note_tags(content).join(" "),
+            self.fields.scope => scope.facet(),
+            self.fields.modified => modified,
+        );
+        writer.add_document(document).map_err(index_error)?;
+        Ok(())
+    }
+
+    fn commit(&self, writer: &mut IndexWriter) -> io::Result<()> {
+        writer.commit().map_err(index_error)?;
+        self.reader.reload().map_err(index_error)
+    }
+
+
+    fn add_trash(&self, writer: &IndexWriter, vault: &Vault) -> io::Result<usize> {
+        let trashed = trash::list_trash(vault)?;
+        for note in &trashed {
+            let path = format!("{}/{}", TRASHED_NOTES_DIR, note.id);
+            let content = file_operations::read_from_file(&format!("{}/{}.md", vault.path, path))?;
+            let title = note.path.rsplit('/').next().unwrap_or(&note.path);
+            self.add_document(writer, &path, title, &content, Scope::Trash, 0)?;
+        }
+        Ok(trashed.len())
+    }
+
+    // Re-reads the given notes from disk, dropping the ones that no longer exist or are in excluded folders.
+    pub fn sync(&self, vault: &Vault, paths: &[String]) -> io::Result<()> {
+        let config = vault.config()?;
+        let (changed, removed): (Vec<String>, Vec<String>) = paths.iter().cloned().partition(|path| {
+            !config.is_excluded(path) && Path::new(&file_operations::resolve_path(&format!("{}/{}.md", vault.path, path))).exists()
+        });
+        self.update(vault, &changed, &removed)
+    }
+
+    // Indexes the changed notes and drops the removed ones in a single commit. When notes were removed
+    // the trash is indexed again, as they may have moved there.
+    pub fn update(&self, vault: &Vault, changed: &[String], removed: &[String]) -> io::Result<()> {
+        let folders = archive_folders(vault)?;
+        let mut writer = self.writer.lock().unwrap();
+        for path in changed.iter().chain(removed) {
+            writer.delete_term(Term::from_field_text(self.fields.path, path));
+        }
+        for path in changed {
+            let content = match file_operations::read_from_file(&format!("{}/{}.md", vault.path, path)) {
+                Ok(content) => content,
+                // Deleted since it was listed; its removal gets synced on its own
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            let title = path.rsplit('/').next().unwrap_or(path);
+            self.add_document(&writer, path, title, &content, scope_of(path, &folders), modified_at(vault, path))?;
+        }
+        if !removed.is_empty() {
+            writer.delete_term(Term::from_facet(self.fields.scope, &Scope::Trash.facet()));
+            self.add_trash(&writer, vault)?;
+        }
+        self.commit(&mut writer)
+    }
+
+    // Paths and file times of the indexed notes, the trash left out.
+    fn indexed_notes(&self) -> io::Result<HashMap<String, u64>> {
+        let searcher = self.reader.searcher();
+        let mut notes = HashMap::new();
+        for address in searcher.search(&AllQuery, &DocSetCollector).map_err(index_error)? {
+            let document: TantivyDocument = searcher.doc(address).map_err(index_error)?;
+            let path = document.get_first(self.fields.path).and_then(|value| value.as_str()).unwrap_or_default();
+            if !path.is_empty() && !path.starts_with(&format!("{}/", TRASHED_NOTES_DIR)) {
+                let modified = document.get_first(self.fields.modified).and_then(|value| value.as_u64()).unwrap_or(0);
+                notes.insert(path.to_string(), modified);
+            }
+        }
+        Ok(notes)
     }
 
-    pub fn search(&self, query: &str) -> Vec<String> {
-        todo!("Search for notes matching the query");
+    // Compares the notes on disk with the index, without reading any note.
+    pub fn changes(&self, vault: &Vault) -> io::Result<IndexChanges> {
+        let mut indexed = self.indexed_notes()?;
+        let mut changes = IndexChanges::default();
+        for path in refactor::note_paths(vault)? {
+            match indexed.remove(&path) {
+                Some(modified) if modified != 0 && modified == modified_at(vault, &path) => {}
+                Some(_) => changes.changed.push(path),
+                None => {
+                    changes.added.push(path.clone());
+                    changes.changed.push(path);
+                }
+            }
+        }
+        changes.removed = indexed.into_keys().collect();
+        changes.removed.sort();
+        Ok(changes)
     }
-}
\ No newline at end of file
+
+    // Rebuilds the whole index from the notes of the vault, trash included, and returns the number of
+    // indexed notes.
+    pub fn reindex(&self, vault: &Vault) -> io::Result<usize> {
+        let mut writer = self.writer.lock().unwrap();
+        writer.delete_all_documents().map_err(index_error)?;
+        let folders = archive_folders(vault)?;
+        let paths = refactor::note_paths(vault)?;
+        for path in &paths {
+            let content = file_operations::read_from_file(&format!("{}/{}.md", vault.path, path))?;
+            let title = path.rsplit('/').next().unwrap_or(path);
+            self.add_document(&writer, path, title, &content, scope_of(path, &folders), modified_at(vault, path))?;
+        }
+        let trashed = self.add_trash(&writer, vault)?;
+        self.commit(&mut writer)?;
+        Ok(paths.len() + trashed)
+    }
+
+    // Returns the best matching notes for a query, with a snippet, badges and the location of the first match.
+    // Archived and trashed notes are left out unless included.
+    pub fn search(
+        &self,
+        vault: &Vault,
+        store: Option<&MetadataStore>,
+        query: &str,
+        limit: usize,
+        filter: SearchFilter,
+    ) -> io::Result<Vec<SearchHit>> {
+        let mut parser = QueryParser::for_index(&self.index, vec![self.fields.title, self.fields.body, self.fields.tags]);
+        parser.set_field_boost(self.fields.title, 2.0);
+        parser.set_field_boost(self.fields.tags, 1.5);
+        // User input is not a query language; ignore the parts tantivy cannot understand
+        let (text_query, _) = parser.parse_query_lenient(query.trim());
+        let scopes = [(Scope::Active, true), (Scope::Archived, filter.include_archived), (Scope::Trash, filter.include_trash)];
+        let scope_query: Vec<(Occur, Box<dyn Query>)> = scopes
+            .into_iter()
+            .filter(|(_, included)| *included)
+            .map(|(scope, _)| {
+                let term = Term::from_facet(self.fields.scope, &scope.facet());
+                (Occur::Should, Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as Box<dyn Query>)
+            })
+            .collect();
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, text_query.box_clone()),
+            (Occur::Must, Box::new(BooleanQuery::new(scope_query))),
+        ]);
+
+        let searcher = self.reader.searcher();
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit)).map_err(index_error)?;
+        let mut snippets = SnippetGenerator::create(&searcher, &*text_query, self.fields.body).map_err(index_error)?;
+        snippets.set_max_num_chars(SNIPPET_LENGTH);
+
+        let mut terms = Vec::new();
+        text_query.query_terms(&mut |term, _| {
+            if let Some(text) = term.value().as_str() {
+                terms.push(text.to_string());
+            }
+        });
+
+        let folders = archive_folders(vault)?;
+        let mut hits = Vec::new();
+        for (score, address) in top_docs {
+            let document: TantivyDocument = searcher.doc(address).map_err(index_error)?;
+            let text = |field: Field| {
+                document
+                    .get_first(field)
+                    .and_then(|value| value.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let (path, title, body) = (text(self.fields.path), text(self.fields.title), text(self.fields.body));
+            let scope = scope_of(&path, &folders);
+            // Restoring or emptying the trash does not update the index; skip what is gone
+            if scope == Scope::Trash && !Path::new(&file_operations::resolve_path(&format!("{}/{}.md", vault.path, path))).exists() {
+                continue;
+            }
+            let location = terms
+                .iter()
+                .find_map(|term| outline::locate_matches(&body, term).into_iter().next());
+
+            let mut snippet = snippets.snippet_from_doc(&document);
+            snippet.set_snippet_prefix_postfix("<mark>", "</mark>");
+
+            hits.push(SearchHit {
+                score,
+                snippet: snippet.to_html(),
+                stats: note_stats::note_stats(vault, store, &title, &body),
+                location,
+                id: lookup::note_id(&body),
+                title,
+                path,
+                scope,
+            });
+        }
+        Ok(hits)
+    }
+}
+
+// Open search indexes, one per vault; tantivy allows a single writer per index.
+#[derive(Default)]
+pub struct SearchIndexes {
+    indexes: Mutex<HashMap<String, Arc<NoteSearch>>>,
+    // Vaults whose index is being brought up to date in the background
+    indexing: Mutex<HashSet<String>>,
+}
+
+impl SearchIndexes {
+    // Returns the index of a vault, building it from the notes the first time it is created, unless the
+    // vault is being indexed in the background already.
+    pub fn get(&self, vault: &Vault) -> io::Result<Arc<NoteSearch>> {
+        let mut indexes = self.indexes.lock().unwrap();
+        if let Some(search) = indexes.get(&vault.path) {
+            return Ok(search.clone());
+        }
+        let search = Arc::new(NoteSearch::new(vault)?);
+        if search.is_empty() && !self.is_indexing(vault) {
+            search.reindex(vault)?;
+        }
+        indexes.insert(vault.path.clone(), search.clone());
+        Ok(search)
+    }
+
+    // Closes the index of a vault so its files can be moved or rebuilt.
+    pub fn forget(&self, vault: &Vault) {
+        self.indexes.lock().unwrap().remove(&vault.path);
+    }
+
+    // Marks a vault as being indexed in the background; false when it already is.
+    pub fn start_indexing(&self, vault: &Vault) -> bool {
+        self.indexing.lock().unwrap().insert(vault.path.clone())
+    }
+
+    pub fn finish_indexing(&self, vault: &Vault) {
+        self.indexing.lock().unwrap().remove(&vault.path);
+    }
+
+    pub fn is_indexing(&self, vault: &Vault) -> bool {
+        self.indexing.lock().unwrap().contains(&vault.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_index_and_search() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Rust.md", vault.path), "# Intro\nBorrow checker\n## Lifetimes\nThe compiler tracks lifetimes.").unwrap();
+        file_operations::write_to_file(&format!("{}/Cooking.md", vault.path), "Pasta and #recipes").unwrap();
+
+        let indexes = SearchIndexes::default();
+        let search = indexes.get(&vault).unwrap();
+        let hits = search.search(&vault, None, "lifetimes", DEFAULT_SEARCH_LIMIT, SearchFilter::default()).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].title, "Rust");
+        assert!(hits[0].snippet.contains("<mark>lifetimes</mark>"));
+        assert!(hits[0].score > 0.0);
+        assert_eq!(hits[0].stats.word_count, 8);
+        assert_eq!(hits[0].location.as_ref().and_then(|location| location.heading.as_deref()), Some("lifetimes"));
+        assert_eq!(search.search(&vault, None, "recipes", 10, SearchFilter::default()).unwrap()[0].path, "Cooking");
+
+        // Incremental updates
+        file_operations::write_to_file(&format!("{}/Cooking.md", vault.path), "---\nid: c1\n---\nNow about lifetimes too").unwrap();
+        search.sync(&vault, &["Cooking".to_string()]).unwrap();
+        assert_eq!(search.search(&vault, None, "lifetimes", 10, SearchFilter::default()).unwrap().len(), 2);
+        file_operations::delete_file(&format!("{}/Rust.md", vault.path)).unwrap();
+        search.sync(&vault, &["Rust".to_string()]).unwrap();
+        assert_eq!(search.search(&vault, None, "lifetimes", 10, SearchFilter::default()).unwrap().len(), 1);
+        assert!(search.search(&vault, None, "AND (", 10, SearchFilter::default()).is_ok());
+
+        // The index persists between sessions
+        drop(search);
+        indexes.forget(&vault);
+        let search = NoteSearch::new(&vault).unwrap();
+        let hits = search.search(&vault, None, "lifetimes", 10, SearchFilter::default()).unwrap();
+        assert_eq!((hits[0].title.as_str(), hits[0].id.as_deref()), ("Cooking", Some("c1")));
+        assert_eq!(search.reindex(&vault).unwrap(), 1);
+
+        // Cleanup
+        drop(search);
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_index_changes() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        for title in ["Kept", "Edited", "Deleted"] {
+            file_operations::write_to_file(&format!("{}/{}.md", vault.path, title), title).unwrap();
+        }
+        let search = NoteSearch::new(&vault).unwrap();
+        search.reindex(&vault).unwrap();
+        assert_eq!(search.changes(&vault).unwrap(), IndexChanges::default());
+
+        // Notes changed while the app was closed
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        file_operations::write_to_file(&format!("{}/Edited.md", vault.path), "Edited outside").unwrap();
+        file_operations::write_to_file(&format!("{}/New.md", vault.path), "Created outside").unwrap();
+        file_operations::delete_file(&format!("{}/Deleted.md", vault.path)).unwrap();
+        let changes = search.changes(&vault).unwrap();
+        let mut changed = changes.changed.clone();
+        changed.sort();
+        assert_eq!((changed, changes.removed.clone()), (vec!["Edited".to_string(), "New".to_string()], vec!["Deleted".to_string()]));
+        assert_eq!(changes.added, vec!["New"]);
+
+        search.update(&vault, &changes.changed, &changes.removed).unwrap();
+        assert_eq!(search.changes(&vault).unwrap(), IndexChanges::default());
+        assert_eq!(search.search(&vault, None, "outside", 10, SearchFilter::default()).unwrap().len(), 2);
+
+        // Cleanup
+        drop(search);
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_search_archived_and_trash() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        vault.create_folder(DEFAULT_ARCHIVE_FOLDER).unwrap();
+        file_operations::write_to_file(&format!("{}/Current.md", vault.path), "Quarterly budget").unwrap();
+        file_operations::write_to_file(&format!("{}/Archive/Old.md", vault.path), "Last year's budget").unwrap();
+        file_operations::write_to_file(&format!("{}/Draft.md", vault.path), "Budget draft").unwrap();
+        let trash_id = trash::move_to_trash(&vault, "Draft").unwrap();
+
+        let indexes = SearchIndexes::default();
+        let search = indexes.get(&vault).unwrap();
+        let scopes = |filter: SearchFilter| {
+            let mut hits: Vec<(String, Scope)> =
+                search.search(&vault, None, "budget", 10, filter).unwrap().into_iter().map(|hit| (hit.title, hit.scope)).collect();
+            hits.sort_by(|a, b| a.0.cmp(&b.0));
+            hits
+        };
+        assert_eq!(scopes(SearchFilter::default()), vec![("Current".to_string(), Scope::Active)]);
+        assert_eq!(
+            scopes(SearchFilter { include_archived: true, include_trash: true }),
+            vec![("Current".to_string(), Scope::Active), ("Draft".to_string(), Scope::Trash), ("Old".to_string(), Scope::Archived)]
+        );
+        let hits = search.search(&vault, None, "draft", 10, SearchFilter { include_archived: false, include_trash: true }).unwrap();
+        assert_eq!(hits[0].path, format!("{}/{}", TRASHED_NOTES_DIR, trash_id));
+
+        // Deleting a note indexes it in the trash, restoring it brings it back
+        trash::move_to_trash(&vault, "Current").unwrap();
+        search.sync(&vault, &["Current".to_string()]).unwrap();
+        assert!(scopes(SearchFilter::default()).is_empty());
+        assert_eq!(scopes(SearchFilter { include_archived: false, include_trash: true }).len(), 2);
+        let restored = trash::restore_note(&vault, &trash_id).unwrap();
+        search.sync(&vault, &[restored]).unwrap();
+        assert_eq!(
+            scopes(SearchFilter { include_archived: false, include_trash: true }),
+            vec![("Current".to_string(), Scope::Trash), ("Draft".to_string(), Scope::Active)]
+        );
+
+        // Cleanup
+        drop(search);
+        indexes.forget(&vault);
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
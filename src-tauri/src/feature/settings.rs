@@ -0,0 +1,211 @@
+// Application settings persisted in the app data directory
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Error, ErrorKind};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::feature::keybindings;
+use crate::utils::{file_operations, markdown, message::Message};
+
+pub const SETTINGS_FILE: &str = "settings.json";
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+// Whether a note's file name follows its first `# Heading` when the heading changes on save.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleSync {
+    #[default]
+    Off,
+    Suggest,
+    Auto,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorSettings {
+    pub font_family: String,
+    pub font_size: u32,
+    pub tab_size: u32,
+    pub line_wrap: bool,
+    pub spell_check: bool,
+    pub line_numbers: bool,
+    pub vim_mode: bool,
+    pub title_sync: TitleSync,
+    // URL scheme of wikilinks in rendered notes, e.g. `note` for `note://Note%20Title`
+    pub link_scheme: String,
+    // Notes saved larger than this have pasted base64 and binary blobs moved to attachments; 0 turns it off
+    pub large_note_limit_kb: u32,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        Self {
+            font_family: "monospace".to_string(),
+            font_size: 16,
+            tab_size: 4,
+            line_wrap: true,
+            spell_check: true,
+            line_numbers: false,
+            vim_mode: false,
+            title_sync: TitleSync::Off,
+            link_scheme: markdown::DEFAULT_LINK_SCHEME.to_string(),
+            large_note_limit_kb: 512,
+        }
+    }
+}
+
+// PEM files of the certificate and private key that shared notes are served with over HTTPS.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShareSettings {
+    pub certificate: Option<String>,
+    pub private_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: Theme,
+    pub editor: EditorSettings,
+    pub hotkeys: BTreeMap<String, String>,
+    pub default_vault: Option<String>,
+    pub share: ShareSettings,
+}
+
+impl Settings {
+    pub fn validate(&self) -> io::Result<()> {
+        if !(8..=48).contains(&self.editor.font_size) {
+            return Err(Message::new("settings.font_size_out_of_range", "Font size must be between {min} and {max}")
+                .with("min", 8)
+                .with("max", 48)
+                .error(ErrorKind::InvalidInput));
+        }
+        if !(1..=8).contains(&self.editor.tab_size) {
+            return Err(Message::new("settings.tab_size_out_of_range", "Tab size must be between {min} and {max}")
+                .with("min", 1)
+                .with("max", 8)
+                .error(ErrorKind::InvalidInput));
+        }
+        if !markdown::is_valid_link_scheme(&self.editor.link_scheme) {
+            return Err(Message::new("settings.invalid_link_scheme", "Link scheme must be a lowercase URL scheme such as \"note\"").error(ErrorKind::InvalidInput));
+        }
+        for combo in self.hotkeys.values().filter(|combo| !combo.is_empty()) {
+            keybindings::normalize_combo(combo)?;
+        }
+        if self.share.certificate.is_some() != self.share.private_key.is_some() {
+            return Err(Message::new("settings.share_tls_incomplete", "Sharing over HTTPS needs both a certificate and a private key").error(ErrorKind::InvalidInput));
+        }
+        Ok(())
+    }
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    // Loads the settings file, falling back to defaults when it is missing or unreadable.
+    pub fn load(path: PathBuf) -> Self {
+        let settings = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                println!("❌ Failed to parse settings {}: {}", path.display(), e);
+                Settings::default()
+            }),
+            Err(_) => Settings::default(),
+        };
+        Self {
+            path,
+            settings: Mutex::new(settings),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    // Validates and persists new settings, returning the stored value.
+    pub fn set(&self, settings: Settings) -> io::Result<Settings> {
+        settings.validate()?;
+        let mut current = self.settings.lock().unwrap();
+        self.write(&settings)?;
+        *current = settings.clone();
+        Ok(settings)
+    }
+
+    // Applies a fallible change to the current settings and persists the result.
+    pub fn update<F>(&self, change: F) -> io::Result<Settings>
+    where
+        F: FnOnce(&mut Settings) -> io::Result<()>,
+    {
+        let mut settings = self.get();
+        change(&mut settings)?;
+        self.set(settings)
+    }
+
+    pub fn reset(&self) -> io::Result<Settings> {
+        self.set(Settings::default())
+    }
+
+    fn write(&self, settings: &Settings) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(settings).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        // Never leaves a truncated settings file behind, even on a crash
+        file_operations::write_atomic(&self.path, json.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_settings_store() {
+        file_operations::set_base_path(None);
+        let dir = std::env::temp_dir().join(format!("test_settings_{}", nanoid!()));
+        let path = dir.join(SETTINGS_FILE);
+
+        let store = SettingsStore::load(path.clone());
+        assert_eq!(store.get(), Settings::default());
+
+        let mut settings = store.get();
+        settings.theme = Theme::Dark;
+        settings.default_vault = Some("Work".to_string());
+        store.set(settings.clone()).unwrap();
+
+        settings.editor.font_size = 2;
+        assert!(store.set(settings.clone()).is_err());
+        assert_eq!(store.get().editor.font_size, 16);
+        settings.editor.font_size = 16;
+        settings.editor.link_scheme = "javascript".to_string();
+        assert!(store.set(settings.clone()).is_err());
+        settings.editor.link_scheme = Settings::default().editor.link_scheme;
+        settings.share.certificate = Some("cert.pem".to_string());
+        assert!(store.set(settings).is_err());
+
+        let reloaded = SettingsStore::load(path.clone());
+        assert_eq!(reloaded.get().theme, Theme::Dark);
+        assert_eq!(reloaded.get().default_vault.as_deref(), Some("Work"));
+        assert_eq!(reloaded.get().editor.font_size, 16);
+
+        reloaded.reset().unwrap();
+        assert_eq!(SettingsStore::load(path).get(), Settings::default());
+
+        // Cleanup
+        fs::remove_dir_all(dir).expect("Failed to delete settings directory");
+    }
+}
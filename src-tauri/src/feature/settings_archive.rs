@@ -0,0 +1,177 @@
+// Export and import of all settings in a single archive, for moving to a new machine
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::feature::settings::{Settings, SettingsStore};
+use crate::storage::vault::{Vault, VAULT_CONFIG_FILE};
+use crate::utils::{file_operations, message::Message};
+
+pub const ARCHIVE_VERSION: u32 = 1;
+
+// Per-vault configuration (vault settings, snippets, templates, publish target) relative to the vault root.
+const VAULT_CONFIG_PATHS: &[&str] = &[VAULT_CONFIG_FILE, ".config", ".templates", ".publish/config.json"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsArchive {
+    pub version: u32,
+    pub exported_at: String,
+    // Application settings, including the keybindings
+    pub settings: Settings,
+    // Vault name -> relative file path -> content
+    pub vaults: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveSummary {
+    pub vaults: Vec<String>,
+    pub files: usize,
+}
+
+fn is_config_path(relative: &str) -> bool {
+    let inside = VAULT_CONFIG_PATHS
+        .iter()
+        .any(|allowed| relative == *allowed || relative.starts_with(&format!("{}/", allowed)));
+    inside && !relative.split('/').any(|part| part == ".." || part.is_empty())
+}
+
+fn collect_vault_files(vault: &Vault) -> io::Result<BTreeMap<String, String>> {
+    let root = file_operations::resolve_path(&vault.path);
+    let mut files = BTreeMap::new();
+    for config in VAULT_CONFIG_PATHS {
+        let path = Path::new(&root).join(config);
+        if !path.exists() {
+            continue;
+        }
+        for entry in WalkDir::new(&path).sort_by_file_name() {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+            // Binary files are not configuration
+            if let Ok(content) = fs::read_to_string(entry.path()) {
+                files.insert(relative.to_string_lossy().replace('\\', "/"), content);
+            }
+        }
+    }
+    Ok(files)
+}
+
+// Writes the application settings and the configuration of the given vaults to one JSON archive.
+pub fn export_settings(settings: &Settings, vaults: &[Vault], output: &Path) -> io::Result<ArchiveSummary> {
+    let mut archive = SettingsArchive {
+        version: ARCHIVE_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        settings: settings.clone(),
+        vaults: BTreeMap::new(),
+    };
+    let mut summary = ArchiveSummary::default();
+    for vault in vaults {
+        let files = collect_vault_files(vault)?;
+        if files.is_empty() {
+            continue;
+        }
+        summary.vaults.push(vault.name.clone());
+        summary.files += files.len();
+        archive.vaults.insert(vault.name.clone(), files);
+    }
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(&archive).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    fs::write(output, json)?;
+    Ok(summary)
+}
+
+// Restores an archive: replaces the application settings and writes the vault configuration files,
+// creating vaults that do not exist yet. Returns the imported settings with the summary.
+pub fn import_settings(store: &SettingsStore, input: &Path) -> io::Result<(Settings, ArchiveSummary)> {
+    let json = fs::read_to_string(input)?;
+    let archive: SettingsArchive = serde_json::from_str(&json).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    if archive.version > ARCHIVE_VERSION {
+        return Err(Message::new("settings_archive.unsupported_version", "Settings archive version {version} is newer than this app supports")
+            .with("version", archive.version)
+            .error(ErrorKind::InvalidData));
+    }
+    if let Some(path) = archive.vaults.values().flat_map(|files| files.keys()).find(|path| !is_config_path(path)) {
+        return Err(Message::new("settings_archive.unexpected_file", "Unexpected file in settings archive: {path}")
+            .with("path", path)
+            .error(ErrorKind::InvalidData));
+    }
+
+    // Validate the settings before touching any vault
+    archive.settings.validate()?;
+    let mut summary = ArchiveSummary::default();
+    for (name, files) in &archive.vaults {
+        let vault = Vault::create_vault(name)?;
+        for (relative, content) in files {
+            let path = format!("{}/{}", vault.path, relative);
+            if let Some((parent, _)) = path.rsplit_once('/') {
+                file_operations::create_directory(parent)?;
+            }
+            file_operations::write_to_file(&path, content)?;
+            summary.files += 1;
+        }
+        summary.vaults.push(vault.name);
+    }
+    let settings = store.set(archive.settings)?;
+    Ok((settings, summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::snippets::{self, Snippet};
+    use crate::storage::vault::VaultConfig;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_export_and_import() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let snippet = Snippet { trigger: "sig".to_string(), template: "Cheers".to_string(), description: String::new() };
+        snippets::save_snippet(&vault, snippet.clone()).unwrap();
+        file_operations::create_directory(&format!("{}/.templates", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/.templates/journal.md", vault.path), "# {{date}}").unwrap();
+        file_operations::write_to_file(&format!("{}/Note.md", vault.path), "not config").unwrap();
+        let config = vault.set_config(&VaultConfig { excluded_dirs: vec!["Archive".to_string()], ..VaultConfig::default() }).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("test_settings_{}", nanoid!()));
+        let archive = dir.join("settings-archive.json");
+        let mut settings = Settings::default();
+        settings.hotkeys.insert("note.new".to_string(), "Mod+Alt+N".to_string());
+        let summary = export_settings(&settings, std::slice::from_ref(&vault), &archive).unwrap();
+        assert_eq!(summary, ArchiveSummary { vaults: vec![vault.name.clone()], files: 3 });
+
+        // Import on a "new machine"
+        vault.delete_vault().unwrap();
+        let store = SettingsStore::load(dir.join("settings.json"));
+        let (imported, summary) = import_settings(&store, &archive).unwrap();
+        assert_eq!(summary.files, 3);
+        assert_eq!(imported.hotkeys.get("note.new").map(String::as_str), Some("Mod+Alt+N"));
+        assert_eq!(store.get().hotkeys, imported.hotkeys);
+        assert_eq!(snippets::load_snippets(&vault).unwrap(), vec![snippet]);
+        assert_eq!(vault.config().unwrap(), config);
+        assert!(file_operations::read_from_file(&format!("{}/Note.md", vault.path)).is_err());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_is_config_path() {
+        assert!(is_config_path(".config/snippets.json"));
+        assert!(is_config_path(".publish/config.json"));
+        assert!(is_config_path(".vault.json"));
+        assert!(!is_config_path(".publish/status.json"));
+        assert!(!is_config_path(".config/../Note.md"));
+        assert!(!is_config_path("Note.md"));
+    }
+}
@@ -0,0 +1,224 @@
+// Temporary read-only sharing of a note on the local network
+use ammonia::clean_text;
+use chrono::{DateTime, Duration, Utc};
+use nanoid::nanoid;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tiny_http::{Header, Response, Server, SslConfig};
+
+use crate::feature::settings::ShareSettings;
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{excerpt, frontmatter, markdown, message::Message};
+
+pub const DEFAULT_SHARE_TTL: u64 = 15 * 60;
+pub const MAX_SHARE_TTL: u64 = 24 * 60 * 60;
+// Length of the description shown by link previews of a shared note
+const SHARE_DESCRIPTION_LENGTH: usize = 160;
+
+const SHARE_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<meta name="description" content="{description}">
+<title>{title}</title>
+<style>
+body { max-width: 46rem; margin: 2rem auto; padding: 0 1rem; font-family: system-ui, sans-serif; line-height: 1.6; }
+</style>
+</head>
+<body>
+<main>
+{content}
+</main>
+</body>
+</html>
+"#;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedLink {
+    pub token: String,
+    pub url: String,
+    pub expires_at: String,
+}
+
+struct SharedPage {
+    html: String,
+    expires_at: DateTime<Utc>,
+    // Pages shared over HTTPS are not served over plain HTTP
+    https: bool,
+}
+
+type Shares = Arc<Mutex<HashMap<String, SharedPage>>>;
+
+// Serves shared notes over HTTP, or HTTPS with the certificate of the settings. Each server is started on
+// the first share that needs it and lives with the app, so a new certificate is used after a restart.
+#[derive(Default)]
+pub struct ShareServer {
+    port: Mutex<Option<u16>>,
+    https_port: Mutex<Option<u16>>,
+    shares: Shares,
+}
+
+impl ShareServer {
+    // Renders a note and serves it at a random token URL until the TTL expires, over HTTPS when `tls`
+    // is given.
+    pub fn share(&self, vault: &Vault, title: &str, ttl_seconds: Option<u64>, tls: Option<&ShareSettings>) -> io::Result<SharedLink> {
+        let ttl = ttl_seconds.unwrap_or(DEFAULT_SHARE_TTL);
+        if ttl == 0 || ttl > MAX_SHARE_TTL {
+            return Err(Message::new("share.duration_out_of_range", "Share duration must be between 1 second and 24 hours").error(ErrorKind::InvalidInput));
+        }
+        let tls = match tls.map(|tls| (&tls.certificate, &tls.private_key)) {
+            Some((Some(certificate), Some(private_key))) => Some((certificate.as_str(), private_key.as_str())),
+            Some(_) => {
+                return Err(Message::new("share.no_certificate", "Set a certificate and a private key to share over HTTPS").error(ErrorKind::InvalidInput));
+            }
+            None => None,
+        };
+
+        let content = Note::read_note(vault, title)?;
+        // Other notes are not reachable from the shared page, so wikilinks become plain text
+        let body = markdown::rewrite_wikilinks(frontmatter::strip(&content), |_, label| label.to_string());
+        let html = SHARE_TEMPLATE
+            .replace("{title}", &clean_text(title))
+            .replace("{description}", &clean_text(&excerpt::excerpt(&content, SHARE_DESCRIPTION_LENGTH)))
+            .replace("{content}", &markdown::render_markdown(&body));
+        let port = self.ensure_started(tls)?;
+
+        let token = nanoid!(32);
+        let expires_at = Utc::now() + Duration::seconds(ttl as i64);
+        let mut shares = self.shares.lock().unwrap();
+        shares.retain(|_, page| page.expires_at > Utc::now());
+        shares.insert(token.clone(), SharedPage { html, expires_at, https: tls.is_some() });
+
+        let scheme = if tls.is_some() { "https" } else { "http" };
+        Ok(SharedLink {
+            url: format!("{}://{}:{}/share/{}", scheme, lan_address(), port, token),
+            token,
+            expires_at: expires_at.to_rfc3339(),
+        })
+    }
+
+    // Stops serving a shared note before it expires.
+    pub fn revoke(&self, token: &str) -> bool {
+        self.shares.lock().unwrap().remove(token).is_some()
+    }
+
+    // Starts the HTTP server, or the HTTPS one with the PEM files of `tls`, unless it already runs.
+    fn ensure_started(&self, tls: Option<(&str, &str)>) -> io::Result<u16> {
+        let mut port = match tls {
+            Some(_) => self.https_port.lock().unwrap(),
+            None => self.port.lock().unwrap(),
+        };
+        if let Some(port) = *port {
+            return Ok(port);
+        }
+
+        let server = match tls {
+            Some((certificate, private_key)) => {
+                let config = SslConfig { certificate: std::fs::read(certificate)?, private_key: std::fs::read(private_key)? };
+                Server::https("0.0.0.0:0", config)
+            }
+            None => Server::http("0.0.0.0:0"),
+        }
+        .map_err(|e| {
+            Message::new("share.start_failed", "Failed to start share server: {detail}").with("detail", e).error(ErrorKind::Other)
+        })?;
+        let bound = server
+            .server_addr()
+            .to_ip()
+            .map(|addr| addr.port())
+            .ok_or_else(|| Message::new("share.no_address", "Share server has no TCP address").error(ErrorKind::Other))?;
+        let shares = Arc::clone(&self.shares);
+        let https = tls.is_some();
+        thread::spawn(move || serve(server, shares, https));
+
+        *port = Some(bound);
+        Ok(bound)
+    }
+}
+
+fn serve(server: Server, shares: Shares, https: bool) {
+    for request in server.incoming_requests() {
+        let token = request.url().strip_prefix("/share/").unwrap_or_default().to_string();
+        let response = {
+            let mut shares = shares.lock().unwrap();
+            match shares.get(&token).filter(|page| page.https == https) {
+                Some(page) if page.expires_at > Utc::now() => {
+                    let header = Header::from_bytes("Content-Type", "text/html; charset=utf-8").unwrap();
+                    Response::from_string(page.html.clone()).with_header(header)
+                }
+                Some(_) => {
+                    shares.remove(&token);
+                    Response::from_string("This share link has expired").with_status_code(410)
+                }
+                None => Response::from_string("Not found").with_status_code(404),
+            }
+        };
+        if let Err(e) = request.respond(response) {
+            println!("❌ Failed to answer share request: {}", e);
+        }
+    }
+}
+
+// Finds the address other devices on the LAN can reach (no packet is sent).
+fn lan_address() -> IpAddr {
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect("192.0.2.1:80")?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    fn get(link: &SharedLink) -> String {
+        let (address, path) = link.url.trim_start_matches("http://").split_once('/').unwrap();
+        let port = address.rsplit_once(':').unwrap().1;
+        let mut stream = TcpStream::connect(format!("127.0.0.1:{}", port)).unwrap();
+        write!(stream, "GET /{} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_share_and_revoke() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Shared.md", vault.path), "---\nsecret: x\n---\n# Hello").unwrap();
+        let server = ShareServer::default();
+
+        assert!(server.share(&vault, "Shared", Some(0), None).is_err());
+        let link = server.share(&vault, "Shared", Some(60), None).unwrap();
+        let response = get(&link);
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("<h1>Hello</h1>"));
+        assert!(!response.contains("secret"));
+
+        assert!(server.revoke(&link.token));
+        assert!(get(&link).starts_with("HTTP/1.1 404"));
+
+        // HTTPS needs a certificate and its key, and fails to start with files that are not PEM
+        let no_key = ShareSettings { certificate: Some("cert.pem".to_string()), private_key: None };
+        assert_eq!(server.share(&vault, "Shared", None, Some(&no_key)).unwrap_err().kind(), ErrorKind::InvalidInput);
+        let note = file_operations::resolve_path(&format!("{}/Shared.md", vault.path));
+        let not_pem = ShareSettings { certificate: Some(note.clone()), private_key: Some(note) };
+        assert!(server.share(&vault, "Shared", None, Some(&not_pem)).is_err());
+        assert!(server.https_port.lock().unwrap().is_none());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
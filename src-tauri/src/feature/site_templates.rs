@@ -0,0 +1,199 @@
+// Tera templates of the static-site exporter. A vault can override any of them with a file of the same
+// name in `.templates/site/`; the others fall back to the built-in ones. Values passed to the templates
+// are already HTML-escaped (and `content` is rendered HTML), so templates print them as they are.
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, Error, ErrorKind};
+use tera::{Context, Tera};
+
+use crate::feature::templates::TEMPLATES_DIR;
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, message::Message};
+
+pub const SITE_TEMPLATES_DIR: &str = "site";
+pub const LAYOUT_TEMPLATE: &str = "base.html";
+pub const PAGE_TEMPLATE: &str = "page.html";
+pub const INDEX_TEMPLATE: &str = "index.html";
+pub const TAG_TEMPLATE: &str = "tag.html";
+
+const BUILTIN_LAYOUT: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{% block title %}{{ title }}{% endblock title %}</title>
+<style>
+body { max-width: 46rem; margin: 2rem auto; padding: 0 1rem; font-family: system-ui, sans-serif; line-height: 1.6; }
+nav { margin-bottom: 2rem; }
+img { max-width: 100%; }
+</style>
+</head>
+<body>
+<nav><a href="{{ root }}index.html">Index</a></nav>
+<main>
+{% block content %}{% endblock content %}
+</main>
+</body>
+</html>
+"#;
+
+const BUILTIN_PAGE: &str = r#"{% extends "base.html" %}{% block content %}{{ content }}{% if tags %}<p class="tags">{% for tag in tags %}<a href="{{ tag.href }}">#{{ tag.name }}</a> {% endfor %}</p>
+{% endif %}{% endblock content %}"#;
+
+const BUILTIN_INDEX: &str = r#"{% extends "base.html" %}{% block content %}<h1>Index</h1>
+{% for folder in folders %}{% if folder.name %}<h2>{{ folder.name }}</h2>
+{% endif %}<ul>
+{% for page in folder.pages %}<li><a href="{{ page.href }}">{{ page.title }}</a></li>
+{% endfor %}</ul>
+{% endfor %}{% if tags %}<h2>Tags</h2>
+<ul>
+{% for tag in tags %}<li><a href="{{ tag.href }}">#{{ tag.name }}</a> ({{ tag.count }})</li>
+{% endfor %}</ul>
+{% endif %}{% endblock content %}"#;
+
+const BUILTIN_TAG: &str = r#"{% extends "base.html" %}{% block content %}<h1>#{{ tag }}</h1>
+<ul>
+{% for page in pages %}<li><a href="{{ page.href }}">{{ page.title }}</a></li>
+{% endfor %}</ul>
+{% endblock content %}"#;
+
+// Each template with the blocks it must define
+const TEMPLATES: [(&str, &str, &[&str]); 4] = [
+    (LAYOUT_TEMPLATE, BUILTIN_LAYOUT, &["title", "content"]),
+    (PAGE_TEMPLATE, BUILTIN_PAGE, &["content"]),
+    (INDEX_TEMPLATE, BUILTIN_INDEX, &["content"]),
+    (TAG_TEMPLATE, BUILTIN_TAG, &["content"]),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SiteTemplates {
+    // Templates the vault overrides, sorted
+    pub custom: Vec<String>,
+    // Files in the site templates folder the exporter does not use, e.g. misspelled names
+    pub unknown: Vec<String>,
+}
+
+fn site_dir(vault: &Vault) -> String {
+    format!("{}/{}/{}", vault.path, TEMPLATES_DIR, SITE_TEMPLATES_DIR)
+}
+
+fn read_custom(vault: &Vault, name: &str) -> io::Result<Option<String>> {
+    match file_operations::read_from_file(&format!("{}/{}", site_dir(vault), name)) {
+        Ok(source) => Ok(Some(source)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn defined_blocks(source: &str) -> BTreeSet<String> {
+    let block_re = Regex::new(r"\{%-?\s*block\s+(\w+)").unwrap();
+    block_re.captures_iter(source).map(|caps| caps[1].to_string()).collect()
+}
+
+// Loads the site templates of a vault, checking that custom ones define the blocks the exporter needs
+// and compile.
+pub fn load(vault: &Vault) -> io::Result<(Tera, SiteTemplates)> {
+    let mut sources = Vec::new();
+    let mut custom = Vec::new();
+    for (name, builtin, blocks) in TEMPLATES {
+        match read_custom(vault, name)? {
+            Some(source) => {
+                let defined = defined_blocks(&source);
+                if let Some(missing) = blocks.iter().find(|block| !defined.contains(**block)) {
+                    return Err(Message::new("site_template.missing_block", "Site template {template} is missing the `{block}` block")
+                        .with("template", name)
+                        .with("block", missing)
+                        .error(ErrorKind::InvalidData));
+                }
+                custom.push(name.to_string());
+                sources.push((name, source));
+            }
+            None => sources.push((name, builtin.to_string())),
+        }
+    }
+
+    let mut tera = Tera::default();
+    tera.autoescape_on(Vec::new());
+    tera.add_raw_templates(sources).map_err(|e| template_error(&e))?;
+    custom.sort();
+    Ok((tera, SiteTemplates { custom, unknown: unknown_templates(vault)? }))
+}
+
+// Lists the vault's custom site templates, or explains why they cannot be used.
+pub fn validate(vault: &Vault) -> io::Result<SiteTemplates> {
+    load(vault).map(|(_, templates)| templates)
+}
+
+// Tera keeps the useful part of an error (which template, which line) in its sources.
+fn template_error(e: &tera::Error) -> Error {
+    let mut message = e.to_string();
+    let mut source = std::error::Error::source(e);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    Message::new("site_template.error", "Site template error: {detail}").with("detail", message).error(ErrorKind::InvalidData)
+}
+
+pub fn render(tera: &Tera, name: &str, context: &Context) -> io::Result<String> {
+    tera.render(name, context).map_err(|e| template_error(&e))
+}
+
+fn unknown_templates(vault: &Vault) -> io::Result<Vec<String>> {
+    let entries = match fs::read_dir(file_operations::resolve_path(&site_dir(vault))) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut unknown = Vec::new();
+    for entry in entries {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if !TEMPLATES.iter().any(|(known, _, _)| *known == name) {
+            unknown.push(name);
+        }
+    }
+    unknown.sort();
+    Ok(unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_load_site_templates() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        assert!(validate(&vault).unwrap().custom.is_empty());
+
+        let dir = site_dir(&vault);
+        file_operations::create_directory(&dir).unwrap();
+        file_operations::write_to_file(&format!("{}/page.html", dir), "{% extends \"base.html\" %}{% block content %}<article>{{ content }}</article>{% endblock %}").unwrap();
+        file_operations::write_to_file(&format!("{}/tags.html", dir), "").unwrap();
+        let (tera, templates) = load(&vault).unwrap();
+        assert_eq!(templates, SiteTemplates { custom: vec!["page.html".to_string()], unknown: vec!["tags.html".to_string()] });
+
+        let mut context = Context::new();
+        context.insert("title", "A &amp; B");
+        context.insert("root", "../");
+        context.insert("content", "<p>Hi</p>");
+        let html = render(&tera, PAGE_TEMPLATE, &context).unwrap();
+        assert!(html.contains("<title>A &amp; B</title>"));
+        assert!(html.contains("<a href=\"../index.html\">"));
+        assert!(html.contains("<article><p>Hi</p></article>"));
+
+        // Layouts need both blocks, and templates must compile
+        file_operations::write_to_file(&format!("{}/base.html", dir), "{% block content %}{% endblock %}").unwrap();
+        let error = load(&vault).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains("base.html is missing the `title` block"));
+        file_operations::write_to_file(&format!("{}/base.html", dir), "{% block title %}{% endblock %}{% block content %}{% if %}{% endblock %}").unwrap();
+        assert!(load(&vault).unwrap_err().to_string().contains("base.html"));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,116 @@
+// Labelled snapshots taken before operations that rewrite many notes at once (replace in notes, tag
+// renames, imports). A snapshot is the operation's entry in the undo history, which holds the notes it
+// changed as they were before, so rolling back to it undoes it together with everything done since.
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeSet;
+use std::io::{self, ErrorKind};
+
+use crate::feature::history::History;
+use crate::storage::vault::Vault;
+use crate::utils::message::Message;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub id: String,
+    // What the snapshot was taken before, e.g. `Rename tag`
+    pub label: String,
+    pub created_at: String,
+    // Notes the operation changed
+    pub notes: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RollbackReport {
+    pub snapshot: String,
+    // Notes written back to their content at snapshot time
+    pub restored: Vec<String>,
+    // Notes created after the snapshot, deleted again
+    pub removed: Vec<String>,
+}
+
+// Lists the snapshots of a vault, most recent first.
+pub fn list_snapshots(history: &History, vault: &Vault) -> Vec<Snapshot> {
+    history
+        .snapshots(vault)
+        .into_iter()
+        .map(|op| Snapshot { id: op.id, label: op.label, created_at: op.created_at, notes: op.changes.len() })
+        .collect()
+}
+
+// Brings the notes of the vault back to a snapshot, the most recent one when no id is given, by undoing
+// the operations recorded since. They can be redone one by one afterwards.
+pub fn rollback(history: &History, vault: &Vault, id: Option<&str>) -> io::Result<RollbackReport> {
+    let undone = history.rollback_to(vault, id)?.ok_or_else(|| match id {
+        Some(id) => Message::new("snapshot.not_found", "Snapshot {id} does not exist").with("id", id).error(ErrorKind::NotFound),
+        None => Message::new("snapshot.none", "The vault has no snapshots").error(ErrorKind::NotFound),
+    })?;
+
+    // The last operation undone is the snapshot, and the oldest content of a note is its snapshot state
+    let mut restored = BTreeSet::new();
+    let mut removed = BTreeSet::new();
+    for change in undone.iter().rev().flat_map(|op| &op.changes) {
+        if !restored.contains(&change.title) && !removed.contains(&change.title) {
+            match change.before {
+                Some(_) => restored.insert(change.title.clone()),
+                None => removed.insert(change.title.clone()),
+            };
+        }
+    }
+    Ok(RollbackReport {
+        snapshot: undone.last().map(|op| op.id.clone()).unwrap_or_default(),
+        restored: restored.into_iter().collect(),
+        removed: removed.into_iter().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::history::FileChange;
+    use crate::storage::note::Note;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    fn change(title: &str, before: Option<&str>, after: &str) -> FileChange {
+        FileChange { title: title.to_string(), before: before.map(str::to_string), after: Some(after.to_string()) }
+    }
+
+    #[test]
+    fn test_snapshot_and_rollback() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let other = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let history = History::default();
+        assert_eq!(rollback(&history, &vault, None).unwrap_err().kind(), ErrorKind::NotFound);
+
+        file_operations::write_to_file(&format!("{}/Plan.md", vault.path), "#final plan").unwrap();
+        history.record_snapshot("Rename tag", &vault, vec![change("Plan", Some("#draft plan"), "#final plan")]);
+        file_operations::write_to_file(&format!("{}/Imported.md", vault.path), "imported").unwrap();
+        history.record_snapshot("Import notes", &vault, vec![change("Imported", None, "imported")]);
+        file_operations::write_to_file(&format!("{}/Kept.md", other.path), "new").unwrap();
+        history.record("Replace in notes", &other, vec![change("Kept", Some("old"), "new")]);
+        file_operations::write_to_file(&format!("{}/Plan.md", vault.path), "#final plan, edited").unwrap();
+        history.record("Replace in notes", &vault, vec![change("Plan", Some("#final plan"), "#final plan, edited")]);
+
+        let snapshots = list_snapshots(&history, &vault);
+        assert_eq!(snapshots.iter().map(|snapshot| snapshot.label.as_str()).collect::<Vec<_>>(), vec!["Import notes", "Rename tag"]);
+        assert!(chrono::DateTime::parse_from_rfc3339(&snapshots[0].created_at).is_ok());
+
+        // Rolling back undoes the snapshot and what the vault went through since, but not other vaults
+        let report = rollback(&history, &vault, Some(&snapshots[1].id)).unwrap();
+        assert_eq!(report, RollbackReport { snapshot: snapshots[1].id.clone(), restored: vec!["Plan".to_string()], removed: vec!["Imported".to_string()] });
+        assert_eq!(Note::read_note(&vault, "Plan").unwrap(), "#draft plan");
+        assert!(Note::read_note(&vault, "Imported").is_err());
+        assert_eq!(Note::read_note(&other, "Kept").unwrap(), "new");
+        assert!(list_snapshots(&history, &vault).is_empty());
+        assert_eq!(rollback(&history, &vault, Some("missing")).unwrap_err().kind(), ErrorKind::NotFound);
+
+        // The rolled back operations can be redone
+        assert_eq!(history.redo_last().unwrap().map(|op| op.label).as_deref(), Some("Rename tag"));
+        assert_eq!(Note::read_note(&vault, "Plan").unwrap(), "#final plan");
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+        other.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,170 @@
+// Text snippets expanded from triggers
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::io::{self, Error, ErrorKind};
+
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, message::Message};
+
+const CONFIG_DIR: &str = ".config";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub trigger: String,
+    pub template: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Expansion {
+    pub text: String,
+    pub cursor: Option<usize>,
+}
+
+fn snippets_path(vault: &Vault) -> String {
+    format!("{}/{}/snippets.json", vault.path, CONFIG_DIR)
+}
+
+// Loads the snippets of a vault, sorted by trigger.
+pub fn load_snippets(vault: &Vault) -> io::Result<Vec<Snippet>> {
+    match file_operations::read_from_file(&snippets_path(vault)) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| Error::new(ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+fn save_snippets(vault: &Vault, snippets: &mut [Snippet]) -> io::Result<()> {
+    snippets.sort_by(|a, b| a.trigger.cmp(&b.trigger));
+    file_operations::create_directory(&format!("{}/{}", vault.path, CONFIG_DIR))?;
+    let json = serde_json::to_string_pretty(snippets).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    file_operations::write_to_file(&snippets_path(vault), &json)
+}
+
+// Adds a snippet or replaces the one with the same trigger.
+pub fn save_snippet(vault: &Vault, snippet: Snippet) -> io::Result<()> {
+    if snippet.trigger.is_empty() || snippet.trigger.chars().any(char::is_whitespace) {
+        return Err(Message::new("snippet.invalid_trigger", "Snippet trigger must be a single word").error(ErrorKind::InvalidInput));
+    }
+
+    let mut snippets = load_snippets(vault)?;
+    snippets.retain(|existing| existing.trigger != snippet.trigger);
+    snippets.push(snippet);
+    save_snippets(vault, &mut snippets)
+}
+
+pub fn delete_snippet(vault: &Vault, trigger: &str) -> io::Result<()> {
+    let mut snippets = load_snippets(vault)?;
+    let count = snippets.len();
+    snippets.retain(|snippet| snippet.trigger != trigger);
+    if snippets.len() == count {
+        return Err(Message::new("snippet.not_found", "Snippet does not exist").error(ErrorKind::NotFound));
+    }
+    save_snippets(vault, &mut snippets)
+}
+
+// Fills the placeholders of a template: `{{date}}`, `{{time}}`, `{{datetime}}`, `{{weekday}}`,
+// `{{date:<strftime>}}`, custom variables, `{{prompt:<label>|<default>}}` answers (looked up by label)
+// and the `{{cursor}}` position. Unknown placeholders and unanswered prompts without default are kept.
+pub fn render(template: &str, variables: &HashMap<String, String>, now: DateTime<Local>) -> Expansion {
+    let placeholder_re = Regex::new(r"\{\{\s*([a-zA-Z_][\w-]*)(?::([^}]*))?\s*\}\}").unwrap();
+    let mut text = String::new();
+    let mut cursor = None;
+    let mut last = 0;
+
+    for caps in placeholder_re.captures_iter(template) {
+        let whole = caps.get(0).unwrap();
+        text.push_str(&template[last..whole.start()]);
+        last = whole.end();
+
+        let name = &caps[1];
+        let value = match (name, caps.get(2)) {
+            ("cursor", _) => {
+                cursor.get_or_insert(text.len());
+                String::new()
+            }
+            ("date", Some(format)) => now.format(format.as_str()).to_string(),
+            ("date", None) => now.format("%Y-%m-%d").to_string(),
+            ("time", _) => now.format("%H:%M").to_string(),
+            ("datetime", _) => now.format("%Y-%m-%d %H:%M").to_string(),
+            ("weekday", _) => now.format("%A").to_string(),
+            ("prompt", Some(prompt)) => {
+                let (label, default) = parse_prompt(prompt.as_str());
+                variables
+                    .get(&label)
+                    .cloned()
+                    .or(default)
+                    .unwrap_or_else(|| whole.as_str().to_string())
+            }
+            _ => variables.get(name).cloned().unwrap_or_else(|| whole.as_str().to_string()),
+        };
+        text.push_str(&value);
+    }
+    text.push_str(&template[last..]);
+    Expansion { text, cursor }
+}
+
+// Splits the argument of a `{{prompt:<label>|<default>}}` placeholder.
+pub fn parse_prompt(prompt: &str) -> (String, Option<String>) {
+    match prompt.split_once('|') {
+        Some((label, default)) => (label.trim().to_string(), Some(default.trim().to_string())),
+        None => (prompt.trim().to_string(), None),
+    }
+}
+
+// Expands the snippet registered for a trigger.
+pub fn expand(vault: &Vault, trigger: &str, variables: &HashMap<String, String>) -> io::Result<Expansion> {
+    let snippet = load_snippets(vault)?
+        .into_iter()
+        .find(|snippet| snippet.trigger == trigger)
+        .ok_or_else(|| Message::new("snippet.not_found", "Snippet does not exist").error(ErrorKind::NotFound))?;
+    Ok(render(&snippet.template, variables, Local::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_render() {
+        let now = Local.with_ymd_and_hms(2024, 5, 3, 14, 30, 0).unwrap();
+        let variables = HashMap::from([("project".to_string(), "Atlas".to_string())]);
+        let expansion = render("## {{date}} {{weekday}} ({{date:%d/%m}})\n{{project}}: {{cursor}} {{unknown}}", &variables, now);
+        assert_eq!(expansion.text, "## 2024-05-03 Friday (03/05)\nAtlas:  {{unknown}}");
+        assert_eq!(expansion.cursor, Some("## 2024-05-03 Friday (03/05)\nAtlas: ".len()));
+
+        let variables = HashMap::from([("Project name".to_string(), "Atlas".to_string())]);
+        let expansion = render("{{prompt:Project name}} {{prompt:Owner|me}} {{prompt:Due}}", &variables, now);
+        assert_eq!(expansion.text, "Atlas me {{prompt:Due}}");
+    }
+
+    #[test]
+    fn test_manage_and_expand_snippets() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let snippet = |trigger: &str, template: &str| Snippet {
+            trigger: trigger.to_string(),
+            template: template.to_string(),
+            description: String::new(),
+        };
+
+        save_snippet(&vault, snippet("sig", "Regards")).unwrap();
+        save_snippet(&vault, snippet("sig", "Best regards")).unwrap();
+        save_snippet(&vault, snippet("ts", "{{time}}")).unwrap();
+        assert!(save_snippet(&vault, snippet("two words", "x")).is_err());
+        assert_eq!(load_snippets(&vault).unwrap().len(), 2);
+
+        assert_eq!(expand(&vault, "sig", &HashMap::new()).unwrap().text, "Best regards");
+        delete_snippet(&vault, "sig").unwrap();
+        assert!(expand(&vault, "sig", &HashMap::new()).is_err());
+        assert!(delete_snippet(&vault, "sig").is_err());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
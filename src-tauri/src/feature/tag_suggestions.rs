@@ -0,0 +1,176 @@
+// Tag suggestions for a note, learned from the tagged notes of the vault: tags that often appear together
+// with the tags the note already has, and tags whose notes share words with it.
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeSet, HashMap};
+use std::io;
+
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{frontmatter, markdown};
+
+pub const DEFAULT_SUGGESTION_LIMIT: usize = 5;
+// Shorter words are mostly stop words
+const MIN_WORD_LENGTH: usize = 4;
+// Words in fewer tagged notes say nothing reliable about a tag
+const MIN_WORD_NOTES: usize = 2;
+const MIN_SCORE: f64 = 0.1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagSuggestion {
+    pub tag: String,
+    // Higher is more likely; co-occurrence and content each add up to 1 per reason
+    pub score: f64,
+    // Tags of the note this tag often appears with
+    pub co_occurs_with: Vec<String>,
+    // Words of the note that are typical of notes with this tag, most telling first
+    pub matched_words: Vec<String>,
+}
+
+fn words(content: &str) -> BTreeSet<String> {
+    markdown::extract_plain_text(frontmatter::strip(content))
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_WORD_LENGTH && !word.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[derive(Default)]
+struct TagStats {
+    // Notes per tag
+    notes: HashMap<String, usize>,
+    // Notes per pair of tags, keyed by the first tag
+    pairs: HashMap<String, HashMap<String, usize>>,
+    // Tagged notes per word
+    word_notes: HashMap<String, usize>,
+    // Notes per word and tag
+    word_tags: HashMap<String, HashMap<String, usize>>,
+}
+
+impl TagStats {
+    fn add(&mut self, tags: &[String], words: &BTreeSet<String>) {
+        for tag in tags {
+            *self.notes.entry(tag.clone()).or_default() += 1;
+            for other in tags.iter().filter(|other| *other != tag) {
+                *self.pairs.entry(tag.clone()).or_default().entry(other.clone()).or_default() += 1;
+            }
+        }
+        for word in words {
+            *self.word_notes.entry(word.clone()).or_default() += 1;
+            let by_tag = self.word_tags.entry(word.clone()).or_default();
+            for tag in tags {
+                *by_tag.entry(tag.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    // How often a candidate appears with the note's tags: the sum of P(candidate | tag).
+    fn co_occurrence(&self, candidate: &str, tags: &[String]) -> (f64, Vec<String>) {
+        let mut score = 0.0;
+        let mut with = Vec::new();
+        for tag in tags {
+            let together = self.pairs.get(tag).and_then(|pairs| pairs.get(candidate)).copied().unwrap_or(0);
+            if together > 0 {
+                score += together as f64 / self.notes[tag] as f64;
+                with.push(tag.clone());
+            }
+        }
+        (score, with)
+    }
+
+    // How typical the note's words are of the candidate: the average share of notes with the word that
+    // carry the tag, over the words known well enough.
+    fn content(&self, candidate: &str, words: &BTreeSet<String>) -> (f64, Vec<String>) {
+        let known: Vec<&String> = words
+            .iter()
+            .filter(|word| self.word_notes.get(*word).is_some_and(|notes| *notes >= MIN_WORD_NOTES))
+            .collect();
+        if known.is_empty() {
+            return (0.0, Vec::new());
+        }
+        let mut matched: Vec<(f64, String)> = known
+            .iter()
+            .filter_map(|word| {
+                let tagged = self.word_tags.get(*word).and_then(|tags| tags.get(candidate)).copied().unwrap_or(0);
+                (tagged > 0).then(|| (tagged as f64 / self.word_notes[*word] as f64, word.to_string()))
+            })
+            .collect();
+        let score = matched.iter().map(|(share, _)| share).sum::<f64>() / known.len() as f64;
+        matched.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        (score, matched.into_iter().map(|(_, word)| word).collect())
+    }
+}
+
+// Suggests tags for the note at `path` with the given content (which may not be saved yet), best first.
+// The note itself is left out of the statistics, and tags it already has are never suggested.
+pub fn suggest_tags(vault: &Vault, path: &str, content: &str, limit: usize) -> io::Result<Vec<TagSuggestion>> {
+    let mut stats = TagStats::default();
+    for other in Note::list_notes(vault)? {
+        if other == path {
+            continue;
+        }
+        let other_content = Note::read_note(vault, &other)?;
+        let tags = markdown::note_tags(&other_content);
+        if !tags.is_empty() {
+            stats.add(&tags, &words(&other_content));
+        }
+    }
+
+    let tags = markdown::note_tags(content);
+    let words = words(content);
+    let mut suggestions: Vec<TagSuggestion> = stats
+        .notes
+        .keys()
+        .filter(|candidate| !tags.contains(candidate))
+        .map(|candidate| {
+            let (co_score, co_occurs_with) = stats.co_occurrence(candidate, &tags);
+            let (content_score, matched_words) = stats.content(candidate, &words);
+            TagSuggestion { tag: candidate.clone(), score: co_score + content_score, co_occurs_with, matched_words }
+        })
+        .filter(|suggestion| suggestion.score >= MIN_SCORE)
+        .collect();
+    suggestions.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.tag.cmp(&b.tag)));
+    suggestions.truncate(limit);
+    Ok(suggestions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_suggest_tags() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let notes = [
+            ("Borrowing", "---\ntags: [rust, programming]\n---\nThe borrow checker and lifetimes"),
+            ("Traits", "Traits and generics in the compiler #rust #programming"),
+            ("Lifetimes", "---\ntags: [rust]\n---\nLifetimes keep the compiler happy"),
+            ("Pasta", "---\ntags: [cooking]\n---\nBoil water, salt it, cook the pasta"),
+            ("Risotto", "---\ntags: [cooking]\n---\nStir the rice and salt it slowly"),
+        ];
+        for (title, content) in notes {
+            file_operations::write_to_file(&format!("{}/{}.md", vault.path, title), content).unwrap();
+        }
+
+        // An untagged note is matched on its words
+        let suggestions = suggest_tags(&vault, "Draft", "Fighting the compiler over lifetimes", DEFAULT_SUGGESTION_LIMIT).unwrap();
+        assert_eq!(suggestions[0].tag, "rust");
+        assert_eq!(suggestions[0].matched_words, vec!["compiler", "lifetimes"]);
+        assert!(suggestions.iter().all(|suggestion| suggestion.tag != "cooking"));
+
+        // A tagged note also gets the tags that come with its own, but never those it has
+        let suggestions = suggest_tags(&vault, "Lifetimes", &Note::read_note(&vault, "Lifetimes").unwrap(), 10).unwrap();
+        assert_eq!(suggestions[0].tag, "programming");
+        assert_eq!(suggestions[0].co_occurs_with, vec!["rust"]);
+        assert!(suggestions.iter().all(|suggestion| suggestion.tag != "rust"));
+
+        let suggestions = suggest_tags(&vault, "Soup", "Salt the water", 1).unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].tag, "cooking");
+        assert!(suggest_tags(&vault, "Empty", "", 5).unwrap().is_empty());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
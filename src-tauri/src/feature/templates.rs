@@ -0,0 +1,155 @@
+// Note templates in `.templates/`, with prompts the frontend asks for before creating a note
+use chrono::Local;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+
+use crate::feature::{folder_settings, snippets};
+use crate::storage::{note::{Note, OnConflict}, vault::Vault};
+use crate::utils::{file_operations, error::AppError, message::Message};
+
+pub const TEMPLATES_DIR: &str = ".templates";
+
+// A value asked from the user for a `{{prompt:<label>|<default>}}` placeholder.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplatePrompt {
+    pub label: String,
+    pub default: Option<String>,
+}
+
+fn template_path(vault: &Vault, name: &str) -> io::Result<String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(AppError::InvalidName(Message::new("template.invalid_name", "Template must be a file name in .templates")).into());
+    }
+    Ok(format!("{}/{}/{}", vault.path, TEMPLATES_DIR, name))
+}
+
+pub fn read_template(vault: &Vault, name: &str) -> io::Result<String> {
+    file_operations::read_from_file(&template_path(vault, name)?)
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => Message::new("template.not_found", "Template does not exist").error(ErrorKind::NotFound),
+            _ => e,
+        })
+}
+
+// Lists the template file names of a vault, sorted.
+pub fn list_templates(vault: &Vault) -> io::Result<Vec<String>> {
+    let dir = file_operations::resolve_path(&format!("{}/{}", vault.path, TEMPLATES_DIR));
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.file_type()?.is_file() && name.ends_with(".md") && !name.starts_with('.') {
+            names.push(name);
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+// Returns the prompts of a template in order of first appearance, one per label.
+pub fn prompts(template: &str) -> Vec<TemplatePrompt> {
+    let prompt_re = Regex::new(r"\{\{\s*prompt:([^}]*)\}\}").unwrap();
+    let mut prompts: Vec<TemplatePrompt> = Vec::new();
+    for caps in prompt_re.captures_iter(template) {
+        let (label, default) = snippets::parse_prompt(&caps[1]);
+        match prompts.iter_mut().find(|prompt| prompt.label == label) {
+            // A later occurrence may be the one carrying the default
+            Some(existing) => existing.default = existing.default.take().or(default),
+            None if !label.is_empty() => prompts.push(TemplatePrompt { label, default }),
+            None => {}
+        }
+    }
+    prompts
+}
+
+pub fn template_prompts(vault: &Vault, name: &str) -> io::Result<Vec<TemplatePrompt>> {
+    Ok(prompts(&read_template(vault, name)?))
+}
+
+// Renders a template with the prompt answers (keyed by label) and `{{title}}`.
+pub fn render_template(template: &str, title: &str, values: &HashMap<String, String>) -> io::Result<String> {
+    let mut variables = values.clone();
+    // Prompts declared with a default elsewhere in the template use it everywhere
+    for prompt in prompts(template) {
+        if variables.contains_key(&prompt.label) {
+            continue;
+        }
+        match prompt.default {
+            Some(default) => variables.insert(prompt.label, default),
+            None => {
+                return Err(Message::new("template.missing_prompt_value", "Missing value for prompt: {prompt}")
+                    .with("prompt", prompt.label)
+                    .error(ErrorKind::InvalidInput))
+            }
+        };
+    }
+    variables.insert("title".to_string(), title.to_string());
+    Ok(snippets::render(template, &variables, Local::now()).text)
+}
+
+// Creates a note from a template in a folder (empty for the vault root). Returns the vault-relative path.
+pub fn create_from_template(
+    vault: &Vault,
+    name: &str,
+    title: &str,
+    folder: &str,
+    values: &HashMap<String, String>,
+) -> io::Result<String> {
+    let content = render_template(&read_template(vault, name)?, title, values)?;
+    folder_settings::create_note(vault, folder, &Note::new(title, &content), OnConflict::Fail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    const TEMPLATE: &str = "# {{title}}\nProject: {{prompt:Project name}}\nOwner: {{ prompt:Owner }}\n{{prompt:Project name|x}} / {{prompt:Owner|me}}";
+
+    #[test]
+    fn test_prompts_and_render() {
+        assert_eq!(
+            prompts(TEMPLATE),
+            vec![
+                TemplatePrompt { label: "Project name".to_string(), default: Some("x".to_string()) },
+                TemplatePrompt { label: "Owner".to_string(), default: Some("me".to_string()) },
+            ]
+        );
+
+        let values = HashMap::from([("Project name".to_string(), "Atlas".to_string())]);
+        let content = render_template(TEMPLATE, "Kickoff", &values).unwrap();
+        assert_eq!(content, "# Kickoff\nProject: Atlas\nOwner: me\nAtlas / me");
+        assert!(render_template("{{prompt:Due}}", "x", &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_create_from_template() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        assert!(list_templates(&vault).unwrap().is_empty());
+        file_operations::create_directory(&format!("{}/{}", vault.path, TEMPLATES_DIR)).unwrap();
+        file_operations::write_to_file(&format!("{}/{}/project.md", vault.path, TEMPLATES_DIR), TEMPLATE).unwrap();
+        file_operations::write_to_file(&format!("{}/{}/notes.txt", vault.path, TEMPLATES_DIR), "").unwrap();
+
+        assert_eq!(list_templates(&vault).unwrap(), vec!["project.md"]);
+        assert_eq!(template_prompts(&vault, "project.md").unwrap().len(), 2);
+        assert_eq!(template_prompts(&vault, "../x.md").unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        let values = HashMap::from([("Project name".to_string(), "Atlas".to_string())]);
+        let path = create_from_template(&vault, "project.md", "Atlas Kickoff", "Projects", &values).unwrap();
+        assert_eq!(path, "Projects/Atlas Kickoff");
+        let content = file_operations::read_from_file(&format!("{}/{}.md", vault.path, path)).unwrap();
+        assert!(content.starts_with("# Atlas Kickoff\nProject: Atlas\n"));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,92 @@
+// Attachment thumbnails
+use std::fs;
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+use std::process::Command;
+
+use crate::storage::{attachment::AttachmentKind, vault::Vault};
+use crate::utils::{file_operations, message::Message};
+
+const THUMBNAILS_DIR: &str = ".cache/thumbnails";
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+// Returns the absolute path of a cached thumbnail for an attachment, generating it when stale.
+// Yields `None` for attachment kinds that cannot be previewed.
+pub fn thumbnail(vault: &Vault, attachment_path: &str) -> io::Result<Option<String>> {
+    let source = format!("{}/{}", vault.path, attachment_path);
+    if !Path::new(&file_operations::resolve_path(&source)).exists() {
+        return Err(Message::new("attachment.not_found", "Attachment does not exist").error(ErrorKind::NotFound));
+    }
+
+    let cache_dir = format!("{}/{}", vault.path, THUMBNAILS_DIR);
+    file_operations::create_directory(&cache_dir)?;
+    let target = format!("{}/{}.png", cache_dir, attachment_path.replace(['/', '\\'], "_"));
+
+    if !file_operations::is_up_to_date(&source, &target) {
+        let (source, target) = (file_operations::resolve_path(&source), file_operations::resolve_path(&target));
+        let rendered = match AttachmentKind::from_name(attachment_path) {
+            AttachmentKind::Image => render_image(&source, &target)?,
+            AttachmentKind::Pdf => render_pdf(&source, &target)?,
+            _ => false,
+        };
+        if !rendered {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(fs::canonicalize(file_operations::resolve_path(&target))?.to_string_lossy().to_string()))
+}
+
+fn render_image(source: &str, target: &str) -> io::Result<bool> {
+    let image = image::open(source).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .save_with_format(target, image::ImageFormat::Png)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    Ok(true)
+}
+
+// Renders the first page of a PDF with poppler's `pdftoppm`, if it is installed.
+fn render_pdf(source: &str, target: &str) -> io::Result<bool> {
+    let output_prefix = target.trim_end_matches(".png");
+    let status = Command::new("pdftoppm")
+        .args(["-png", "-singlefile", "-f", "1", "-l", "1", "-scale-to"])
+        .arg(THUMBNAIL_SIZE.to_string())
+        .arg(source)
+        .arg(output_prefix)
+        .status();
+
+    match status {
+        Ok(status) if status.success() => Ok(true),
+        Ok(_) => Err(Message::new("thumbnail.pdf_render_failed", "Failed to render PDF thumbnail").error(ErrorKind::InvalidData)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::attachment;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_image_thumbnail_is_generated_and_cached() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&attachment::attachments_dir(&vault)).unwrap();
+        let source = format!("{}/wide.png", attachment::attachments_dir(&vault));
+        image::RgbImage::new(512, 128).save(file_operations::resolve_path(&source)).unwrap();
+
+        let path = thumbnail(&vault, "attachments/wide.png").unwrap().unwrap();
+        let generated = image::open(&path).unwrap();
+        assert_eq!((generated.width(), generated.height()), (256, 64));
+        assert_eq!(thumbnail(&vault, "attachments/wide.png").unwrap().unwrap(), path);
+
+        file_operations::write_to_file(&format!("{}/notes.txt", attachment::attachments_dir(&vault)), "text").unwrap();
+        assert!(thumbnail(&vault, "attachments/notes.txt").unwrap().is_none());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,170 @@
+// Time tracking entries in notes
+use chrono::{DateTime, FixedOffset, Local, NaiveDate};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind};
+
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{frontmatter, message::Message};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackAction {
+    Start,
+    Stop,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeEntry {
+    pub start: String,
+    pub end: Option<String>,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TimeReport {
+    pub total_seconds: i64,
+    pub by_note: BTreeMap<String, i64>,
+    pub by_tag: BTreeMap<String, i64>,
+    pub by_day: BTreeMap<String, i64>,
+    pub running: Vec<String>,
+}
+
+// Parses the `⏱ start` / `⏱ stop` markers of a note into entries; an unmatched start stays open.
+pub fn parse_entries(content: &str) -> Vec<TimeEntry> {
+    let re = Regex::new(r"⏱\s*(start|stop)\s+(\S+)").unwrap();
+    let mut entries = Vec::new();
+    let mut open: Option<DateTime<FixedOffset>> = None;
+
+    for caps in re.captures_iter(content) {
+        let Ok(time) = DateTime::parse_from_rfc3339(&caps[2]) else {
+            continue;
+        };
+        match (&caps[1], open) {
+            ("start", None) => open = Some(time),
+            ("stop", Some(start)) => {
+                entries.push(TimeEntry {
+                    start: start.to_rfc3339(),
+                    end: Some(time.to_rfc3339()),
+                    seconds: (time - start).num_seconds().max(0),
+                });
+                open = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(start) = open {
+        entries.push(TimeEntry {
+            start: start.to_rfc3339(),
+            end: None,
+            seconds: 0,
+        });
+    }
+    entries
+}
+
+// Appends a timestamped start or stop marker to a note.
+pub fn track(vault: &Vault, title: &str, action: TrackAction) -> io::Result<TimeEntry> {
+    let mut content = Note::read_note(vault, title)?;
+    let running = parse_entries(&content).last().is_some_and(|entry| entry.end.is_none());
+    match (action, running) {
+        (TrackAction::Start, true) => {
+            return Err(Message::new("time_tracking.already_running", "Time tracking is already running").error(ErrorKind::InvalidInput))
+        }
+        (TrackAction::Stop, false) => {
+            return Err(Message::new("time_tracking.not_running", "Time tracking is not running").error(ErrorKind::InvalidInput))
+        }
+        _ => {}
+    }
+
+    let marker = match action {
+        TrackAction::Start => "start",
+        TrackAction::Stop => "stop",
+    };
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("- ⏱ {} {}\n", marker, Local::now().to_rfc3339()));
+    Note::update_note(vault, title, &content)?;
+
+    parse_entries(&content)
+        .pop()
+        .ok_or_else(|| Message::new("time_tracking.record_failed", "Failed to record time entry").error(ErrorKind::Other))
+}
+
+// Sums tracked time per note, tag and day (of the entry start), optionally limited to a date range.
+pub fn report(vault: &Vault, from: Option<NaiveDate>, to: Option<NaiveDate>) -> io::Result<TimeReport> {
+    let mut report = TimeReport::default();
+    for title in Note::list_notes(vault)? {
+        let content = Note::read_note(vault, &title)?;
+        let tags = frontmatter::parse(&content).map(|fm| fm.tags()).unwrap_or_default();
+
+        for entry in parse_entries(&content) {
+            if entry.end.is_none() {
+                report.running.push(title.clone());
+                continue;
+            }
+            let Ok(start) = DateTime::parse_from_rfc3339(&entry.start) else {
+                continue;
+            };
+            let day = start.date_naive();
+            if from.is_some_and(|from| day < from) || to.is_some_and(|to| day > to) {
+                continue;
+            }
+
+            report.total_seconds += entry.seconds;
+            *report.by_note.entry(title.clone()).or_default() += entry.seconds;
+            *report.by_day.entry(day.to_string()).or_default() += entry.seconds;
+            for tag in &tags {
+                *report.by_tag.entry(tag.clone()).or_default() += entry.seconds;
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_parse_entries() {
+        let content = "Work\n- ⏱ start 2024-05-01T09:00:00+00:00\n- ⏱ stop 2024-05-01T10:30:00+00:00\n⏱ start 2024-05-02T09:00:00+00:00\n";
+        let entries = parse_entries(content);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].seconds, 5400);
+        assert!(entries[1].end.is_none());
+    }
+
+    #[test]
+    fn test_track_and_report() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(
+            &format!("{}/Project.md", vault.path),
+            "---\ntags: [client]\n---\n- ⏱ start 2024-05-01T09:00:00+00:00\n- ⏱ stop 2024-05-01T10:00:00+00:00\n",
+        )
+        .unwrap();
+
+        assert!(track(&vault, "Project", TrackAction::Stop).is_err());
+        let entry = track(&vault, "Project", TrackAction::Start).unwrap();
+        assert!(entry.end.is_none());
+        assert!(track(&vault, "Project", TrackAction::Start).is_err());
+
+        let report = report(&vault, None, None).unwrap();
+        assert_eq!(report.total_seconds, 3600);
+        assert_eq!(report.by_tag["client"], 3600);
+        assert_eq!(report.by_day["2024-05-01"], 3600);
+        assert_eq!(report.running, vec!["Project"]);
+
+        let entry = track(&vault, "Project", TrackAction::Stop).unwrap();
+        assert!(entry.end.is_some());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,163 @@
+// Timeline of notes: notes grouped by day, week, month or year of a chosen date (created, updated or a
+// frontmatter field), oldest group first, for a chronological view of a project or journal.
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind};
+use std::time::SystemTime;
+
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{file_operations, frontmatter, message::Message};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    Day,
+    Week,
+    #[default]
+    Month,
+    Year,
+}
+
+// Inclusive range of `YYYY-MM-DD` dates; open ends are unbounded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DateRange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl DateRange {
+    pub fn bounds(&self) -> io::Result<(Option<NaiveDate>, Option<NaiveDate>)> {
+        let parse = |date: &Option<String>| {
+            date.as_deref()
+                .map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+                .transpose()
+                .map_err(|_| Message::new("date.invalid", "Dates must be formatted as YYYY-MM-DD").error(ErrorKind::InvalidInput))
+        };
+        let (from, to) = (parse(&self.from)?, parse(&self.to)?);
+        if from.zip(to).is_some_and(|(from, to)| from > to) {
+            return Err(Message::new("timeline.range_reversed", "Timeline range ends before it starts").error(ErrorKind::InvalidInput));
+        }
+        Ok((from, to))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub path: String,
+    pub title: String,
+    pub date: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineGroup {
+    // `2024-05-03`, `2024-W18`, `2024-05` or `2024`
+    pub label: String,
+    // First day of the group
+    pub start: String,
+    // Oldest first
+    pub notes: Vec<TimelineEntry>,
+}
+
+// The first day and label of the bucket holding a date.
+fn bucket_of(date: NaiveDate, bucket: Bucket) -> (NaiveDate, String) {
+    match bucket {
+        Bucket::Day => (date, date.format("%Y-%m-%d").to_string()),
+        Bucket::Week => {
+            let week = date.iso_week();
+            let start = NaiveDate::from_isoywd_opt(week.year(), week.week(), chrono::Weekday::Mon).unwrap_or(date);
+            (start, format!("{}-W{:02}", week.year(), week.week()))
+        }
+        Bucket::Month => (date.with_day(1).unwrap_or(date), date.format("%Y-%m").to_string()),
+        Bucket::Year => (date.with_ordinal(1).unwrap_or(date), date.format("%Y").to_string()),
+    }
+}
+
+fn file_date(time: io::Result<SystemTime>) -> Option<NaiveDate> {
+    time.ok().map(|time| DateTime::<Local>::from(time).date_naive())
+}
+
+// The date of a note for a timeline field. `created` and `updated` prefer the frontmatter fields of the
+// same name and fall back to the file times; any other field is read from the frontmatter only.
+pub fn note_date(vault: &Vault, path: &str, content: &str, field: &str) -> io::Result<Option<NaiveDate>> {
+    let from_frontmatter = frontmatter::parse(content).ok().and_then(|fields| fields.get_date(field));
+    if from_frontmatter.is_some() || !matches!(field, "created" | "updated") {
+        return Ok(from_frontmatter);
+    }
+    let file = std::fs::metadata(file_operations::resolve_path(&format!("{}/{}.md", vault.path, path)))?;
+    Ok(if field == "created" { file_date(file.created()) } else { file_date(file.modified()) })
+}
+
+// Groups the notes of a vault with a date in `range` by the bucket of that date. Notes without the field
+// are left out.
+pub fn get_timeline(vault: &Vault, field: &str, range: &DateRange, bucket: Bucket) -> io::Result<Vec<TimelineGroup>> {
+    let field = field.trim();
+    if field.is_empty() {
+        return Err(Message::new("timeline.field_empty", "Timeline field is empty").error(ErrorKind::InvalidInput));
+    }
+    let (from, to) = range.bounds()?;
+    let in_range = |date: &NaiveDate| from.is_none_or(|from| *date >= from) && to.is_none_or(|to| *date <= to);
+
+    let mut groups: BTreeMap<NaiveDate, TimelineGroup> = BTreeMap::new();
+    for path in Note::list_notes(vault)? {
+        let content = Note::read_note(vault, &path)?;
+        let Some(date) = note_date(vault, &path, &content, field)?.filter(in_range) else {
+            continue;
+        };
+        let (start, label) = bucket_of(date, bucket);
+        let title = path.rsplit('/').next().unwrap_or(&path).to_string();
+        groups
+            .entry(start)
+            .or_insert_with(|| TimelineGroup { label, start: start.to_string(), notes: Vec::new() })
+            .notes
+            .push(TimelineEntry { path, title, date: date.to_string() });
+    }
+
+    let mut groups: Vec<TimelineGroup> = groups.into_values().collect();
+    for group in &mut groups {
+        group.notes.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.path.cmp(&b.path)));
+    }
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_get_timeline() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Project", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Project/Kickoff.md", vault.path), "---\ndue: 2024-05-03\n---\n").unwrap();
+        file_operations::write_to_file(&format!("{}/Project/Review.md", vault.path), "---\ndue: 2024-05-01\ncreated: 2023-01-02\n---\n").unwrap();
+        file_operations::write_to_file(&format!("{}/Launch.md", vault.path), "---\ndue: 2024-07-15T09:00:00Z\n---\n").unwrap();
+        file_operations::write_to_file(&format!("{}/Undated.md", vault.path), "No dates").unwrap();
+
+        let groups = get_timeline(&vault, "due", &DateRange::default(), Bucket::Month).unwrap();
+        let labels: Vec<&str> = groups.iter().map(|group| group.label.as_str()).collect();
+        assert_eq!(labels, vec!["2024-05", "2024-07"]);
+        assert_eq!(groups[0].start, "2024-05-01");
+        let paths: Vec<&str> = groups[0].notes.iter().map(|note| note.path.as_str()).collect();
+        assert_eq!(paths, vec!["Project/Review", "Project/Kickoff"]);
+
+        let range = DateRange { from: None, to: Some("2024-06-30".to_string()) };
+        let weeks = get_timeline(&vault, "due", &range, Bucket::Week).unwrap();
+        assert_eq!(weeks.len(), 1);
+        assert_eq!((weeks[0].label.as_str(), weeks[0].start.as_str()), ("2024-W18", "2024-04-29"));
+
+        // Created dates come from the frontmatter, then from the file
+        let created = get_timeline(&vault, "created", &DateRange::default(), Bucket::Year).unwrap();
+        assert_eq!(created[0].label, "2023");
+        assert_eq!(created.iter().map(|group| group.notes.len()).sum::<usize>(), 4);
+
+        let range = DateRange { from: Some("2024-02-01".to_string()), to: Some("2024-01-01".to_string()) };
+        assert_eq!(get_timeline(&vault, "due", &range, Bucket::Day).unwrap_err().kind(), ErrorKind::InvalidInput);
+        let range = DateRange { from: Some("May".to_string()), to: None };
+        assert_eq!(get_timeline(&vault, "due", &range, Bucket::Day).unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,265 @@
+// Keeping note file names in line with their first `# Heading`
+use serde::{Serialize, Deserialize};
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use crate::feature::paste_guard::ExtractionNotice;
+use crate::feature::refactor::{self, MoveReport};
+use crate::feature::settings::TitleSync;
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{file_operations, filename_policy, outline, string_utils, message::Message, plan::Plan};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SaveOutcome {
+    // Path of the note after saving (changes when it was renamed)
+    pub path: String,
+    pub renamed: Option<MoveReport>,
+    // Title the note could be renamed to, when renaming is left to the user
+    pub suggested_title: Option<String>,
+    // Set when pasted blobs were moved out of the note before saving
+    pub extracted: Option<ExtractionNotice>,
+}
+
+// Before/after report of naming the notes of an older vault after their headings. `renamed` is what
+// `rollback_names` takes to undo the migration.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NameMigration {
+    pub renamed: Vec<MoveReport>,
+    // Notes whose heading is already the name of another note, or of another note being renamed
+    pub skipped: Vec<String>,
+    // Notes without a heading to name them after, which keep their name
+    pub unnamed: Vec<String>,
+}
+
+// Returns the text of the first level-1 heading of a note.
+pub fn first_h1(content: &str) -> Option<String> {
+    outline::headings(content)
+        .into_iter()
+        .find(|heading| heading.level == 1)
+        .map(|heading| heading.text)
+}
+
+// Returns the file name the note should get when its first heading changed between two versions.
+fn renamed_title(path: &str, before: Option<&str>, after: &str) -> Option<String> {
+    let heading = first_h1(after)?;
+    if before.and_then(first_h1).as_deref() == Some(heading.as_str()) {
+        return None;
+    }
+    let title = string_utils::title_filename(&heading);
+    let current = path.rsplit('/').next().unwrap_or(path);
+    (!title.is_empty() && title != current).then_some(title)
+}
+
+// The name notes got from the first three words of their content before they were named after their title.
+fn legacy_file_name(content: &str) -> String {
+    let words: Vec<&str> = content.split_whitespace().collect();
+    let file_name = if words.len() >= 3 { words[..3].join("-") } else { content.to_string() };
+    string_utils::sanitize_filename(&file_name)
+}
+
+// Notes still named after the first words of their content, with the title their first heading gives
+// them, None when they have no heading. Notes whose name already is their heading are left out.
+fn legacy_names(vault: &Vault) -> io::Result<Vec<(String, Option<String>)>> {
+    let mut names = Vec::new();
+    for path in Note::list_notes(vault)? {
+        let content = Note::read_note(vault, &path)?;
+        let name = path.rsplit('/').next().unwrap_or(&path);
+        if name != legacy_file_name(&content) {
+            continue;
+        }
+        let title = first_h1(&content).map(|heading| string_utils::title_filename(&heading)).unwrap_or_default();
+        if title != name {
+            names.push((path, (!title.is_empty()).then_some(title)));
+        }
+    }
+    Ok(names)
+}
+
+// The path a note gets when named `title` in its folder.
+fn renamed_path(path: &str, title: &str) -> String {
+    match path.rsplit_once('/') {
+        Some((folder, _)) => format!("{}/{}", folder, title),
+        None => title.to_string(),
+    }
+}
+
+// Sorts the notes still named after their content into the ones to rename, with their new path, the
+// ones whose new name is taken, by another note or by another note being renamed, and the ones without
+// a heading.
+fn migration_targets(vault: &Vault) -> io::Result<(Vec<(String, String)>, NameMigration)> {
+    let mut targets: Vec<(String, String)> = Vec::new();
+    let mut report = NameMigration::default();
+    for (path, title) in legacy_names(vault)? {
+        let Some(title) = title else {
+            report.unnamed.push(path);
+            continue;
+        };
+        let after = renamed_path(&path, &title);
+        let taken = Note::taken_by(vault, &after)?.is_some_and(|existing| existing != path)
+            || targets.iter().any(|(_, other)| filename_policy::same_name(other, &after));
+        if taken {
+            report.skipped.push(path);
+        } else {
+            targets.push((path, after));
+        }
+    }
+    Ok((targets, report))
+}
+
+// Plans naming the notes of an older vault after their first heading. Notes whose new name is taken are
+// left out, as the migration skips them.
+pub fn plan_name_migration(vault: &Vault) -> io::Result<Plan> {
+    let mut plan = Plan::default();
+    for (path, after) in migration_targets(vault)?.0 {
+        let size = std::fs::metadata(file_operations::resolve_path(&format!("{}/{}.md", vault.path, path)))?.len();
+        plan.move_file(&format!("{}.md", path), &format!("{}.md", after), size);
+    }
+    Ok(plan)
+}
+
+// Names the notes that were named after the first words of their content, as notes used to be, after
+// their first heading, updating the links to them. Notes without a heading keep their name.
+pub fn migrate_names(vault: &Vault) -> io::Result<NameMigration> {
+    let (targets, mut migration) = migration_targets(vault)?;
+    for (path, after) in targets {
+        let title = after.rsplit('/').next().unwrap_or(&after).to_string();
+        match refactor::rename_note(vault, &path, &title) {
+            Ok(report) => migration.renamed.push(report),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => migration.skipped.push(path),
+            Err(e) => return Err(e),
+        }
+    }
+    migration.skipped.sort();
+    Ok(migration)
+}
+
+// Rolls a migration back by renaming its notes to their old names in reverse order, which points the
+// links back at them too. Returns the renames made, for the metadata of the notes to follow them.
+pub fn rollback_names(vault: &Vault, renamed: &[MoveReport]) -> io::Result<Vec<MoveReport>> {
+    let mut reverted = Vec::new();
+    for report in renamed.iter().rev() {
+        let title = report.from.rsplit('/').next().unwrap_or(&report.from);
+        reverted.push(refactor::rename_note(vault, &report.to, title)?);
+    }
+    Ok(reverted)
+}
+
+// Saves a note and, depending on the title sync mode, renames it (with link refactoring) or suggests a rename
+// when its first heading changed.
+pub fn save_note(vault: &Vault, path: &str, content: &str, mode: TitleSync) -> io::Result<SaveOutcome> {
+    let path = refactor::clean_path(path);
+    let file = format!("{}/{}.md", vault.path, path);
+    if path.is_empty() || !Path::new(&file_operations::resolve_path(&file)).exists() {
+        return Err(Message::new("note.not_found", "Note file does not exist").error(ErrorKind::NotFound));
+    }
+    let before = file_operations::read_from_file(&file)?;
+    file_operations::write_to_file(&file, content)?;
+
+    let mut outcome = SaveOutcome { path: path.clone(), ..Default::default() };
+    let Some(title) = renamed_title(&path, Some(&before), content).filter(|_| mode != TitleSync::Off) else {
+        return Ok(outcome);
+    };
+    if mode == TitleSync::Auto {
+        match refactor::rename_note(vault, &path, &title) {
+            Ok(report) => {
+                outcome.path = report.to.clone();
+                outcome.renamed = Some(report);
+                return Ok(outcome);
+            }
+            // Another note already has this name; let the user pick one
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+    outcome.suggested_title = Some(title);
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_renamed_title() {
+        assert_eq!(renamed_title("Draft", Some("# Draft"), "# Launch plan\n"), Some("Launch plan".to_string()));
+        assert_eq!(renamed_title("Draft", Some("# Launch plan"), "# Launch plan\nmore"), None);
+        assert_eq!(renamed_title("Draft", None, "## Only a subheading"), None);
+        assert_eq!(renamed_title("Draft", Some("text"), "# Draft"), None);
+    }
+
+    #[test]
+    fn test_save_note() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Draft.md", vault.path), "# Draft").unwrap();
+        file_operations::write_to_file(&format!("{}/Index.md", vault.path), "[[Draft]]").unwrap();
+
+        let outcome = save_note(&vault, "Draft", "# Road-map\nText", TitleSync::Suggest).unwrap();
+        assert_eq!(outcome.path, "Draft");
+        assert_eq!(outcome.suggested_title.as_deref(), Some("Road-map"));
+
+        let outcome = save_note(&vault, "Draft", "# Roadmap\nText", TitleSync::Auto).unwrap();
+        assert_eq!(outcome.path, "Roadmap");
+        assert_eq!(outcome.renamed.unwrap().updated, vec!["Index"]);
+        let index = file_operations::read_from_file(&format!("{}/Index.md", vault.path)).unwrap();
+        assert_eq!(index, "[[Roadmap]]");
+
+        let outcome = save_note(&vault, "Roadmap", "# Index\nText", TitleSync::Auto).unwrap();
+        assert_eq!(outcome.path, "Roadmap");
+        assert_eq!(outcome.suggested_title.as_deref(), Some("Index"));
+        assert_eq!(save_note(&vault, "Roadmap", "# Other", TitleSync::Off).unwrap(), SaveOutcome {
+            path: "Roadmap".to_string(),
+            ..Default::default()
+        });
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_migrate_names() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Projects", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/-Launch-plan.md", vault.path), "# Launch plan\nSoon").unwrap();
+        file_operations::write_to_file(&format!("{}/Projects/-Roadmap-draft.md", vault.path), "# Roadmap draft").unwrap();
+        file_operations::write_to_file(&format!("{}/Buy-milk-today.md", vault.path), "Buy milk today").unwrap();
+        file_operations::write_to_file(&format!("{}/-Index-page.md", vault.path), "# Index page").unwrap();
+        let index = "[[-Launch-plan]] [[Projects/-Roadmap-draft]]";
+        file_operations::write_to_file(&format!("{}/Index page.md", vault.path), index).unwrap();
+
+        // The dry run leaves out the note whose name is taken, and changes nothing
+        let plan = plan_name_migration(&vault).unwrap();
+        let moves: Vec<(&str, Option<&str>)> = plan.changes.iter().map(|change| (change.path.as_str(), change.to.as_deref())).collect();
+        assert_eq!(moves, vec![("-Launch-plan.md", Some("Launch plan.md")), ("Projects/-Roadmap-draft.md", Some("Projects/Roadmap draft.md"))]);
+        assert!(Note::read_note(&vault, "-Launch-plan").is_ok());
+
+        let migration = migrate_names(&vault).unwrap();
+        let renamed: Vec<(&str, &str, &[String])> =
+            migration.renamed.iter().map(|report| (report.from.as_str(), report.to.as_str(), report.updated.as_slice())).collect();
+        assert_eq!(
+            renamed,
+            vec![
+                ("-Launch-plan", "Launch plan", &["Index page".to_string()][..]),
+                ("Projects/-Roadmap-draft", "Projects/Roadmap draft", &["Index page".to_string()][..]),
+            ]
+        );
+        assert_eq!(migration.skipped, vec!["-Index-page"]);
+        assert_eq!(migration.unnamed, vec!["Buy-milk-today"]);
+        assert_eq!(Note::read_note(&vault, "Index page").unwrap(), "[[Launch plan]] [[Projects/Roadmap draft]]");
+        // Notes without a heading keep their name, and nothing is left for a second run
+        assert!(Note::read_note(&vault, "Buy-milk-today").is_ok());
+        assert!(plan_name_migration(&vault).unwrap().changes.is_empty());
+
+        // Rolling back renames the notes and their links back
+        let reverted = rollback_names(&vault, &migration.renamed).unwrap();
+        assert_eq!(reverted.iter().map(|report| report.to.as_str()).collect::<Vec<_>>(), vec!["Projects/-Roadmap-draft", "-Launch-plan"]);
+        assert_eq!(Note::read_note(&vault, "Index page").unwrap(), index);
+        assert!(Note::read_note(&vault, "Launch plan").is_err());
+        assert_eq!(plan_name_migration(&vault).unwrap(), plan);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
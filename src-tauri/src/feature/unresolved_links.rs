@@ -0,0 +1,138 @@
+// Links to notes that do not exist yet. Writing `[[Some Idea]]` before the note exists is a way of
+// planning notes; the links are listed by the note they appear in, and a missing note can be created
+// straight from its link.
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+use std::io::{self, ErrorKind};
+
+use crate::feature::{backlinks, folder_settings, lookup, refactor};
+use crate::storage::note::{Note, OnConflict};
+use crate::storage::vault::Vault;
+use crate::utils::{error::AppError, file_operations, frontmatter, markdown, message::Message, string_utils};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnresolvedGroup {
+    // Path relative to the vault, without the `.md` extension
+    pub source: String,
+    pub title: String,
+    // Targets of the links, without alias or section, in order of first appearance
+    pub links: Vec<String>,
+}
+
+// What links can resolve to, lowercased: note paths, and the titles and aliases bare links match. Targets
+// are also compared the way `create_note_from_link` names their note, so `[[Some Idea]]` resolves to
+// the `SomeIdea` note created from it.
+struct Resolver {
+    paths: HashSet<String>,
+    names: HashSet<String>,
+}
+
+impl Resolver {
+    fn new(vault: &Vault, paths: &[String]) -> io::Result<Self> {
+        let mut names = HashSet::new();
+        for path in paths {
+            names.insert(path.rsplit('/').next().unwrap_or(path).to_lowercase());
+            let content = file_operations::read_from_file(&format!("{}/{}.md", vault.path, path))?;
+            let aliases = frontmatter::parse(&content).map(|fields| fields.aliases()).unwrap_or_default();
+            names.extend(aliases.iter().map(|alias| alias.to_lowercase()));
+        }
+        Ok(Self { paths: paths.iter().map(|path| path.to_lowercase()).collect(), names })
+    }
+
+    fn resolves(&self, target: &str) -> bool {
+        [target.to_lowercase(), created_path(target).to_lowercase()]
+            .iter()
+            .any(|target| self.paths.contains(target) || (!target.contains('/') && self.names.contains(target)))
+    }
+}
+
+// The path `create_note_from_link` gives the note of a link target: its folders cleaned up, and each
+// segment named like any new note.
+fn created_path(target: &str) -> String {
+    string_utils::sanitize_path(&refactor::clean_path(target))
+}
+
+// Lists the links to missing notes, grouped by the note they appear in and sorted by its path. Links to
+// attachments are not note links and are left out.
+pub fn unresolved_links(vault: &Vault) -> io::Result<Vec<UnresolvedGroup>> {
+    let paths = refactor::note_paths(vault)?;
+    let resolver = Resolver::new(vault, &paths)?;
+    let mut groups = Vec::new();
+    for source in paths {
+        let content = file_operations::read_from_file(&format!("{}/{}.md", vault.path, source))?;
+        let links: Vec<String> = backlinks::outgoing_links(&source, &content)
+            .into_iter()
+            .filter(|target| !resolver.resolves(target))
+            .collect();
+        if !links.is_empty() {
+            groups.push(UnresolvedGroup { title: source.rsplit('/').next().unwrap_or(&source).to_string(), source, links });
+        }
+    }
+    Ok(groups)
+}
+
+// Creates the note a link points at, with the template and tags of its folder. The link may be written
+// as `[[Folder/Title|alias]]` or just its target. Returns the path of the new note; links that already
+// resolve to a note are refused.
+pub fn create_note_from_link(vault: &Vault, link: &str) -> io::Result<String> {
+    let link = link.trim();
+    let link = link.strip_prefix("[[").and_then(|link| link.strip_suffix("]]")).unwrap_or(link);
+    let target = refactor::clean_path(markdown::link_target(link));
+    if target.is_empty() {
+        return Err(AppError::InvalidName(Message::new("note.title_empty", "Note title is empty")).into());
+    }
+    match lookup::resolve_title(vault, &target) {
+        Ok(existing) => {
+            return Err(Message::new("link.resolved", "[[{link}]] already links to {path}")
+                .with("link", &target)
+                .with("path", existing)
+                .error(ErrorKind::AlreadyExists));
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let (folder, title) = target.rsplit_once('/').unwrap_or(("", &target));
+    folder_settings::create_note(vault, folder, &Note::new(title, ""), OnConflict::Fail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_unresolved_links() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Projects", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Projects/Plan.md", vault.path), "---\naliases: [Roadmap]\n---\n").unwrap();
+        file_operations::write_to_file(
+            &format!("{}/Index.md", vault.path),
+            "[[Plan]] [[roadmap]] [[Projects/plan#Goals]] [[Someday|later]] ![[photo.png]] [[Someday]] [[Projects/Launch]] [[Big Idea]]",
+        )
+        .unwrap();
+        file_operations::write_to_file(&format!("{}/Log.md", vault.path), "See [[Index]] and [[Someday]]").unwrap();
+
+        let groups = unresolved_links(&vault).unwrap();
+        assert_eq!(
+            groups,
+            vec![
+                UnresolvedGroup { source: "Index".to_string(), title: "Index".to_string(), links: vec!["Someday".to_string(), "Projects/Launch".to_string(), "Big Idea".to_string()] },
+                UnresolvedGroup { source: "Log".to_string(), title: "Log".to_string(), links: vec!["Someday".to_string()] },
+            ]
+        );
+
+        assert_eq!(create_note_from_link(&vault, "[[Projects/Launch|the launch]]").unwrap(), "Projects/Launch");
+        assert_eq!(create_note_from_link(&vault, "Someday#Ideas").unwrap(), "Someday");
+        // Multi-word links resolve to the note created from them, so it is only created once
+        assert_eq!(create_note_from_link(&vault, "[[Big Idea]]").unwrap(), "Big Idea");
+        assert!(unresolved_links(&vault).unwrap().is_empty());
+        assert_eq!(create_note_from_link(&vault, "Big Idea").unwrap_err().kind(), ErrorKind::AlreadyExists);
+
+        assert_eq!(create_note_from_link(&vault, "Roadmap").unwrap_err().kind(), ErrorKind::AlreadyExists);
+        assert_eq!(AppError::from(create_note_from_link(&vault, "[[ ]]").unwrap_err()).code(), "invalid_name");
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
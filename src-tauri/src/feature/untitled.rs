@@ -0,0 +1,91 @@
+// Untitled notes: created under a temporary id-based file name so writing can start right away, and
+// named later with `finalize_title`, which renames the file and updates the links pointing at it.
+use nanoid::nanoid;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use crate::feature::{folder_settings, refactor::{self, MoveReport}};
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, message::Message};
+
+pub const UNTITLED_PREFIX: &str = "untitled_";
+// Ids stay alphanumeric so file name sanitizing never changes them
+pub const ID_ALPHABET: [char; 36] = [
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l',
+    'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
+];
+pub const ID_LENGTH: usize = 12;
+
+// Whether a note still has its temporary name.
+pub fn is_untitled(path: &str) -> bool {
+    path.rsplit('/').next().is_some_and(|name| name.starts_with(UNTITLED_PREFIX))
+}
+
+// Creates an untitled note in a folder (empty for the vault root) with the folder's template and tags
+// applied. Returns the vault-relative path of the note.
+pub fn create_untitled(vault: &Vault, folder: &str, content: &str) -> io::Result<String> {
+    let folder = refactor::clean_path(folder);
+    let name = format!("{}{}", UNTITLED_PREFIX, nanoid!(ID_LENGTH, &ID_ALPHABET));
+    let path = if folder.is_empty() { name } else { format!("{}/{}", folder, name) };
+
+    let settings = folder_settings::settings_for(vault, &folder)?;
+    let content = folder_settings::apply(vault, &settings, "", &folder, content)?;
+    file_operations::create_directory(&format!("{}/{}", vault.path, folder))?;
+    let file = format!("{}/{}.md", vault.path, path);
+    if Path::new(&file_operations::resolve_path(&file)).exists() {
+        return Err(Message::new("note.already_exists", "Note already exists").error(ErrorKind::AlreadyExists));
+    }
+    file_operations::write_to_file(&file, &content)?;
+    Ok(path)
+}
+
+// Gives an untitled note its name: the file is renamed within its folder and links to it are rewritten.
+pub fn finalize_title(vault: &Vault, path: &str, title: &str) -> io::Result<MoveReport> {
+    let path = refactor::clean_path(path);
+    if !is_untitled(&path) {
+        return Err(Message::new("untitled.already_titled", "Note already has a title; rename it instead").error(ErrorKind::InvalidInput));
+    }
+    if title.trim().to_lowercase().starts_with(UNTITLED_PREFIX) {
+        return Err(Message::new("untitled.title_missing", "Pick a title for the note").error(ErrorKind::InvalidInput));
+    }
+    refactor::rename_note(vault, &path, title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::folder_settings::FolderSettings;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_untitled_flow() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        folder_settings::set_folder_settings(&vault, "Ideas", FolderSettings { template: None, tags: vec!["idea".to_string()] }).unwrap();
+
+        let path = create_untitled(&vault, "Ideas", "Some thoughts").unwrap();
+        assert!(path.starts_with("Ideas/untitled_") && is_untitled(&path));
+        assert_eq!(path.len(), "Ideas/untitled_".len() + ID_LENGTH);
+        let content = file_operations::read_from_file(&format!("{}/{}.md", vault.path, path)).unwrap();
+        assert_eq!(content, "---\ntags:\n- idea\n---\nSome thoughts");
+
+        let name = path.rsplit('/').next().unwrap();
+        file_operations::write_to_file(&format!("{}/Index.md", vault.path), &format!("See [[{}]]", path)).unwrap();
+        assert!(finalize_title(&vault, &path, "").is_err());
+        assert!(finalize_title(&vault, &path, "untitled_2").is_err());
+
+        let report = finalize_title(&vault, &path, "Garden ideas").unwrap();
+        assert_eq!(report.to, "Ideas/Garden ideas");
+        assert_eq!(report.updated, vec!["Index"]);
+        let index = file_operations::read_from_file(&format!("{}/Index.md", vault.path)).unwrap();
+        assert_eq!(index, "See [[Ideas/Garden ideas]]");
+        assert!(!index.contains(name));
+
+        // Named notes are renamed through rename_note
+        assert_eq!(finalize_title(&vault, "Ideas/Garden ideas", "Other").unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert!(is_untitled(&create_untitled(&vault, "", "").unwrap()));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,148 @@
+// Presentation state of open notes (edit or preview mode, zoom, scroll position), so reopening a note
+// restores the view it was left in. The state is kept per note and, for notes open in several windows,
+// per window; a window without its own state falls back to the last state of the note.
+use serde::{Serialize, Deserialize};
+use std::io::{self, Error, ErrorKind};
+
+use crate::feature::metadata::MetadataStore;
+use crate::utils::message::Message;
+
+pub const VIEW_STATE_TREE: &str = "view_state";
+// Separates the window label from the note path in window keys; cannot appear in either
+const WINDOW_SEPARATOR: char = '\0';
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewMode {
+    #[default]
+    Edit,
+    Preview,
+    Split,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewState {
+    pub mode: ViewMode,
+    // Zoom factor of the editor and preview, 1.0 being the default size
+    pub zoom: f32,
+    // Scroll offset of the editor in pixels
+    pub scroll_top: f64,
+    // Caret position as a character offset into the note
+    pub cursor: Option<usize>,
+}
+
+impl Default for ViewState {
+    fn default() -> Self {
+        Self { mode: ViewMode::Edit, zoom: 1.0, scroll_top: 0.0, cursor: None }
+    }
+}
+
+fn window_key(window: &str, path: &str) -> String {
+    format!("{}{}{}", window, WINDOW_SEPARATOR, path)
+}
+
+fn read(tree: &sled::Tree, key: &str) -> io::Result<Option<ViewState>> {
+    match tree.get(key)? {
+        Some(bytes) => serde_json::from_slice(&bytes).map(Some).map_err(|e| Error::new(ErrorKind::InvalidData, e)),
+        None => Ok(None),
+    }
+}
+
+// Returns the saved view of a note in a window, or the last view of the note in any window.
+pub fn get_view_state(store: &MetadataStore, path: &str, window: Option<&str>) -> io::Result<Option<ViewState>> {
+    let tree = store.tree(VIEW_STATE_TREE)?;
+    if let Some(window) = window {
+        if let Some(state) = read(&tree, &window_key(window, path))? {
+            return Ok(Some(state));
+        }
+    }
+    read(&tree, path)
+}
+
+// Saves the view of a note, for the window when one is given and as the note's last view.
+pub fn set_view_state(store: &MetadataStore, path: &str, window: Option<&str>, state: &ViewState) -> io::Result<()> {
+    if !(0.25..=5.0).contains(&state.zoom) {
+        return Err(Message::new("view_state.zoom_out_of_range", "Zoom must be between {min} and {max}")
+            .with("min", 0.25)
+            .with("max", 5)
+            .error(ErrorKind::InvalidInput));
+    }
+    if !state.scroll_top.is_finite() || state.scroll_top < 0.0 {
+        return Err(Message::new("view_state.invalid_scroll", "Scroll position must be a positive number").error(ErrorKind::InvalidInput));
+    }
+    if window.is_some_and(|window| window.is_empty() || window.contains(WINDOW_SEPARATOR)) {
+        return Err(Message::new("view_state.invalid_window", "Invalid window label").error(ErrorKind::InvalidInput));
+    }
+
+    let tree = store.tree(VIEW_STATE_TREE)?;
+    let json = serde_json::to_vec(state).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    if let Some(window) = window {
+        tree.insert(window_key(window, path).as_str(), json.as_slice())?;
+    }
+    tree.insert(path, json)?;
+    tree.flush()?;
+    Ok(())
+}
+
+// Moves the saved views of a renamed note to its new path.
+pub fn rename(store: &MetadataStore, from: &str, to: &str) -> io::Result<()> {
+    if from == to {
+        return Ok(());
+    }
+    let tree = store.tree(VIEW_STATE_TREE)?;
+    let suffix = format!("{}{}", WINDOW_SEPARATOR, from);
+    let mut moved = Vec::new();
+    for entry in tree.iter() {
+        let (key, value) = entry?;
+        let key = String::from_utf8_lossy(&key).to_string();
+        if key == from {
+            moved.push((key, to.to_string(), value));
+        } else if let Some(window) = key.strip_suffix(&suffix) {
+            moved.push((key.clone(), window_key(window, to), value));
+        }
+    }
+    for (old, new, value) in moved {
+        tree.remove(old.as_str())?;
+        tree.insert(new.as_str(), value)?;
+    }
+    tree.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_view_state() {
+        file_operations::set_base_path(None);
+        let path = format!("test_meta_{}", nanoid!());
+        let store = MetadataStore::new(&path).unwrap();
+        assert_eq!(get_view_state(&store, "Plan", None).unwrap(), None);
+
+        let preview = ViewState { mode: ViewMode::Preview, zoom: 1.25, scroll_top: 480.0, cursor: Some(12) };
+        set_view_state(&store, "Plan", Some("main"), &preview).unwrap();
+        let split = ViewState { mode: ViewMode::Split, ..ViewState::default() };
+        set_view_state(&store, "Plan", Some("window-2"), &split).unwrap();
+
+        assert_eq!(get_view_state(&store, "Plan", Some("main")).unwrap(), Some(preview.clone()));
+        // A window that never showed the note gets the last view of it
+        assert_eq!(get_view_state(&store, "Plan", Some("window-3")).unwrap(), Some(split.clone()));
+        assert_eq!(get_view_state(&store, "Plan", None).unwrap(), Some(split.clone()));
+
+        assert!(set_view_state(&store, "Plan", None, &ViewState { zoom: 9.0, ..ViewState::default() }).is_err());
+        assert!(set_view_state(&store, "Plan", Some(""), &ViewState::default()).is_err());
+
+        rename(&store, "Plan", "Projects/Plan").unwrap();
+        assert_eq!(get_view_state(&store, "Plan", Some("main")).unwrap(), None);
+        assert_eq!(get_view_state(&store, "Projects/Plan", Some("main")).unwrap(), Some(preview));
+        assert_eq!(get_view_state(&store, "Projects/Plan", None).unwrap(), Some(split));
+
+        // Cleanup
+        drop(store);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
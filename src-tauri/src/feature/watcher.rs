@@ -0,0 +1,327 @@
+// Watches vault folders for notes changed outside the app (an external editor, a sync client), so open
+// views can refresh without a restart. Raw file system events are debounced, reduced to one change per
+// note and checked against the disk; writes made by the app itself are not reported back.
+use notify::event::{EventKind, ModifyKind, RenameMode};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, message::Message};
+
+// How long to wait for an editor to finish a burst of writes before reporting the change
+const DEBOUNCE: Duration = Duration::from_millis(500);
+// Events for a note the app wrote within this window are assumed to be its own. The window of a write made
+// during a held operation starts when the operation ends.
+const OWN_WRITE_WINDOW: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteChange {
+    pub kind: ChangeKind,
+    // Path relative to the vault, without the `.md` extension
+    pub path: String,
+}
+
+// Maps a file to its note path; anything but markdown files outside hidden folders is ignored.
+fn note_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    if relative.extension().is_none_or(|ext| ext != "md")
+        || relative.components().any(|part| part.as_os_str().to_string_lossy().starts_with('.'))
+    {
+        return None;
+    }
+    Some(relative.with_extension("").to_string_lossy().replace('\\', "/"))
+}
+
+// Turns a raw file system event into the note changes it describes.
+pub fn classify(root: &Path, event: &Event) -> Vec<NoteChange> {
+    let kinds: Vec<ChangeKind> = match event.kind {
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => vec![ChangeKind::Created],
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => vec![ChangeKind::Deleted],
+        // A rename within the vault reports the old path first, then the new one
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => vec![ChangeKind::Deleted, ChangeKind::Created],
+        // The backend could not tell which side of a rename this is, the disk will
+        EventKind::Modify(ModifyKind::Name(_)) => vec![ChangeKind::Modified],
+        EventKind::Modify(ModifyKind::Metadata(_)) | EventKind::Access(_) => Vec::new(),
+        EventKind::Modify(_) | EventKind::Any | EventKind::Other => vec![ChangeKind::Modified],
+    };
+    event
+        .paths
+        .iter()
+        .enumerate()
+        .filter_map(|(index, path)| {
+            let kind = *kinds.get(index).or(kinds.last())?;
+            Some(NoteChange { kind, path: note_path(root, path)? })
+        })
+        .collect()
+}
+
+// Changes collected during one debounce window, keeping the first kind seen for each note.
+#[derive(Default)]
+struct Pending {
+    first: HashMap<String, ChangeKind>,
+    order: Vec<String>,
+}
+
+impl Pending {
+    fn add(&mut self, changes: Vec<NoteChange>) {
+        for change in changes {
+            if !self.first.contains_key(&change.path) {
+                self.order.push(change.path.clone());
+                self.first.insert(change.path, change.kind);
+            }
+        }
+    }
+
+    // Settles each note against the disk: a note created and removed again within the window (an
+    // editor's temporary file) is dropped, and one deleted and written back (an atomic save) was modified.
+    fn flush(self, root: &Path) -> Vec<NoteChange> {
+        let mut changes = Vec::new();
+        for path in self.order {
+            let exists = root.join(format!("{}.md", path)).is_file();
+            let kind = match (self.first[&path], exists) {
+                (ChangeKind::Created, false) => continue,
+                (_, false) => ChangeKind::Deleted,
+                (ChangeKind::Created, true) => ChangeKind::Created,
+                (_, true) => ChangeKind::Modified,
+            };
+            changes.push(NoteChange { kind, path });
+        }
+        changes
+    }
+}
+
+// Notes recently written by the app, keyed by vault path and note path, and the number of operations
+// holding each vault's changes back.
+#[derive(Default)]
+struct OwnWrites {
+    writes: Mutex<HashMap<(String, String), Instant>>,
+    holds: Mutex<HashMap<String, usize>>,
+}
+
+impl OwnWrites {
+    fn record(&self, vault: &Vault, path: &str) {
+        let mut writes = self.writes.lock().unwrap();
+        writes.retain(|_, written| written.elapsed() < OWN_WRITE_WINDOW);
+        writes.insert((vault.path.clone(), path.to_string()), Instant::now());
+    }
+
+    fn is_own(&self, vault: &Vault, path: &str) -> bool {
+        let writes = self.writes.lock().unwrap();
+        writes
+            .get(&(vault.path.clone(), path.to_string()))
+            .is_some_and(|written| written.elapsed() < OWN_WRITE_WINDOW)
+    }
+
+    fn is_held(&self, vault: &Vault) -> bool {
+        self.holds.lock().unwrap().contains_key(&vault.path)
+    }
+
+    fn hold(&self, vault: &Vault) {
+        *self.holds.lock().unwrap().entry(vault.path.clone()).or_default() += 1;
+    }
+
+    // Ends a hold; the writes recorded for the vault count from now, so the changes held back are
+    // matched against a fresh window.
+    fn release(&self, vault: &str) {
+        let mut holds = self.holds.lock().unwrap();
+        if let Some(count) = holds.get_mut(vault) {
+            *count -= 1;
+            if *count == 0 {
+                holds.remove(vault);
+            }
+        }
+        let now = Instant::now();
+        for ((written_vault, _), written) in self.writes.lock().unwrap().iter_mut() {
+            if written_vault == vault {
+                *written = now;
+            }
+        }
+    }
+}
+
+// Held while the app rewrites many notes of a vault; changes seen meanwhile are reported after it is
+// dropped, once the notes the operation wrote have been recorded.
+pub struct WriteHold<'a> {
+    own_writes: &'a OwnWrites,
+    vault: String,
+}
+
+impl Drop for WriteHold<'_> {
+    fn drop(&mut self) {
+        self.own_writes.release(&self.vault);
+    }
+}
+
+#[derive(Default)]
+pub struct VaultWatchers {
+    watchers: Mutex<HashMap<String, RecommendedWatcher>>,
+    own_writes: Arc<OwnWrites>,
+}
+
+impl VaultWatchers {
+    // Starts watching a vault; `on_change` runs on a background thread for every external change.
+    // Watching an already watched vault does nothing.
+    pub fn watch<F>(&self, vault: &Vault, on_change: F) -> io::Result<()>
+    where
+        F: Fn(NoteChange) + Send + 'static,
+    {
+        let mut watchers = self.watchers.lock().unwrap();
+        if watchers.contains_key(&vault.path) {
+            return Ok(());
+        }
+
+        // Some backends report canonical paths, so strip against the canonical root
+        let root = PathBuf::from(file_operations::resolve_path(&vault.path));
+        let root = root.canonicalize().unwrap_or(root);
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+            Ok(event) => {
+                let _ = sender.send(event);
+            }
+            Err(e) => println!("❌ File watcher error: {}", e),
+        })
+        .map_err(|e| Message::new("watcher.start_failed", "Failed to start the file watcher: {detail}")
+            .with("detail", e)
+            .error(ErrorKind::Other))?;
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| {
+                Message::new("watcher.watch_failed", "Failed to watch {path}: {detail}")
+                    .with("path", &vault.path)
+                    .with("detail", e)
+                    .error(ErrorKind::Other)
+            })?;
+        watchers.insert(vault.path.clone(), watcher);
+
+        let vault = vault.clone();
+        let own_writes = Arc::clone(&self.own_writes);
+        thread::spawn(move || debounce(&root, &vault, receiver, &own_writes, on_change));
+        Ok(())
+    }
+
+    // Stops watching a vault; returns whether it was watched.
+    pub fn unwatch(&self, vault: &Vault) -> bool {
+        // Dropping the watcher closes the channel, which ends the debounce thread
+        self.watchers.lock().unwrap().remove(&vault.path).is_some()
+    }
+
+    // Marks a note as written by the app, so the watcher does not report it as an external change.
+    pub fn record_write(&self, vault: &Vault, path: &str) {
+        self.own_writes.record(vault, path);
+    }
+
+    // Holds the changes of a vault back for the length of a long operation. Take it before writing
+    // anything, and record the written notes before dropping it.
+    pub fn hold(&self, vault: &Vault) -> WriteHold<'_> {
+        self.own_writes.hold(vault);
+        WriteHold { own_writes: &self.own_writes, vault: vault.path.clone() }
+    }
+}
+
+// Collects events until the vault has been quiet for a moment and no operation holds it, then reports the
+// settled changes.
+fn debounce<F: Fn(NoteChange)>(
+    root: &Path,
+    vault: &Vault,
+    receiver: Receiver<Event>,
+    own_writes: &OwnWrites,
+    on_change: F,
+) {
+    while let Ok(event) = receiver.recv() {
+        let mut pending = Pending::default();
+        pending.add(classify(root, &event));
+        let mut deadline = Instant::now() + DEBOUNCE;
+        loop {
+            match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(event) => pending.add(classify(root, &event)),
+                Err(RecvTimeoutError::Timeout) if own_writes.is_held(vault) => deadline = Instant::now() + DEBOUNCE,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+        for change in pending.flush(root) {
+            if !own_writes.is_own(vault, &change.path) {
+                on_change(change);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+    use notify::event::{CreateKind, DataChange, MetadataKind, RemoveKind};
+
+    fn event(kind: EventKind, paths: &[&str]) -> Event {
+        paths.iter().fold(Event::new(kind), |event, path| event.add_path(PathBuf::from("/vault").join(path)))
+    }
+
+    #[test]
+    fn test_classify_events() {
+        file_operations::set_base_path(None);
+        let root = Path::new("/vault");
+        let created = event(EventKind::Create(CreateKind::File), &["Projects/Plan.md", "photo.png", ".config/x.md"]);
+        assert_eq!(classify(root, &created), vec![NoteChange { kind: ChangeKind::Created, path: "Projects/Plan".to_string() }]);
+
+        let renamed = event(EventKind::Modify(ModifyKind::Name(RenameMode::Both)), &["Old.md", "New.md"]);
+        let changes: Vec<(ChangeKind, String)> = classify(root, &renamed).into_iter().map(|c| (c.kind, c.path)).collect();
+        assert_eq!(changes, vec![(ChangeKind::Deleted, "Old".to_string()), (ChangeKind::Created, "New".to_string())]);
+
+        assert_eq!(classify(root, &event(EventKind::Modify(ModifyKind::Data(DataChange::Content)), &["Index.md"]))[0].kind, ChangeKind::Modified);
+        assert!(classify(root, &event(EventKind::Modify(ModifyKind::Metadata(MetadataKind::Any)), &["Index.md"])).is_empty());
+        assert_eq!(classify(root, &event(EventKind::Remove(RemoveKind::File), &["Index.md"]))[0].kind, ChangeKind::Deleted);
+    }
+
+    #[test]
+    fn test_pending_settles_against_disk() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let root = PathBuf::from(file_operations::resolve_path(&vault.path));
+        file_operations::write_to_file(&format!("{}/Saved.md", vault.path), "new content").unwrap();
+        file_operations::write_to_file(&format!("{}/Added.md", vault.path), "").unwrap();
+
+        let change = |kind, path: &str| NoteChange { kind, path: path.to_string() };
+        let mut pending = Pending::default();
+        pending.add(vec![change(ChangeKind::Created, "Draft"), change(ChangeKind::Deleted, "Saved")]);
+        pending.add(vec![change(ChangeKind::Deleted, "Draft"), change(ChangeKind::Created, "Saved")]);
+        pending.add(vec![change(ChangeKind::Created, "Added"), change(ChangeKind::Modified, "Added")]);
+        pending.add(vec![change(ChangeKind::Modified, "Gone")]);
+        assert_eq!(
+            pending.flush(&root),
+            vec![change(ChangeKind::Modified, "Saved"), change(ChangeKind::Created, "Added"), change(ChangeKind::Deleted, "Gone")]
+        );
+
+        let own_writes = OwnWrites::default();
+        own_writes.record(&vault, "Saved");
+        assert!(own_writes.is_own(&vault, "Saved"));
+        assert!(!own_writes.is_own(&vault, "Added"));
+
+        // Writes made during a hold count from its end
+        own_writes.hold(&vault);
+        own_writes.record(&vault, "Bulk");
+        own_writes.writes.lock().unwrap().insert((vault.path.clone(), "Bulk".to_string()), Instant::now() - OWN_WRITE_WINDOW);
+        assert!(own_writes.is_held(&vault));
+        own_writes.release(&vault.path);
+        assert!(!own_writes.is_held(&vault));
+        assert!(own_writes.is_own(&vault, "Bulk"));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
@@ -0,0 +1,188 @@
+// Weekly review notes: a checklist of the tasks completed during a date range and of those still open,
+// gathered from the whole vault, each linking back to the note it comes from.
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+
+use crate::feature::{folder_settings, templates, timeline::{self, DateRange}};
+use crate::storage::{note::{Note, OnConflict}, vault::Vault};
+
+pub const REVIEWS_FOLDER: &str = "Reviews";
+pub const REVIEW_TEMPLATE: &str = "weekly-review.md";
+const DEFAULT_REVIEW_TEMPLATE: &str = "---\ntype: review\nfrom: {{from}}\nto: {{to}}\n---\n# {{title}}\n\n## Completed ({{completed_count}})\n{{completed}}\n\n## Outstanding ({{outstanding_count}})\n{{outstanding}}\n";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewTask {
+    // Note the task is written in
+    pub note: String,
+    pub text: String,
+    // Completion date of completed tasks, due date of open ones
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyReview {
+    pub path: String,
+    pub from: String,
+    pub to: String,
+    pub completed: Vec<ReviewTask>,
+    pub outstanding: Vec<ReviewTask>,
+}
+
+struct Task {
+    done: bool,
+    text: String,
+    date: Option<NaiveDate>,
+}
+
+// Reads the tasks of a note with their completion (`✅ 2024-05-03`, `done: 2024-05-03`) or due date
+// (`📅 2024-05-03`, `due: 2024-05-03`).
+fn tasks(content: &str) -> Vec<Task> {
+    let task_re = Regex::new(r"^\s*[-*+]\s+\[([ xX])\]\s+(.*)$").unwrap();
+    let done_re = Regex::new(r"(?:✅|\bdone::?)\s*(\d{4}-\d{2}-\d{2})").unwrap();
+    let due_re = Regex::new(r"(?:📅|\bdue::?)\s*(\d{4}-\d{2}-\d{2})").unwrap();
+    content
+        .lines()
+        .filter_map(|line| task_re.captures(line))
+        .map(|caps| {
+            let done = &caps[1] != " ";
+            let date_re = if done { &done_re } else { &due_re };
+            let date = date_re
+                .captures(&caps[2])
+                .and_then(|date| NaiveDate::parse_from_str(&date[1], "%Y-%m-%d").ok());
+            Task { done, text: caps[2].trim().to_string(), date }
+        })
+        .collect()
+}
+
+// The range of a review: missing ends default to the current week, or to the week from `from`.
+fn review_range(range: &DateRange) -> io::Result<(NaiveDate, NaiveDate)> {
+    let (from, to) = range.bounds()?;
+    let today = Local::now().date_naive();
+    let from = from.unwrap_or_else(|| {
+        let end = to.unwrap_or(today);
+        end - Duration::days(end.weekday().num_days_from_monday() as i64)
+    });
+    Ok((from, to.unwrap_or(from + Duration::days(6))))
+}
+
+fn checklist(tasks: &[ReviewTask], done: bool) -> String {
+    if tasks.is_empty() {
+        return "None".to_string();
+    }
+    let mark = if done { "x" } else { " " };
+    tasks
+        .iter()
+        .map(|task| format!("- [{}] {} ([[{}]])", mark, task.text, task.note))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Collects the tasks of a range without writing anything. Completed tasks count when they were completed
+// in the range, or, without a completion date, when their note was updated in it. Open tasks count when
+// they are due by the end of the range, or, without a due date, when their note was updated in it.
+// Earlier reviews are left out, so their checklists are not counted twice.
+pub fn collect_tasks(vault: &Vault, from: NaiveDate, to: NaiveDate) -> io::Result<(Vec<ReviewTask>, Vec<ReviewTask>)> {
+    let in_range = |date: NaiveDate| date >= from && date <= to;
+    let mut completed = Vec::new();
+    let mut outstanding = Vec::new();
+    for path in Note::list_notes(vault)? {
+        if path.starts_with(&format!("{}/", REVIEWS_FOLDER)) {
+            continue;
+        }
+        let content = Note::read_note(vault, &path)?;
+        let tasks = tasks(&content);
+        if tasks.is_empty() {
+            continue;
+        }
+        let updated_in_range = timeline::note_date(vault, &path, &content, "updated")?.is_some_and(in_range);
+        for task in tasks {
+            let counts = match (task.done, task.date) {
+                (true, Some(date)) => in_range(date),
+                (false, Some(date)) => date <= to,
+                (_, None) => updated_in_range,
+            };
+            if counts {
+                let entry = ReviewTask { note: path.clone(), text: task.text, date: task.date.map(|date| date.to_string()) };
+                if task.done { completed.push(entry) } else { outstanding.push(entry) }
+            }
+        }
+    }
+    Ok((completed, outstanding))
+}
+
+// Creates the review note of a date range in the reviews folder. The note starts from
+// `.templates/weekly-review.md` when the vault has one, which can use `{{from}}`, `{{to}}`,
+// `{{completed}}`, `{{outstanding}}` (checklists) and `{{completed_count}}`, `{{outstanding_count}}`.
+pub fn create_weekly_review(vault: &Vault, range: &DateRange, on_conflict: OnConflict) -> io::Result<WeeklyReview> {
+    let (from, to) = review_range(range)?;
+    let (completed, outstanding) = collect_tasks(vault, from, to)?;
+
+    let template = match templates::read_template(vault, REVIEW_TEMPLATE) {
+        Ok(template) => template,
+        Err(e) if e.kind() == ErrorKind::NotFound => DEFAULT_REVIEW_TEMPLATE.to_string(),
+        Err(e) => return Err(e),
+    };
+    let values = HashMap::from([
+        ("from".to_string(), from.to_string()),
+        ("to".to_string(), to.to_string()),
+        ("completed".to_string(), checklist(&completed, true)),
+        ("outstanding".to_string(), checklist(&outstanding, false)),
+        ("completed_count".to_string(), completed.len().to_string()),
+        ("outstanding_count".to_string(), outstanding.len().to_string()),
+    ]);
+    let title = format!("Weekly review {}", from);
+    let content = templates::render_template(&template, &title, &values)?;
+    let path = folder_settings::create_note(vault, REVIEWS_FOLDER, &Note::new(&title, &content), on_conflict)?;
+    Ok(WeeklyReview { path, from: from.to_string(), to: to.to_string(), completed, outstanding })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_operations;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_create_weekly_review() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        vault.create_folder("Projects").unwrap();
+        let launch = "---\nupdated: 2024-05-08\n---\n- [x] Write the post\n- [ ] Book the room\n- [x] Old task ✅ 2024-04-01";
+        file_operations::write_to_file(&format!("{}/Projects/Launch.md", vault.path), launch).unwrap();
+        let chores = "---\nupdated: 2023-01-01\n---\n* [ ] Pay rent 📅 2024-05-03\n- [ ] Someday\n- [X] Fix the sink done:: 2024-05-07";
+        file_operations::write_to_file(&format!("{}/Chores.md", vault.path), chores).unwrap();
+
+        let range = DateRange { from: Some("2024-05-06".to_string()), to: None };
+        let review = create_weekly_review(&vault, &range, OnConflict::Fail).unwrap();
+        assert_eq!((review.path.as_str(), review.to.as_str()), ("Reviews/Weekly review 2024-05-06", "2024-05-12"));
+        let texts = |tasks: &[ReviewTask]| tasks.iter().map(|task| task.text.clone()).collect::<Vec<_>>();
+        assert_eq!(texts(&review.completed), vec!["Fix the sink done:: 2024-05-07", "Write the post"]);
+        assert_eq!(texts(&review.outstanding), vec!["Pay rent 📅 2024-05-03", "Book the room"]);
+        assert_eq!(review.outstanding[0].date.as_deref(), Some("2024-05-03"));
+
+        let content = Note::read_note(&vault, &review.path).unwrap();
+        assert!(content.starts_with("---\ntype: review\nfrom: 2024-05-06\nto: 2024-05-12\n---\n# Weekly review 2024-05-06\n"));
+        assert!(content.contains("## Completed (2)\n- [x] Fix the sink done:: 2024-05-07 ([[Chores]])\n- [x] Write the post ([[Projects/Launch]])\n"));
+        assert!(content.contains("## Outstanding (2)\n- [ ] Pay rent 📅 2024-05-03 ([[Chores]])\n"));
+
+        // The review's own checklists are not tasks of the next one
+        let (completed, _) = collect_tasks(&vault, NaiveDate::from_ymd_opt(2024, 5, 6).unwrap(), Local::now().date_naive()).unwrap();
+        assert_eq!(completed.len(), 2);
+        assert_eq!(create_weekly_review(&vault, &range, OnConflict::Fail).unwrap_err().kind(), ErrorKind::AlreadyExists);
+        let reversed = DateRange { from: Some("2024-05-06".to_string()), to: Some("2024-05-01".to_string()) };
+        assert_eq!(create_weekly_review(&vault, &reversed, OnConflict::Fail).unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        // A vault template replaces the default layout
+        file_operations::create_directory(&format!("{}/.templates", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/.templates/{}", vault.path, REVIEW_TEMPLATE), "{{completed_count}} done\n{{outstanding}}").unwrap();
+        let range = DateRange { from: Some("2024-04-01".to_string()), to: Some("2024-04-01".to_string()) };
+        let review = create_weekly_review(&vault, &range, OnConflict::Fail).unwrap();
+        assert_eq!(Note::read_note(&vault, &review.path).unwrap(), "1 done\nNone");
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
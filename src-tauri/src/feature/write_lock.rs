@@ -0,0 +1,129 @@
+// Short-lived vault write locks, so export, sync and backup work on a consistent snapshot of the notes.
+// Saves share the lock of their vault; a snapshot waits for running saves to finish and holds new ones
+// back until it is released, so they queue instead of interleaving with the copy.
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex};
+
+use crate::storage::vault::Vault;
+
+#[derive(Default)]
+struct LockState {
+    writers: usize,
+    snapshot: bool,
+}
+
+#[derive(Default)]
+pub struct WriteLocks {
+    vaults: Mutex<HashMap<String, LockState>>,
+    changed: Condvar,
+}
+
+// Held while a note of the vault is being written.
+pub struct WriteGuard<'a> {
+    locks: &'a WriteLocks,
+    vault: String,
+}
+
+// Held while the vault is being exported, synced or backed up.
+pub struct SnapshotGuard<'a> {
+    locks: &'a WriteLocks,
+    vault: String,
+}
+
+impl WriteLocks {
+    // Waits for any snapshot of the vault to be released, then registers a write.
+    pub fn write(&self, vault: &Vault) -> WriteGuard<'_> {
+        let mut vaults = self.vaults.lock().unwrap();
+        while vaults.get(&vault.path).is_some_and(|state| state.snapshot) {
+            vaults = self.changed.wait(vaults).unwrap();
+        }
+        vaults.entry(vault.path.clone()).or_default().writers += 1;
+        WriteGuard { locks: self, vault: vault.path.clone() }
+    }
+
+    // Blocks new writes to the vault and waits for the running ones to finish.
+    pub fn snapshot(&self, vault: &Vault) -> SnapshotGuard<'_> {
+        let mut vaults = self.vaults.lock().unwrap();
+        while vaults.get(&vault.path).is_some_and(|state| state.snapshot) {
+            vaults = self.changed.wait(vaults).unwrap();
+        }
+        vaults.entry(vault.path.clone()).or_default().snapshot = true;
+        while vaults.get(&vault.path).is_some_and(|state| state.writers > 0) {
+            vaults = self.changed.wait(vaults).unwrap();
+        }
+        SnapshotGuard { locks: self, vault: vault.path.clone() }
+    }
+
+    fn release(&self, vault: &str, snapshot: bool) {
+        let mut vaults = self.vaults.lock().unwrap();
+        if let Some(state) = vaults.get_mut(vault) {
+            if snapshot {
+                state.snapshot = false;
+            } else {
+                state.writers = state.writers.saturating_sub(1);
+            }
+            if state.writers == 0 && !state.snapshot {
+                vaults.remove(vault);
+            }
+        }
+        self.changed.notify_all();
+    }
+}
+
+impl Drop for WriteGuard<'_> {
+    fn drop(&mut self) {
+        self.locks.release(&self.vault, false);
+    }
+}
+
+impl Drop for SnapshotGuard<'_> {
+    fn drop(&mut self) {
+        self.locks.release(&self.vault, true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn test_writes_queue_behind_snapshot() {
+        let locks = WriteLocks::default();
+        let vault = Vault { name: "Notes".to_string(), path: "Vaults/Notes".to_string() };
+        let other = Vault { name: "Other".to_string(), path: "Vaults/Other".to_string() };
+        let written = AtomicBool::new(false);
+
+        std::thread::scope(|scope| {
+            let snapshot = locks.snapshot(&vault);
+            let writer = scope.spawn(|| {
+                let _write = locks.write(&vault);
+                written.store(true, Ordering::SeqCst);
+            });
+            // Other vaults are not affected
+            drop(locks.write(&other));
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!written.load(Ordering::SeqCst));
+
+            drop(snapshot);
+            writer.join().unwrap();
+            assert!(written.load(Ordering::SeqCst));
+        });
+
+        // A snapshot waits for the running write
+        let done = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            let write = locks.write(&vault);
+            let snapshot = scope.spawn(|| {
+                let _snapshot = locks.snapshot(&vault);
+                done.store(true, Ordering::SeqCst);
+            });
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!done.load(Ordering::SeqCst));
+            drop(write);
+            snapshot.join().unwrap();
+        });
+        assert!(locks.vaults.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,196 @@
+// Daily word goal and writing streaks, derived from the word counts recorded on every save
+use chrono::{Local, NaiveDate};
+use serde::{Serialize, Deserialize};
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+
+use crate::feature::metadata::MetadataStore;
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, markdown};
+
+const CONFIG_DIR: &str = ".config";
+// Last known word count of each note
+pub const WORD_COUNTS_TREE: &str = "word_counts";
+// Words written per day (YYYY-MM-DD)
+const DAILY_WORDS_TREE: &str = "daily_words";
+pub const HISTORY_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WritingGoal {
+    pub daily_words: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyWords {
+    pub date: String,
+    pub words: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WritingProgress {
+    pub today: u64,
+    pub goal: Option<u64>,
+    pub goal_met: bool,
+    // Consecutive days meeting the goal (or writing at all without a goal), up to today
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    // The last `HISTORY_DAYS` days, oldest first
+    pub history: Vec<DailyWords>,
+}
+
+fn goal_path(vault: &Vault) -> String {
+    format!("{}/{}/writing.json", vault.path, CONFIG_DIR)
+}
+
+pub fn load_goal(vault: &Vault) -> io::Result<WritingGoal> {
+    match file_operations::read_from_file(&goal_path(vault)) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| Error::new(ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(WritingGoal::default()),
+        Err(e) => Err(e),
+    }
+}
+
+// Sets the daily word goal of a vault; `None` or zero removes it.
+pub fn set_goal(vault: &Vault, daily_words: Option<u64>) -> io::Result<WritingGoal> {
+    let goal = WritingGoal { daily_words: daily_words.filter(|words| *words > 0) };
+    file_operations::create_directory(&format!("{}/{}", vault.path, CONFIG_DIR))?;
+    let json = serde_json::to_string_pretty(&goal).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    file_operations::write_to_file(&goal_path(vault), &json)?;
+    Ok(goal)
+}
+
+fn read_u64(tree: &sled::Tree, key: &str) -> io::Result<Option<u64>> {
+    Ok(tree.get(key)?.and_then(|bytes| bytes.as_ref().try_into().ok()).map(u64::from_be_bytes))
+}
+
+// Records the words added to a note by a save. The first save of a note seen before this tracking
+// existed only sets its baseline, unless the note was just created.
+pub fn record_save(vault: &Vault, store: &MetadataStore, path: &str, created: bool, day: NaiveDate) -> io::Result<u64> {
+    let file = format!("{}/{}.md", vault.path, path);
+    if !Path::new(&file_operations::resolve_path(&file)).exists() {
+        return Ok(0);
+    }
+    let words = markdown::word_count(&file_operations::read_from_file(&file)?) as u64;
+
+    let counts = store.tree(WORD_COUNTS_TREE)?;
+    let added = match read_u64(&counts, path)? {
+        Some(previous) => words.saturating_sub(previous),
+        None if created => words,
+        None => 0,
+    };
+    counts.insert(path, &words.to_be_bytes())?;
+    counts.flush()?;
+
+    if added > 0 {
+        let daily = store.tree(DAILY_WORDS_TREE)?;
+        let key = day.format("%Y-%m-%d").to_string();
+        let total = read_u64(&daily, &key)?.unwrap_or(0) + added;
+        daily.insert(key.as_str(), &total.to_be_bytes())?;
+        daily.flush()?;
+    }
+    Ok(added)
+}
+
+// Computes today's progress towards the goal and the writing streaks.
+pub fn progress(vault: &Vault, store: &MetadataStore, today: NaiveDate) -> io::Result<WritingProgress> {
+    let goal = load_goal(vault)?.daily_words;
+    let daily = store.tree(DAILY_WORDS_TREE)?;
+    let mut days = Vec::new();
+    for entry in daily.iter() {
+        let (key, bytes) = entry?;
+        let date = NaiveDate::parse_from_str(&String::from_utf8_lossy(&key), "%Y-%m-%d");
+        if let (Ok(date), Ok(bytes)) = (date, <[u8; 8]>::try_from(bytes.as_ref())) {
+            days.push((date, u64::from_be_bytes(bytes)));
+        }
+    }
+    // Keys are ISO dates, so the tree iterates in chronological order
+    let words_on = |date: NaiveDate| days.iter().find(|(day, _)| *day == date).map(|(_, words)| *words).unwrap_or(0);
+    let met = |words: u64| words > 0 && goal.is_none_or(|goal| words >= goal);
+
+    let mut longest_streak = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for (date, words) in &days {
+        if !met(*words) {
+            run = 0;
+            continue;
+        }
+        run = match previous {
+            Some(day) if day.succ_opt() == Some(*date) && run > 0 => run + 1,
+            _ => 1,
+        };
+        previous = Some(*date);
+        longest_streak = longest_streak.max(run);
+    }
+
+    // Today still counts as in progress: the streak holds until the day is over
+    let today_words = words_on(today);
+    let mut current_streak = 0;
+    let mut day = if met(today_words) { Some(today) } else { today.pred_opt() };
+    while let Some(date) = day.filter(|date| met(words_on(*date))) {
+        current_streak += 1;
+        day = date.pred_opt();
+    }
+
+    let history = (0..HISTORY_DAYS)
+        .rev()
+        .filter_map(|offset| today.checked_sub_signed(chrono::Duration::days(offset)))
+        .map(|date| DailyWords { date: date.format("%Y-%m-%d").to_string(), words: words_on(date) })
+        .collect();
+
+    Ok(WritingProgress {
+        today: today_words,
+        goal,
+        goal_met: met(today_words),
+        current_streak,
+        longest_streak,
+        history,
+    })
+}
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_words_and_streaks() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let store = MetadataStore::open(&vault).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+        let write = |content: &str| file_operations::write_to_file(&format!("{}/Draft.md", vault.path), content).unwrap();
+
+        // An existing note only gets a baseline
+        write("one two three");
+        assert_eq!(record_save(&vault, &store, "Draft", false, day(1)).unwrap(), 0);
+        write("one two three four five");
+        assert_eq!(record_save(&vault, &store, "Draft", false, day(1)).unwrap(), 2);
+        write("one two");
+        assert_eq!(record_save(&vault, &store, "Draft", false, day(2)).unwrap(), 0);
+        write("one two three four");
+        record_save(&vault, &store, "Draft", false, day(2)).unwrap();
+        write("one two three four five six seven");
+        record_save(&vault, &store, "Draft", false, day(4)).unwrap();
+
+        let progress = progress(&vault, &store, day(5)).unwrap();
+        assert_eq!((progress.today, progress.goal_met), (0, false));
+        assert_eq!(progress.current_streak, 1);
+        assert_eq!(progress.longest_streak, 2);
+        assert_eq!(progress.history.len(), HISTORY_DAYS as usize);
+        assert_eq!(progress.history[HISTORY_DAYS as usize - 2], DailyWords { date: "2024-05-04".to_string(), words: 3 });
+
+        set_goal(&vault, Some(3)).unwrap();
+        let progress = super::progress(&vault, &store, day(4)).unwrap();
+        assert_eq!((progress.goal, progress.goal_met, progress.current_streak, progress.longest_streak), (Some(3), true, 1, 1));
+        assert_eq!(set_goal(&vault, Some(0)).unwrap().daily_words, None);
+
+        // Cleanup
+        drop(store);
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
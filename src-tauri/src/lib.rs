@@ -1,79 +1,2372 @@
-use tauri::Manager;
-use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
 
+mod events;
+mod feature;
 mod storage;
 mod utils;
 
-use storage::{note::{self, Note}, vault::{self, Vault}};
-use utils::markdown;
+use feature::{activity::{self, ActivityEntry}, app_state::AppState, archival::{self, ArchivalReport, ArchiveRule}, audio::{self, AudioMemo, TranscriptionHook}, autocomplete::{self, Completion}, backlinks::{self, BacklinkGroup}, block_refs::{self, BlockReference}, bookmarks::{self, Bookmark}, bulk_edit::{self, TagRename}, calendar::{self, CalendarDay}, csv_export, duplicates::{self, DuplicateCluster}, export::{self, ExportReport}, file_tree::{self, GroupBy, SortBy, TreeFolder}, folder_settings::{self, FolderSettings}, git_sync::{self, GitConfig, PullReport}, graph::{self, GraphData, GraphFormat, GraphStats, NoteGraph}, graph_layout::{self, GraphLayout}, health::{self, HealthReport}, history::{FileChange, History}, import::{self, ImportReport, ImportSource, VaultImportReport}, inbox::{self, InboxItem}, index_notes::{self, IndexKind, IndexReport}, journal::{self, DailyNote}, link_rot::{self, LinkChecks, LinkReport}, keybindings, lint::{self, Diagnostic, LintConfig, LintReport}, locks::{self, LockStatus, NoteLock}, lookup, meetings::{self, MeetingNote}, metadata::NoteMetadata, note_image::{self, CardTheme}, note_stats::{self, NoteStats}, ocr, paste_guard, pdf::{self, PdfReport, PrintStyle}, people::{self, Person}, pinned, previews::{self, NotePreview}, publish::{self, PublishConfig, PublishScheduler, PublishStatus, PublishTrigger}, recovery::{RecoveryReport, SafeMode, SafeModeStatus}, refactor::{self, MoveReport}, review::{self, ReviewItem}, search::{self, SearchFilter, SearchHit, SearchIndexes}, settings::{self, Settings, SettingsStore}, settings_archive::{self, ArchiveSummary}, share::{ShareServer, SharedLink}, site_templates::{self, SiteTemplates}, snapshots::{self, RollbackReport, Snapshot}, snippets::{self, Expansion, Snippet}, tag_suggestions::{self, TagSuggestion}, templates::{self, TemplatePrompt}, thumbnails, time_tracking::{self, TimeEntry, TimeReport, TrackAction}, timeline::{self, Bucket, DateRange, TimelineGroup}, title_sync::{self, NameMigration, SaveOutcome}, unresolved_links::{self, UnresolvedGroup}, untitled, view_state::{self, ViewState}, watcher::VaultWatchers, weekly_review::{self, WeeklyReview}, write_lock::WriteLocks, writing_stats::{self, WritingGoal, WritingProgress}};
+use storage::{attachment::{self, AttachedFile, AttachmentUsage, DedupeReport, GarbageReport}, note::{self, Note, NoteEntry, OnConflict}, trash::{self, TrashedNote}, vault::{self, Vault, VaultConfig}};
+use utils::{conflict::{self, ConflictResolution, Segment}, error::AppError, excerpt, frontmatter::{self, Properties}, markdown, message::Message, outline::{self, Direction, Heading, MatchLocation}, plan::Outcome, table::{self, Table, TableOp}};
 
-#[derive(Serialize, Deserialize)]
-struct NoteMetadata {
+// Commands are async and run their work on the blocking thread pool, so disk IO and index updates never
+// hold up the main thread or the async runtime. Managed state is taken from the app handle inside the work.
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> Result<T, AppError> {
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| AppError::Io(Message::new("task.failed", "Background task failed: {detail}").with("detail", e)))
+}
+
+#[tauri::command]
+async fn create_vault(vault: String) -> Result<(), AppError> {
+    blocking(move || {
+        vault::Vault::create_vault(&vault)
+            .map(|_vault| ())
+            .map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn list_vaults(base_path: String) -> Result<Vec<String>, AppError> {
+    blocking(move || {
+        vault::Vault::list_vaults(&base_path).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Opens an existing vault; note commands called without a vault use it until it is closed. Its search
+// index and backlinks are brought up to date and its stores checked in the background (see
+// `events::index_in_background`).
+#[tauri::command]
+async fn open_vault(app: AppHandle, name: String) -> Result<Vault, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = Vault::open(&name).map_err(AppError::from)?;
+        state.open(vault.clone());
+        events::index_in_background(&app, &vault);
+        Ok(vault)
+    })
+    .await?
+}
+
+// Closes the open vault and returns it, if one was open.
+#[tauri::command]
+async fn close_vault(app: AppHandle) -> Result<Option<Vault>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        state.close()
+    })
+    .await
+}
+
+// Creates a note in the vault root, or inside `folder` with the template and default tags of that folder.
+// A taken title fails with `note.already_exists` unless `on_conflict` is `suffix`.
+#[tauri::command]
+async fn create_note(
+    app: AppHandle,
+    vault: Option<Vault>,
+    note: Note,
+    folder: Option<String>,
+    on_conflict: Option<OnConflict>,
+) -> Result<String, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let mut vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let on_conflict = on_conflict.unwrap_or_default();
+        let path = match folder {
+            Some(folder) => folder_settings::create_note(&vault, &folder, &note, on_conflict).map_err(AppError::from)?,
+            None => note.create_note(&mut vault, on_conflict).map_err(AppError::from)?,
+        };
+        if let Err(e) = lookup::ensure_id(&vault, &path) {
+            println!("❌ Failed to give note {} an id: {}", path, e);
+        }
+        events::note_saved(&app, &vault, &path);
+        Ok(path)
+    })
+    .await?
+}
+
+// Lists the links to notes that do not exist yet, grouped by the note they appear in.
+#[tauri::command]
+async fn get_unresolved_links(app: AppHandle, vault: Option<Vault>) -> Result<Vec<UnresolvedGroup>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        unresolved_links::unresolved_links(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Creates the missing note a wikilink points at and returns its path.
+#[tauri::command]
+async fn create_note_from_link(app: AppHandle, vault: Option<Vault>, link: String) -> Result<String, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let path = unresolved_links::create_note_from_link(&vault, &link).map_err(AppError::from)?;
+        if let Err(e) = lookup::ensure_id(&vault, &path) {
+            println!("❌ Failed to give note {} an id: {}", path, e);
+        }
+        events::note_saved(&app, &vault, &path);
+        Ok(path)
+    })
+    .await?
+}
+
+// Reads a note by its path or its id; the returned note carries its path as title.
+#[tauri::command]
+async fn read_note(app: AppHandle, vault: Option<Vault>, title: String) -> Result<Note, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let title = lookup::resolve_note(&vault, &title).map_err(AppError::from)?;
+        let content = note::Note::read_note(&vault, &title).map_err(AppError::from)?;
+        // Recording the open is best effort; the note is read either way
+        let _ = safe_mode.metadata(&vault).and_then(|store| store.record_open(&title));
+        Ok(Note { title, content })
+    })
+    .await?
+}
+
+// Saves the content of a note; with title sync enabled a changed first heading renames the note.
+#[tauri::command]
+async fn save_note(
+    app: AppHandle,
+    vault: Option<Vault>,
+    path: String,
+    content: String,
+) -> Result<SaveOutcome, AppError> {
+    blocking(move || {
+        let store = app.state::<SettingsStore>();
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let editor = store.get().editor;
+        let limit = editor.large_note_limit_kb as usize * 1024;
+        let (content, extracted) = paste_guard::extract_blobs(&vault, &path, &content, limit).map_err(AppError::from)?;
+        let mut outcome = title_sync::save_note(&vault, &path, &content, editor.title_sync).map_err(AppError::from)?;
+        outcome.extracted = extracted;
+        events::note_saved(&app, &vault, &outcome.path);
+        if let Some(report) = &outcome.renamed {
+            events::note_moved(&app, &vault, report);
+        }
+        Ok(outcome)
+    })
+    .await?
+}
+
+// Names the notes an older version named after the first words of their content after their first
+// heading. A dry run returns the renames instead; the report of a run lists the renames that
+// `rollback_note_names` undoes.
+#[tauri::command]
+async fn migrate_note_names(
+    app: AppHandle,
+    vault: Option<Vault>,
+    dry_run: Option<bool>,
+) -> Result<Outcome<NameMigration>, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        if dry_run.unwrap_or(false) {
+            return title_sync::plan_name_migration(&vault).map(|plan| Outcome::Planned { plan }).map_err(AppError::from);
+        }
+        let _write = locks.write(&vault);
+        let watchers = app.state::<VaultWatchers>();
+        let _hold = watchers.hold(&vault);
+        let migration = title_sync::migrate_names(&vault).map_err(AppError::from)?;
+        for report in &migration.renamed {
+            events::note_moved(&app, &vault, report);
+        }
+        Ok(Outcome::Done { result: migration })
+    })
+    .await?
+}
+
+// Undoes a name migration: renames its notes back and points the links and metadata back at them.
+#[tauri::command]
+async fn rollback_note_names(app: AppHandle, vault: Option<Vault>, renamed: Vec<MoveReport>) -> Result<Vec<MoveReport>, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let watchers = app.state::<VaultWatchers>();
+        let _hold = watchers.hold(&vault);
+        let reverted = title_sync::rollback_names(&vault, &renamed).map_err(AppError::from)?;
+        for report in &reverted {
+            events::note_moved(&app, &vault, report);
+        }
+        Ok(reverted)
+    })
+    .await?
+}
+
+// Moves a note, given by its path or its id as title, to the trash.
+#[tauri::command]
+async fn delete_note(app: AppHandle, vault: Option<Vault>, note: Note) -> Result<(), AppError> {
+    blocking(move || {
+        let history = app.state::<History>();
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let mut vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let title = lookup::resolve_note(&vault, &note.title).map_err(AppError::from)?;
+        let snapshot = Note::read_note(&vault, &title).ok();
+        Note::new(&title, &note.content).delete_note(&mut vault).map_err(AppError::from)?;
+        events::note_deleted(&app, &vault, &title);
+        let change = FileChange { title, before: snapshot, after: None };
+        history.record("Delete note", &vault, vec![change]);
+        Ok(())
+    })
+    .await?
+}
+
+// Lists the deleted notes kept in the vault's trash, most recent first.
+#[tauri::command]
+async fn list_trash(app: AppHandle, vault: Option<Vault>) -> Result<Vec<TrashedNote>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        trash::list_trash(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Moves a note out of the trash; returns its path, which gets a `-2` suffix when the old one is taken.
+#[tauri::command]
+async fn restore_note(app: AppHandle, vault: Option<Vault>, id: String) -> Result<String, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let path = trash::restore_note(&vault, &id).map_err(AppError::from)?;
+        events::index_updated(&app, &vault, vec![path.clone()]);
+        Ok(path)
+    })
+    .await?
+}
+
+// Permanently deletes the notes and attachments in the vault's trash. A dry run returns what would be
+// deleted instead.
+#[tauri::command]
+async fn empty_trash(app: AppHandle, vault: Option<Vault>, dry_run: Option<bool>) -> Result<Outcome<usize>, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        if dry_run.unwrap_or(false) {
+            return trash::plan_empty_trash(&vault).map(|plan| Outcome::Planned { plan }).map_err(AppError::from);
+        }
+        let _write = locks.write(&vault);
+        trash::empty_trash(&vault).map(|result| Outcome::Done { result }).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Lists the folders and notes of a vault as a tree; note paths are relative to the vault.
+#[tauri::command]
+async fn list_notes(app: AppHandle, vault: Option<Vault>) -> Result<Vec<NoteEntry>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        Note::list_tree(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn create_folder(app: AppHandle, vault: Option<Vault>, path: String) -> Result<String, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let path = vault.create_folder(&path).map_err(AppError::from)?;
+        events::index_updated(&app, &vault, Vec::new());
+        Ok(path)
+    })
+    .await?
+}
+
+// Streams the sorted note list in `vault://notes-batch` events and returns the total right away,
+// so huge vaults can be rendered incrementally.
+#[tauri::command]
+async fn stream_notes(app: AppHandle, vault: Option<Vault>, batch_size: Option<usize>) -> Result<usize, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let mut titles = Note::list_notes(&vault).map_err(AppError::from)?;
+        titles.sort_by_key(|title| title.to_lowercase());
+        let total = titles.len();
+        let batch_size = batch_size.unwrap_or(events::NOTE_BATCH_SIZE).max(1);
+
+        std::thread::spawn(move || {
+            if titles.is_empty() {
+                events::notes_batch(&app, &vault, 0, 0, &[]);
+            }
+            for (index, batch) in titles.chunks(batch_size).enumerate() {
+                events::notes_batch(&app, &vault, index * batch_size, total, batch);
+            }
+        });
+        Ok(total)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn render_html(app: AppHandle, vault: Option<Vault>, note: Note) -> Result<String, AppError> {
+    blocking(move || {
+        let store = app.state::<SettingsStore>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        note.render_html(&vault, &store.get().editor.link_scheme).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn extract_links(vault_name: String, title: String) -> Result<Vec<String>, AppError> {
+    blocking(move || {
+        let vault = Vault::create_vault(&vault_name).map_err(AppError::from)?;
+        let content = note::Note::read_note(&vault, &title).map_err(AppError::from)?;
+        Ok(markdown::extract_links(&content))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn extract_plain_text(content: String) -> Result<String, AppError> {
+    blocking(move || {
+        Ok(markdown::extract_plain_text(&content))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn delete_vault(app: AppHandle, vault: String, dry_run: Option<bool>) -> Result<Outcome<()>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        if dry_run.unwrap_or(false) {
+            // Opening rather than creating, so a dry run never leaves an empty vault behind
+            let vault = Vault::open(&vault).map_err(AppError::from)?;
+            return vault.plan_delete().map(|plan| Outcome::Planned { plan }).map_err(AppError::from);
+        }
+        let vault = Vault::create_vault(&vault).map_err(AppError::from)?;
+        vault.delete_vault().map_err(AppError::from)?;
+        state.forget(&vault.name);
+        Ok(Outcome::Done { result: () })
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn random_note(app: AppHandle, vault: Option<Vault>, filter: Option<String>) -> Result<Option<String>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        review::random_note(&vault, filter.as_deref()).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn review_queue(app: AppHandle, vault: Option<Vault>, limit: usize) -> Result<Vec<ReviewItem>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let Some(store) = safe_mode.optional_metadata(&vault).map_err(AppError::from)? else {
+            return Ok(Vec::new());
+        };
+        review::review_queue(&vault, &store, limit).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn mark_reviewed(app: AppHandle, vault: Option<Vault>, title: String) -> Result<(), AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let store = safe_mode.metadata(&vault).map_err(AppError::from)?;
+        store.record_open(&title).map_err(AppError::from)?;
+        events::metadata_changed(&app, &vault, Some(&title));
+        Ok(())
+    })
+    .await?
+}
+
+// Suggests tags for a note from the tags and words of the other notes. `content` is the unsaved text of
+// the editor; without it the saved note is used.
+#[tauri::command]
+async fn suggest_tags(app: AppHandle, vault: Option<Vault>, title: String, content: Option<String>, limit: Option<usize>) -> Result<Vec<TagSuggestion>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let path = refactor::clean_path(&title);
+        let content = match content {
+            Some(content) => content,
+            None => Note::read_note(&vault, &path).map_err(AppError::from)?,
+        };
+        let limit = limit.unwrap_or(tag_suggestions::DEFAULT_SUGGESTION_LIMIT);
+        tag_suggestions::suggest_tags(&vault, &path, &content, limit).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn find_duplicates(app: AppHandle, vault: Option<Vault>, threshold: Option<f64>) -> Result<Vec<DuplicateCluster>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let threshold = threshold.unwrap_or(duplicates::DEFAULT_SIMILARITY_THRESHOLD);
+        duplicates::find_duplicates(&vault, threshold).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn clean_attachments(
+    app: AppHandle,
+    vault: Option<Vault>,
+    confirm: bool,
+    trash: bool,
+    dry_run: Option<bool>,
+) -> Result<Outcome<GarbageReport>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        if dry_run.unwrap_or(false) {
+            return attachment::plan_garbage(&vault, trash).map(|plan| Outcome::Planned { plan }).map_err(AppError::from);
+        }
+        let watchers = app.state::<VaultWatchers>();
+        let _hold = watchers.hold(&vault);
+        let report = attachment::collect_garbage(&vault, confirm, trash).map_err(AppError::from)?;
+        if report.removed {
+            events::index_updated(&app, &vault, Vec::new());
+        }
+        Ok(Outcome::Done { result: report })
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn list_attachments(app: AppHandle, vault: Option<Vault>) -> Result<Vec<AttachmentUsage>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        attachment::attachment_usages(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_thumbnail(app: AppHandle, vault: Option<Vault>, path: String) -> Result<Option<String>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        thumbnails::thumbnail(&vault, &path).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn rename_attachment(
+    app: AppHandle,
+    vault: Option<Vault>,
+    path: String,
+    new_name: String,
+) -> Result<Vec<String>, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let updated = attachment::rename_attachment(&vault, &path, &new_name).map_err(AppError::from)?;
+        events::index_updated(&app, &vault, updated.clone());
+        Ok(updated)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn save_audio_attachment(
+    app: AppHandle,
+    vault: Option<Vault>,
+    title: String,
+    data: Vec<u8>,
+    extension: String,
+    transcription: Option<TranscriptionHook>,
+) -> Result<AudioMemo, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let memo = audio::save_audio_memo(&vault, &title, &data, &extension, transcription.as_ref())
+            .map_err(AppError::from)?;
+        events::note_saved(&app, &vault, &title);
+        Ok(memo)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn ocr_attachment(
+    app: AppHandle,
+    vault: Option<Vault>,
+    path: String,
+    language: Option<String>,
+    insert_into: Option<String>,
+) -> Result<String, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let text = ocr::extract_text(&vault, &path, language.as_deref()).map_err(AppError::from)?;
+        if let Some(title) = insert_into {
+            let _write = locks.write(&vault);
+            ocr::insert_into_note(&vault, &title, &text).map_err(AppError::from)?;
+            events::note_saved(&app, &vault, &title);
+        }
+        Ok(text)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_ocr_text(app: AppHandle, vault: Option<Vault>, path: String) -> Result<Option<String>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        Ok(ocr::read_sidecar(&vault, &path))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn configure_publish(app: AppHandle, vault: Option<Vault>, config: PublishConfig) -> Result<(), AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        publish::save_config(&vault, &config).map_err(AppError::from)?;
+        apply_publish_schedule(&app, &vault, &config);
+        Ok(())
+    })
+    .await?
+}
+
+// Starts or stops the publish timer of a vault to match its configuration.
+fn apply_publish_schedule(app: &AppHandle, vault: &Vault, config: &PublishConfig) {
+    let scheduler = app.state::<PublishScheduler>();
+    match config.auto.interval_minutes {
+        Some(minutes) => {
+            let (handle, scheduled) = (app.clone(), vault.clone());
+            scheduler.schedule(vault, Duration::from_secs(minutes * 60), move || {
+                if let Err(e) = events::publish(&handle, &scheduled, PublishTrigger::Schedule) {
+                    println!("❌ Scheduled publish failed: {}", e);
+                }
+            });
+        }
+        None => {
+            scheduler.unschedule(vault);
+        }
+    }
+}
+
+// Starts publishing the vault on its configured interval; call it when a vault is opened.
+#[tauri::command]
+async fn start_auto_publish(app: AppHandle, vault: Option<Vault>) -> Result<(), AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let config = publish::load_config(&vault).map_err(AppError::from)?;
+        apply_publish_schedule(&app, &vault, &config);
+        Ok(())
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn stop_auto_publish(app: AppHandle, vault: Option<Vault>) -> Result<bool, AppError> {
+    blocking(move || {
+        let scheduler = app.state::<PublishScheduler>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        Ok(scheduler.unschedule(&vault))
+    })
+    .await?
+}
+
+// Saves wait while the site is generated and pushed, so it reflects a single state of the vault.
+#[tauri::command]
+async fn publish_notes(app: AppHandle, vault: Option<Vault>) -> Result<PublishStatus, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        events::publish(&app, &vault, PublishTrigger::Manual).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_publish_status(app: AppHandle, vault: Option<Vault>) -> Result<Option<PublishStatus>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        publish::load_status(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Makes the vault a git repository synced with `remote`, committing its current notes.
+#[tauri::command]
+async fn vault_git_init(app: AppHandle, vault: Option<Vault>, remote: Option<String>) -> Result<Option<String>, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _snapshot = locks.snapshot(&vault);
+        git_sync::init(&vault, remote.as_deref()).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn vault_git_commit(app: AppHandle, vault: Option<Vault>, message: String) -> Result<Option<String>, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _snapshot = locks.snapshot(&vault);
+        git_sync::commit(&vault, &message).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Pulls from the remote with saves on hold, then refreshes the indexes of the notes it changed.
+#[tauri::command]
+async fn vault_git_pull(app: AppHandle, vault: Option<Vault>) -> Result<PullReport, AppError> {
+    blocking(move || {
+        let watchers = app.state::<VaultWatchers>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _hold = watchers.hold(&vault);
+        let report = {
+            let locks = app.state::<WriteLocks>();
+            let _snapshot = locks.snapshot(&vault);
+            git_sync::pull(&vault).map_err(AppError::from)?
+        };
+        if !report.changed.is_empty() {
+            events::index_updated(&app, &vault, report.changed.clone());
+        }
+        Ok(report)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn vault_git_push(app: AppHandle, vault: Option<Vault>) -> Result<(), AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        git_sync::push(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_git_config(app: AppHandle, vault: Option<Vault>) -> Result<GitConfig, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        git_sync::load_config(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn set_git_config(app: AppHandle, vault: Option<Vault>, config: GitConfig) -> Result<(), AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        git_sync::save_config(&vault, &config).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn add_bookmark(
+    app: AppHandle,
+    vault: Option<Vault>,
+    url: String,
+    title: String,
+    highlights: Vec<String>,
+    note: Option<String>,
+) -> Result<Bookmark, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let store = safe_mode.metadata(&vault).map_err(AppError::from)?;
+        let bookmark = bookmarks::add_bookmark(&store, &url, &title, highlights, note).map_err(AppError::from)?;
+        events::metadata_changed(&app, &vault, None);
+        Ok(bookmark)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn list_bookmarks(app: AppHandle, vault: Option<Vault>) -> Result<Vec<Bookmark>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let Some(store) = safe_mode.optional_metadata(&vault).map_err(AppError::from)? else {
+            return Ok(Vec::new());
+        };
+        bookmarks::list_bookmarks(&store).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn convert_bookmark_to_note(app: AppHandle, vault: Option<Vault>, id: String) -> Result<String, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let store = safe_mode.metadata(&vault).map_err(AppError::from)?;
+        let title = bookmarks::convert_to_note(&vault, &store, &id).map_err(AppError::from)?;
+        // The store is reopened to stamp the new note
+        drop(store);
+        events::note_saved(&app, &vault, &title);
+        events::metadata_changed(&app, &vault, None);
+        Ok(title)
+    })
+    .await?
+}
+
+// Applies a text transformation to a note, saves it and returns the new content.
+fn rewrite_note<F>(app: &AppHandle, vault: &Vault, title: &str, edit: F) -> Result<String, AppError>
+where
+    F: FnOnce(&str) -> std::io::Result<String>,
+{
+    let locks = app.state::<WriteLocks>();
+    let _write = locks.write(vault);
+    let content = Note::read_note(vault, title).map_err(AppError::from)?;
+    let updated = edit(&content).map_err(AppError::from)?;
+    Note::update_note(vault, title, &updated).map_err(AppError::from)?;
+    events::note_saved(app, vault, title);
+    Ok(updated)
+}
+
+#[tauri::command]
+async fn get_outline(app: AppHandle, vault: Option<Vault>, title: String) -> Result<Vec<Heading>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let content = Note::read_note(&vault, &title).map_err(AppError::from)?;
+        Ok(outline::headings(&content))
+    })
+    .await?
+}
+
+// Returns the `[[Note#Heading]]` link to the heading at `index` in the outline.
+#[tauri::command]
+async fn copy_heading_reference(app: AppHandle, vault: Option<Vault>, title: String, index: usize) -> Result<String, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let content = Note::read_note(&vault, &title).map_err(AppError::from)?;
+        block_refs::heading_reference(&title, &content, index).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Returns the `[[Note#^id]]` link to the block at `line`, adding a block id to the note if needed.
+#[tauri::command]
+async fn copy_block_reference(
+    app: AppHandle,
+    vault: Option<Vault>,
+    title: String,
+    line: usize,
+) -> Result<BlockReference, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let reference = block_refs::block_reference(&vault, &title, line).map_err(AppError::from)?;
+        if reference.created {
+            events::note_saved(&app, &vault, &title);
+        }
+        Ok(reference)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn move_section(app: AppHandle, vault: Option<Vault>, title: String, index: usize, direction: Direction) -> Result<String, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        rewrite_note(&app, &vault, &title, |content| outline::move_section(content, index, direction))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn promote_section(app: AppHandle, vault: Option<Vault>, title: String, index: usize) -> Result<String, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        rewrite_note(&app, &vault, &title, |content| outline::shift_level(content, index, -1))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn demote_section(app: AppHandle, vault: Option<Vault>, title: String, index: usize) -> Result<String, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        rewrite_note(&app, &vault, &title, |content| outline::shift_level(content, index, 1))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_tables(app: AppHandle, vault: Option<Vault>, title: String) -> Result<Vec<Table>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let content = Note::read_note(&vault, &title).map_err(AppError::from)?;
+        Ok(table::find_tables(&content))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn edit_table(app: AppHandle, vault: Option<Vault>, title: String, table_index: usize, op: TableOp) -> Result<String, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        rewrite_note(&app, &vault, &title, |content| table::edit_table(content, table_index, &op))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_conflicts(app: AppHandle, vault: Option<Vault>, title: String) -> Result<Vec<Segment>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let content = Note::read_note(&vault, &title).map_err(AppError::from)?;
+        Ok(conflict::parse_conflicts(&content))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn resolve_conflicts(app: AppHandle, vault: Option<Vault>, title: String, resolutions: Vec<ConflictResolution>) -> Result<String, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        rewrite_note(&app, &vault, &title, |content| conflict::resolve_conflicts(content, &resolutions))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn track_time(app: AppHandle, vault: Option<Vault>, title: String, action: TrackAction) -> Result<TimeEntry, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let entry = time_tracking::track(&vault, &title, action).map_err(AppError::from)?;
+        events::note_saved(&app, &vault, &title);
+        Ok(entry)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn time_report(app: AppHandle, vault: Option<Vault>, from: Option<String>, to: Option<String>) -> Result<TimeReport, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let parse_day = |day: Option<String>| {
+            day.map(|day| {
+                chrono::NaiveDate::parse_from_str(&day, "%Y-%m-%d")
+                    .map_err(|_| AppError::InvalidInput(Message::new("date.invalid", "Dates must be formatted as YYYY-MM-DD")))
+            })
+                .transpose()
+        };
+        time_tracking::report(&vault, parse_day(from)?, parse_day(to)?).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Creates today's meeting note on a topic, linking every attendee to their person note.
+#[tauri::command]
+async fn create_meeting_note(
+    app: AppHandle,
+    vault: Option<Vault>,
+    topic: String,
+    attendees: Vec<String>,
+) -> Result<MeetingNote, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let meeting = meetings::create_meeting_note(&vault, &topic, &attendees).map_err(AppError::from)?;
+        let mut created = meeting.created_people.clone();
+        created.push(meeting.path.clone());
+        events::index_updated(&app, &vault, created);
+        Ok(meeting)
+    })
+    .await?
+}
+
+// Creates the weekly review note of a date range (the current week by default) with the tasks completed
+// in it and those still open.
+#[tauri::command]
+async fn create_weekly_review(
+    app: AppHandle,
+    vault: Option<Vault>,
+    range: Option<DateRange>,
+    on_conflict: Option<OnConflict>,
+) -> Result<WeeklyReview, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let review = weekly_review::create_weekly_review(&vault, &range.unwrap_or_default(), on_conflict.unwrap_or_default())
+            .map_err(AppError::from)?;
+        events::note_saved(&app, &vault, &review.path);
+        Ok(review)
+    })
+    .await?
+}
+
+// Opens today's daily note, creating it from the daily template if it does not exist yet.
+#[tauri::command]
+async fn open_daily_note(app: AppHandle, vault: Option<Vault>) -> Result<DailyNote, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let daily = journal::open_daily_note(&vault, chrono::Local::now().date_naive()).map_err(AppError::from)?;
+        if daily.created {
+            events::note_saved(&app, &vault, &daily.path);
+        }
+        Ok(daily)
+    })
+    .await?
+}
+
+
+// Groups notes by a date (`created`, `updated` or a frontmatter field) for the timeline view.
+#[tauri::command]
+async fn get_timeline(app: AppHandle, vault: Option<Vault>, field: String, range: Option<DateRange>, bucket: Option<Bucket>) -> Result<Vec<TimelineGroup>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        timeline::get_timeline(&vault, &field, &range.unwrap_or_default(), bucket.unwrap_or_default()).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn log(app: AppHandle, vault: Option<Vault>, text: String) -> Result<String, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let title = journal::log(&vault, &text).map_err(AppError::from)?;
+        events::note_saved(&app, &vault, &title);
+        Ok(title)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn autocomplete_link(app: AppHandle, vault: Option<Vault>, prefix: String) -> Result<Vec<Completion>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        autocomplete::autocomplete_link(&vault, &prefix).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn list_snippets(app: AppHandle, vault: Option<Vault>) -> Result<Vec<Snippet>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        snippets::load_snippets(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn save_snippet(app: AppHandle, vault: Option<Vault>, snippet: Snippet) -> Result<(), AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        snippets::save_snippet(&vault, snippet).map_err(AppError::from)?;
+        events::metadata_changed(&app, &vault, None);
+        Ok(())
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn delete_snippet(app: AppHandle, vault: Option<Vault>, trigger: String) -> Result<(), AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        snippets::delete_snippet(&vault, &trigger).map_err(AppError::from)?;
+        events::metadata_changed(&app, &vault, None);
+        Ok(())
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn expand_snippet(app: AppHandle, vault: Option<Vault>, trigger: String, variables: Option<HashMap<String, String>>) -> Result<Expansion, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        snippets::expand(&vault, &trigger, &variables.unwrap_or_default()).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_settings(app: AppHandle) -> Result<Settings, AppError> {
+    blocking(move || {
+        let store = app.state::<SettingsStore>();
+        store.get()
+    })
+    .await
+}
+
+#[tauri::command]
+async fn set_settings(app: AppHandle, settings: Settings) -> Result<Settings, AppError> {
+    blocking(move || {
+        let store = app.state::<SettingsStore>();
+        let settings = store.set(settings).map_err(AppError::from)?;
+        events::settings_changed(&app, &settings);
+        Ok(settings)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn reset_settings(app: AppHandle) -> Result<Settings, AppError> {
+    blocking(move || {
+        let store = app.state::<SettingsStore>();
+        let settings = store.reset().map_err(AppError::from)?;
+        events::settings_changed(&app, &settings);
+        Ok(settings)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_keymap(app: AppHandle) -> Result<BTreeMap<String, String>, AppError> {
+    blocking(move || {
+        let store = app.state::<SettingsStore>();
+        keybindings::effective_keymap(&store.get())
+    })
+    .await
+}
+
+#[tauri::command]
+async fn set_keybinding(
+    app: AppHandle,
+    action: String,
+    combo: Option<String>,
+) -> Result<BTreeMap<String, String>, AppError> {
+    blocking(move || {
+        let store = app.state::<SettingsStore>();
+        let settings = store
+            .update(|settings| keybindings::set_keybinding(settings, &action, combo.as_deref()))
+            .map_err(AppError::from)?;
+        events::settings_changed(&app, &settings);
+        Ok(keybindings::effective_keymap(&settings))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn replace_in_notes(
+    app: AppHandle,
+    vault: Option<Vault>,
+    find: String,
+    replace: String,
+    dry_run: Option<bool>,
+) -> Result<Outcome<Vec<String>>, AppError> {
+    blocking(move || {
+        let history = app.state::<History>();
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        if dry_run.unwrap_or(false) {
+            return bulk_edit::plan_replace_in_notes(&vault, &find, &replace).map(|(_, plan)| Outcome::Planned { plan }).map_err(AppError::from);
+        }
+        let _write = locks.write(&vault);
+        let watchers = app.state::<VaultWatchers>();
+        let _hold = watchers.hold(&vault);
+        let changes = bulk_edit::replace_in_notes(&vault, &find, &replace).map_err(AppError::from)?;
+        let titles: Vec<String> = changes.iter().map(|change| change.title.clone()).collect();
+        history.record_snapshot("Replace in notes", &vault, changes);
+        events::index_updated(&app, &vault, titles.clone());
+        Ok(Outcome::Done { result: titles })
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn rename_tag(
+    app: AppHandle,
+    vault: Option<Vault>,
+    old: String,
+    new: String,
+    dry_run: Option<bool>,
+) -> Result<Outcome<TagRename>, AppError> {
+    blocking(move || {
+        let history = app.state::<History>();
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        if dry_run.unwrap_or(false) {
+            return bulk_edit::plan_rename_tag(&vault, &old, &new).map(|(_, plan, _)| Outcome::Planned { plan }).map_err(AppError::from);
+        }
+        let _write = locks.write(&vault);
+        let watchers = app.state::<VaultWatchers>();
+        let _hold = watchers.hold(&vault);
+        let (changes, failed) = bulk_edit::rename_tag(&vault, &old, &new).map_err(AppError::from)?;
+        let renamed: Vec<String> = changes.iter().map(|change| change.title.clone()).collect();
+        history.record_snapshot("Rename tag", &vault, changes);
+        events::index_updated(&app, &vault, renamed.clone());
+        Ok(Outcome::Done { result: TagRename { renamed, failed } })
+    })
+    .await?
+}
+
+// Lists the snapshots taken before bulk edits and imports, most recent first.
+#[tauri::command]
+async fn list_snapshots(app: AppHandle, vault: Option<Vault>) -> Result<Vec<Snapshot>, AppError> {
+    blocking(move || {
+        let history = app.state::<History>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        Ok(snapshots::list_snapshots(&history, &vault))
+    })
+    .await?
+}
+
+// Rolls the notes of the vault back to a snapshot, the most recent one when no id is given.
+#[tauri::command]
+async fn rollback_snapshot(app: AppHandle, vault: Option<Vault>, id: Option<String>) -> Result<RollbackReport, AppError> {
+    blocking(move || {
+        let history = app.state::<History>();
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let report = snapshots::rollback(&history, &vault, id.as_deref()).map_err(AppError::from)?;
+        let mut changed = report.restored.clone();
+        changed.extend(report.removed.iter().cloned());
+        events::index_updated(&app, &vault, changed);
+        Ok(report)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn undo_last(app: AppHandle) -> Result<Option<String>, AppError> {
+    blocking(move || {
+        let history = app.state::<History>();
+        let operation = history.undo_last().map_err(AppError::from)?;
+        Ok(operation.map(|operation| {
+            events::index_updated(&app, &operation.vault, operation.titles());
+            operation.label
+        }))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn redo_last(app: AppHandle) -> Result<Option<String>, AppError> {
+    blocking(move || {
+        let history = app.state::<History>();
+        let operation = history.redo_last().map_err(AppError::from)?;
+        Ok(operation.map(|operation| {
+            events::index_updated(&app, &operation.vault, operation.titles());
+            operation.label
+        }))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn list_inbox(app: AppHandle, vault: Option<Vault>) -> Result<Vec<InboxItem>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        inbox::list_untriaged(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn triage_note(
+    app: AppHandle,
+    vault: Option<Vault>,
+    item: InboxItem,
+    folder: String,
     tags: Vec<String>,
-    backlinks: Vec<String>,
-    created_at: String,
-    updated_at: String,
+) -> Result<String, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let report = inbox::triage(&vault, &item, &folder, &tags).map_err(AppError::from)?;
+        events::note_moved(&app, &vault, &report);
+        Ok(report.to)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn share_temporarily(
+    app: AppHandle,
+    vault: Option<Vault>,
+    title: String,
+    ttl: Option<u64>,
+    https: Option<bool>,
+) -> Result<SharedLink, AppError> {
+    blocking(move || {
+        let server = app.state::<ShareServer>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let settings = app.state::<SettingsStore>().get();
+        let tls = https.unwrap_or(false).then_some(&settings.share);
+        server.share(&vault, &title, ttl, tls).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn stop_sharing(app: AppHandle, token: String) -> Result<bool, AppError> {
+    blocking(move || {
+        let server = app.state::<ShareServer>();
+        server.revoke(&token)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn list_pinned(app: AppHandle, vault: Option<Vault>) -> Result<Vec<String>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let Some(store) = safe_mode.optional_metadata(&vault).map_err(AppError::from)? else {
+            return Ok(Vec::new());
+        };
+        pinned::list_pinned(&vault, &store).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn pin_note(app: AppHandle, vault: Option<Vault>, title: String) -> Result<Vec<String>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let store = safe_mode.metadata(&vault).map_err(AppError::from)?;
+        let pinned = pinned::pin_note(&vault, &store, &title).map_err(AppError::from)?;
+        events::metadata_changed(&app, &vault, Some(&title));
+        Ok(pinned)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn unpin_note(app: AppHandle, vault: Option<Vault>, title: String) -> Result<Vec<String>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let store = safe_mode.metadata(&vault).map_err(AppError::from)?;
+        let pinned = pinned::unpin_note(&vault, &store, &title).map_err(AppError::from)?;
+        events::metadata_changed(&app, &vault, Some(&title));
+        Ok(pinned)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn reorder_pinned(app: AppHandle, vault: Option<Vault>, titles: Vec<String>) -> Result<Vec<String>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let store = safe_mode.metadata(&vault).map_err(AppError::from)?;
+        let pinned = pinned::reorder_pinned(&vault, &store, &titles).map_err(AppError::from)?;
+        events::metadata_changed(&app, &vault, None);
+        Ok(pinned)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_previews(app: AppHandle, vault: Option<Vault>, titles: Vec<String>) -> Result<Vec<NotePreview>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        previews::get_previews(&vault, &titles).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_calendar(app: AppHandle, vault: Option<Vault>, month: String) -> Result<Vec<CalendarDay>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        calendar::get_calendar(&vault, &month).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn generate_index_notes(app: AppHandle, vault: Option<Vault>, kind: Option<IndexKind>) -> Result<IndexReport, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let report = index_notes::generate_index_notes(&vault, kind).map_err(AppError::from)?;
+        let written: Vec<String> = report.created.iter().chain(&report.updated).cloned().collect();
+        if !written.is_empty() {
+            events::index_updated(&app, &vault, written);
+        }
+        Ok(report)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn acquire_note_lock(
+    app: AppHandle,
+    vault: Option<Vault>,
+    title: String,
+    holder: String,
+) -> Result<LockStatus, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let store = safe_mode.metadata(&vault).map_err(AppError::from)?;
+        let status = locks::acquire_lock(&store, &title, &holder).map_err(AppError::from)?;
+        if status.acquired {
+            events::metadata_changed(&app, &vault, Some(&title));
+        }
+        Ok(status)
+    })
+    .await?
 }
 
 #[tauri::command]
-fn create_vault(vault: String) -> Result<(), String> {
-    vault::Vault::create_vault(&vault)
-        .map(|_vault| ())
-        .map_err(|e| e.to_string())
+async fn release_note_lock(app: AppHandle, vault: Option<Vault>, title: String, holder: String) -> Result<bool, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let store = safe_mode.metadata(&vault).map_err(AppError::from)?;
+        let released = locks::release_lock(&store, &title, &holder).map_err(AppError::from)?;
+        if released {
+            events::metadata_changed(&app, &vault, Some(&title));
+        }
+        Ok(released)
+    })
+    .await?
 }
 
 #[tauri::command]
-fn list_vaults(base_path: String) -> Result<Vec<String>, String> {
-    vault::Vault::list_vaults(&base_path).map_err(|e| e.to_string())
+async fn who_has_lock(app: AppHandle, vault: Option<Vault>, title: String) -> Result<Option<NoteLock>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let Some(store) = safe_mode.optional_metadata(&vault).map_err(AppError::from)? else {
+            return Ok(None);
+        };
+        locks::who_has_lock(&store, &title).map_err(AppError::from)
+    })
+    .await?
 }
 
 #[tauri::command]
-fn create_note(mut vault: Vault, note: Note) -> Result<(), String> {
-    note.create_note(&mut vault).map_err(|e| e.to_string())
+async fn check_vault(app: AppHandle, vault: Option<Vault>) -> Result<SafeModeStatus, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let indexes = app.state::<SearchIndexes>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        Ok(safe_mode.check(&indexes, &vault))
+    })
+    .await?
 }
 
 #[tauri::command]
-fn read_note(vault: Vault, title: String) -> Result<Note, String> {
-    let content = note::Note::read_note(&vault, &title).map_err(|e| e.to_string())?;
-    Ok(Note { title, content })
+async fn recover_vault(app: AppHandle, vault: Option<Vault>) -> Result<RecoveryReport, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let indexes = app.state::<SearchIndexes>();
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        // The metadata is backed up while no save can touch it
+        let _snapshot = locks.snapshot(&vault);
+        let report = safe_mode.recover(&indexes, &vault).map_err(AppError::from)?;
+        events::metadata_changed(&app, &vault, None);
+        Ok(report)
+    })
+    .await?
 }
 
 #[tauri::command]
-fn delete_note(mut vault: Vault, note: Note) -> Result<(), String> {
-    note.delete_note(&mut vault).map_err(|e| e.to_string())
+async fn get_note_stats(app: AppHandle, vault: Option<Vault>, titles: Vec<String>) -> Result<Vec<NoteStats>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let store = safe_mode.optional_metadata(&vault).map_err(AppError::from)?;
+        note_stats::get_note_stats(&vault, store.as_ref(), &titles).map_err(AppError::from)
+    })
+    .await?
 }
 
 #[tauri::command]
-fn list_notes(vault: Vault) -> Result<Vec<String>, String> {
-    Note::list_notes(&vault).map_err(|e| e.to_string())
+async fn find_in_note(app: AppHandle, vault: Option<Vault>, title: String, query: String) -> Result<Vec<MatchLocation>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let content = Note::read_note(&vault, &title).map_err(AppError::from)?;
+        Ok(outline::locate_matches(&content, &query))
+    })
+    .await?
 }
 
 #[tauri::command]
-fn render_html(vault: Vault, note: Note) -> Result<String, String> {
-    note.render_html(&vault).map_err(|e| e.to_string())
+async fn get_file_tree(app: AppHandle, vault: Option<Vault>, sort: Option<SortBy>, group_by: Option<GroupBy>) -> Result<TreeFolder, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        file_tree::get_file_tree(&vault, sort.unwrap_or_default(), group_by).map_err(AppError::from)
+    })
+    .await?
 }
 
+// Every vault under the base path. Plain files there are not vaults, and nothing is created.
+fn all_vaults() -> std::io::Result<Vec<Vault>> {
+    Ok(Vault::list_vaults(&utils::file_operations::resolve_path("Vaults"))
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|name| Vault::open(name).ok())
+        .collect())
+}
+
+#[tauri::command]
+async fn export_settings(app: AppHandle, path: String) -> Result<ArchiveSummary, AppError> {
+    blocking(move || {
+        let store = app.state::<SettingsStore>();
+        let locks = app.state::<WriteLocks>();
+        let vaults = all_vaults().map_err(AppError::from)?;
+        let _writes: Vec<_> = vaults.iter().map(|vault| locks.write(vault)).collect();
+        settings_archive::export_settings(&store.get(), &vaults, Path::new(&path)).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn import_settings(app: AppHandle, path: String) -> Result<ArchiveSummary, AppError> {
+    blocking(move || {
+        let store = app.state::<SettingsStore>();
+        let (settings, summary) = settings_archive::import_settings(&store, Path::new(&path)).map_err(AppError::from)?;
+        events::settings_changed(&app, &settings);
+        Ok(summary)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn move_note(app: AppHandle, vault: Option<Vault>, path: String, folder: String) -> Result<MoveReport, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let report = refactor::move_note(&vault, &path, &folder).map_err(AppError::from)?;
+        events::note_moved(&app, &vault, &report);
+        Ok(report)
+    })
+    .await?
+}
+
+// Renames a note within its folder and rewrites the `[[OldTitle]]` links and Markdown links pointing at it.
+#[tauri::command]
+async fn rename_note(
+    app: AppHandle,
+    vault: Option<Vault>,
+    path: String,
+    new_title: String,
+) -> Result<MoveReport, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let report = refactor::rename_note(&vault, &path, &new_title).map_err(AppError::from)?;
+        events::note_moved(&app, &vault, &report);
+        Ok(report)
+    })
+    .await?
+}
+
+// Full-text search over the notes; archived notes and the trash are only searched when included.
+#[tauri::command]
+async fn search_notes(
+    app: AppHandle,
+    vault: Option<Vault>,
+    query: String,
+    limit: Option<usize>,
+    filter: Option<SearchFilter>,
+) -> Result<Vec<SearchHit>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let indexes = app.state::<SearchIndexes>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let search = safe_mode.search(&indexes, &vault).map_err(AppError::from)?;
+        let store = safe_mode.optional_metadata(&vault).map_err(AppError::from)?;
+        let limit = limit.unwrap_or(search::DEFAULT_SEARCH_LIMIT);
+        search.search(&vault, store.as_ref(), &query, limit, filter.unwrap_or_default()).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Exports notes as CSV for spreadsheets: every note matching the search query (every note when there is
+// none) that has all of `tags`, with the chosen frontmatter fields or else every field the notes use.
+#[tauri::command]
+async fn export_notes_csv(
+    app: AppHandle,
+    vault: Option<Vault>,
+    query: Option<String>,
+    filter: Option<SearchFilter>,
+    tags: Option<Vec<String>>,
+    fields: Option<Vec<String>>,
+) -> Result<String, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let indexes = app.state::<SearchIndexes>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let notes = Note::list_notes(&vault).map_err(AppError::from)?;
+        let paths = match query.as_deref().map(str::trim).filter(|query| !query.is_empty()) {
+            Some(query) => {
+                let search = safe_mode.search(&indexes, &vault).map_err(AppError::from)?;
+                // Every match, trashed notes included when asked for
+                let limit = notes.len() + trash::list_trash(&vault).map_err(AppError::from)?.len();
+                let hits = search.search(&vault, None, query, limit.max(1), filter.unwrap_or_default()).map_err(AppError::from)?;
+                hits.into_iter().map(|hit| hit.path).collect()
+            }
+            None => notes,
+        };
+        csv_export::export_csv(&vault, &paths, &tags.unwrap_or_default(), &fields.unwrap_or_default()).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn rebuild_search_index(app: AppHandle, vault: Option<Vault>) -> Result<usize, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let indexes = app.state::<SearchIndexes>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let search = safe_mode.search(&indexes, &vault).map_err(AppError::from)?;
+        search.reindex(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_folder_settings(app: AppHandle, vault: Option<Vault>) -> Result<BTreeMap<String, FolderSettings>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        folder_settings::load_folder_settings(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn set_folder_settings(app: AppHandle, vault: Option<Vault>, folder: String, settings: FolderSettings) -> Result<(), AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        folder_settings::set_folder_settings(&vault, &folder, settings).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Renders a note, or the selected part of it, to a PNG card for sharing.
 #[tauri::command]
-fn extract_links(vault_name: String, title: String) -> Result<Vec<String>, String> {
-    let vault = Vault::create_vault(&vault_name).map_err(|e| e.to_string())?;
-    let content = note::Note::read_note(&vault, &title).map_err(|e| e.to_string())?;
-    Ok(markdown::extract_links(&content))
+async fn export_note_image(
+    app: AppHandle,
+    vault: Option<Vault>,
+    title: String,
+    selection: Option<String>,
+    theme: Option<CardTheme>,
+    path: String,
+) -> Result<(), AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let theme = theme.unwrap_or_default();
+        note_image::export_note_image(&vault, &title, selection.as_deref(), theme, Path::new(&path))
+            .map_err(AppError::from)
+    })
+    .await?
 }
 
 #[tauri::command]
-fn extract_plain_text(content: String) -> Result<String, String> {
-    Ok(markdown::extract_plain_text(&content))
+async fn get_note_graph(app: AppHandle, vault: Option<Vault>) -> Result<GraphData, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        NoteGraph::from_vault(&vault).map(|graph| graph.data()).map_err(AppError::from)
+    })
+    .await?
 }
 
+// Renders the note graph as DOT (Graphviz) or GraphML (Gephi), for the frontend to save.
 #[tauri::command]
-fn delete_vault(vault: String) -> Result<(), String> {
-    let vault = Vault::create_vault(&vault).map_err(|e| e.to_string())?;
-    vault.delete_vault().map_err(|e| e.to_string())
+async fn export_graph(app: AppHandle, vault: Option<Vault>, format: GraphFormat) -> Result<String, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        graph::export_graph(&vault, format).map_err(AppError::from)
+    })
+    .await?
 }
 
+// Starts checking the external links of the vault in the background; progress and the report arrive as
+// `vault://link-check-*` events. URLs checked in the last day are not checked again unless `force` is set.
 #[tauri::command]
-fn parse_markdown_content(content: String) -> Result<String, String> {
-    Ok(markdown::render_markdown(&content))
+async fn check_external_links(app: AppHandle, vault: Option<Vault>, force: Option<bool>) -> Result<(), AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        events::check_links_in_background(&app, &vault, force.unwrap_or(false)).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Broken and redirected external links per note, from the last checks.
+#[tauri::command]
+async fn get_link_report(app: AppHandle, vault: Option<Vault>) -> Result<LinkReport, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        link_rot::link_report(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+// The notes within `depth` links (1 by default) of a note, for the focused graph view of that note.
+#[tauri::command]
+async fn get_local_graph(app: AppHandle, vault: Option<Vault>, title: String, depth: Option<usize>) -> Result<GraphData, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        graph::local_graph(&vault, &title, depth.unwrap_or(graph::LOCAL_GRAPH_DEPTH)).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Orphans, most linked notes and clusters of the note graph, for the vault health panel.
+#[tauri::command]
+async fn graph_stats(app: AppHandle, vault: Option<Vault>, top: Option<usize>) -> Result<GraphStats, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        graph::graph_stats(&vault, top.unwrap_or(graph::MOST_LINKED)).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Returns the note graph with precomputed node positions, cached until the graph changes.
+#[tauri::command]
+async fn get_graph_layout(app: AppHandle, vault: Option<Vault>) -> Result<GraphLayout, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        graph_layout::graph_layout(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Notes linking to a note, from the backlink index; empty in safe mode.
+#[tauri::command]
+async fn get_backlinks(app: AppHandle, vault: Option<Vault>, title: String) -> Result<Vec<String>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let Some(store) = safe_mode.optional_metadata(&vault).map_err(AppError::from)? else {
+            return Ok(Vec::new());
+        };
+        backlinks::get_backlinks(&vault, &store, &title).map_err(AppError::from)
+    })
+    .await?
+}
+
+// People whose name or alias matches a query, with the notes mentioning them; empty in safe mode.
+#[tauri::command]
+async fn find_people(app: AppHandle, vault: Option<Vault>, query: String) -> Result<Vec<Person>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let Some(store) = safe_mode.optional_metadata(&vault).map_err(AppError::from)? else {
+            return Ok(Vec::new());
+        };
+        let people = people::find_people(&vault, &store, &query).map_err(AppError::from)?;
+        Ok(people.into_iter().map(|(person, _)| person).collect())
+    })
+    .await?
+}
+
+// Completes an `@` mention with links to person notes; empty in safe mode.
+#[tauri::command]
+async fn autocomplete_mention(app: AppHandle, vault: Option<Vault>, query: String) -> Result<Vec<Completion>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let Some(store) = safe_mode.optional_metadata(&vault).map_err(AppError::from)? else {
+            return Ok(Vec::new());
+        };
+        people::autocomplete_mention(&vault, &store, &query).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Mentions of a note grouped by source note, with heading breadcrumbs for the backlinks pane.
+#[tauri::command]
+async fn get_backlink_context(app: AppHandle, vault: Option<Vault>, path: String) -> Result<Vec<BacklinkGroup>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        backlinks::backlink_context(&vault, &path).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_note_metadata(app: AppHandle, vault: Option<Vault>, title: String) -> Result<Option<NoteMetadata>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let store = safe_mode.optional_metadata(&vault).map_err(AppError::from)?;
+        Ok(store.and_then(|store| store.get_metadata(&title)))
+    })
+    .await?
+}
+
+// Replaces the metadata of a note; an empty `created_at` keeps the recorded one.
+#[tauri::command]
+async fn set_note_metadata(
+    app: AppHandle,
+    vault: Option<Vault>,
+    title: String,
+    mut metadata: NoteMetadata,
+) -> Result<NoteMetadata, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let store = safe_mode.metadata(&vault).map_err(AppError::from)?;
+        if metadata.created_at.is_empty() {
+            metadata.created_at = store.get_metadata(&title).map(|existing| existing.created_at).unwrap_or_default();
+        }
+        store.update_metadata(&title, metadata.clone()).map_err(AppError::from)?;
+        events::metadata_changed(&app, &vault, Some(&title));
+        Ok(metadata)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn list_templates(app: AppHandle, vault: Option<Vault>) -> Result<Vec<String>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        templates::list_templates(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Prompts of a template, for the frontend to collect before calling `create_from_template`.
+#[tauri::command]
+async fn get_template_prompts(app: AppHandle, vault: Option<Vault>, template: String) -> Result<Vec<TemplatePrompt>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        templates::template_prompts(&vault, &template).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn create_from_template(
+    app: AppHandle,
+    vault: Option<Vault>,
+    template: String,
+    title: String,
+    folder: Option<String>,
+    values: Option<HashMap<String, String>>,
+) -> Result<String, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let folder = folder.unwrap_or_default();
+        let path = templates::create_from_template(&vault, &template, &title, &folder, &values.unwrap_or_default())
+            .map_err(AppError::from)?;
+        events::note_saved(&app, &vault, &path);
+        Ok(path)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_archival_rules(app: AppHandle, vault: Option<Vault>) -> Result<Vec<ArchiveRule>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        archival::load_rules(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn set_archival_rules(app: AppHandle, vault: Option<Vault>, rules: Vec<ArchiveRule>) -> Result<Vec<ArchiveRule>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        archival::save_rules(&vault, rules).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Lists the moves the archival rules would make, without moving anything.
+#[tauri::command]
+async fn preview_archival(app: AppHandle, vault: Option<Vault>) -> Result<ArchivalReport, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        archival::apply_rules(&vault, true).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_activity_log(app: AppHandle, vault: Option<Vault>, limit: Option<usize>) -> Result<Vec<ActivityEntry>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        activity::read_log(&vault, limit).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Applies the archival rules of every vault at startup and then every `ARCHIVAL_INTERVAL`.
+fn spawn_archival_task(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        for vault in all_vaults().unwrap_or_default() {
+            let locks = app.state::<WriteLocks>();
+            let _write = locks.write(&vault);
+            match archival::apply_rules(&vault, false) {
+                Ok(report) => {
+                    for moved in report.moves {
+                        let moved = MoveReport { from: moved.from, to: moved.to, updated: Vec::new() };
+                        events::note_moved(&app, &vault, &moved);
+                    }
+                    if !report.updated.is_empty() {
+                        events::index_updated(&app, &vault, report.updated);
+                    }
+                }
+                Err(e) => println!("❌ Failed to apply the archival rules of {}: {}", vault.name, e),
+            }
+        }
+        std::thread::sleep(archival::ARCHIVAL_INTERVAL);
+    });
+}
+
+#[tauri::command]
+async fn set_word_goal(app: AppHandle, vault: Option<Vault>, daily_words: Option<u64>) -> Result<WritingGoal, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        writing_stats::set_goal(&vault, daily_words).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_writing_progress(app: AppHandle, vault: Option<Vault>) -> Result<Option<WritingProgress>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let Some(store) = safe_mode.optional_metadata(&vault).map_err(AppError::from)? else {
+            return Ok(None);
+        };
+        writing_stats::progress(&vault, &store, writing_stats::today()).map(Some).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_frontmatter(app: AppHandle, vault: Option<Vault>, title: String) -> Result<Properties, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let content = Note::read_note(&vault, &title).map_err(AppError::from)?;
+        frontmatter::parse(&content).map(Properties::from).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_note_by_id(app: AppHandle, vault: Option<Vault>, id: String) -> Result<Note, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let path = lookup::resolve_note_by_id(&vault, &id).map_err(AppError::from)?;
+        let content = Note::read_note(&vault, &path).map_err(AppError::from)?;
+        Ok(Note { title: path, content })
+    })
+    .await?
+}
+
+// Returns the path of the note with a stable id.
+#[tauri::command]
+async fn resolve_note_by_id(app: AppHandle, vault: Option<Vault>, id: String) -> Result<String, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        lookup::resolve_note_by_id(&vault, &id).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Gives an id to every note that has none; returns the paths of the notes that got one.
+#[tauri::command]
+async fn assign_note_ids(app: AppHandle, vault: Option<Vault>) -> Result<Vec<String>, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let assigned = lookup::assign_ids(&vault).map_err(AppError::from)?;
+        events::index_updated(&app, &vault, assigned.clone());
+        Ok(assigned)
+    })
+    .await?
+}
+
+// Resolves a title or alias to a note path; ambiguous titles fail with the list of candidates.
+#[tauri::command]
+async fn resolve_title(app: AppHandle, vault: Option<Vault>, title: String) -> Result<String, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        lookup::resolve_title(&vault, &title).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Plain-text summary of a note for hover cards and notifications.
+#[tauri::command]
+async fn get_excerpt(app: AppHandle, vault: Option<Vault>, title: String, max_length: Option<usize>) -> Result<String, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let content = Note::read_note(&vault, &title).map_err(AppError::from)?;
+        Ok(excerpt::excerpt(&content, max_length.unwrap_or(previews::PREVIEW_LENGTH)))
+    })
+    .await?
+}
+
+// Imports a Standard Notes backup or a Simplenote export into a folder of the vault.
+#[tauri::command]
+async fn import_notes(
+    app: AppHandle,
+    vault: Option<Vault>,
+    source: ImportSource,
+    path: String,
+    folder: Option<String>,
+) -> Result<ImportReport, AppError> {
+    blocking(move || {
+        let history = app.state::<History>();
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let (report, changes) = import::import_notes(&vault, source, Path::new(&path), &folder.unwrap_or_default())
+            .map_err(AppError::from)?;
+        history.record_snapshot("Import notes", &vault, changes);
+        events::index_updated(&app, &vault, report.imported.clone());
+        Ok(report)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn health_report(app: AppHandle, vault: Option<Vault>) -> Result<HealthReport, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        health::health_report(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Folds identical attachments into one and points every link at the kept copy.
+#[tauri::command]
+async fn dedupe_attachments(
+    app: AppHandle,
+    vault: Option<Vault>,
+    dry_run: Option<bool>,
+) -> Result<Outcome<DedupeReport>, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        if dry_run.unwrap_or(false) {
+            return attachment::plan_dedupe(&vault).map(|(_, plan)| Outcome::Planned { plan }).map_err(AppError::from);
+        }
+        let _write = locks.write(&vault);
+        let watchers = app.state::<VaultWatchers>();
+        let _hold = watchers.hold(&vault);
+        let report = attachment::dedupe_attachments(&vault).map_err(AppError::from)?;
+        if !report.groups.is_empty() {
+            events::index_updated(&app, &vault, report.updated.clone());
+        }
+        Ok(Outcome::Done { result: report })
+    })
+    .await?
+}
+
+// Starts reporting notes changed outside the app as note events; call it when a vault is opened.
+#[tauri::command]
+async fn watch_vault(app: AppHandle, vault: Option<Vault>) -> Result<(), AppError> {
+    blocking(move || {
+        let watchers = app.state::<VaultWatchers>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let handle = app.clone();
+        let watched = vault.clone();
+        watchers
+            .watch(&vault, move |change| events::external_change(&handle, &watched, &change))
+            .map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn unwatch_vault(app: AppHandle, vault: Option<Vault>) -> Result<bool, AppError> {
+    blocking(move || {
+        let watchers = app.state::<VaultWatchers>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        Ok(watchers.unwatch(&vault))
+    })
+    .await?
+}
+
+// Exports a note to a PDF at `output_path`; the print style defaults to A4 with page numbers. Returns
+// what the PDF could not show as written, for the user to be told.
+#[tauri::command]
+async fn export_note_pdf(
+    app: AppHandle,
+    vault: Option<Vault>,
+    title: String,
+    output_path: String,
+    style: Option<PrintStyle>,
+) -> Result<PdfReport, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        pdf::export_note_pdf(&vault, &title, &style.unwrap_or_default(), Path::new(&output_path))
+            .map_err(AppError::from)
+    })
+    .await?
+}
+
+// Returns the saved view of a note, preferring the one of `window`; `None` when there is none yet.
+#[tauri::command]
+async fn get_view_state(
+    app: AppHandle,
+    vault: Option<Vault>,
+    title: String,
+    window: Option<String>,
+) -> Result<Option<ViewState>, AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let Some(store) = safe_mode.optional_metadata(&vault).map_err(AppError::from)? else {
+            return Ok(None);
+        };
+        view_state::get_view_state(&store, &title, window.as_deref()).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn set_view_state(
+    app: AppHandle,
+    vault: Option<Vault>,
+    title: String,
+    window: Option<String>,
+    state: ViewState,
+) -> Result<(), AppError> {
+    blocking(move || {
+        let safe_mode = app.state::<SafeMode>();
+        let app_state = app.state::<AppState>();
+        let vault = app_state.vault(vault).map_err(AppError::from)?;
+        let Some(store) = safe_mode.optional_metadata(&vault).map_err(AppError::from)? else {
+            return Ok(());
+        };
+        view_state::set_view_state(&store, &title, window.as_deref(), &state).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Exports the whole vault as a static HTML site; saves wait until the export is written.
+#[tauri::command]
+async fn export_vault_html(app: AppHandle, vault: Option<Vault>, output_dir: String) -> Result<ExportReport, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _snapshot = locks.snapshot(&vault);
+        export::export_vault(&vault, &output_dir).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Checks the vault's custom site templates in `.templates/site/` and lists the ones in use.
+#[tauri::command]
+async fn validate_site_templates(app: AppHandle, vault: Option<Vault>) -> Result<SiteTemplates, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        site_templates::validate(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_vault_config(app: AppHandle, vault: Option<Vault>) -> Result<VaultConfig, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        vault.config().map_err(AppError::from)
+    })
+    .await?
+}
+
+// Saves the vault settings. Notes that changed excluded folders add to or take out of the note list are
+// indexed again, which drops the excluded ones from search and backlinks.
+#[tauri::command]
+async fn set_vault_config(app: AppHandle, vault: Option<Vault>, config: VaultConfig) -> Result<VaultConfig, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let before: HashSet<String> = Note::list_notes(&vault).map_err(AppError::from)?.into_iter().collect();
+        let config = vault.set_config(&config).map_err(AppError::from)?;
+        let after: HashSet<String> = Note::list_notes(&vault).map_err(AppError::from)?.into_iter().collect();
+        let mut changed: Vec<String> = before.symmetric_difference(&after).cloned().collect();
+        changed.sort();
+        events::index_updated(&app, &vault, changed);
+        Ok(config)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn get_lint_config(app: AppHandle, vault: Option<Vault>) -> Result<LintConfig, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        lint::load_config(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn set_lint_config(app: AppHandle, vault: Option<Vault>, config: LintConfig) -> Result<(), AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        lint::save_config(&vault, &config).map_err(AppError::from)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn lint_note(app: AppHandle, vault: Option<Vault>, title: String) -> Result<Vec<Diagnostic>, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let config = lint::load_config(&vault).map_err(AppError::from)?;
+        let content = Note::read_note(&vault, &title).map_err(AppError::from)?;
+        Ok(lint::lint_note(&config, &title, &content))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn lint_vault(app: AppHandle, vault: Option<Vault>) -> Result<LintReport, AppError> {
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        lint::lint_vault(&vault).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Creates a note with a temporary name, to be named later with `finalize_title`.
+#[tauri::command]
+async fn create_untitled_note(
+    app: AppHandle,
+    vault: Option<Vault>,
+    folder: Option<String>,
+    content: Option<String>,
+) -> Result<String, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let path = untitled::create_untitled(&vault, folder.as_deref().unwrap_or(""), content.as_deref().unwrap_or(""))
+            .map_err(AppError::from)?;
+        events::note_saved(&app, &vault, &path);
+        Ok(path)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn finalize_title(app: AppHandle, vault: Option<Vault>, path: String, title: String) -> Result<MoveReport, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let report = untitled::finalize_title(&vault, &path, &title).map_err(AppError::from)?;
+        events::note_moved(&app, &vault, &report);
+        Ok(report)
+    })
+    .await?
+}
+
+// Creates a new vault from an Obsidian vault folder, converting its attachment embeds.
+#[tauri::command]
+async fn import_obsidian_vault(source_path: String, vault_name: String) -> Result<VaultImportReport, AppError> {
+    blocking(move || {
+        import::import_obsidian_vault(Path::new(&source_path), &vault_name).map_err(AppError::from)
+    })
+    .await?
+}
+
+// Copies a file into the vault's attachments and returns the link to insert into the note.
+#[tauri::command]
+async fn attach_file(
+    app: AppHandle,
+    vault: Option<Vault>,
+    note: String,
+    source_path: String,
+) -> Result<AttachedFile, AppError> {
+    blocking(move || {
+        let locks = app.state::<WriteLocks>();
+        let state = app.state::<AppState>();
+        let vault = state.vault(vault).map_err(AppError::from)?;
+        let _write = locks.write(&vault);
+        let attached = attachment::attach_file(&vault, &note, Path::new(&source_path)).map_err(AppError::from)?;
+        events::index_updated(&app, &vault, Vec::new());
+        Ok(attached)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn parse_markdown_content(
+    app: AppHandle,
+    content: String,
+    vault: Option<Vault>,
+    path: Option<String>,
+) -> Result<String, AppError> {
+    blocking(move || {
+        let store = app.state::<SettingsStore>();
+        let link_scheme = store.get().editor.link_scheme;
+        // Images can only be resolved for content that belongs to a note
+        match (vault, path) {
+            (Some(vault), Some(path)) => Note::render_content(&vault, &path, &content, &link_scheme).map_err(AppError::from),
+            _ => Ok(markdown::render_markdown_with_links(&content, &link_scheme)),
+        }
+    })
+    .await?
 }
 
 pub fn run() {
@@ -82,6 +2375,20 @@ pub fn run() {
             // Use the app handle to manage the application state
             let app_handle = app.handle();
 
+            // Load the application settings from the app data directory
+            let settings_path = app_handle.path().app_data_dir()?.join(settings::SETTINGS_FILE);
+            app_handle.manage(SettingsStore::load(settings_path));
+            app_handle.manage(History::default());
+            app_handle.manage(ShareServer::default());
+            app_handle.manage(SafeMode::default());
+            app_handle.manage(SearchIndexes::default());
+            app_handle.manage(WriteLocks::default());
+            app_handle.manage(VaultWatchers::default());
+            app_handle.manage(PublishScheduler::default());
+            app_handle.manage(LinkChecks::default());
+            app_handle.manage(AppState::default());
+            spawn_archival_task(app_handle.clone());
+
             // Log the app starting
             println!("App started!");
 
@@ -103,6 +2410,154 @@ pub fn run() {
             extract_plain_text,
             delete_vault,
             parse_markdown_content,
+            random_note,
+            review_queue,
+            mark_reviewed,
+            find_duplicates,
+            clean_attachments,
+            list_attachments,
+            get_thumbnail,
+            rename_attachment,
+            save_audio_attachment,
+            ocr_attachment,
+            get_ocr_text,
+            configure_publish,
+            publish_notes,
+            get_publish_status,
+            add_bookmark,
+            list_bookmarks,
+            convert_bookmark_to_note,
+            get_outline,
+            move_section,
+            promote_section,
+            demote_section,
+            get_tables,
+            edit_table,
+            get_conflicts,
+            resolve_conflicts,
+            track_time,
+            time_report,
+            log,
+            autocomplete_link,
+            list_snippets,
+            save_snippet,
+            delete_snippet,
+            expand_snippet,
+            get_settings,
+            set_settings,
+            reset_settings,
+            get_keymap,
+            set_keybinding,
+            replace_in_notes,
+            rename_tag,
+            undo_last,
+            redo_last,
+            list_inbox,
+            triage_note,
+            share_temporarily,
+            stop_sharing,
+            list_pinned,
+            pin_note,
+            unpin_note,
+            reorder_pinned,
+            get_previews,
+            get_calendar,
+            generate_index_notes,
+            acquire_note_lock,
+            release_note_lock,
+            who_has_lock,
+            check_vault,
+            recover_vault,
+            get_note_stats,
+            find_in_note,
+            get_file_tree,
+            export_settings,
+            import_settings,
+            stream_notes,
+            move_note,
+            rename_note,
+            save_note,
+            search_notes,
+            rebuild_search_index,
+            get_folder_settings,
+            set_folder_settings,
+            export_note_image,
+            get_note_graph,
+            get_backlinks,
+            get_backlink_context,
+            get_note_metadata,
+            set_note_metadata,
+            list_templates,
+            get_template_prompts,
+            create_from_template,
+            get_archival_rules,
+            set_archival_rules,
+            preview_archival,
+            get_activity_log,
+            set_word_goal,
+            get_writing_progress,
+            create_folder,
+            get_frontmatter,
+            get_note_by_id,
+            resolve_title,
+            get_excerpt,
+            import_notes,
+            health_report,
+            dedupe_attachments,
+            watch_vault,
+            unwatch_vault,
+            export_note_pdf,
+            get_view_state,
+            set_view_state,
+            export_vault_html,
+            get_lint_config,
+            set_lint_config,
+            lint_note,
+            lint_vault,
+            create_untitled_note,
+            finalize_title,
+            import_obsidian_vault,
+            attach_file,
+            start_auto_publish,
+            stop_auto_publish,
+            copy_heading_reference,
+            copy_block_reference,
+            open_daily_note,
+            create_meeting_note,
+            find_people,
+            autocomplete_mention,
+            list_trash,
+            restore_note,
+            empty_trash,
+            get_graph_layout,
+            vault_git_init,
+            vault_git_commit,
+            vault_git_pull,
+            vault_git_push,
+            get_git_config,
+            set_git_config,
+            get_timeline,
+            validate_site_templates,
+            get_vault_config,
+            set_vault_config,
+            open_vault,
+            close_vault,
+            migrate_note_names,
+            rollback_note_names,
+            resolve_note_by_id,
+            assign_note_ids,
+            suggest_tags,
+            create_weekly_review,
+            export_graph,
+            list_snapshots,
+            rollback_snapshot,
+            graph_stats,
+            check_external_links,
+            get_link_report,
+            get_local_graph,
+            export_notes_csv,
+            get_unresolved_links,
+            create_note_from_link,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,560 @@
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, ErrorKind};
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::storage::{note::Note, vault::Vault};
+use crate::utils::{file_operations, filename_policy, markdown, error::AppError, message::Message, plan::Plan};
+
+pub const ATTACHMENTS_DIR: &str = "attachments";
+const TRASHED_ATTACHMENTS_DIR: &str = ".trash/attachments";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentKind {
+    Image,
+    Pdf,
+    Audio,
+    Video,
+    Other,
+}
+
+impl AttachmentKind {
+    // Guesses the kind of an attachment from its file extension.
+    pub fn from_name(name: &str) -> Self {
+        let extension = name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+        match extension.as_str() {
+            "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "svg" => Self::Image,
+            "pdf" => Self::Pdf,
+            "mp3" | "wav" | "ogg" | "m4a" | "flac" | "webm" => Self::Audio,
+            "mp4" | "mov" | "mkv" | "avi" => Self::Video,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub kind: AttachmentKind,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttachmentUsage {
+    #[serde(flatten)]
+    pub attachment: Attachment,
+    pub referenced_by: Vec<String>,
+}
+
+// A file copied into the attachments, with the Markdown to insert into the note it was attached to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttachedFile {
+    pub path: String,
+    pub link: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GarbageReport {
+    pub unreferenced: Vec<Attachment>,
+    pub total_size: u64,
+    pub removed: bool,
+}
+
+// Identical attachments folded into one: links to the removed copies now point at the kept file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub kept: String,
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DedupeReport {
+    pub groups: Vec<DuplicateGroup>,
+    pub bytes_saved: u64,
+    // Notes whose links were rewritten
+    pub updated: Vec<String>,
+}
+
+// Returns the attachments folder of a vault relative to the vault, as set in its config.
+pub fn attachment_folder(vault: &Vault) -> String {
+    vault.config().map(|config| config.attachment_folder).unwrap_or_else(|_| ATTACHMENTS_DIR.to_string())
+}
+
+// Returns the attachments folder of a vault.
+pub fn attachments_dir(vault: &Vault) -> String {
+    format!("{}/{}", vault.path, attachment_folder(vault))
+}
+
+// SHA-256 of a file's content, hex encoded.
+pub fn content_hash(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+fn file_hash(vault: &Vault, attachment: &Attachment) -> io::Result<String> {
+    let bytes = std::fs::read(file_operations::resolve_path(&format!("{}/{}", vault.path, attachment.path)))?;
+    Ok(content_hash(&bytes))
+}
+
+// Finds a stored attachment with exactly this content.
+pub fn find_identical(vault: &Vault, content: &[u8]) -> io::Result<Option<Attachment>> {
+    let hash = content_hash(content);
+    for attachment in list_attachments(vault)? {
+        // Only files of the same size need hashing
+        if attachment.size == content.len() as u64 && file_hash(vault, &attachment)? == hash {
+            return Ok(Some(attachment));
+        }
+    }
+    Ok(None)
+}
+
+// Stores raw bytes as a new attachment, suffixing the name if it is taken. Content that is already stored
+// is not copied again: the existing attachment is reused.
+// Returns the path of the attachment relative to the vault.
+pub fn save_attachment(vault: &Vault, file_name: &str, content: &[u8]) -> io::Result<String> {
+    validate_name(file_name)?;
+    if let Some(existing) = find_identical(vault, content)? {
+        return Ok(existing.path);
+    }
+    let dir = attachments_dir(vault);
+    file_operations::create_directory(&dir)?;
+
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) => (stem, format!(".{}", extension)),
+        None => (file_name, String::new()),
+    };
+    let mut name = file_name.to_string();
+    let mut counter = 1;
+    while Path::new(&file_operations::resolve_path(&format!("{}/{}", dir, name))).exists() {
+        name = format!("{}-{}{}", stem, counter, extension);
+        counter += 1;
+    }
+
+    file_operations::write_bytes_to_file(&format!("{}/{}", dir, name), content)?;
+    Ok(format!("{}/{}", attachment_folder(vault), name))
+}
+
+// Builds the Markdown pointing at an attachment from a note in `folder`: an image for pictures, a link
+// for other files.
+pub fn markdown_link(folder: &str, path: &str, label: &str) -> String {
+    let dest = markdown::relative_path(folder, path).replace(' ', "%20");
+    match AttachmentKind::from_name(path) {
+        AttachmentKind::Image => format!("![{}]({})", label, dest),
+        _ => format!("[{}]({})", label, dest),
+    }
+}
+
+// Copies a file into the attachments of the vault for a note, returning the stored path and the link
+// to insert, relative to the note's folder.
+pub fn attach_file(vault: &Vault, note: &str, source: &Path) -> io::Result<AttachedFile> {
+    let note = note.trim_matches('/');
+    if !Path::new(&file_operations::resolve_path(&format!("{}/{}.md", vault.path, note))).is_file() {
+        return Err(Message::new("note.not_found", "Note file does not exist").error(ErrorKind::NotFound));
+    }
+    if !source.is_file() {
+        return Err(Message::new("attachment.source_not_found", "File to attach not found").error(ErrorKind::NotFound));
+    }
+    let file_name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| io::Error::from(AppError::InvalidName(Message::new("attachment.invalid_name", "Invalid attachment name"))))?;
+
+    let path = save_attachment(vault, &file_name, &std::fs::read(source)?)?;
+    let folder = note.rsplit_once('/').map(|(folder, _)| folder).unwrap_or("");
+    let label = file_name.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(&file_name);
+    let link = markdown_link(folder, &path, label);
+    Ok(AttachedFile { path, link })
+}
+
+// Lists every file stored in the attachments folder, with paths relative to the vault.
+pub fn list_attachments(vault: &Vault) -> io::Result<Vec<Attachment>> {
+    let root = file_operations::resolve_path(&vault.path);
+    let dir = file_operations::resolve_path(&attachments_dir(vault));
+    if !Path::new(&dir).exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut attachments = Vec::new();
+    for entry in WalkDir::new(&dir).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+        let name = entry.file_name().to_string_lossy().to_string();
+        attachments.push(Attachment {
+            kind: AttachmentKind::from_name(&name),
+            name,
+            path: path.to_string_lossy().replace('\\', "/"),
+            size: entry.metadata()?.len(),
+        });
+    }
+    Ok(attachments)
+}
+
+// Maps each referenced file, by its path relative to the vault, to the notes referencing it. Targets are
+// resolved from the folder of each note, so files of the same name in other folders are told apart.
+pub fn attachment_references(vault: &Vault) -> io::Result<HashMap<String, Vec<String>>> {
+    let attachment_folder = attachment_folder(vault);
+    let mut references: HashMap<String, Vec<String>> = HashMap::new();
+    for title in Note::list_notes(vault)? {
+        let content = Note::read_note(vault, &title)?;
+        let folder = title.rsplit_once('/').map(|(folder, _)| folder).unwrap_or("");
+        for target in markdown::resolve_local_targets(&content, folder, &attachment_folder) {
+            let notes = references.entry(target).or_default();
+            if !notes.contains(&title) {
+                notes.push(title.clone());
+            }
+        }
+    }
+    Ok(references)
+}
+
+// Collects the paths of all attachments referenced from the vault's notes.
+pub fn referenced_attachments(vault: &Vault) -> io::Result<HashSet<String>> {
+    Ok(attachment_references(vault)?.into_keys().collect())
+}
+
+// Lists attachments together with the notes that reference them.
+pub fn attachment_usages(vault: &Vault) -> io::Result<Vec<AttachmentUsage>> {
+    let mut references = attachment_references(vault)?;
+    Ok(list_attachments(vault)?
+        .into_iter()
+        .map(|attachment| AttachmentUsage {
+            referenced_by: references.remove(&attachment.path).unwrap_or_default(),
+            attachment,
+        })
+        .collect())
+}
+
+// Renames an attachment and rewrites every note linking to it. Returns the updated notes.
+pub fn rename_attachment(vault: &Vault, path: &str, new_name: &str) -> io::Result<Vec<String>> {
+    validate_name(new_name)?;
+
+    let old_path = format!("{}/{}", vault.path, path);
+    if !Path::new(&file_operations::resolve_path(&old_path)).exists() {
+        return Err(Message::new("attachment.not_found", "Attachment does not exist").error(ErrorKind::NotFound));
+    }
+    check_inside(vault, path)?;
+    let parent = path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("");
+    let new_path = if parent.is_empty() {
+        format!("{}/{}", vault.path, new_name)
+    } else {
+        format!("{}/{}/{}", vault.path, parent, new_name)
+    };
+    if Path::new(&file_operations::resolve_path(&new_path)).exists() {
+        return Err(Message::new("attachment.already_exists", "An attachment with this name already exists").error(ErrorKind::AlreadyExists));
+    }
+
+    file_operations::rename_file(&old_path, &new_path)?;
+
+    let attachment_folder = attachment_folder(vault);
+    let renamed = if parent.is_empty() { new_name.to_string() } else { format!("{}/{}", parent, new_name) };
+    let mut updated = Vec::new();
+    for title in Note::list_notes(vault)? {
+        let content = Note::read_note(vault, &title)?;
+        let folder = title.rsplit_once('/').map(|(folder, _)| folder).unwrap_or("");
+        let rewritten = markdown::retarget_attachment_links(&content, folder, &attachment_folder, path, &renamed);
+        if rewritten != content {
+            Note::update_note(vault, &title, &rewritten)?;
+            updated.push(title);
+        }
+    }
+    Ok(updated)
+}
+
+// Plans folding identical attachments together; the report tells what the plan achieves. Links are
+// rewritten before any copy is deleted, so a failure part way leaves no link pointing at a deleted file.
+pub fn plan_dedupe(vault: &Vault) -> io::Result<(DedupeReport, Plan)> {
+    let mut by_content: BTreeMap<String, Vec<Attachment>> = BTreeMap::new();
+    let attachments = list_attachments(vault)?;
+    let mut sizes: HashMap<u64, usize> = HashMap::new();
+    for attachment in &attachments {
+        *sizes.entry(attachment.size).or_default() += 1;
+    }
+    for attachment in attachments.into_iter().filter(|attachment| sizes[&attachment.size] > 1) {
+        let hash = file_hash(vault, &attachment)?;
+        by_content.entry(hash).or_default().push(attachment);
+    }
+
+    let mut report = DedupeReport::default();
+    let mut plan = Plan::default();
+    let mut removed = Vec::new();
+    let mut renames = Vec::new();
+    for (_, mut copies) in by_content.into_iter().filter(|(_, copies)| copies.len() > 1) {
+        copies.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+        let kept = copies.remove(0);
+        for copy in &copies {
+            report.bytes_saved += copy.size;
+            removed.push((copy.path.clone(), copy.size));
+            renames.push((copy.path.clone(), kept.path.clone()));
+        }
+        report.groups.push(DuplicateGroup {
+            kept: kept.path,
+            removed: copies.into_iter().map(|copy| copy.path).collect(),
+        });
+    }
+    if renames.is_empty() {
+        return Ok((report, plan));
+    }
+
+    let attachment_folder = attachment_folder(vault);
+    for title in Note::list_notes(vault)? {
+        let content = Note::read_note(vault, &title)?;
+        let folder = title.rsplit_once('/').map(|(folder, _)| folder).unwrap_or("");
+        let rewritten = renames.iter().fold(content.clone(), |content, (old, new)| {
+            markdown::retarget_attachment_links(&content, folder, &attachment_folder, old, new)
+        });
+        if rewritten != content {
+            plan.write(&format!("{}.md", title), &rewritten);
+            report.updated.push(title);
+        }
+    }
+    for (path, size) in removed {
+        plan.delete(&path, size);
+    }
+    Ok((report, plan))
+}
+
+// Folds identical attachments into one, keeping the first by name, and points the links of every note
+// at the kept file.
+pub fn dedupe_attachments(vault: &Vault) -> io::Result<DedupeReport> {
+    let (report, plan) = plan_dedupe(vault)?;
+    plan.apply(vault)?;
+    Ok(report)
+}
+
+// Rejects attachment names that are empty, hidden, contain path separators or are reserved by Windows.
+fn validate_name(name: &str) -> io::Result<()> {
+    if name.trim().is_empty() || name.contains(['/', '\\', ':', '\0']) || name.starts_with('.') {
+        return Err(AppError::InvalidName(Message::new("attachment.invalid_name", "Invalid attachment name")).into());
+    }
+    filename_policy::check_path(name)
+}
+
+// Refuses an existing attachment path that leads outside the vault, through `..`, an absolute or drive
+// path, or a symlinked folder.
+fn check_inside(vault: &Vault, path: &str) -> io::Result<()> {
+    let invalid = || -> io::Error { AppError::InvalidName(Message::new("attachment.invalid_path", "Invalid attachment path")).into() };
+    let has_bad_part = path.split(['/', '\\']).any(|part| matches!(part, "" | "." | "..") || part.contains(':'));
+    if path.is_empty() || has_bad_part {
+        return Err(invalid());
+    }
+    let root = std::fs::canonicalize(file_operations::resolve_path(&vault.path))?;
+    let file = std::fs::canonicalize(file_operations::resolve_path(&format!("{}/{}", vault.path, path)))?;
+    if !file.starts_with(&root) {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+// Plans deleting attachments, or moving them to the trash under names no trashed file (or other planned
+// move) has yet: `name.png`, then `name-2.png`…
+fn garbage_plan(vault: &Vault, unreferenced: &[Attachment], trash: bool) -> Plan {
+    let mut plan = Plan::default();
+    let mut taken: HashSet<String> = HashSet::new();
+    for attachment in unreferenced {
+        if !trash {
+            plan.delete(&attachment.path, attachment.size);
+            continue;
+        }
+        let (stem, extension) = match attachment.name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+            _ => (attachment.name.as_str(), String::new()),
+        };
+        let mut name = attachment.name.clone();
+        let mut suffix = 2;
+        while taken.contains(&name)
+            || Path::new(&file_operations::resolve_path(&format!("{}/{}/{}", vault.path, TRASHED_ATTACHMENTS_DIR, name))).exists()
+        {
+            name = format!("{}-{}{}", stem, suffix, extension);
+            suffix += 1;
+        }
+        plan.move_file(&attachment.path, &format!("{}/{}", TRASHED_ATTACHMENTS_DIR, name), attachment.size);
+        taken.insert(name);
+    }
+    plan
+}
+
+// Plans removing the attachments no note refers to, or moving them to the trash.
+pub fn plan_garbage(vault: &Vault, trash: bool) -> io::Result<Plan> {
+    Ok(garbage_plan(vault, &unreferenced_attachments(vault)?, trash))
+}
+
+// Lists attachments that no note refers to.
+pub fn unreferenced_attachments(vault: &Vault) -> io::Result<Vec<Attachment>> {
+    let referenced = referenced_attachments(vault)?;
+    Ok(list_attachments(vault)?
+        .into_iter()
+        .filter(|attachment| !referenced.contains(&attachment.path))
+        .collect())
+}
+
+// Reports unreferenced attachments and, once confirmed, deletes them or moves them to the trash.
+pub fn collect_garbage(vault: &Vault, confirm: bool, trash: bool) -> io::Result<GarbageReport> {
+    let unreferenced = unreferenced_attachments(vault)?;
+    let total_size = unreferenced.iter().map(|attachment| attachment.size).sum();
+
+    if confirm {
+        garbage_plan(vault, &unreferenced, trash).apply(vault)?;
+    }
+
+    Ok(GarbageReport {
+        unreferenced,
+        total_size,
+        removed: confirm,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::note::OnConflict;
+    use crate::utils::plan::ChangeKind;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_collect_garbage() {
+        file_operations::set_base_path(None);
+        let mut vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&attachments_dir(&vault)).unwrap();
+        file_operations::write_to_file(&format!("{}/used.png", attachments_dir(&vault)), "png").unwrap();
+        file_operations::write_to_file(&format!("{}/unused.png", attachments_dir(&vault)), "unused").unwrap();
+        Note::new("", "Picture here ![img](attachments/used.png)").create_note(&mut vault, OnConflict::Fail).unwrap();
+
+        let report = collect_garbage(&vault, false, false).unwrap();
+        assert_eq!(report.unreferenced.len(), 1);
+        assert_eq!(report.unreferenced[0].path, "attachments/unused.png");
+        assert_eq!(report.total_size, 6);
+        assert_eq!(list_attachments(&vault).unwrap().len(), 2);
+
+        let plan = plan_garbage(&vault, true).unwrap();
+        assert_eq!(plan.changes[0].to.as_deref(), Some(format!("{}/unused.png", TRASHED_ATTACHMENTS_DIR).as_str()));
+        assert_eq!(list_attachments(&vault).unwrap().len(), 2);
+
+        let report = collect_garbage(&vault, true, true).unwrap();
+        assert!(report.removed);
+        assert_eq!(list_attachments(&vault).unwrap().len(), 1);
+        assert!(Path::new(&format!("{}/{}/unused.png", vault.path, TRASHED_ATTACHMENTS_DIR)).exists());
+
+        // Files of the same name are told apart by folder, and never overwrite each other in the trash
+        for folder in ["a", "b", "c"] {
+            file_operations::create_directory(&format!("{}/{}", attachments_dir(&vault), folder)).unwrap();
+            file_operations::write_to_file(&format!("{}/{}/unused.png", attachments_dir(&vault), folder), folder).unwrap();
+        }
+        file_operations::write_to_file(&format!("{}/Trips.md", vault.path), "![map](attachments/b/unused.png)").unwrap();
+        let report = collect_garbage(&vault, true, true).unwrap();
+        let paths: Vec<&str> = report.unreferenced.iter().map(|attachment| attachment.path.as_str()).collect();
+        assert_eq!(paths, vec!["attachments/a/unused.png", "attachments/c/unused.png"]);
+        let trashed = |name: &str| file_operations::read_from_file(&format!("{}/{}/{}", vault.path, TRASHED_ATTACHMENTS_DIR, name)).unwrap();
+        assert_eq!((trashed("unused.png"), trashed("unused-2.png"), trashed("unused-3.png")), ("unused".to_string(), "a".to_string(), "c".to_string()));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_rename_attachment_rewrites_links() {
+        file_operations::set_base_path(None);
+        let mut vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&attachments_dir(&vault)).unwrap();
+        file_operations::write_to_file(&format!("{}/old.png", attachments_dir(&vault)), "png").unwrap();
+        Note::new("Linked-image-here", "Linked image here ![img](attachments/old.png) and ![[old.png]]").create_note(&mut vault, OnConflict::Fail).unwrap();
+
+        let usages = attachment_usages(&vault).unwrap();
+        assert_eq!(usages[0].attachment.kind, AttachmentKind::Image);
+        assert_eq!(usages[0].referenced_by, vec!["Linked-image-here"]);
+
+        let updated = rename_attachment(&vault, "attachments/old.png", "new.png").unwrap();
+        assert_eq!(updated, vec!["Linked-image-here"]);
+        let content = Note::read_note(&vault, "Linked-image-here").unwrap();
+        assert_eq!(content, "Linked image here ![img](attachments/new.png) and ![[new.png]]");
+        assert_eq!(list_attachments(&vault).unwrap()[0].name, "new.png");
+
+        // Neither the attachment nor its new name may lead out of the attachment's folder
+        for name in ["../new.png", "..", "sub/new.png", "C:new.png"] {
+            let error = AppError::from(rename_attachment(&vault, "attachments/new.png", name).unwrap_err());
+            assert_eq!(error.code(), "invalid_name", "{} should be refused", name);
+        }
+        file_operations::write_to_file(&format!("{}/outside.png", vault.path), "png").unwrap();
+        let error = AppError::from(rename_attachment(&vault, "attachments/../outside.png", "moved.png").unwrap_err());
+        assert_eq!(error.code(), "invalid_name");
+        assert!(Path::new(&format!("{}/outside.png", vault.path)).exists());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_dedupe_attachments() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        assert_eq!(save_attachment(&vault, "photo.png", b"same").unwrap(), "attachments/photo.png");
+        assert_eq!(save_attachment(&vault, "pasted.png", b"same").unwrap(), "attachments/photo.png");
+        assert_eq!(save_attachment(&vault, "photo.png", b"other").unwrap(), "attachments/photo-1.png");
+
+        // Copies stored before deduplication existed, one in another folder, next to an unrelated file of
+        // the same name as a copy
+        file_operations::create_directory(&format!("{}/trips", attachments_dir(&vault))).unwrap();
+        file_operations::write_to_file(&format!("{}/copy.png", attachments_dir(&vault)), "same").unwrap();
+        file_operations::write_to_file(&format!("{}/trips/copy.png", attachments_dir(&vault)), "same").unwrap();
+        file_operations::write_to_file(&format!("{}/trips/photo.png", attachments_dir(&vault)), "trip").unwrap();
+        let trip = "![a](attachments/photo.png) ![[copy.png]] ![b](attachments/trips/photo.png)";
+        file_operations::write_to_file(&format!("{}/Trip.md", vault.path), trip).unwrap();
+        file_operations::write_to_file(&format!("{}/Other.md", vault.path), "![[attachments/trips/copy.png]]").unwrap();
+
+        // Links are rewritten before the copies are deleted
+        let (_, plan) = plan_dedupe(&vault).unwrap();
+        let changes: Vec<(ChangeKind, &str)> = plan.changes.iter().map(|change| (change.kind, change.path.as_str())).collect();
+        assert_eq!(
+            changes,
+            vec![
+                (ChangeKind::Write, "Other.md"),
+                (ChangeKind::Write, "Trip.md"),
+                (ChangeKind::Delete, "attachments/trips/copy.png"),
+                (ChangeKind::Delete, "attachments/photo.png"),
+            ]
+        );
+        assert_eq!(list_attachments(&vault).unwrap().len(), 5);
+
+        let report = dedupe_attachments(&vault).unwrap();
+        let removed = vec!["attachments/trips/copy.png".to_string(), "attachments/photo.png".to_string()];
+        assert_eq!(report.groups, vec![DuplicateGroup { kept: "attachments/copy.png".to_string(), removed }]);
+        assert_eq!(report.bytes_saved, 8);
+        assert_eq!(report.updated, vec!["Other", "Trip"]);
+        assert_eq!(Note::read_note(&vault, "Trip").unwrap(), "![a](attachments/copy.png) ![[copy.png]] ![b](attachments/trips/photo.png)");
+        assert_eq!(Note::read_note(&vault, "Other").unwrap(), "![[copy.png]]");
+        assert_eq!(list_attachments(&vault).unwrap().len(), 3);
+        assert!(dedupe_attachments(&vault).unwrap().groups.is_empty());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_attach_file() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Trips", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Trips/Rome.md", vault.path), "Day one").unwrap();
+        let source = std::env::temp_dir().join(format!("{} beach.png", nanoid!(8, &['a', 'b', 'c'])));
+        std::fs::write(&source, b"png").unwrap();
+        let name = source.file_name().unwrap().to_string_lossy().to_string();
+
+        let attached = attach_file(&vault, "Trips/Rome", &source).unwrap();
+        assert_eq!(attached.path, format!("attachments/{}", name));
+        assert_eq!(attached.link, format!("![{}](../attachments/{})", name.trim_end_matches(".png"), name.replace(' ', "%20")));
+        // The same file attached again reuses the stored copy
+        assert_eq!(attach_file(&vault, "Trips/Rome", &source).unwrap().path, attached.path);
+
+        assert_eq!(attach_file(&vault, "Missing", &source).unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(attach_file(&vault, "Trips/Rome", Path::new("/missing/file.pdf")).unwrap_err().kind(), ErrorKind::NotFound);
+
+        // Cleanup
+        std::fs::remove_file(&source).unwrap();
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
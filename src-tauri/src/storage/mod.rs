@@ -1,2 +1,4 @@
 pub mod vault;
-pub mod note;
\ No newline at end of file
+pub mod note;
+pub mod attachment;
+pub mod trash;
\ No newline at end of file
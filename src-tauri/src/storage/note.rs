@@ -1,10 +1,11 @@
 use serde::{Serialize, Deserialize};
 use std::path::Path;
-use std::io::{self, Error, ErrorKind};
+use std::io::{self, ErrorKind};
 use nanoid::nanoid;
+use walkdir::WalkDir;
 
-use crate::utils::{file_operations, string_utils, markdown};
-use crate::storage::vault::Vault;
+use crate::utils::{file_operations, filename_policy, string_utils, markdown, error::AppError, message::Message};
+use crate::storage::{attachment, trash, vault::{Vault, VaultConfig}};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Note {
@@ -12,6 +13,75 @@ pub struct Note {
     pub content: String,
 }
 
+// An entry of the vault tree: folders list their subfolders first, then their notes, both sorted by name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum NoteEntry {
+    Folder {
+        name: String,
+        // Path relative to the vault
+        path: String,
+        children: Vec<NoteEntry>,
+    },
+    File {
+        title: String,
+        // Path relative to the vault, without the `.md` extension
+        path: String,
+    },
+}
+
+// What creating a note does when another note already has its file name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnConflict {
+    // Fail with a `note.already_exists` error
+    #[default]
+    Fail,
+    // Add `-2`, `-3`… to the name until it is free
+    Suffix,
+}
+
+// Hidden folders hold app data (metadata, templates, config) and are not part of the note tree
+fn is_hidden(name: &std::ffi::OsStr) -> bool {
+    name.to_string_lossy().starts_with('.')
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/")
+}
+
+fn list_entries(root: &Path, dir: &Path, config: &VaultConfig) -> io::Result<Vec<NoteEntry>> {
+    let mut folders = Vec::new();
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if is_hidden(&entry.file_name()) || config.is_excluded(&relative_path(root, &path)) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            folders.push(NoteEntry::Folder {
+                name: entry.file_name().to_string_lossy().to_string(),
+                path: relative_path(root, &path),
+                children: list_entries(root, &path, config)?,
+            });
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            files.push(NoteEntry::File {
+                title: path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+                path: relative_path(root, &path.with_extension("")),
+            });
+        }
+    }
+
+    let name = |entry: &NoteEntry| match entry {
+        NoteEntry::Folder { name, .. } | NoteEntry::File { title: name, .. } => name.to_lowercase(),
+    };
+    folders.sort_by_key(name);
+    files.sort_by_key(name);
+    folders.extend(files);
+    Ok(folders)
+}
+
 impl Note {
     #[allow(dead_code)]
     pub fn new(title: &str, content: &str) -> Self {
@@ -21,25 +91,62 @@ impl Note {
         }
     }
 
-    pub fn generate_file_name(content: &str) -> String {
-        let words: Vec<&str> = content.split_whitespace().collect();
-        let file_name = if words.len() >= 3 {
-            format!("{}-{}-{}", words[0], words[1], words[2])
-        } else {
-            content.to_string()
-        };
-        string_utils::sanitize_filename(&file_name)
+    // The file name a note with this title gets: the sanitized title, or an `untitled_` id when nothing of
+    // the title is left. Notes are keyed by their file name, so editing the content never moves them, and
+    // the name is the one `path` gives the title, so the note can be read back by it.
+    pub fn file_name_for(title: &str) -> String {
+        match string_utils::title_filename(title) {
+            name if name.is_empty() => format!("untitled_{}", nanoid!()),
+            name => name,
+        }
     }
 
-    pub fn create_note(&self, vault: &mut Vault) -> io::Result<()> {
-        let _title = if self.title.trim().is_empty() {
-            let id = nanoid!();
-            format!("untitled_{}", id)
-        } else {
-            self.title.clone()
+    // The vault-relative path the note is stored at: its title, which may include folders.
+    pub fn path(&self) -> String {
+        string_utils::sanitize_path(&self.title)
+    }
+
+    // The note a vault-relative path would collide with, as spelled on disk: the note at the path, or one
+    // whose path differs only in case (see `filename_policy`).
+    pub fn taken_by(vault: &Vault, path: &str) -> io::Result<Option<String>> {
+        let existing = filename_policy::find_ignoring_case(&vault.path, &format!("{}.md", path))?;
+        Ok(existing.map(|existing| existing.trim_end_matches(".md").to_string()))
+    }
+
+    // The error for a note path taken by `existing`: `note.already_exists`, or `note.case_conflict` when
+    // the two differ only in case.
+    pub fn conflict_error(path: &str, existing: &str) -> io::Error {
+        if path == existing {
+            return Message::new("note.already_exists", "Note {path} already exists").with("path", path).error(ErrorKind::AlreadyExists);
+        }
+        AppError::AlreadyExists(
+            Message::new("note.case_conflict", "Note {path} differs only in case from {existing}")
+                .with("path", path)
+                .with("existing", existing),
+        )
+        .into()
+    }
+
+    // Returns a vault-relative note path no note has yet: `path` itself, or, when it is taken and
+    // conflicts are suffixed, `path-2`, `path-3`… Names reserved by Windows are refused.
+    pub fn free_path(vault: &Vault, path: &str, on_conflict: OnConflict) -> io::Result<String> {
+        filename_policy::check_path(path)?;
+        let Some(existing) = Self::taken_by(vault, path)? else {
+            return Ok(path.to_string());
         };
+        if on_conflict == OnConflict::Fail {
+            return Err(Self::conflict_error(path, &existing));
+        }
+        let mut suffix = 2;
+        while Self::taken_by(vault, &format!("{}-{}", path, suffix))?.is_some() {
+            suffix += 1;
+        }
+        Ok(format!("{}-{}", path, suffix))
+    }
 
-        let file_name = Self::generate_file_name(&self.content);
+    // Creates the note in the vault root under its title. Returns the file name used.
+    pub fn create_note(&self, vault: &mut Vault, on_conflict: OnConflict) -> io::Result<String> {
+        let file_name = Self::free_path(vault, &Self::file_name_for(&self.title), on_conflict)?;
         let clean_content = string_utils::normalize_whitespace(&self.content);
 
         // Use file_operations::create_directory instead of std::fs::create_dir_all
@@ -50,75 +157,130 @@ impl Note {
         file_operations::write_to_file(&note_path, &clean_content)?;
 
         if !Path::new(&note_path).exists() {
-            return Err(Error::new(ErrorKind::Other, format!("❌ File was not created: {}", note_path)));
+            return Err(Message::new("note.not_created", "File was not created: {path}").with("path", &note_path).error(ErrorKind::Other));
         }
 
         // Use file_operations::read_from_file instead of std::fs::read_to_string
         let verify_content = file_operations::read_from_file(&note_path)?;
         if verify_content.is_empty() {
-            return Err(Error::new(ErrorKind::Other, "❌ File was created but is empty"));
+            return Err(Message::new("note.created_empty", "File was created but is empty").error(ErrorKind::Other));
         }
 
-        Ok(())
+        Ok(file_name)
     }
 
-    pub fn read_note(vault: &Vault, file_name: &str) -> io::Result<String> {
-        let safe_file_name = string_utils::sanitize_filename(file_name);
-        let note_path = format!("{}/{}.md", vault.path, safe_file_name);
+    // Creates a note under an explicit title, keeping the content as is. Returns the file name used.
+    pub fn create_named_note(vault: &Vault, title: &str, content: &str) -> io::Result<String> {
+        let file_name = string_utils::title_filename(title);
+        if file_name.is_empty() {
+            return Err(AppError::InvalidName(Message::new("note.title_empty", "Note title is empty")).into());
+        }
 
-        if !Path::new(&note_path).exists() {
-            return Err(Error::new(ErrorKind::NotFound, "❌ Note file does not exist"));
+        filename_policy::check_path(&file_name)?;
+        if let Some(existing) = Self::taken_by(vault, &file_name)? {
+            return Err(Self::conflict_error(&file_name, &existing));
+        }
+
+        let note_path = format!("{}/{}.md", vault.path, file_name);
+
+        file_operations::create_directory(&vault.path)?;
+        file_operations::write_to_file(&note_path, content)?;
+        Ok(file_name)
+    }
+
+    // Reads a note by its path relative to the vault, e.g. `projects/ideas/my-note`.
+    pub fn read_note(vault: &Vault, path: &str) -> io::Result<String> {
+        let safe_path = string_utils::sanitize_path(path);
+        let note_path = format!("{}/{}.md", vault.path, safe_path);
+
+        if safe_path.is_empty() || !Path::new(&note_path).exists() {
+            return Err(Message::new("note.not_found", "Note file does not exist").error(ErrorKind::NotFound));
         }
 
         // Use file_operations::read_from_file instead of std::fs::read_to_string
         file_operations::read_from_file(&note_path)
     }
 
+    // Overwrites the content of an existing note.
+    pub fn update_note(vault: &Vault, path: &str, content: &str) -> io::Result<()> {
+        let safe_path = string_utils::sanitize_path(path);
+        let note_path = format!("{}/{}.md", vault.path, safe_path);
+
+        if safe_path.is_empty() || !Path::new(&note_path).exists() {
+            return Err(Message::new("note.not_found", "Note file does not exist").error(ErrorKind::NotFound));
+        }
+
+        file_operations::write_to_file(&note_path, content)
+    }
+
     pub fn delete_note(&self, vault: &mut Vault) -> io::Result<()> {
-        let file_name = Self::generate_file_name(&self.content);
+        let file_name = self.path();
         let note_path = format!("{}/{}.md", vault.path, file_name);
 
-        if Path::new(&note_path).exists() {
-            // Use file_operations::delete_file instead of std::fs::remove_file
-            file_operations::delete_file(&note_path)?;
+        if !file_name.is_empty() && Path::new(&note_path).exists() {
+            // Deleted notes go to the vault's trash, where they can be restored from
+            trash::move_to_trash(vault, &file_name)?;
         } else {
-            return Err(Error::new(ErrorKind::NotFound, "❌ Note file does not exist"));
+            return Err(Message::new("note.not_found", "Note file does not exist").error(ErrorKind::NotFound));
         }
 
         Ok(())
     }
 
+    // Lists every note of the vault, in folders too, as vault-relative paths without extension. Notes in
+    // the vault's excluded folders are left out.
     pub fn list_notes(vault: &Vault) -> io::Result<Vec<String>> {
-        // Use file_operations::read_dir (if implemented) or keep using std::fs::read_dir
-        let paths = std::fs::read_dir(&vault.path)?;
-        Ok(paths
-            .filter_map(|entry| entry.ok().map(|e| e.file_name().into_string().ok()))
-            .flatten()
-            .filter(|name| name.ends_with(".md"))
-            .map(|name| name.trim_end_matches(".md").to_string())
-            .collect())
+        Self::walk_notes(vault, true)
     }
 
-    pub fn render_html(&self, vault: &Vault) -> Result<String, String> {
-        let file_name = Self::generate_file_name(&self.content);
-        let content = Self::read_note(vault, &file_name).map_err(|e| e.to_string())?;
-        Ok(markdown::render_markdown(&content))
+    // Lists every note on disk, the ones in excluded folders included, for the data kept about notes that
+    // must not be dropped just because a folder is excluded.
+    pub fn list_all_notes(vault: &Vault) -> io::Result<Vec<String>> {
+        Self::walk_notes(vault, false)
     }
 
-    #[allow(dead_code)]
-    pub fn rename_note(&mut self, vault: &mut Vault, new_title: &str) -> io::Result<()> {
-        let old_file_name = Self::generate_file_name(&self.content);
-        let old_file_path = format!("{}/{}.md", vault.path, old_file_name);
-
-        self.title = new_title.to_string();
+    fn walk_notes(vault: &Vault, skip_excluded: bool) -> io::Result<Vec<String>> {
+        let root = file_operations::resolve_path(&vault.path);
+        let config = vault.config()?;
+        let walker = WalkDir::new(&root).sort_by_file_name().into_iter().filter_entry(|entry| {
+            entry.depth() == 0
+                || !(is_hidden(entry.file_name())
+                    || (skip_excluded && config.is_excluded(&relative_path(Path::new(&root), entry.path()))))
+        });
+
+        let mut paths = Vec::new();
+        for entry in walker {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type().is_file() && path.extension().is_some_and(|ext| ext == "md") {
+                paths.push(relative_path(Path::new(&root), &path.with_extension("")));
+            }
+        }
+        Ok(paths)
+    }
 
-        let new_file_name = Self::generate_file_name(&self.content);
-        let new_file_path = format!("{}/{}.md", vault.path, new_file_name);
+    // Lists the folders and notes of the vault as a tree.
+    pub fn list_tree(vault: &Vault) -> io::Result<Vec<NoteEntry>> {
+        let root = file_operations::resolve_path(&vault.path);
+        list_entries(Path::new(&root), Path::new(&root), &vault.config()?)
+    }
 
-        // Use file_operations::rename_file instead of std::fs::rename
-        file_operations::rename_file(&old_file_path, &new_file_path)?;
+    // Renders the note to HTML; wikilinks point at `<link_scheme>://Target`.
+    pub fn render_html(&self, vault: &Vault, link_scheme: &str) -> io::Result<String> {
+        let file_name = self.path();
+        let content = Self::read_note(vault, &file_name)?;
+        Self::render_content(vault, &file_name, &content, link_scheme)
+    }
 
-        Ok(())
+    // Renders Markdown of the note at `path` to HTML, with note embeds resolved and images relative to
+    // the note served from the vault folder.
+    pub fn render_content(vault: &Vault, path: &str, content: &str, link_scheme: &str) -> io::Result<String> {
+        let content = markdown::resolve_embeds(content, |title| Self::read_note(vault, title).ok());
+        let vault_dir = vault.absolute_path()?;
+        let note_folder = path.trim_matches('/').rsplit_once('/').map(|(folder, _)| folder).unwrap_or("");
+        let attachment_folder = attachment::attachment_folder(vault);
+        let assets = markdown::AssetBase { vault_dir: &vault_dir, note_folder, attachment_folder: &attachment_folder };
+        Ok(markdown::render_note_markdown(&content, link_scheme, &assets))
     }
 }
 
@@ -129,10 +291,29 @@ mod tests {
 
     #[test]
     fn test_create_note() {
+        file_operations::set_base_path(None);
         let vault_name = format!("test_vault_{}", nanoid!());
         let mut vault = Vault::create_vault(&vault_name).unwrap();
         let note = Note::new("Test Note", "This is a test note content.");
-        assert!(note.create_note(&mut vault).is_ok());
+        assert!(note.create_note(&mut vault, OnConflict::Fail).is_ok());
+        let untitled = Note::new(" ", "Content never names the file").create_note(&mut vault, OnConflict::Fail).unwrap();
+        assert!(untitled.starts_with("untitled_"));
+
+        // A taken name is never overwritten
+        let same_title = Note::new("Test Note", "Other content");
+        assert_eq!(same_title.create_note(&mut vault, OnConflict::Fail).unwrap_err().kind(), ErrorKind::AlreadyExists);
+        assert_eq!(Note::read_note(&vault, "Test Note").unwrap(), "This is a test note content.");
+        assert_eq!(same_title.create_note(&mut vault, OnConflict::Suffix).unwrap(), "Test Note-2");
+        assert_eq!(same_title.create_note(&mut vault, OnConflict::Suffix).unwrap(), "Test Note-3");
+
+        // Titles differing only in case are the same note on macOS and Windows, so they collide everywhere
+        let other_case = Note::new("test note", "Lower case");
+        let error = AppError::from(other_case.create_note(&mut vault, OnConflict::Fail).unwrap_err());
+        assert_eq!((error.code(), error.message().key.as_str()), ("already_exists", "note.case_conflict"));
+        assert_eq!(error.message().params["existing"], "Test Note");
+        assert_eq!(other_case.create_note(&mut vault, OnConflict::Suffix).unwrap(), "test note-4");
+        let reserved = AppError::from(Note::new("Aux", "").create_note(&mut vault, OnConflict::Suffix).unwrap_err());
+        assert_eq!(reserved.code(), "invalid_name");
 
         // Cleanup
         vault.delete_vault().expect("Failed to delete vault");
@@ -140,6 +321,7 @@ mod tests {
 
     #[test]
     fn test_read_note() {
+        file_operations::set_base_path(None);
         let vault_name = format!("test_vault_{}", nanoid!());
         let mut vault = Vault::create_vault(&vault_name).unwrap();
 
@@ -147,9 +329,8 @@ mod tests {
         let content = "This is a test note content.";
 
         let note = Note::new(title, content);
-        note.create_note(&mut vault).unwrap();
-
-        let file_name = Note::generate_file_name(content);
+        let file_name = note.create_note(&mut vault, OnConflict::Fail).unwrap();
+        assert_eq!(file_name, "Test Note");
         let note_path = format!("{}/{}.md", vault.path, file_name);
 
         assert!(Path::new(&note_path).exists(), "❌ Note file does not exist");
@@ -161,13 +342,66 @@ mod tests {
         vault.delete_vault().expect("Failed to delete vault");
     }
 
+    #[test]
+    fn test_spaced_title() {
+        file_operations::set_base_path(None);
+        let mut vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let note = Note::new("Test Note", "First draft");
+        assert_eq!(note.create_note(&mut vault, OnConflict::Fail).unwrap(), "Test Note");
+
+        // The title the note was created under reads, updates and deletes it
+        assert_eq!(Note::read_note(&vault, "Test Note").unwrap(), "First draft");
+        Note::update_note(&vault, "Test Note", "Second draft").unwrap();
+        assert_eq!(Note::read_note(&vault, "Test Note").unwrap(), "Second draft");
+        note.delete_note(&mut vault).unwrap();
+        assert_eq!(Note::read_note(&vault, "Test Note").unwrap_err().kind(), ErrorKind::NotFound);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    #[test]
+    fn test_nested_notes() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        let folder = vault.create_folder("projects/ideas").unwrap();
+        assert_eq!(folder, "projects/ideas");
+        vault.create_folder("archive").unwrap();
+        assert!(vault.create_folder("../..").is_err());
+        file_operations::write_to_file(&format!("{}/projects/ideas/my-note.md", vault.path), "idea").unwrap();
+        file_operations::write_to_file(&format!("{}/Inbox.md", vault.path), "inbox").unwrap();
+
+        Note::update_note(&vault, "projects/ideas/my-note", "better idea").unwrap();
+        assert_eq!(Note::read_note(&vault, "projects/ideas/my-note").unwrap(), "better idea");
+        assert!(Note::read_note(&vault, "projects/ideas").is_err());
+        assert_eq!(Note::list_notes(&vault).unwrap(), vec!["Inbox", "projects/ideas/my-note"]);
+
+        let file = |title: &str, path: &str| NoteEntry::File { title: title.to_string(), path: path.to_string() };
+        let folder = |name: &str, path: &str, children| NoteEntry::Folder { name: name.to_string(), path: path.to_string(), children };
+        assert_eq!(
+            Note::list_tree(&vault).unwrap(),
+            vec![
+                folder("archive", "archive", vec![]),
+                folder("projects", "projects", vec![folder("ideas", "projects/ideas", vec![file("my-note", "projects/ideas/my-note")])]),
+                file("Inbox", "Inbox"),
+            ]
+        );
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
     #[test]
     fn test_delete_note() {
+        file_operations::set_base_path(None);
         let vault_name = format!("test_vault_{}", nanoid!());
         let mut vault = Vault::create_vault(&vault_name).unwrap();
         let note = Note::new("Test Note", "This is a test note content.");
-        note.create_note(&mut vault).unwrap();
-        assert!(note.delete_note(&mut vault).is_ok());
+        note.create_note(&mut vault, OnConflict::Fail).unwrap();
+        // Edited content does not change which file the note is
+        let edited = Note::new("Test Note", "Rewritten from the first word on");
+        assert!(edited.delete_note(&mut vault).is_ok());
+        assert_eq!(trash::list_trash(&vault).unwrap()[0].path, "Test Note");
 
         // Cleanup
         vault.delete_vault().expect("Failed to delete vault");
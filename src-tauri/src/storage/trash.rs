@@ -0,0 +1,176 @@
+// Per-vault recycle bin: deleted notes are moved to `.trash/notes`, keeping their folder and getting a
+// deletion timestamp suffix (`Projects/Plan.20261016-143000.md`), so they can be restored later.
+use chrono::{Local, NaiveDateTime};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::io::{self, ErrorKind};
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::storage::vault::Vault;
+use crate::utils::{file_operations, message::Message, plan::Plan};
+
+pub const TRASH_DIR: &str = ".trash";
+pub const TRASHED_NOTES_DIR: &str = ".trash/notes";
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashedNote {
+    // Identifies the note in the trash: its path in the trash folder, without extension
+    pub id: String,
+    // Path the note had before it was deleted
+    pub path: String,
+    pub deleted_at: String,
+    pub size: u64,
+}
+
+// Splits a trash id into the original path and the deletion time.
+fn parse_id(id: &str) -> Option<(String, NaiveDateTime)> {
+    let re = Regex::new(r"^(.+)\.(\d{8}-\d{6})(?:-\d+)?$").unwrap();
+    let caps = re.captures(id)?;
+    let deleted_at = NaiveDateTime::parse_from_str(&caps[2], STAMP_FORMAT).ok()?;
+    Some((caps[1].to_string(), deleted_at))
+}
+
+fn note_file(vault: &Vault, dir: &str, path: &str) -> String {
+    if dir.is_empty() {
+        format!("{}/{}.md", vault.path, path)
+    } else {
+        format!("{}/{}/{}.md", vault.path, dir, path)
+    }
+}
+
+fn exists(file: &str) -> bool {
+    Path::new(&file_operations::resolve_path(file)).exists()
+}
+
+fn move_file(from: &str, to: &str) -> io::Result<()> {
+    if let Some((parent, _)) = to.rsplit_once('/') {
+        file_operations::create_directory(parent)?;
+    }
+    file_operations::rename_file(from, to)
+}
+
+// Moves a note to the trash and returns its trash id.
+pub fn move_to_trash(vault: &Vault, path: &str) -> io::Result<String> {
+    let file = note_file(vault, "", path);
+    if path.is_empty() || !exists(&file) {
+        return Err(Message::new("note.not_found", "Note file does not exist").error(ErrorKind::NotFound));
+    }
+    let stamped = format!("{}.{}", path, Local::now().format(STAMP_FORMAT));
+    let mut id = stamped.clone();
+    let mut suffix = 2;
+    while exists(&note_file(vault, TRASHED_NOTES_DIR, &id)) {
+        id = format!("{}-{}", stamped, suffix);
+        suffix += 1;
+    }
+    move_file(&file, &note_file(vault, TRASHED_NOTES_DIR, &id))?;
+    Ok(id)
+}
+
+// Lists the notes in the trash, most recently deleted first.
+pub fn list_trash(vault: &Vault) -> io::Result<Vec<TrashedNote>> {
+    let root = file_operations::resolve_path(&format!("{}/{}", vault.path, TRASHED_NOTES_DIR));
+    if !Path::new(&root).exists() {
+        return Ok(Vec::new());
+    }
+    let mut notes = Vec::new();
+    for entry in WalkDir::new(&root) {
+        let entry = entry?;
+        let file = entry.path();
+        if !entry.file_type().is_file() || file.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+        let id = file.strip_prefix(&root).unwrap_or(file).with_extension("").to_string_lossy().replace('\\', "/");
+        // Files put there by hand have no timestamp and are left alone
+        if let Some((path, deleted_at)) = parse_id(&id) {
+            notes.push(TrashedNote {
+                id,
+                path,
+                deleted_at: deleted_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                size: entry.metadata()?.len(),
+            });
+        }
+    }
+    notes.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then(a.path.cmp(&b.path)));
+    Ok(notes)
+}
+
+// Moves a note out of the trash to the path it had, or next to it with `-2`, `-3`… when that path is
+// taken again. Returns the path of the restored note.
+pub fn restore_note(vault: &Vault, id: &str) -> io::Result<String> {
+    let (path, _) = parse_id(id)
+        .filter(|_| !id.split('/').any(|part| part.is_empty() || part == "." || part == ".."))
+        .ok_or_else(|| Message::new("trash.invalid_entry", "Invalid trash entry").error(ErrorKind::InvalidInput))?;
+    let trashed = note_file(vault, TRASHED_NOTES_DIR, id);
+    if !exists(&trashed) {
+        return Err(Message::new("trash.note_not_found", "Note is not in the trash").error(ErrorKind::NotFound));
+    }
+    let mut restored = path.clone();
+    let mut suffix = 2;
+    while exists(&note_file(vault, "", &restored)) {
+        restored = format!("{}-{}", path, suffix);
+        suffix += 1;
+    }
+    move_file(&trashed, &note_file(vault, "", &restored))?;
+    Ok(restored)
+}
+
+// Plans permanently deleting everything in the trash.
+pub fn plan_empty_trash(vault: &Vault) -> io::Result<Plan> {
+    let mut plan = Plan::default();
+    plan.remove_folder(vault, TRASH_DIR)?;
+    Ok(plan)
+}
+
+// Permanently deletes everything in the trash and returns how many notes were removed.
+pub fn empty_trash(vault: &Vault) -> io::Result<usize> {
+    let count = list_trash(vault)?.len();
+    plan_empty_trash(vault)?.apply(vault)?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::note::Note;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_trash() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Projects", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Projects/Plan.md", vault.path), "first").unwrap();
+
+        let first = move_to_trash(&vault, "Projects/Plan").unwrap();
+        assert!(first.starts_with("Projects/Plan."));
+        assert!(Note::list_notes(&vault).unwrap().is_empty());
+        assert_eq!(move_to_trash(&vault, "Projects/Plan").unwrap_err().kind(), ErrorKind::NotFound);
+
+        // Deleting a note of the same name again keeps both copies
+        file_operations::write_to_file(&format!("{}/Projects/Plan.md", vault.path), "second").unwrap();
+        let second = move_to_trash(&vault, "Projects/Plan").unwrap();
+        assert_ne!(first, second);
+        let trash = list_trash(&vault).unwrap();
+        assert_eq!(trash.len(), 2);
+        assert!(trash.iter().all(|note| note.path == "Projects/Plan"));
+
+        assert_eq!(restore_note(&vault, &first).unwrap(), "Projects/Plan");
+        assert_eq!(restore_note(&vault, &second).unwrap(), "Projects/Plan-2");
+        assert_eq!(Note::read_note(&vault, "Projects/Plan").unwrap(), "first");
+        assert_eq!(Note::read_note(&vault, "Projects/Plan-2").unwrap(), "second");
+        assert_eq!(restore_note(&vault, &first).unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(restore_note(&vault, "../Plan.20261016-143000").unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        let trashed = move_to_trash(&vault, "Projects/Plan-2").unwrap();
+        let plan = plan_empty_trash(&vault).unwrap();
+        assert_eq!(plan.changes[0].path, format!("{}/{}.md", TRASHED_NOTES_DIR, trashed));
+        assert_eq!(list_trash(&vault).unwrap().len(), 1);
+        assert_eq!(empty_trash(&vault).unwrap(), 1);
+        assert!(list_trash(&vault).unwrap().is_empty());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
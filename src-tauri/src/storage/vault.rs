@@ -1,13 +1,115 @@
+use chrono::NaiveDate;
+use chrono::format::{Item, StrftimeItems};
 use serde::{Serialize, Deserialize};
+use std::io::{Error, ErrorKind};
 
-use crate::utils::{file_operations, string_utils};
+use crate::utils::{file_operations, filename_policy, string_utils, error::AppError, message::Message, plan::Plan};
 
-#[derive(Serialize, Deserialize)]
+pub const VAULT_CONFIG_FILE: &str = ".vault.json";
+// Where daily note settings were kept before they moved into `.vault.json`
+const LEGACY_DAILY_NOTES_FILE: &str = ".config/daily_notes.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Vault {
     pub name: String,
     pub path: String,
 }
 
+// Settings stored with the vault in `.vault.json`, so they travel with it between machines.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VaultConfig {
+    // Template file name within `.templates/` for new notes in folders without a template of their own
+    pub default_template: Option<String>,
+    // Folder new attachments are stored in, relative to the vault
+    pub attachment_folder: String,
+    // Folders (relative to the vault) whose notes are left out of the note list, search and graph
+    pub excluded_dirs: Vec<String>,
+    pub daily_notes: DailyNotesConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyNotesConfig {
+    // strftime format of daily note names, `%Y-%m-%d` giving `2024-05-17`
+    pub date_format: String,
+    // Folder daily notes are kept in, empty for the vault root
+    pub folder: String,
+    // Template in `.templates` that new daily notes start from, when it exists
+    pub template: Option<String>,
+}
+
+impl Default for DailyNotesConfig {
+    fn default() -> Self {
+        Self { date_format: "%Y-%m-%d".to_string(), folder: String::new(), template: Some("daily.md".to_string()) }
+    }
+}
+
+impl DailyNotesConfig {
+    // Checks the settings and returns them with the folder path cleaned up.
+    fn validated(&self) -> std::io::Result<Self> {
+        if StrftimeItems::new(&self.date_format).any(|item| matches!(item, Item::Error)) {
+            return Err(Message::new("date_format.invalid", "Invalid date format").error(ErrorKind::InvalidInput));
+        }
+        // Names must survive file name sanitizing, or the note could not be found again
+        let sample = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap().format(&self.date_format).to_string();
+        if sample.is_empty() || string_utils::sanitize_filename(&sample) != sample {
+            return Err(Message::new("date_format.unsafe_characters", "Date format may only produce letters, digits, - and _").error(ErrorKind::InvalidInput));
+        }
+        if string_utils::sanitize_path(&self.folder) != self.folder.trim_matches('/') {
+            return Err(Message::new("journal.invalid_folder", "Invalid daily notes folder").error(ErrorKind::InvalidInput));
+        }
+        Ok(Self { folder: self.folder.trim_matches('/').to_string(), ..self.clone() })
+    }
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            default_template: None,
+            attachment_folder: "attachments".to_string(),
+            excluded_dirs: Vec::new(),
+            daily_notes: DailyNotesConfig::default(),
+        }
+    }
+}
+
+impl VaultConfig {
+    // Whether a vault-relative path lies in an excluded folder.
+    pub fn is_excluded(&self, path: &str) -> bool {
+        self.excluded_dirs
+            .iter()
+            .any(|dir| path == dir || path.strip_prefix(dir.as_str()).is_some_and(|rest| rest.starts_with('/')))
+    }
+
+    // Checks the settings and returns them with folder paths cleaned up.
+    fn validated(&self) -> std::io::Result<Self> {
+        if let Some(template) = &self.default_template {
+            if template.is_empty() || template.contains(['/', '\\']) || template.starts_with('.') {
+                return Err(AppError::InvalidName(Message::new("template.invalid_name", "Template must be a file name in .templates")).into());
+            }
+        }
+        let attachment_folder = string_utils::sanitize_path(&self.attachment_folder);
+        if attachment_folder.is_empty() || attachment_folder != self.attachment_folder.trim_matches('/') {
+            return Err(Message::new("vault.invalid_attachment_folder", "Invalid attachment folder").error(ErrorKind::InvalidInput));
+        }
+        let mut excluded_dirs = Vec::new();
+        for dir in &self.excluded_dirs {
+            let clean = string_utils::sanitize_path(dir);
+            if clean.is_empty() || clean != dir.trim_matches('/') {
+                return Err(Message::new("vault.invalid_excluded_folder", "Invalid excluded folder: {folder}")
+                    .with("folder", dir)
+                    .error(ErrorKind::InvalidInput));
+            }
+            if !excluded_dirs.contains(&clean) {
+                excluded_dirs.push(clean);
+            }
+        }
+        let daily_notes = self.daily_notes.validated()?;
+        Ok(Self { attachment_folder, excluded_dirs, daily_notes, ..self.clone() })
+    }
+}
+
 impl Vault {
     pub fn create_vault(name: &str) -> std::io::Result<Self> {
         let sanitized_name = string_utils::sanitize_filename(name);
@@ -22,12 +124,79 @@ impl Vault {
         })
     }
 
+    // Opens an existing vault by name, without creating it.
+    pub fn open(name: &str) -> std::io::Result<Self> {
+        let sanitized_name = string_utils::sanitize_filename(name);
+        let vault_path = format!("Vaults/{}", sanitized_name);
+        if sanitized_name.is_empty() || !std::path::Path::new(&file_operations::resolve_path(&vault_path)).is_dir() {
+            return Err(Message::new("vault.not_found", "Vault {name} does not exist").with("name", name).error(ErrorKind::NotFound));
+        }
+        Ok(Vault { name: sanitized_name, path: vault_path })
+    }
+
     pub fn delete_vault(&self) -> std::io::Result<()> {
         // Use file_operations::delete_directory instead of std::fs::remove_dir_all
         file_operations::delete_directory(&self.path)?;
         Ok(())
     }
 
+    // Plans deleting the vault: every file in it, then its folder.
+    pub fn plan_delete(&self) -> std::io::Result<Plan> {
+        let mut plan = Plan::default();
+        plan.remove_folder(self, "")?;
+        Ok(plan)
+    }
+
+    // Creates a folder (and its parents) inside the vault. Returns its sanitized vault-relative path.
+    pub fn create_folder(&self, path: &str) -> std::io::Result<String> {
+        let path = string_utils::sanitize_path(path);
+        if path.is_empty() {
+            return Err(AppError::InvalidName(Message::new("folder.name_empty", "Folder name is empty")).into());
+        }
+        filename_policy::check_path(&path)?;
+        file_operations::create_directory(&format!("{}/{}", self.path, path))?;
+        Ok(path)
+    }
+
+    fn config_path(&self) -> String {
+        format!("{}/{}", self.path, VAULT_CONFIG_FILE)
+    }
+
+    // Reads the vault's `.vault.json`; vaults without one use the defaults. Daily note settings still in
+    // their old file are moved into it first.
+    pub fn config(&self) -> std::io::Result<VaultConfig> {
+        let config = match file_operations::read_from_file(&self.config_path()) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| Error::new(ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => VaultConfig::default(),
+            Err(e) => return Err(e),
+        };
+        let legacy = format!("{}/{}", self.path, LEGACY_DAILY_NOTES_FILE);
+        match file_operations::read_from_file(&legacy) {
+            Ok(json) => {
+                let daily_notes = serde_json::from_str(&json).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+                let config = self.set_config(&VaultConfig { daily_notes, ..config })?;
+                file_operations::delete_file(&legacy)?;
+                Ok(config)
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(config),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Validates and stores the vault settings. Returns them as saved.
+    pub fn set_config(&self, config: &VaultConfig) -> std::io::Result<VaultConfig> {
+        let config = config.validated()?;
+        let json = serde_json::to_string_pretty(&config).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        file_operations::write_to_file(&self.config_path(), &json)?;
+        Ok(config)
+    }
+
+    // Absolute path of the vault folder, as the webview's asset protocol needs it.
+    pub fn absolute_path(&self) -> std::io::Result<String> {
+        let path = std::fs::canonicalize(file_operations::resolve_path(&self.path))?;
+        Ok(path.to_string_lossy().to_string())
+    }
+
     pub fn list_vaults(base_path: &str) -> std::io::Result<Vec<String>> {
         // Use file_operations::read_dir (if implemented) or keep using std::fs::read_dir
         let paths = std::fs::read_dir(base_path)?;
@@ -46,6 +215,7 @@ mod tests {
 
     #[test]
     fn test_create_vault() {
+        file_operations::set_base_path(None);
         let vault_name = "TestVault";
         let vault = Vault::create_vault(vault_name).expect("Failed to create test vault");
         assert_eq!(vault.name, vault_name, "Vault name mismatch");
@@ -58,6 +228,7 @@ mod tests {
 
     #[test]
     fn test_delete_vault() {
+        file_operations::set_base_path(None);
         let vault_name = "TestVaultToDelete";
         let vault = Vault::create_vault(vault_name).expect("Failed to create test vault");
         assert!(Path::new(&vault.path).exists(), "Vault should exist before deletion");
@@ -66,4 +237,49 @@ mod tests {
         vault.delete_vault().expect("Vault deletion failed");
         assert!(!Path::new(&vault.path).exists(), "Vault directory was not deleted");
     }
+
+    #[test]
+    fn test_vault_config() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid::nanoid!())).unwrap();
+        assert_eq!(vault.config().unwrap(), VaultConfig::default());
+
+        let config = VaultConfig {
+            default_template: Some("note.md".to_string()),
+            attachment_folder: "/files/".to_string(),
+            excluded_dirs: vec!["Archive".to_string(), "Archive/".to_string()],
+            daily_notes: DailyNotesConfig { date_format: "%Y%m%d".to_string(), folder: "Journal/".to_string(), template: None },
+        };
+        let saved = vault.set_config(&config).unwrap();
+        assert_eq!((saved.attachment_folder.as_str(), saved.excluded_dirs.clone()), ("files", vec!["Archive".to_string()]));
+        assert_eq!(saved.daily_notes.folder, "Journal");
+        assert_eq!(vault.config().unwrap(), saved);
+        assert!(saved.is_excluded("Archive/Old") && saved.is_excluded("Archive"));
+        assert!(!saved.is_excluded("Archived/Note"));
+
+        // Notes in excluded folders are left out of the note list
+        file_operations::create_directory(&format!("{}/Archive", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Archive/Old.md", vault.path), "old").unwrap();
+        file_operations::write_to_file(&format!("{}/Current.md", vault.path), "new").unwrap();
+        assert_eq!(crate::storage::note::Note::list_notes(&vault).unwrap(), vec!["Current".to_string()]);
+
+        for invalid in [
+            VaultConfig { default_template: Some("../x.md".to_string()), ..Default::default() },
+            VaultConfig { attachment_folder: " ".to_string(), ..Default::default() },
+            VaultConfig { excluded_dirs: vec!["a:b".to_string()], ..Default::default() },
+            VaultConfig { daily_notes: DailyNotesConfig { date_format: "%d %B %Y".to_string(), ..Default::default() }, ..Default::default() },
+        ] {
+            assert_eq!(vault.set_config(&invalid).unwrap_err().kind(), ErrorKind::InvalidInput);
+        }
+
+        // Daily note settings from their old file move into the vault config
+        file_operations::create_directory(&format!("{}/.config", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/{}", vault.path, LEGACY_DAILY_NOTES_FILE), r#"{"date_format": "%d-%m-%Y"}"#).unwrap();
+        assert_eq!(vault.config().unwrap().daily_notes.date_format, "%d-%m-%Y");
+        assert!(file_operations::read_from_file(&format!("{}/{}", vault.path, LEGACY_DAILY_NOTES_FILE)).is_err());
+        assert_eq!(vault.config().unwrap().excluded_dirs, vec!["Archive".to_string()]);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,202 @@
+use serde::{Serialize, Deserialize};
+use std::io::{self, ErrorKind};
+
+use crate::utils::message::Message;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Segment {
+    Common {
+        text: String,
+    },
+    Conflict {
+        index: usize,
+        ours_label: String,
+        ours: String,
+        base: Option<String>,
+        theirs_label: String,
+        theirs: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "choice", content = "text", rename_all = "lowercase")]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    Both,
+    Custom(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictResolution {
+    pub index: usize,
+    pub resolution: Resolution,
+}
+
+enum State {
+    Common,
+    Ours,
+    Base,
+    Theirs,
+}
+
+// Returns the label following a conflict marker, if the line is that marker.
+fn marker<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(marker)?;
+    let rest = rest.trim_end_matches(['\r', '\n']);
+    if rest.is_empty() || rest.starts_with(' ') {
+        Some(rest.trim())
+    } else {
+        None
+    }
+}
+
+// Splits a note into common text and `<<<<<<<`/`=======`/`>>>>>>>` conflict regions (with optional diff3 base).
+// Unterminated regions are kept as common text.
+pub fn parse_conflicts(content: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut common = String::new();
+    let mut raw = String::new();
+    let (mut ours, mut base, mut theirs) = (String::new(), None::<String>, String::new());
+    let mut ours_label = String::new();
+    let mut state = State::Common;
+    let mut index = 0;
+
+    for line in content.split_inclusive('\n') {
+        match state {
+            State::Common => {
+                if let Some(label) = marker(line, "<<<<<<<") {
+                    ours_label = label.to_string();
+                    raw = line.to_string();
+                    state = State::Ours;
+                } else {
+                    common.push_str(line);
+                }
+            }
+            State::Ours | State::Base => {
+                raw.push_str(line);
+                if marker(line, "|||||||").is_some() {
+                    base = Some(String::new());
+                    state = State::Base;
+                } else if marker(line, "=======").is_some() {
+                    state = State::Theirs;
+                } else if let State::Base = state {
+                    base.get_or_insert_with(String::new).push_str(line);
+                } else {
+                    ours.push_str(line);
+                }
+            }
+            State::Theirs => {
+                raw.push_str(line);
+                if let Some(theirs_label) = marker(line, ">>>>>>>") {
+                    if !common.is_empty() {
+                        segments.push(Segment::Common { text: std::mem::take(&mut common) });
+                    }
+                    segments.push(Segment::Conflict {
+                        index,
+                        ours_label: std::mem::take(&mut ours_label),
+                        ours: std::mem::take(&mut ours),
+                        base: base.take(),
+                        theirs_label: theirs_label.to_string(),
+                        theirs: std::mem::take(&mut theirs),
+                    });
+                    index += 1;
+                    raw.clear();
+                    state = State::Common;
+                } else {
+                    theirs.push_str(line);
+                }
+            }
+        }
+    }
+
+    common.push_str(&raw);
+    if !common.is_empty() {
+        segments.push(Segment::Common { text: common });
+    }
+    segments
+}
+
+// Applies resolutions to the conflict regions of a note; unresolved regions keep their markers.
+pub fn resolve_conflicts(content: &str, resolutions: &[ConflictResolution]) -> io::Result<String> {
+    let segments = parse_conflicts(content);
+    let conflicts = segments.iter().filter(|segment| matches!(segment, Segment::Conflict { .. })).count();
+    if let Some(invalid) = resolutions.iter().find(|r| r.index >= conflicts) {
+        return Err(Message::new("conflict.not_found", "Conflict {index} does not exist")
+            .with("index", invalid.index)
+            .error(ErrorKind::InvalidInput));
+    }
+
+    let mut resolved = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Common { text } => resolved.push_str(&text),
+            Segment::Conflict { index, ours_label, ours, base, theirs_label, theirs } => {
+                match resolutions.iter().find(|r| r.index == index).map(|r| &r.resolution) {
+                    Some(Resolution::Ours) => resolved.push_str(&ours),
+                    Some(Resolution::Theirs) => resolved.push_str(&theirs),
+                    Some(Resolution::Both) => {
+                        resolved.push_str(&ours);
+                        resolved.push_str(&theirs);
+                    }
+                    Some(Resolution::Custom(text)) => {
+                        resolved.push_str(text);
+                        if !text.is_empty() && !text.ends_with('\n') {
+                            resolved.push('\n');
+                        }
+                    }
+                    None => {
+                        resolved.push_str(&format!("<<<<<<< {}\n{}", ours_label, ours));
+                        if let Some(base) = base {
+                            resolved.push_str(&format!("|||||||\n{}", base));
+                        }
+                        resolved.push_str(&format!("=======\n{}>>>>>>> {}\n", theirs, theirs_label));
+                    }
+                }
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "Intro\n<<<<<<< laptop\nmine\n||||||| base\noriginal\n=======\ntheirs\n>>>>>>> phone\nMiddle\n<<<<<<< HEAD\na\n=======\nb\n>>>>>>> remote\nEnd\n";
+
+    #[test]
+    fn test_parse_conflicts() {
+        let segments = parse_conflicts(NOTE);
+        assert_eq!(segments.len(), 5);
+        assert_eq!(segments[0], Segment::Common { text: "Intro\n".to_string() });
+        assert_eq!(
+            segments[1],
+            Segment::Conflict {
+                index: 0,
+                ours_label: "laptop".to_string(),
+                ours: "mine\n".to_string(),
+                base: Some("original\n".to_string()),
+                theirs_label: "phone".to_string(),
+                theirs: "theirs\n".to_string(),
+            }
+        );
+        assert_eq!(parse_conflicts("<<<<<<< unterminated\ntext\n").len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_conflicts() {
+        let resolutions = vec![
+            ConflictResolution { index: 0, resolution: Resolution::Custom("merged".to_string()) },
+            ConflictResolution { index: 1, resolution: Resolution::Both },
+        ];
+        assert_eq!(resolve_conflicts(NOTE, &resolutions).unwrap(), "Intro\nmerged\nMiddle\na\nb\nEnd\n");
+
+        let partial = resolve_conflicts(NOTE, &resolutions[..1]).unwrap();
+        assert_eq!(parse_conflicts(&partial).len(), 3);
+
+        let invalid = vec![ConflictResolution { index: 5, resolution: Resolution::Ours }];
+        assert!(resolve_conflicts(NOTE, &invalid).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+// Errors returned by commands. The `code` tells the frontend what failed (`not_found`, `invalid_name`,
+// `index_error`, …) so it can react to specific failures; the message inside is shown to the user.
+//
+// Modules keep returning `io::Result`s. Most errors get their code from the IO error kind; modules tag
+// the failures the kind cannot tell apart (invalid names, search index errors) by returning an `AppError`
+// converted into an IO error, which commands recover with `AppError::from`.
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::io::{self, ErrorKind};
+use thiserror::Error;
+
+use crate::utils::message::Message;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum AppError {
+    #[error("{0}")]
+    NotFound(Message),
+    #[error("{0}")]
+    AlreadyExists(Message),
+    // A note, folder, tag, template or attachment name that cannot be used
+    #[error("{0}")]
+    InvalidName(Message),
+    #[error("{0}")]
+    InvalidInput(Message),
+    // A stored file that cannot be read back
+    #[error("{0}")]
+    InvalidData(Message),
+    #[error("{0}")]
+    PermissionDenied(Message),
+    // Another operation holds what is needed
+    #[error("{0}")]
+    Busy(Message),
+    #[error("{0}")]
+    IndexError(Message),
+    // Any other failure, mostly from the file system or a library
+    #[error("{0}")]
+    Io(Message),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "not_found",
+            AppError::AlreadyExists(_) => "already_exists",
+            AppError::InvalidName(_) => "invalid_name",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::InvalidData(_) => "invalid_data",
+            AppError::PermissionDenied(_) => "permission_denied",
+            AppError::Busy(_) => "busy",
+            AppError::IndexError(_) => "index_error",
+            AppError::Io(_) => "io",
+        }
+    }
+
+    pub fn message(&self) -> &Message {
+        match self {
+            AppError::NotFound(message)
+            | AppError::AlreadyExists(message)
+            | AppError::InvalidName(message)
+            | AppError::InvalidInput(message)
+            | AppError::InvalidData(message)
+            | AppError::PermissionDenied(message)
+            | AppError::Busy(message)
+            | AppError::IndexError(message)
+            | AppError::Io(message) => message,
+        }
+    }
+
+    fn kind(&self) -> ErrorKind {
+        match self {
+            AppError::NotFound(_) => ErrorKind::NotFound,
+            AppError::AlreadyExists(_) => ErrorKind::AlreadyExists,
+            AppError::InvalidName(_) | AppError::InvalidInput(_) => ErrorKind::InvalidInput,
+            AppError::InvalidData(_) => ErrorKind::InvalidData,
+            AppError::PermissionDenied(_) => ErrorKind::PermissionDenied,
+            AppError::Busy(_) => ErrorKind::WouldBlock,
+            AppError::IndexError(_) | AppError::Io(_) => ErrorKind::Other,
+        }
+    }
+}
+
+// Sent as `{ code, message, key, params }`: the code to react on, the English text, and the key and
+// parameters the frontend translates it with.
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let message = self.message();
+        let mut error = serializer.serialize_struct("AppError", 4)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &message.message)?;
+        error.serialize_field("key", &message.key)?;
+        error.serialize_field("params", &message.params)?;
+        error.end()
+    }
+}
+
+impl From<AppError> for io::Error {
+    fn from(e: AppError) -> Self {
+        io::Error::new(e.kind(), e)
+    }
+}
+
+impl From<&io::Error> for AppError {
+    fn from(e: &io::Error) -> Self {
+        if let Some(error) = e.get_ref().and_then(|inner| inner.downcast_ref::<AppError>()) {
+            return error.clone();
+        }
+        let message = Message::from(e);
+        match e.kind() {
+            ErrorKind::NotFound => AppError::NotFound(message),
+            ErrorKind::AlreadyExists => AppError::AlreadyExists(message),
+            ErrorKind::InvalidInput => AppError::InvalidInput(message),
+            ErrorKind::InvalidData => AppError::InvalidData(message),
+            ErrorKind::PermissionDenied => AppError::PermissionDenied(message),
+            ErrorKind::WouldBlock => AppError::Busy(message),
+            _ => AppError::Io(message),
+        }
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(e: io::Error) -> Self {
+        AppError::from(&e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_error_codes() {
+        let error = AppError::from(Message::new("note.not_found", "Note {path} does not exist").with("path", "Plan").error(ErrorKind::NotFound));
+        assert_eq!(error.code(), "not_found");
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "not_found");
+        assert_eq!(json["message"], "Note Plan does not exist");
+        assert_eq!((json["key"].as_str(), json["params"]["path"].as_str()), (Some("note.not_found"), Some("Plan")));
+
+        // Tagged errors keep their code through `io::Result`s, and their message for reports
+        let tagged = io::Error::from(AppError::InvalidName(Message::new("tag.invalid_name", "Tag names must be single words")));
+        assert_eq!(tagged.kind(), ErrorKind::InvalidInput);
+        assert_eq!(Message::from(&tagged).key, "tag.invalid_name");
+        assert_eq!(AppError::from(tagged).code(), "invalid_name");
+
+        let plain = AppError::from(io::Error::new(ErrorKind::WouldBlock, "locked"));
+        assert_eq!((plain.code(), plain.to_string().as_str()), ("busy", "locked"));
+        assert_eq!(AppError::from(io::Error::other("disk failure")).code(), "io");
+    }
+}
@@ -0,0 +1,84 @@
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+
+use crate::utils::{frontmatter, markdown};
+
+pub const ELLIPSIS: char = '…';
+
+// Flattens a note into a single line of plain text: markup, link targets, code blocks, images and
+// embeds are dropped, wikilinks are replaced by their label.
+pub fn plain_text(content: &str) -> String {
+    let mut text = String::new();
+    let mut in_code_block = false;
+    let mut in_image = false;
+
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    for event in Parser::new_ext(frontmatter::strip(content), options) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Start(Tag::Image { .. }) => in_image = true,
+            Event::End(TagEnd::Image) => in_image = false,
+            Event::Text(part) | Event::Code(part) if !in_code_block && !in_image => text.push_str(&part),
+            // Keep words of consecutive blocks and lines apart
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::TableCell) => text.push(' '),
+            _ => {}
+        }
+    }
+
+    let embed_re = Regex::new(r"!\[\[[^\]]*\]\]").unwrap();
+    let text = embed_re.replace_all(&text, "");
+    let text = markdown::rewrite_wikilinks(&text, |_, label| label.to_string());
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Shortens text to at most `max_chars` characters (ellipsis included), cutting between words. A single
+// word longer than the limit is cut inside.
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    let text = text.trim();
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    if max_chars == 0 {
+        return String::new();
+    }
+
+    let limit = max_chars - 1;
+    let end = text.char_indices().nth(limit).map(|(index, _)| index).unwrap_or(text.len());
+    let cut = &text[..end];
+    // A word boundary right after the cut means the last word is whole
+    let at_boundary = text[end..].starts_with(char::is_whitespace);
+    let short = match cut.rfind(char::is_whitespace) {
+        Some(space) if !at_boundary => &cut[..space],
+        _ => cut,
+    };
+    let short = short.trim_end().trim_end_matches(|c: char| c.is_ascii_punctuation() && c != ')');
+    format!("{}{}", short, ELLIPSIS)
+}
+
+// Length-bounded plain-text summary of a note, for notifications, share previews and hover cards.
+pub fn excerpt(content: &str, max_chars: usize) -> String {
+    truncate(&plain_text(content), max_chars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excerpt() {
+        let content = "---\ntags: [a]\n---\n# Launch\nSee [[Projects/Plan|the plan]] and **[docs](https://example.com/very/long)**.\n\n```rust\nfn main() {}\n```\n\n![[photo.png]] ![chart](chart.png)\n- [ ] Ship `v2`";
+        assert_eq!(plain_text(content), "Launch See the plan and docs. Ship v2");
+        assert_eq!(excerpt(content, 100), "Launch See the plan and docs. Ship v2");
+        assert_eq!(excerpt(content, 20), "Launch See the plan…");
+        assert_eq!(excerpt(content, 19), "Launch See the…");
+        assert_eq!(excerpt(content, 30), "Launch See the plan and docs…");
+        assert_eq!(truncate("Supercalifragilistic", 6), "Super…");
+        assert_eq!(truncate("Déjà vu encore", 8), "Déjà vu…");
+        assert_eq!(truncate("text", 0), "");
+    }
+}
@@ -3,6 +3,9 @@ use std::io::{self, Write, Read};
 use std::path::Path;
 use std::sync::Mutex;
 use lazy_static::lazy_static;
+use nanoid::nanoid;
+
+use crate::utils::message::Message;
 
 lazy_static! {
     static ref PATH: Mutex<Option<String>> = Mutex::new(Some("Vaults".to_string()));
@@ -14,6 +17,15 @@ pub fn set_base_path(path: Option<String>) {
     *PATH.lock().unwrap() = path;
 }
 
+// Resolves a path against the configured base path.
+pub fn resolve_path(path: &str) -> String {
+    let base_path = PATH.lock().unwrap();
+    match &*base_path {
+        Some(base) => format!("{}/{}", base, path),
+        None => path.to_string(),
+    }
+}
+
 // Creates a directory if it doesn't already exist.
 pub fn create_directory(path: &str) -> io::Result<()> {
     let base_path = PATH.lock().unwrap();
@@ -30,49 +42,71 @@ pub fn create_directory(path: &str) -> io::Result<()> {
 
 // Deletes a directory and all its contents if it exists.
 pub fn delete_directory(path: &str) -> io::Result<()> {
-    let base_path = PATH.lock().unwrap();
-    let full_path = match &*base_path {
-        Some(base) => format!("{}/{}", base, path),
-        None => path.to_string(),
-    };
+    let full_path = resolve_path(path);
     
     if Path::new(&full_path).exists() {
         println!("🧹 Attempting to delete directory: {}", full_path);
-        // Recursively delete all files and subdirectories
-        for entry in fs::read_dir(&full_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_dir() {
-                if let Err(e) = delete_directory(&path.to_string_lossy()) {
-                    println!("❌ Failed to delete subdirectory {}: {}", path.display(), e);
-                }
-            } else {
-                if let Err(e) = fs::remove_file(&path) {
-                    println!("❌ Failed to delete file {}: {}", path.display(), e);
-                }
+        remove_directory(Path::new(&full_path))?;
+        println!("✅ Successfully deleted directory: {}", full_path);
+    }
+    Ok(())
+}
+
+// Recursively deletes an already resolved directory.
+fn remove_directory(full_path: &Path) -> io::Result<()> {
+    // Recursively delete all files and subdirectories
+    for entry in fs::read_dir(full_path)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            if let Err(e) = remove_directory(&path) {
+                println!("❌ Failed to delete subdirectory {}: {}", path.display(), e);
             }
+        } else if let Err(e) = fs::remove_file(&path) {
+            println!("❌ Failed to delete file {}: {}", path.display(), e);
         }
-        // Delete the directory itself
-        if let Err(e) = fs::remove_dir(&full_path) {
-            println!("❌ Failed to delete directory {}: {}", full_path, e);
-            return Err(e);
-        }
-        println!("✅ Successfully deleted directory: {}", full_path);
+    }
+    // Delete the directory itself
+    if let Err(e) = fs::remove_dir(full_path) {
+        println!("❌ Failed to delete directory {}: {}", full_path.display(), e);
+        return Err(e);
     }
     Ok(())
 }
 
+// Replaces the content of an already resolved file so that a crash leaves either the old or the new
+// content: the new content is written to a temporary file in the same directory, synced to disk and
+// renamed over the target.
+pub fn write_atomic(full_path: &Path, content: &[u8]) -> io::Result<()> {
+    let dir = full_path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = full_path
+        .file_name()
+        .ok_or_else(|| Message::new("path.not_a_file", "Path does not name a file").error(io::ErrorKind::InvalidInput))?;
+    // Hidden, so note listings and the file watcher never pick it up
+    let temp = dir.join(format!(".{}.{}.tmp", name.to_string_lossy(), nanoid!(8)));
+
+    let written = File::create(&temp).and_then(|mut file| {
+        file.write_all(content)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| fs::rename(&temp, full_path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+    // Persist the rename itself; directories cannot be opened for syncing on Windows
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
 // Writes content to a file, creating it if necessary.
 pub fn write_to_file(path: &str, content: &str) -> io::Result<()> {
-    let base_path = PATH.lock().unwrap();
-    let full_path = match &*base_path {
-        Some(base) => format!("{}/{}", base, path),
-        None => path.to_string(),
-    };
-    
-    let mut file = File::create(&full_path)?;
-    file.write_all(content.as_bytes())?;
-    Ok(())
+    write_atomic(Path::new(&resolve_path(path)), content.as_bytes())
+}
+
+// Writes raw bytes to a file, creating it if necessary.
+pub fn write_bytes_to_file(path: &str, content: &[u8]) -> io::Result<()> {
+    write_atomic(Path::new(&resolve_path(path)), content)
 }
 
 // Reads content from a file.
@@ -89,6 +123,15 @@ pub fn read_from_file(path: &str) -> io::Result<String> {
     Ok(content)
 }
 
+// Checks whether a derived file exists and is at least as recent as its source.
+pub fn is_up_to_date(source: &str, target: &str) -> bool {
+    let modified = |path: &str| fs::metadata(resolve_path(path)).and_then(|meta| meta.modified()).ok();
+    match (modified(source), modified(target)) {
+        (Some(source), Some(target)) => target >= source,
+        _ => false,
+    }
+}
+
 // Deletes a file if it exists.
 pub fn delete_file(path: &str) -> io::Result<()> {
     let base_path = PATH.lock().unwrap();
@@ -151,6 +194,28 @@ mod tests {
         assert!(!Path::new(test_file).exists());
     }
 
+    #[test]
+    fn test_write_atomic() {
+        let dir = std::env::temp_dir().join(format!("test_atomic_{}", nanoid!()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("note.md");
+
+        write_atomic(&file, b"first").unwrap();
+        write_atomic(&file, b"second").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "second");
+        // Only the target is left behind
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // A failed write keeps the old content
+        fs::create_dir(dir.join("folder.md")).unwrap();
+        assert!(write_atomic(&dir.join("folder.md"), b"x").is_err());
+        assert!(write_atomic(&dir.join("missing/note.md"), b"x").is_err());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        // Cleanup
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_delete_directory() {
         // Disable the base path for tests
@@ -0,0 +1,131 @@
+// Portable file names. A vault moves between Linux, macOS and Windows (sync, git), so the names it gets
+// follow the strictest of them: device names Windows reserves (`CON`, `aux.md`, `LPT1`…) are refused,
+// and names that differ only in case count as the same name, as they do on the default file systems of
+// macOS and Windows. Checking this ourselves keeps behavior the same on every platform: without it a
+// note `plan` would overwrite `Plan` on a Mac but become a second note on Linux.
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+use crate::utils::{error::AppError, file_operations, message::Message};
+
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+// Whether Windows reserves a file or folder name. Extensions do not help: `con.md` is the console too.
+pub fn is_reserved(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end_matches(' ');
+    RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+// Refuses a vault-relative path if any of its segments is a reserved name.
+pub fn check_path(path: &str) -> io::Result<()> {
+    match path.split('/').find(|segment| is_reserved(segment)) {
+        Some(segment) => Err(AppError::InvalidName(
+            Message::new("file_name.reserved", "{name} is reserved by Windows and cannot be used as a name").with("name", segment),
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
+// Whether two names are the same file on a case-insensitive file system.
+pub fn same_name(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+// Finds a vault-relative path (`Projects/Plan.md`) on disk, ignoring case segment by segment, and returns
+// it as spelled on disk. An exact match wins over one that differs in case. None when nothing matches.
+pub fn find_ignoring_case(root: &str, path: &str) -> io::Result<Option<String>> {
+    let mut dir = PathBuf::from(file_operations::resolve_path(root));
+    let mut found = Vec::new();
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        let names: Vec<String> = match fs::read_dir(&dir) {
+            Ok(entries) => entries
+                .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().to_string()))
+                .collect::<io::Result<_>>()?,
+            Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::NotADirectory) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let name = names
+            .iter()
+            .find(|name| *name == segment)
+            .or_else(|| names.iter().find(|name| same_name(name, segment)));
+        let Some(name) = name else {
+            return Ok(None);
+        };
+        dir.push(name);
+        found.push(name.clone());
+    }
+    Ok(Some(found.join("/")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::vault::Vault;
+    use nanoid::nanoid;
+
+    #[test]
+    fn test_reserved_names() {
+        for name in ["CON", "con", "Aux.md", "nul.tar.gz", "COM1", "lpt9 "] {
+            assert!(is_reserved(name), "{} should be reserved", name);
+        }
+        for name in ["CONSOLE", "Contacts", "COM10", "aux_notes", "LPT"] {
+            assert!(!is_reserved(name), "{} should not be reserved", name);
+        }
+        assert!(check_path("Projects/Plan").is_ok());
+        let error = AppError::from(check_path("Projects/prn/Plan").unwrap_err());
+        assert_eq!((error.code(), error.message().params["name"].as_str()), ("invalid_name", "prn"));
+    }
+
+    #[test]
+    fn test_find_ignoring_case() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/Projects", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/Projects/Plan.md", vault.path), "").unwrap();
+
+        assert_eq!(find_ignoring_case(&vault.path, "Projects/Plan.md").unwrap().as_deref(), Some("Projects/Plan.md"));
+        assert_eq!(find_ignoring_case(&vault.path, "projects/PLAN.md").unwrap().as_deref(), Some("Projects/Plan.md"));
+        assert_eq!(find_ignoring_case(&vault.path, "Projects/Plan.md/x").unwrap(), None);
+        assert_eq!(find_ignoring_case(&vault.path, "Other/Plan.md").unwrap(), None);
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    // Case-sensitive file systems keep both spellings; the exact one is found first.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_case_sensitive_file_system() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Plan.md", vault.path), "upper").unwrap();
+        file_operations::write_to_file(&format!("{}/plan.md", vault.path), "lower").unwrap();
+
+        assert_eq!(find_ignoring_case(&vault.path, "plan.md").unwrap().as_deref(), Some("plan.md"));
+        assert_eq!(find_ignoring_case(&vault.path, "Plan.md").unwrap().as_deref(), Some("Plan.md"));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+
+    // Case-insensitive file systems write both spellings to one file; the name is reported as on disk.
+    #[cfg(any(target_os = "macos", windows))]
+    #[test]
+    fn test_case_insensitive_file_system() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::write_to_file(&format!("{}/Plan.md", vault.path), "upper").unwrap();
+        file_operations::write_to_file(&format!("{}/plan.md", vault.path), "lower").unwrap();
+
+        assert_eq!(file_operations::read_from_file(&format!("{}/Plan.md", vault.path)).unwrap(), "lower");
+        assert_eq!(find_ignoring_case(&vault.path, "plan.md").unwrap().as_deref(), Some("Plan.md"));
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
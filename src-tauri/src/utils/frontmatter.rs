@@ -0,0 +1,223 @@
+use chrono::{DateTime, NaiveDate};
+use serde::{Serialize, Deserialize};
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::io::{self, Error, ErrorKind};
+
+use crate::utils::message::Message;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Frontmatter {
+    pub fields: Mapping,
+}
+
+impl Frontmatter {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.fields.get(key)
+    }
+
+    pub fn set<V: Into<Value>>(&mut self, key: &str, value: V) {
+        self.fields.insert(Value::String(key.to_string()), value.into());
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key).and_then(Value::as_bool)
+    }
+
+    // Reads a list value, also accepting a single comma or space separated string.
+    pub fn get_list(&self, key: &str) -> Vec<String> {
+        match self.get(key) {
+            Some(Value::Sequence(items)) => items
+                .iter()
+                .filter_map(|item| match item {
+                    Value::String(s) => Some(s.trim().to_string()),
+                    Value::Number(n) => Some(n.to_string()),
+                    _ => None,
+                })
+                .filter(|item| !item.is_empty())
+                .collect(),
+            Some(Value::String(s)) => s
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // Returns the note tags without their leading `#`.
+    pub fn tags(&self) -> Vec<String> {
+        self.get_list("tags")
+            .into_iter()
+            .map(|tag| tag.trim_start_matches('#').to_string())
+            .collect()
+    }
+
+    // Returns the alternative titles of the note.
+    pub fn aliases(&self) -> Vec<String> {
+        match self.get("aliases").or_else(|| self.get("alias")) {
+            // A single alias may contain spaces, unlike a list written as a plain string
+            Some(Value::String(alias)) if !alias.contains(',') => vec![alias.trim().to_string()],
+            Some(_) if self.get("aliases").is_some() => self.get_list("aliases"),
+            Some(_) => self.get_list("alias"),
+            None => Vec::new(),
+        }
+    }
+
+    // Returns the CSS classes the note asks to be rendered with (`cssclasses`, or the older `cssclass`).
+    // Names that are not plain class names are dropped, so a value cannot inject markup.
+    pub fn css_classes(&self) -> Vec<String> {
+        let key = if self.get("cssclasses").is_some() { "cssclasses" } else { "cssclass" };
+        let mut classes: Vec<String> = self
+            .get_list(key)
+            .into_iter()
+            .filter(|class| {
+                class.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '-')
+                    && class.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            })
+            .collect();
+        classes.dedup();
+        classes
+    }
+
+    // Reads a date value (`2024-05-01` or an RFC 3339 timestamp).
+    pub fn get_date(&self, key: &str) -> Option<NaiveDate> {
+        let value = self.get(key)?.as_str()?.trim();
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .or_else(|| DateTime::parse_from_rfc3339(value).ok().map(|date| date.date_naive()))
+    }
+
+    // Every field holding a date, keyed by field name.
+    pub fn dates(&self) -> BTreeMap<String, NaiveDate> {
+        self.fields
+            .keys()
+            .filter_map(Value::as_str)
+            .filter_map(|key| self.get_date(key).map(|date| (key.to_string(), date)))
+            .collect()
+    }
+}
+
+// The frontmatter of a note with the well-known fields already extracted, for the properties panel.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Properties {
+    pub tags: Vec<String>,
+    pub aliases: Vec<String>,
+    // Date fields normalized to `YYYY-MM-DD`
+    pub dates: BTreeMap<String, String>,
+    // Every field as written, including the ones above
+    pub fields: Frontmatter,
+}
+
+impl From<Frontmatter> for Properties {
+    fn from(fields: Frontmatter) -> Self {
+        Self {
+            tags: fields.tags(),
+            aliases: fields.aliases(),
+            dates: fields
+                .dates()
+                .into_iter()
+                .map(|(key, date)| (key, date.format("%Y-%m-%d").to_string()))
+                .collect(),
+            fields,
+        }
+    }
+}
+
+// Splits a note into its raw `---` delimited frontmatter and its body.
+pub fn split(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content.strip_prefix("---\n").or_else(|| content.strip_prefix("---\r\n")) else {
+        return (None, content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+// Returns the body of a note without its frontmatter.
+pub fn strip(content: &str) -> &str {
+    split(content).1
+}
+
+// Parses the frontmatter of a note; notes without frontmatter yield an empty one.
+pub fn parse(content: &str) -> io::Result<Frontmatter> {
+    let Some(raw) = split(content).0 else {
+        return Ok(Frontmatter::default());
+    };
+    if raw.trim().is_empty() {
+        return Ok(Frontmatter::default());
+    }
+
+    match serde_yaml::from_str(raw) {
+        Ok(Value::Mapping(fields)) => Ok(Frontmatter { fields }),
+        Ok(_) => Err(Message::new("frontmatter.not_a_mapping", "Frontmatter must be a mapping").error(ErrorKind::InvalidData)),
+        Err(e) => Err(Message::new("frontmatter.invalid", "Invalid frontmatter: {detail}").with("detail", e).error(ErrorKind::InvalidData)),
+    }
+}
+
+// Replaces the frontmatter of a note, keeping its body; an empty frontmatter removes the block.
+pub fn replace(content: &str, frontmatter: &Frontmatter) -> io::Result<String> {
+    let body = strip(content);
+    if frontmatter.fields.is_empty() {
+        return Ok(body.to_string());
+    }
+    let yaml = serde_yaml::to_string(&frontmatter.fields).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    Ok(format!("---\n{}---\n{}", yaml, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let content = "---\ntitle: Hello\n---\n# Body\n";
+        assert_eq!(split(content), (Some("title: Hello\n"), "# Body\n"));
+        assert_eq!(split("# No frontmatter"), (None, "# No frontmatter"));
+        assert_eq!(split("---\nunterminated"), (None, "---\nunterminated"));
+        assert_eq!(strip(content), "# Body\n");
+    }
+
+    #[test]
+    fn test_parse() {
+        let content = "---\ntags: [rust, \"#notes\"]\naliases: first, second\npublish: true\n---\nBody";
+        let frontmatter = parse(content).unwrap();
+        assert_eq!(frontmatter.tags(), vec!["rust", "notes"]);
+        assert_eq!(frontmatter.get_list("aliases"), vec!["first", "second"]);
+        assert_eq!(frontmatter.get_bool("publish"), Some(true));
+        assert_eq!(parse("Body").unwrap(), Frontmatter::default());
+        assert!(parse("---\n: [\n---\nBody").is_err());
+    }
+
+    #[test]
+    fn test_properties() {
+        let content = "---\naliases: Launch plan\ncreated: 2024-05-01\nupdated: 2024-05-03T10:00:00+02:00\ndue: soon\n---\nBody";
+        let properties = Properties::from(parse(content).unwrap());
+        assert_eq!(properties.aliases, vec!["Launch plan"]);
+        assert!(properties.tags.is_empty());
+        assert_eq!(properties.dates.len(), 2);
+        assert_eq!(properties.dates["updated"], "2024-05-03");
+        assert_eq!(properties.fields.get("due").and_then(Value::as_str), Some("soon"));
+        assert_eq!(parse("---\nalias: [a, b]\n---\n").unwrap().aliases(), vec!["a", "b"]);
+        assert_eq!(parse("---\ncssclasses: [wide, two-column, \"x\\\" onclick\", 1up]\n---\n").unwrap().css_classes(), vec!["wide", "two-column"]);
+        assert_eq!(parse("---\ncssclass: wide tables\n---\n").unwrap().css_classes(), vec!["wide", "tables"]);
+    }
+
+    #[test]
+    fn test_replace() {
+        let content = "---\ntitle: Hello\n---\n# Body\n";
+        let mut frontmatter = parse(content).unwrap();
+        frontmatter.set("tags", vec!["a".to_string()]);
+        assert_eq!(replace(content, &frontmatter).unwrap(), "---\ntitle: Hello\ntags:\n- a\n---\n# Body\n");
+        assert_eq!(replace(content, &Frontmatter::default()).unwrap(), "# Body\n");
+        assert_eq!(replace("Body", &frontmatter).unwrap(), "---\ntitle: Hello\ntags:\n- a\n---\nBody");
+    }
+}
@@ -1,21 +1,189 @@
-use pulldown_cmark::{Parser, Options, Event, Tag, TagEnd, html};
+use pulldown_cmark::{Parser, Options, Event, Tag, TagEnd, TextMergeStream, html};
 use regex::Regex;
-use ammonia::clean;
+use ammonia::clean_text;
+
+use crate::storage::attachment::AttachmentKind;
+use crate::utils::{frontmatter, outline};
+
+// Embeds nested deeper than this are left as links, which also stops embed cycles.
+const MAX_EMBED_DEPTH: usize = 4;
+
+// URL scheme of rendered wikilinks (`note://Note%20Title`), intercepted by the frontend.
+pub const DEFAULT_LINK_SCHEME: &str = "note";
+// Tauri's protocol for serving local files to the webview
+pub const ASSET_SCHEME: &str = "asset";
+
+// Where the files a note refers to live on disk, so relative image paths can be served to the webview.
+pub struct AssetBase<'a> {
+    // Absolute path of the vault folder
+    pub vault_dir: &'a str,
+    // Folder of the note, relative to the vault
+    pub note_folder: &'a str,
+    // Attachments folder of the vault, relative to the vault
+    pub attachment_folder: &'a str,
+}
+
+impl AssetBase<'_> {
+    // Returns the asset URL of a path relative to the note, or `None` for URLs and absolute paths.
+    fn url(&self, dest: &str) -> Option<String> {
+        if dest.is_empty() || dest.contains(':') || dest.starts_with(['/', '#', '\\']) {
+            return None;
+        }
+        let dest = dest.replace("%20", " ");
+        let path = join_path(self.note_folder, dest.split(['?', '#']).next().unwrap_or_default());
+        Some(asset_url(&format!("{}/{}", self.vault_dir.trim_end_matches('/'), path)))
+    }
+
+    // Returns the URL of a `![[file.png]]` embed; names without a folder are looked up in the attachments.
+    fn embed_url(&self, target: &str) -> String {
+        let path = if target.contains('/') { normalize_path(target) } else { format!("{}/{}", self.attachment_folder, target) };
+        asset_url(&format!("{}/{}", self.vault_dir.trim_end_matches('/'), path))
+    }
+}
+
+// The origin Tauri serves local files from: `asset://localhost`, except on Windows and Android, whose
+// webviews only load custom protocols as `http://asset.localhost`.
+pub fn asset_origin() -> String {
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost", ASSET_SCHEME)
+    } else {
+        format!("{}://localhost", ASSET_SCHEME)
+    }
+}
+
+// Builds the URL Tauri serves a local file at, encoding the path like `convertFileSrc` does.
+pub fn asset_url(absolute_path: &str) -> String {
+    let encoded: String = absolute_path
+        .replace('\\', "/")
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'!' | b'~' | b'*' | b'\'' | b'(' | b')' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+    format!("{}/{}", asset_origin(), encoded)
+}
 
 // Renders Markdown content to HTML.
 pub fn render_markdown(content: &str) -> String {
+    render_markdown_with_links(content, DEFAULT_LINK_SCHEME)
+}
+
+// Checks that a link scheme is a plain URL scheme that cannot run code or leave the app.
+pub fn is_valid_link_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '+' | '-' | '.'))
+        && !["javascript", "vbscript", "data", "file", "http", "https"].contains(&scheme)
+}
+
+// Percent-encodes a wikilink target for use in a URL, keeping folder separators.
+fn encode_target(target: &str) -> String {
+    target
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+// Builds the anchor of a `[[Target#Section|Label]]` wikilink.
+fn wikilink_anchor(scheme: &str, link: &str, label: &str) -> String {
+    let (target, section) = match link.split_once('#') {
+        Some((target, section)) => (target.trim(), Some(section.trim())),
+        None => (link.trim(), None),
+    };
+    let target = target.trim_end_matches(".md");
+    let mut href = format!("{}://{}", scheme, encode_target(target));
+    if let Some(section) = section.filter(|section| !section.is_empty()) {
+        href.push('#');
+        href.push_str(&encode_target(section));
+    }
+    format!(
+        "<a href=\"{}\" class=\"wikilink\" data-target=\"{}\">{}</a>",
+        href,
+        clean_text(target),
+        clean_text(label)
+    )
+}
+
+// Renders Markdown content to HTML, turning `[[Note]]` and `[[Note|alias]]` wikilinks into anchors
+// that point at `<scheme>://Note`. Wikilinks inside code are left as they are.
+pub fn render_markdown_with_links(content: &str, scheme: &str) -> String {
+    render(content, scheme, None)
+}
+
+// Renders the Markdown of a note like `render_markdown_with_links`, with relative image paths and
+// `![[image.png]]` embeds pointing at the asset URLs of the files so the webview can display them.
+pub fn render_note_markdown(content: &str, scheme: &str, assets: &AssetBase) -> String {
+    render(content, scheme, Some(assets))
+}
+
+fn render(content: &str, scheme: &str, assets: Option<&AssetBase>) -> String {
+    let scheme = if is_valid_link_scheme(scheme) { scheme } else { DEFAULT_LINK_SCHEME };
     let mut options = Options::empty();
     options.insert(Options::ENABLE_TABLES);
     options.insert(Options::ENABLE_FOOTNOTES);
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_TASKLISTS);
 
-    let parser = Parser::new_ext(content, options);
+    // Frontmatter is note metadata, not part of the rendered text
+    let parser = Parser::new_ext(frontmatter::strip(content), options);
+    let wikilink_re = Regex::new(r"(!?)\[\[([^\]|]+)(?:\|([^\]]+))?\]\]").unwrap();
+    let mut in_code_block = false;
+    let mut events = Vec::new();
+    // Merged text events, so a wikilink is never split across events
+    for event in TextMergeStream::new(parser) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+                let dest_url = assets.and_then(|assets| assets.url(&dest_url)).map(Into::into).unwrap_or(dest_url);
+                events.push(Event::Start(Tag::Image { link_type, dest_url, title, id }));
+                continue;
+            }
+            Event::Text(ref text) if !in_code_block && text.contains("[[") => {
+                let mut last = 0;
+                for caps in wikilink_re.captures_iter(text) {
+                    let target = caps[2].trim();
+                    let whole = caps.get(0).unwrap();
+                    let html = match (caps[1].is_empty(), assets) {
+                        (true, _) => {
+                            let label = caps.get(3).map(|label| label.as_str().trim()).unwrap_or(target);
+                            wikilink_anchor(scheme, &caps[2], label)
+                        }
+                        // Image embeds are shown once their file can be served; other embeds stay as text
+                        (false, Some(assets)) if AttachmentKind::from_name(target) == AttachmentKind::Image => {
+                            format!("<img src=\"{}\" alt=\"{}\">", assets.embed_url(target), clean_text(target))
+                        }
+                        (false, _) => continue,
+                    };
+                    events.push(Event::Text(text[last..whole.start()].to_string().into()));
+                    events.push(Event::InlineHtml(html.into()));
+                    last = whole.end();
+                }
+                events.push(Event::Text(text[last..].to_string().into()));
+                continue;
+            }
+            _ => {}
+        }
+        events.push(event);
+    }
+
     let mut html_output = String::new();
-    html::push_html(&mut html_output, parser);
+    html::push_html(&mut html_output, events.into_iter());
+
+    // Notes opt into layouts (wide tables, columns…) with `cssclasses`, set on a root element
+    let classes = frontmatter::parse(content).map(|fields| fields.css_classes()).unwrap_or_default();
+    if !classes.is_empty() {
+        html_output = format!("<div class=\"{}\">\n{}</div>\n", classes.join(" "), html_output);
+    }
 
     // Sanitize the HTML output
-    sanitize_html(&html_output)
+    sanitize_html(&html_output, scheme, &classes)
 }
 
 // Extracts Wikilinks ([[wikilink]]) from Markdown content.
@@ -26,6 +194,320 @@ pub fn extract_links(content: &str) -> Vec<String> {
         .collect()
 }
 
+// Returns the note a wikilink points at, without its alias, section and `.md` extension.
+pub fn link_target(link: &str) -> &str {
+    let target = link.split(['|', '#']).next().unwrap_or_default().trim();
+    target.strip_suffix(".md").unwrap_or(target)
+}
+
+// Rewrites [[Target]] and [[Target|Alias]] wikilinks (leaving embeds untouched) with the given function.
+pub fn rewrite_wikilinks<F>(content: &str, rewrite: F) -> String
+where
+    F: Fn(&str, &str) -> String,
+{
+    let re = Regex::new(r"(!?)\[\[([^\]|]+)(?:\|([^\]]+))?\]\]").unwrap();
+    re.replace_all(content, |caps: &regex::Captures| {
+        if !caps[1].is_empty() {
+            return caps[0].to_string();
+        }
+        let target = caps[2].trim();
+        let label = caps.get(3).map(|label| label.as_str().trim()).unwrap_or(target);
+        rewrite(target, label)
+    })
+    .to_string()
+}
+
+// Replaces `![[Note]]`, `![[Note#Section]]` and `![[Note#^block]]` embeds with the embedded text,
+// loading notes with the given function. Attachment embeds and unresolved embeds are left untouched.
+pub fn resolve_embeds<F>(content: &str, load: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    resolve_embeds_at(content, &load, 0)
+}
+
+fn resolve_embeds_at<F>(content: &str, load: &F, depth: usize) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    if depth >= MAX_EMBED_DEPTH {
+        return content.to_string();
+    }
+    let re = Regex::new(r"!\[\[([^\]|#]+)(?:#([^\]|]+))?(?:\|[^\]]*)?\]\]").unwrap();
+    re.replace_all(content, |caps: &regex::Captures| {
+        let target = caps[1].trim();
+        // Note titles never contain a dot, so anything with an extension is an attachment
+        if target.rsplit('/').next().is_some_and(|name| name.contains('.')) {
+            return caps[0].to_string();
+        }
+        let Some(note) = load(target) else {
+            return caps[0].to_string();
+        };
+        let body = frontmatter::strip(&note);
+        let embedded = match caps.get(2).map(|anchor| anchor.as_str().trim()) {
+            None => Some(body.trim_end().to_string()),
+            Some(anchor) => match anchor.strip_prefix('^') {
+                Some(block) => extract_block_ids(body).into_iter().find(|(id, _)| id == block).map(|(_, text)| text),
+                None => outline::section(body, anchor),
+            },
+        };
+        match embedded {
+            Some(text) => resolve_embeds_at(&text, load, depth + 1),
+            None => caps[0].to_string(),
+        }
+    })
+    .to_string()
+}
+
+// Normalizes a `/`-separated relative path, resolving `.` and `..` segments.
+pub fn normalize_path(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn join_path(dir: &str, path: &str) -> String {
+    normalize_path(&format!("{}/{}", dir, path))
+}
+
+// Builds the path of `target` relative to the folder `from` (both relative to the vault root).
+pub fn relative_path(from: &str, target: &str) -> String {
+    let from: Vec<&str> = from.split('/').filter(|part| !part.is_empty()).collect();
+    let target: Vec<&str> = target.split('/').collect();
+    let common = from.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    let mut parts = vec![".."; from.len() - common];
+    parts.extend(&target[common..]);
+    parts.join("/")
+}
+
+// Rewrites the local destinations of inline Markdown links and images, keeping fragments and link titles.
+fn rewrite_link_destinations<F>(content: &str, rewrite: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let re = Regex::new(r"\]\(([^)\s]+)([^)]*)\)").unwrap();
+    re.replace_all(content, |caps: &regex::Captures| {
+        let dest = &caps[1];
+        if dest.contains("://") || dest.starts_with('#') || dest.starts_with('/') || dest.starts_with("mailto:") {
+            return caps[0].to_string();
+        }
+        let (path, fragment) = match dest.split_once('#') {
+            Some((path, fragment)) => (path, format!("#{}", fragment)),
+            None => (dest, String::new()),
+        };
+        match rewrite(&path.replace("%20", " ")) {
+            Some(new) => format!("]({}{}{})", new.replace(' ', "%20"), fragment, &caps[2]),
+            None => caps[0].to_string(),
+        }
+    })
+    .to_string()
+}
+
+// Points wikilinks, embeds and relative Markdown links at a note that moved from `old_path` to `new_path`
+// (vault-relative, without extension). `source_dir` is the folder of the note being rewritten.
+// Bare `[[Title]]` links keep resolving by title, so they only change when the title itself changed, and
+// only when `bare` says they resolved to the moved note rather than to another note of the same name.
+pub fn retarget_note_links(content: &str, source_dir: &str, old_path: &str, new_path: &str, bare: bool) -> String {
+    let old_title = old_path.rsplit('/').next().unwrap_or(old_path);
+    let new_title = new_path.rsplit('/').next().unwrap_or(new_path);
+
+    let wiki_re = Regex::new(r"(!?\[\[)([^\]|#]+)([^\]]*\]\])").unwrap();
+    let content = wiki_re.replace_all(content, |caps: &regex::Captures| {
+        let target = caps[2].trim();
+        let target = target.strip_suffix(".md").unwrap_or(target);
+        let new_target = if target.contains('/') && normalize_path(target) == old_path {
+            new_path
+        } else if bare && !target.contains('/') && target.eq_ignore_ascii_case(old_title) && old_title != new_title {
+            new_title
+        } else {
+            return caps[0].to_string();
+        };
+        format!("{}{}{}", &caps[1], new_target, &caps[3])
+    });
+
+    let old_file = format!("{}.md", old_path);
+    rewrite_link_destinations(&content, |dest| {
+        let resolved = join_path(source_dir, dest);
+        if resolved == old_file {
+            Some(relative_path(source_dir, &format!("{}.md", new_path)))
+        } else if resolved == old_path {
+            Some(relative_path(source_dir, new_path))
+        } else {
+            None
+        }
+    })
+}
+
+// Re-bases the relative Markdown links of a note that moved from the folder `old_dir` to `new_dir`.
+pub fn rebase_relative_links(content: &str, old_dir: &str, new_dir: &str) -> String {
+    rewrite_link_destinations(content, |dest| {
+        let rebased = relative_path(new_dir, &join_path(old_dir, dest));
+        (rebased != dest).then_some(rebased)
+    })
+}
+
+// Local destinations of the Markdown links and images of a content, without anchors.
+fn local_destinations(content: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    for event in Parser::new(content) {
+        if let Event::Start(Tag::Image { dest_url, .. }) | Event::Start(Tag::Link { dest_url, .. }) = event {
+            let target = dest_url.split('#').next().unwrap_or_default().replace("%20", " ");
+            if !target.is_empty() && !target.contains("://") && !target.starts_with("mailto:") {
+                targets.push(target);
+            }
+        }
+    }
+    targets
+}
+
+// Targets of the `![[file]]` embeds of a content, without size or section.
+pub fn embed_targets(content: &str) -> Vec<String> {
+    let re = Regex::new(r"!\[\[([^\]|#]+)").unwrap();
+    re.captures_iter(content).map(|cap| cap[1].trim().to_string()).collect()
+}
+
+// The vault-relative file an embed points at, as rendering resolves it: from the vault root when it names
+// a folder, from the attachment folder otherwise.
+fn embed_path(target: &str, attachment_folder: &str) -> String {
+    if target.contains('/') { normalize_path(target) } else { join_path(attachment_folder, target) }
+}
+
+// Extracts local link, image and embed targets (no URLs or anchors) from Markdown content.
+pub fn extract_local_targets(content: &str) -> Vec<String> {
+    let mut targets = local_destinations(content);
+    targets.extend(embed_targets(content));
+    targets
+}
+
+// Resolves the local targets of a note in `source_dir` to vault-relative paths: link and image
+// destinations relative to the note, embeds like `embed_path` does.
+pub fn resolve_local_targets(content: &str, source_dir: &str, attachment_folder: &str) -> Vec<String> {
+    let mut targets: Vec<String> = local_destinations(content).iter().map(|dest| join_path(source_dir, dest)).collect();
+    targets.extend(embed_targets(content).iter().map(|target| embed_path(target, attachment_folder)));
+    targets
+}
+
+// Extracts the http(s) URLs of links, images and bare URLs in the text, each once in order of appearance.
+// URLs in code are left out.
+pub fn extract_external_urls(content: &str) -> Vec<String> {
+    let bare_re = Regex::new(r"https?://[^\s<>()\[\]]+").unwrap();
+    let mut urls: Vec<String> = Vec::new();
+    let mut push = |url: &str| {
+        let url = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '"', '\'']);
+        if !urls.iter().any(|known| known == url) {
+            urls.push(url.to_string());
+        }
+    };
+    let mut in_code = false;
+    for event in Parser::new(frontmatter::strip(content)) {
+        match event {
+            Event::Start(Tag::Image { dest_url, .. }) | Event::Start(Tag::Link { dest_url, .. })
+                if dest_url.starts_with("http://") || dest_url.starts_with("https://") =>
+            {
+                push(&dest_url)
+            }
+            Event::Start(Tag::CodeBlock(_)) => in_code = true,
+            Event::End(TagEnd::CodeBlock) => in_code = false,
+            Event::Text(text) if !in_code => bare_re.find_iter(&text).for_each(|url| push(url.as_str())),
+            _ => {}
+        }
+    }
+    urls
+}
+
+// Points the links, images and embeds of a note in `source_dir` that resolve to the file at `old_path`
+// (vault-relative) at `new_path`. Files of the same name in other folders keep their links.
+pub fn retarget_attachment_links(content: &str, source_dir: &str, attachment_folder: &str, old_path: &str, new_path: &str) -> String {
+    let wiki_re = Regex::new(r"(!?\[\[)([^\]|#]+)([^\]]*\]\])").unwrap();
+    let content = wiki_re.replace_all(content, |caps: &regex::Captures| {
+        if embed_path(caps[2].trim(), attachment_folder) != old_path {
+            return caps[0].to_string();
+        }
+        // Files directly in the attachment folder are embedded by name, others by path
+        let new_target = match new_path.strip_prefix(&format!("{}/", attachment_folder)) {
+            Some(name) if !name.contains('/') => name,
+            _ => new_path,
+        };
+        format!("{}{}{}", &caps[1], new_target, &caps[3])
+    });
+    rewrite_link_destinations(&content, |dest| (join_path(source_dir, dest) == old_path).then(|| relative_path(source_dir, new_path)))
+}
+
+// Extracts inline `#tags` (including nested `#tag/child`) that start a word.
+pub fn extract_inline_tags(content: &str) -> Vec<String> {
+    let re = Regex::new(r"#[\w/-]*[A-Za-z_][\w/-]*").unwrap();
+    let mut tags: Vec<String> = Vec::new();
+    for found in re.find_iter(content) {
+        let at_word_start = content[..found.start()].chars().last().is_none_or(char::is_whitespace);
+        let tag = found.as_str()[1..].trim_end_matches('/').to_string();
+        if at_word_start && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+// Returns the tags of a note: its frontmatter `tags` followed by the inline tags of its body.
+pub fn note_tags(content: &str) -> Vec<String> {
+    let mut tags = frontmatter::parse(content).map(|fields| fields.tags()).unwrap_or_default();
+    for tag in extract_inline_tags(frontmatter::strip(content)) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+// Extracts `^block-id` markers ending a line, with the text of the block they label.
+pub fn extract_block_ids(content: &str) -> Vec<(String, String)> {
+    let re = Regex::new(r"^(.*?)\s+\^([A-Za-z0-9-]+)\s*$").unwrap();
+    content
+        .lines()
+        .filter_map(|line| re.captures(line))
+        .map(|caps| (caps[2].to_string(), caps[1].trim().to_string()))
+        .collect()
+}
+
+// Returns the plain text of the first paragraph that has any, ignoring images and embeds.
+pub fn first_paragraph(content: &str) -> Option<String> {
+    let embed_re = Regex::new(r"!\[\[[^\]]*\]\]").unwrap();
+    let mut text = String::new();
+    let mut in_paragraph = false;
+    let mut in_image = false;
+
+    for event in Parser::new(content) {
+        match event {
+            Event::Start(Tag::Paragraph) => {
+                in_paragraph = true;
+                text.clear();
+            }
+            Event::Start(Tag::Image { .. }) => in_image = true,
+            Event::End(TagEnd::Image) => in_image = false,
+            Event::Text(part) | Event::Code(part) if in_paragraph && !in_image => text.push_str(&part),
+            Event::SoftBreak | Event::HardBreak if in_paragraph => text.push(' '),
+            Event::End(TagEnd::Paragraph) => {
+                in_paragraph = false;
+                let embeds_removed = embed_re.replace_all(&text, "").to_string();
+                let plain = rewrite_wikilinks(&embeds_removed, |_, label| label.to_string());
+                let plain = plain.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !plain.is_empty() {
+                    return Some(plain);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 // Extracts text-only content from Markdown (without formatting).
 pub fn extract_plain_text(content: &str) -> String {
     let parser = Parser::new(content);
@@ -49,9 +531,31 @@ pub fn extract_plain_text(content: &str) -> String {
     plain_text
 }
 
-// Sanitizes HTML to prevent XSS attacks.
-pub fn sanitize_html(html: &str) -> String {
-    clean(html)
+// Counts the words of the rendered text of a note, ignoring frontmatter, markup and link targets.
+pub fn word_count(content: &str) -> usize {
+    Parser::new(frontmatter::strip(content))
+        .map(|event| match event {
+            Event::Text(text) | Event::Code(text) => text
+                .split_whitespace()
+                .filter(|word| word.chars().any(char::is_alphanumeric))
+                .count(),
+            _ => 0,
+        })
+        .sum()
+}
+
+// Sanitizes HTML to prevent XSS attacks, keeping the wikilink anchors that use `link_scheme` and the
+// `cssclasses` of the note on `div`s.
+pub fn sanitize_html(html: &str, link_scheme: &str, css_classes: &[String]) -> String {
+    let mut builder = ammonia::Builder::default();
+    builder
+        .add_url_schemes([link_scheme, ASSET_SCHEME])
+        .add_allowed_classes("a", &["wikilink"])
+        .add_tag_attributes("a", &["data-target"]);
+    if !css_classes.is_empty() {
+        builder.add_allowed_classes("div", css_classes.iter().map(String::as_str));
+    }
+    builder.clean(html).to_string()
 }
 
 #[cfg(test)]
@@ -64,6 +568,53 @@ mod tests {
         let html_content = render_markdown(md_content);
         assert!(html_content.contains("<h1>Title</h1>"));
         assert!(html_content.contains("<strong>bold</strong>"));
+        assert_eq!(render_markdown("---\ntags: [a]\n---\nText"), "<p>Text</p>\n");
+    }
+
+    #[test]
+    fn test_render_css_classes() {
+        let html = render_markdown("---\ncssclasses: [wide-tables, two-column]\n---\nText");
+        assert_eq!(html, "<div class=\"wide-tables two-column\">\n<p>Text</p>\n</div>\n");
+        // Only the note's own classes survive sanitization
+        let html = render_markdown("---\ncssclasses: wide\n---\n<div class=\"wide evil\">x</div>");
+        assert!(html.starts_with("<div class=\"wide\">\n<div class=\"wide\">x</div>"));
+        assert_eq!(render_markdown("<div class=\"wide\">x</div>"), "<div>x</div>");
+    }
+
+    #[test]
+    fn test_render_wikilinks() {
+        let html = render_markdown("See [[Note Title]], [[Projects/Plan#Next steps|the plan]] and `[[code]]`.\n\n![[photo.png]]");
+        assert_eq!(
+            html,
+            "<p>See <a href=\"note://Note%20Title\" class=\"wikilink\" data-target=\"Note Title\" rel=\"noopener noreferrer\">Note Title</a>, \
+             <a href=\"note://Projects/Plan#Next%20steps\" class=\"wikilink\" data-target=\"Projects/Plan\" rel=\"noopener noreferrer\">the plan</a> \
+             and <code>[[code]]</code>.</p>\n<p>![[photo.png]]</p>\n"
+        );
+        let html = render_markdown_with_links("[[A & B|x\"y]]", "app");
+        assert_eq!(
+            html,
+            "<p><a href=\"app://A%20%26%20B\" class=\"wikilink\" data-target=\"A &amp; B\" rel=\"noopener noreferrer\">x\"y</a></p>\n"
+        );
+        assert!(render_markdown_with_links("[[A]]", "javascript").contains("note://A"));
+        assert!(!is_valid_link_scheme("Note") && is_valid_link_scheme("obsidian+app"));
+    }
+
+    #[test]
+    fn test_render_assets() {
+        let assets = AssetBase { vault_dir: "/home/me/Vaults/Work", note_folder: "Trips", attachment_folder: "attachments" };
+        let html = render_note_markdown(
+            "![Beach](../attachments/my%20beach.png) ![Web](https://example.com/a.png)\n\n![[map.jpg]] ![[Other note]]",
+            DEFAULT_LINK_SCHEME,
+            &assets,
+        );
+        let origin = asset_origin();
+        assert!(html.contains(&format!("src=\"{}/%2Fhome%2Fme%2FVaults%2FWork%2Fattachments%2Fmy%20beach.png\"", origin)));
+        assert!(html.contains("src=\"https://example.com/a.png\""));
+        assert!(html.contains(&format!("<img src=\"{}/%2Fhome%2Fme%2FVaults%2FWork%2Fattachments%2Fmap.jpg\" alt=\"map.jpg\">", origin)));
+        assert!(html.contains("![[Other note]]"));
+        assert_eq!(asset_url("C:\\Vaults\\a b (1).png"), format!("{}/C%3A%2FVaults%2Fa%20b%20(1).png", origin));
+        let expected = if cfg!(any(windows, target_os = "android")) { "http://asset.localhost" } else { "asset://localhost" };
+        assert_eq!(origin, expected);
     }
 
     #[test]
@@ -71,6 +622,120 @@ mod tests {
         let md_content = "This note links to [[AnotherNote]] and [[TestNote]].";
         let links = extract_links(md_content);
         assert_eq!(links, vec!["AnotherNote", "TestNote"]);
+        assert_eq!(link_target("Folder/Note.md#Intro|alias"), "Folder/Note");
+    }
+
+    #[test]
+    fn test_extract_local_targets() {
+        let md_content = "![diagram](attachments/my%20diagram.png) [site](https://example.com) [doc](files/spec.pdf#page=2) ![[photo.jpg|200]]";
+        let targets = extract_local_targets(md_content);
+        assert_eq!(targets, vec!["attachments/my diagram.png", "files/spec.pdf", "photo.jpg"]);
+        let resolved = resolve_local_targets(&format!("{} ![[Trips/map.png]] ![](../attachments/b.png)", md_content), "Notes", "attachments");
+        assert_eq!(
+            resolved,
+            vec!["Notes/attachments/my diagram.png", "Notes/files/spec.pdf", "attachments/b.png", "attachments/photo.jpg", "Trips/map.png"]
+        );
+    }
+
+    #[test]
+    fn test_extract_external_urls() {
+        let content = "---\nsource: https://front.matter\n---\n[site](https://example.com/a) ![img](http://img.example/x.png) \
+            [local](notes/a.md) <https://auto.link> See https://bare.example/path?q=1. Again [dup](https://example.com/a)\n\n\
+            `https://inline.code`\n\n```\nhttps://code.block\n```\n";
+        assert_eq!(
+            extract_external_urls(content),
+            vec!["https://example.com/a", "http://img.example/x.png", "https://auto.link", "https://bare.example/path?q=1"]
+        );
+    }
+
+    #[test]
+    fn test_retarget_attachment_links() {
+        let md_content = "![a](../attachments/old%20name.png#x) [b](old name.png) ![[old name.png|100]] ![[Trips/old name.png]] ![c](other.png)";
+        let renamed = retarget_attachment_links(md_content, "Notes", "attachments", "attachments/old name.png", "attachments/new name.png");
+        assert_eq!(
+            renamed,
+            "![a](../attachments/new%20name.png#x) [b](old name.png) ![[new name.png|100]] ![[Trips/old name.png]] ![c](other.png)"
+        );
+        // Files of the same name elsewhere keep their links; moves out of the attachment folder embed by path
+        let moved = retarget_attachment_links("![[Trips/a.png]] ![[a.png]]", "", "attachments", "Trips/a.png", "attachments/sub/a.png");
+        assert_eq!(moved, "![[attachments/sub/a.png]] ![[a.png]]");
+    }
+
+    #[test]
+    fn test_retarget_note_links() {
+        let content = "[[Inbox/Clip]] [[Inbox/Clip#Part|alias]] ![[Inbox/Clip]] [[Clip]] [x](../Inbox/Clip.md#top) [y](https://a.b/Inbox/Clip.md)";
+        assert_eq!(
+            retarget_note_links(content, "Daily", "Inbox/Clip", "Projects/Web/Clip", true),
+            "[[Projects/Web/Clip]] [[Projects/Web/Clip#Part|alias]] ![[Projects/Web/Clip]] [[Clip]] [x](../Projects/Web/Clip.md#top) [y](https://a.b/Inbox/Clip.md)"
+        );
+        // Renames also update bare links
+        assert_eq!(retarget_note_links("[[Old]] [[old]] [o](Old.md)", "", "Old", "New", true), "[[New]] [[New]] [o](New.md)");
+        // Bare links that resolved to another note of the same name are left alone
+        assert_eq!(retarget_note_links("[[Old]] [[Inbox/Old]]", "", "Inbox/Old", "Inbox/New", false), "[[Old]] [[Inbox/New]]");
+    }
+
+    #[test]
+    fn test_rebase_relative_links() {
+        let content = "![img](assets/a.png) [n](../Other.md) [w](https://x.y/z)";
+        assert_eq!(
+            rebase_relative_links(content, "Inbox", "Projects/Web"),
+            "![img](../../Inbox/assets/a.png) [n](../../Other.md) [w](https://x.y/z)"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_wikilinks() {
+        let md_content = "See [[Note A]], [[Note B|the other one]] and ![[image.png]].";
+        let rewritten = rewrite_wikilinks(md_content, |target, label| format!("<{}:{}>", target, label));
+        assert_eq!(rewritten, "See <Note A:Note A>, <Note B:the other one> and ![[image.png]].");
+    }
+
+    #[test]
+    fn test_resolve_embeds() {
+        let load = |title: &str| match title {
+            "Guide" => Some("---\ntags: [a]\n---\n# Intro\nHello\n# Setup\nInstall it ^install\n".to_string()),
+            "Loop" => Some("![[Loop]]".to_string()),
+            _ => None,
+        };
+        let content = "![[Guide#Setup]]\n\n![[Guide#^install]]\n\n![[Guide]]\n\n![[photo.png]] ![[Missing]]";
+        let resolved = resolve_embeds(content, load);
+        assert!(resolved.starts_with("# Setup\nInstall it ^install\n\nInstall it\n\n# Intro\nHello\n# Setup"));
+        assert!(resolved.ends_with("![[photo.png]] ![[Missing]]"));
+        assert_eq!(resolve_embeds("![[Loop]]", load), "![[Loop]]");
+    }
+
+    #[test]
+    fn test_extract_inline_tags() {
+        let md_content = "#rust notes about #rust/async, issue #42 and a#fake [x](#anchor)\n# Heading";
+        assert_eq!(extract_inline_tags(md_content), vec!["rust", "rust/async"]);
+    }
+
+    #[test]
+    fn test_note_tags() {
+        assert_eq!(note_tags("---\ntags: [rust, \"#web\"]\n---\n#web and #cli"), vec!["rust", "web", "cli"]);
+    }
+
+    #[test]
+    fn test_extract_block_ids() {
+        let md_content = "A paragraph ^intro\nNo id here\n- item ^list-1 \nnot^an-id";
+        let ids = extract_block_ids(md_content);
+        assert_eq!(ids, vec![
+            ("intro".to_string(), "A paragraph".to_string()),
+            ("list-1".to_string(), "- item".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_first_paragraph() {
+        let md_content = "# Title\n\n![cover](cover.png)\n\nFirst **real**\nparagraph with [[Link|a link]] ![[img.png]].\n\nSecond.";
+        assert_eq!(first_paragraph(md_content).as_deref(), Some("First real paragraph with a link ."));
+        assert_eq!(first_paragraph("# Only a heading"), None);
+    }
+
+    #[test]
+    fn test_word_count() {
+        let content = "---\ntags: [a, b]\n---\n# Two words\n\nSee [the docs](https://example.com) and `code`.";
+        assert_eq!(word_count(content), 7);
     }
 
     #[test]
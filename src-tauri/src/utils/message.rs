@@ -0,0 +1,130 @@
+// Messages shown to the user, as a stable key with parameters (`note.not_found`, `{ "path": … }`) that
+// the frontend looks up in its translations. Each message also carries its English text, used in logs
+// and by the frontend for keys it has no translation of.
+//
+// Inside the backend errors stay `io::Error`s: `Message::error` wraps a message into one, and commands
+// turn errors into `AppError`s that carry it (see `utils::error`). Errors that did not start as a message (plain IO
+// errors, library errors) get an `io.<kind>` key with their text as the `detail` parameter.
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, ErrorKind};
+
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredMessage")]
+pub struct Message {
+    pub key: String,
+    pub params: BTreeMap<String, String>,
+    // English text with the parameters filled in
+    pub message: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredMessage {
+    Keyed {
+        key: String,
+        #[serde(default)]
+        params: BTreeMap<String, String>,
+        message: String,
+    },
+    // Reports saved before messages had keys
+    Text(String),
+}
+
+impl From<StoredMessage> for Message {
+    fn from(stored: StoredMessage) -> Self {
+        match stored {
+            StoredMessage::Keyed { key, params, message } => Self { key, params, message },
+            StoredMessage::Text(text) => Self::new("text", "{text}").with("text", text.trim_start_matches(['❌', '✅', ' '])),
+        }
+    }
+}
+
+impl Message {
+    // A message with its English text; `{name}` placeholders in it are filled in by `with`.
+    pub fn new(key: &str, text: &str) -> Self {
+        Self { key: key.to_string(), params: BTreeMap::new(), message: text.to_string() }
+    }
+
+    // Adds a parameter to the message.
+    pub fn with(mut self, name: &str, value: impl fmt::Display) -> Self {
+        let value = value.to_string();
+        self.message = self.message.replace(&format!("{{{}}}", name), &value);
+        self.params.insert(name.to_string(), value);
+        self
+    }
+
+    // Wraps the message into an IO error, so it can be returned through `io::Result`s.
+    pub fn error(self, kind: ErrorKind) -> io::Error {
+        io::Error::new(kind, self)
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Message {}
+
+fn kind_key(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::NotFound => "io.not_found",
+        ErrorKind::PermissionDenied => "io.permission_denied",
+        ErrorKind::AlreadyExists => "io.already_exists",
+        ErrorKind::InvalidInput => "io.invalid_input",
+        ErrorKind::InvalidData => "io.invalid_data",
+        ErrorKind::WouldBlock => "io.busy",
+        _ => "io.other",
+    }
+}
+
+impl From<&io::Error> for Message {
+    fn from(e: &io::Error) -> Self {
+        let inner = e.get_ref();
+        if let Some(error) = inner.and_then(|inner| inner.downcast_ref::<AppError>()) {
+            return error.message().clone();
+        }
+        match inner.and_then(|inner| inner.downcast_ref::<Message>()) {
+            Some(message) => message.clone(),
+            None => Message::new(kind_key(e.kind()), "{detail}").with("detail", e),
+        }
+    }
+}
+
+impl From<io::Error> for Message {
+    fn from(e: io::Error) -> Self {
+        Message::from(&e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_roundtrip() {
+        let error = Message::new("note.not_found", "Note {path} does not exist").with("path", "Projects/Plan").error(ErrorKind::NotFound);
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+        assert_eq!(error.to_string(), "Note Projects/Plan does not exist");
+
+        let message = Message::from(error);
+        assert_eq!(message.key, "note.not_found");
+        assert_eq!(message.params.get("path").map(String::as_str), Some("Projects/Plan"));
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["message"], "Note Projects/Plan does not exist");
+        assert_eq!(serde_json::from_value::<Message>(json).unwrap(), message);
+
+        // Plain IO errors keep their text as a parameter
+        let message = Message::from(io::Error::new(ErrorKind::PermissionDenied, "access denied"));
+        assert_eq!((message.key.as_str(), message.message.as_str()), ("io.permission_denied", "access denied"));
+
+        // Texts saved before messages had keys still load
+        let old: Message = serde_json::from_str("\"✅ Published 3 notes\"").unwrap();
+        assert_eq!((old.key.as_str(), old.message.as_str()), ("text", "Published 3 notes"));
+    }
+}
@@ -1,3 +1,12 @@
 pub mod file_operations;
 pub mod string_utils;
-pub mod markdown;
\ No newline at end of file
+pub mod markdown;
+pub mod frontmatter;
+pub mod outline;
+pub mod table;
+pub mod conflict;
+pub mod excerpt;
+pub mod message;
+pub mod plan;
+pub mod error;
+pub mod filename_policy;
@@ -0,0 +1,285 @@
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+
+use crate::utils::{frontmatter, string_utils, message::Message};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Heading {
+    pub level: usize,
+    pub text: String,
+    pub slug: String,
+    pub line: usize,
+}
+
+// Where a match sits in a note: its byte offset and the slug of the enclosing heading, if any.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchLocation {
+    pub offset: usize,
+    pub line: usize,
+    pub heading: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+// Lists the ATX headings of a note, skipping frontmatter and fenced code blocks.
+// Duplicate slugs are numbered (`intro`, `intro-1`, ...) like most Markdown renderers do.
+pub fn headings(content: &str) -> Vec<Heading> {
+    let heading_re = Regex::new(r"^(#{1,6})\s+(.*?)(?:\s+#+)?\s*$").unwrap();
+    let skipped = match frontmatter::split(content) {
+        (Some(_), body) => content[..content.len() - body.len()].lines().count(),
+        (None, _) => 0,
+    };
+
+    let mut headings = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut in_code = false;
+    for (line_number, line) in content.lines().enumerate().skip(skipped) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        if let Some(caps) = heading_re.captures(line) {
+            let text = caps[2].to_string();
+            let base = string_utils::slugify(&text);
+            let count = seen.entry(base.clone()).or_insert(0);
+            let slug = if *count == 0 { base.clone() } else { format!("{}-{}", base, count) };
+            *count += 1;
+            headings.push(Heading {
+                level: caps[1].len(),
+                text,
+                slug,
+                line: line_number,
+            });
+        }
+    }
+    headings
+}
+
+// Returns the nearest heading at or above a line.
+pub fn enclosing_heading(headings: &[Heading], line: usize) -> Option<&Heading> {
+    headings.iter().take_while(|heading| heading.line <= line).last()
+}
+
+// Returns the chain of headings enclosing a line, outermost first (`# A` > `## B` > `### C`).
+pub fn breadcrumbs(headings: &[Heading], line: usize) -> Vec<&Heading> {
+    let mut chain: Vec<&Heading> = Vec::new();
+    for heading in headings.iter().take_while(|heading| heading.line <= line) {
+        chain.retain(|parent| parent.level < heading.level);
+        chain.push(heading);
+    }
+    chain
+}
+
+// Finds the case-insensitive occurrences of a query, anchored to their enclosing headings.
+pub fn locate_matches(content: &str, query: &str) -> Vec<MatchLocation> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let all = headings(content);
+    let re = Regex::new(&format!("(?i){}", regex::escape(query))).unwrap();
+    re.find_iter(content)
+        .map(|found| {
+            let line = content[..found.start()].matches('\n').count();
+            MatchLocation {
+                offset: found.start(),
+                line,
+                heading: enclosing_heading(&all, line).map(|heading| heading.slug.clone()),
+            }
+        })
+        .collect()
+}
+
+// Returns the line range `[start, end)` covered by the section of a heading.
+fn section_range(headings: &[Heading], index: usize, line_count: usize) -> (usize, usize) {
+    let heading = &headings[index];
+    let end = headings[index + 1..]
+        .iter()
+        .find(|next| next.level <= heading.level)
+        .map(|next| next.line)
+        .unwrap_or(line_count);
+    (heading.line, end)
+}
+
+// Returns the section under a heading (given by slug or text), including the heading line and subsections.
+pub fn section(content: &str, name: &str) -> Option<String> {
+    let all = headings(content);
+    let slug = string_utils::slugify(name);
+    let index = all
+        .iter()
+        .position(|heading| heading.slug == slug || heading.text.eq_ignore_ascii_case(name.trim()))?;
+    let lines: Vec<&str> = content.lines().collect();
+    let (start, end) = section_range(&all, index, lines.len());
+    Some(lines[start..end].join("\n"))
+}
+
+fn heading_at(headings: &[Heading], index: usize) -> io::Result<&Heading> {
+    headings
+        .get(index)
+        .ok_or_else(|| Message::new("heading.not_found", "Heading does not exist").error(ErrorKind::NotFound))
+}
+
+fn join_lines(lines: &[&str], original: &str) -> String {
+    let mut joined = lines.join("\n");
+    if original.ends_with('\n') {
+        joined.push('\n');
+    }
+    joined
+}
+
+// Swaps a section (with its body and subsections) with its previous or next sibling section.
+pub fn move_section(content: &str, index: usize, direction: Direction) -> io::Result<String> {
+    let all = headings(content);
+    let heading = heading_at(&all, index)?;
+    let lines: Vec<&str> = content.lines().collect();
+    let (start, end) = section_range(&all, index, lines.len());
+
+    let sibling = match direction {
+        Direction::Up => (0..index)
+            .rev()
+            .find(|&j| all[j].level == heading.level && section_range(&all, j, lines.len()).1 == start),
+        Direction::Down => (index + 1..all.len()).find(|&k| all[k].level == heading.level && all[k].line == end),
+    }
+    .ok_or_else(|| Message::new("outline.cannot_move_section", "Section cannot be moved further").error(ErrorKind::InvalidInput))?;
+
+    let (first, second) = match direction {
+        Direction::Up => (section_range(&all, sibling, lines.len()), (start, end)),
+        Direction::Down => ((start, end), section_range(&all, sibling, lines.len())),
+    };
+    let mut reordered: Vec<&str> = lines[..first.0].to_vec();
+    reordered.extend_from_slice(&lines[second.0..second.1]);
+    reordered.extend_from_slice(&lines[first.0..first.1]);
+    reordered.extend_from_slice(&lines[second.1..]);
+
+    // Headings of the swapped sections keep their text, only their position changes
+    let new_line = |line: usize| {
+        if (first.0..first.1).contains(&line) {
+            line + (second.1 - second.0)
+        } else if (second.0..second.1).contains(&line) {
+            line - (first.1 - first.0)
+        } else {
+            line
+        }
+    };
+    Ok(renumber_anchors(&all, &join_lines(&reordered, content), new_line))
+}
+
+// Rewrites `](#slug)` anchor links after reordering changed the numbering of duplicate headings.
+fn renumber_anchors<F>(before: &[Heading], content: &str, new_line: F) -> String
+where
+    F: Fn(usize) -> usize,
+{
+    let after = headings(content);
+    let slug_at: HashMap<usize, &str> = after.iter().map(|h| (h.line, h.slug.as_str())).collect();
+    let renamed: HashMap<&str, &str> = before
+        .iter()
+        .filter_map(|old| slug_at.get(&new_line(old.line)).map(|new| (old.slug.as_str(), *new)))
+        .filter(|(old, new)| old != new)
+        .collect();
+    if renamed.is_empty() {
+        return content.to_string();
+    }
+
+    let anchor_re = Regex::new(r"\]\(#([^)\s]+)\)").unwrap();
+    anchor_re
+        .replace_all(content, |caps: &regex::Captures| match renamed.get(&caps[1]) {
+            Some(new) => format!("](#{})", new),
+            None => caps[0].to_string(),
+        })
+        .to_string()
+}
+
+// Promotes (negative delta) or demotes (positive delta) a heading together with its subheadings.
+pub fn shift_level(content: &str, index: usize, delta: isize) -> io::Result<String> {
+    let all = headings(content);
+    heading_at(&all, index)?;
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let (_, end) = section_range(&all, index, lines.len());
+
+    let affected: Vec<&Heading> = all[index..].iter().take_while(|h| h.line < end).collect();
+    if affected.iter().any(|h| !(1..=6).contains(&(h.level as isize + delta))) {
+        return Err(Message::new("outline.heading_level_out_of_range", "Heading level must stay between 1 and 6").error(ErrorKind::InvalidInput));
+    }
+    for heading in affected {
+        let line = &lines[heading.line];
+        let rest = line.trim_start_matches('#');
+        lines[heading.line] = format!("{}{}", "#".repeat((heading.level as isize + delta) as usize), rest);
+    }
+
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    Ok(join_lines(&lines, content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---\ntitle: x\n---\n# A\ntext a\n## Notes\na notes\n# B\n```\n# not a heading\n```\n## Notes\nb notes [go](#notes-1)\n";
+
+    #[test]
+    fn test_headings() {
+        let slugs: Vec<String> = headings(NOTE).into_iter().map(|h| h.slug).collect();
+        assert_eq!(slugs, vec!["a", "notes", "b", "notes-1"]);
+        assert_eq!(headings(NOTE)[2].line, 7);
+    }
+
+    #[test]
+    fn test_breadcrumbs() {
+        let all = headings(NOTE);
+        let texts = |line| breadcrumbs(&all, line).iter().map(|h| h.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts(6), vec!["A", "Notes"]);
+        assert_eq!(texts(8), vec!["B"]);
+        assert!(texts(1).is_empty());
+    }
+
+    #[test]
+    fn test_locate_matches() {
+        let matches = locate_matches(NOTE, "NOTES");
+        let slugs: Vec<Option<&str>> = matches.iter().map(|m| m.heading.as_deref()).collect();
+        assert_eq!(slugs, vec![Some("notes"), Some("notes"), Some("notes-1"), Some("notes-1"), Some("notes-1")]);
+        assert_eq!(matches[0].offset, NOTE.find("Notes").unwrap());
+        assert_eq!(matches[0].line, 5);
+        assert_eq!(locate_matches(NOTE, "title")[0].heading, None);
+        assert!(locate_matches(NOTE, " ").is_empty());
+    }
+
+    #[test]
+    fn test_section() {
+        assert_eq!(section(NOTE, "A").as_deref(), Some("# A\ntext a\n## Notes\na notes"));
+        assert_eq!(section(NOTE, "notes-1").as_deref(), Some("## Notes\nb notes [go](#notes-1)"));
+        assert_eq!(section(NOTE, "Missing"), None);
+    }
+
+    #[test]
+    fn test_move_section_renumbers_anchors() {
+        let moved = move_section(NOTE, 2, Direction::Up).unwrap();
+        assert_eq!(
+            moved,
+            "---\ntitle: x\n---\n# B\n```\n# not a heading\n```\n## Notes\nb notes [go](#notes)\n# A\ntext a\n## Notes\na notes\n"
+        );
+        assert_eq!(move_section(&moved, 0, Direction::Down).unwrap(), NOTE);
+        assert!(move_section(NOTE, 0, Direction::Up).is_err());
+        assert!(move_section(NOTE, 1, Direction::Down).is_err());
+    }
+
+    #[test]
+    fn test_shift_level() {
+        let demoted = shift_level(NOTE, 0, 1).unwrap();
+        assert!(demoted.contains("## A\ntext a\n### Notes\n"));
+        assert!(demoted.contains("# B\n"));
+        assert!(shift_level(NOTE, 0, -1).is_err());
+        let promoted = shift_level(NOTE, 3, -1).unwrap();
+        assert!(promoted.contains("# Notes\nb notes"));
+    }
+}
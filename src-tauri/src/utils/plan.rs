@@ -0,0 +1,155 @@
+// Dry runs of destructive operations. Such an operation first plans the files it would delete, write or
+// move, then applies the plan; a dry run returns the plan instead of applying it, so what the user is
+// shown is exactly what the real run would do.
+use serde::{Serialize, Deserialize};
+use std::io;
+use walkdir::WalkDir;
+
+use crate::storage::vault::Vault;
+use crate::utils::file_operations;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Delete,
+    Write,
+    Move,
+    // A folder and anything left in it
+    RemoveFolder,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub kind: ChangeKind,
+    // Relative to the vault; an empty path is the vault folder itself
+    pub path: String,
+    // Destination of a move, relative to the vault
+    pub to: Option<String>,
+    // Size of the deleted or moved file, or of the written content
+    pub size: u64,
+    #[serde(skip)]
+    content: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Plan {
+    pub changes: Vec<PlannedChange>,
+}
+
+// What a command that supports dry runs returns: the plan for a dry run, its usual result otherwise.
+// Serialized with a `status` of `planned` or `done`, so a result of `()` is still told apart.
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Outcome<T> {
+    Planned { plan: Plan },
+    Done { result: T },
+}
+
+impl Plan {
+    fn push(&mut self, kind: ChangeKind, path: &str, to: Option<&str>, size: u64, content: Option<String>) {
+        self.changes.push(PlannedChange { kind, path: path.to_string(), to: to.map(str::to_string), size, content });
+    }
+
+    pub fn delete(&mut self, path: &str, size: u64) {
+        self.push(ChangeKind::Delete, path, None, size, None);
+    }
+
+    pub fn write(&mut self, path: &str, content: &str) {
+        self.push(ChangeKind::Write, path, None, content.len() as u64, Some(content.to_string()));
+    }
+
+    pub fn move_file(&mut self, path: &str, to: &str, size: u64) {
+        self.push(ChangeKind::Move, path, Some(to), size, None);
+    }
+
+    // Plans the removal of a folder: every file in it, then the folder itself. Missing folders plan nothing.
+    pub fn remove_folder(&mut self, vault: &Vault, path: &str) -> io::Result<()> {
+        let root = file_operations::resolve_path(&join(&vault.path, path));
+        if !std::path::Path::new(&root).exists() {
+            return Ok(());
+        }
+        for entry in WalkDir::new(&root).sort_by_file_name() {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                let relative = entry.path().strip_prefix(&root).unwrap_or(entry.path()).to_string_lossy().replace('\\', "/");
+                self.delete(&join(path, &relative), entry.metadata()?.len());
+            }
+        }
+        self.push(ChangeKind::RemoveFolder, path, None, 0, None);
+        Ok(())
+    }
+
+    // Performs the planned changes in order.
+    pub fn apply(&self, vault: &Vault) -> io::Result<()> {
+        for change in &self.changes {
+            let path = join(&vault.path, &change.path);
+            match change.kind {
+                ChangeKind::Delete => file_operations::delete_file(&path)?,
+                ChangeKind::Write => file_operations::write_to_file(&path, change.content.as_deref().unwrap_or_default())?,
+                ChangeKind::Move => {
+                    let to = join(&vault.path, change.to.as_deref().unwrap_or_default());
+                    if let Some((parent, _)) = to.rsplit_once('/') {
+                        file_operations::create_directory(parent)?;
+                    }
+                    file_operations::rename_file(&path, &to)?;
+                }
+                ChangeKind::RemoveFolder => file_operations::delete_directory(&path)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn join(base: &str, path: &str) -> String {
+    match (base.is_empty(), path.is_empty()) {
+        (_, true) => base.to_string(),
+        (true, false) => path.to_string(),
+        _ => format!("{}/{}", base, path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanoid::nanoid;
+    use std::path::Path;
+
+    #[test]
+    fn test_plan_apply() {
+        file_operations::set_base_path(None);
+        let vault = Vault::create_vault(&format!("test_vault_{}", nanoid!())).unwrap();
+        file_operations::create_directory(&format!("{}/old/deep", vault.path)).unwrap();
+        file_operations::write_to_file(&format!("{}/old/deep/a.md", vault.path), "abc").unwrap();
+        file_operations::write_to_file(&format!("{}/Note.md", vault.path), "before").unwrap();
+        file_operations::write_to_file(&format!("{}/image.png", vault.path), "png").unwrap();
+
+        let mut plan = Plan::default();
+        plan.write("Note.md", "after");
+        plan.move_file("image.png", "attachments/image.png", 3);
+        plan.remove_folder(&vault, "old").unwrap();
+        plan.remove_folder(&vault, "missing").unwrap();
+        let kinds: Vec<(ChangeKind, &str)> = plan.changes.iter().map(|change| (change.kind, change.path.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (ChangeKind::Write, "Note.md"),
+                (ChangeKind::Move, "image.png"),
+                (ChangeKind::Delete, "old/deep/a.md"),
+                (ChangeKind::RemoveFolder, "old"),
+            ]
+        );
+        // Planning touches nothing, and the content to write stays out of the plan sent to the frontend
+        assert!(Path::new(&format!("{}/old/deep/a.md", vault.path)).exists());
+        assert!(!serde_json::to_string(&plan).unwrap().contains("after"));
+        assert_eq!(serde_json::to_string(&Outcome::Done { result: () }).unwrap(), "{\"status\":\"done\",\"result\":null}");
+        assert!(serde_json::to_string(&Outcome::<()>::Planned { plan: plan.clone() }).unwrap().starts_with("{\"status\":\"planned\",\"plan\":"));
+
+        plan.apply(&vault).unwrap();
+        assert_eq!(file_operations::read_from_file(&format!("{}/Note.md", vault.path)).unwrap(), "after");
+        assert!(Path::new(&format!("{}/attachments/image.png", vault.path)).exists());
+        assert!(!Path::new(&format!("{}/old", vault.path)).exists());
+
+        // Cleanup
+        vault.delete_vault().expect("Failed to delete vault");
+    }
+}
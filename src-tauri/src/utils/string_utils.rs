@@ -6,6 +6,21 @@ pub fn sanitize_filename(name: &str) -> String {
     re.replace_all(name, "").to_string()
 }
 
+// Sanitizes a note title into a file name like `sanitize_filename`, but keeps the spaces between words, so
+// `My Note` is stored as `My Note.md`. Runs of whitespace become one space and the ends are trimmed.
+pub fn title_filename(title: &str) -> String {
+    title.split_whitespace().map(sanitize_filename).filter(|word| !word.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+// Sanitizes every segment of a vault-relative folder or note path, dropping empty segments.
+pub fn sanitize_path(path: &str) -> String {
+    path.split(['/', '\\'])
+        .map(title_filename)
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
 // Trims and normalizes whitespace in a string.
 #[allow(dead_code)]
 pub fn normalize_whitespace(input: &str) -> String {
@@ -13,6 +28,19 @@ pub fn normalize_whitespace(input: &str) -> String {
     re.replace_all(input.trim(), " ").to_string()
 }
 
+// Turns heading text into a URL anchor slug (lowercase, dashes instead of spaces).
+pub fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            c if c.is_whitespace() => Some('-'),
+            _ => None,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -25,10 +53,31 @@ mod tests {
         assert_eq!(sanitize_filename("hello_world"), "hello_world");
     }
 
+    #[test]
+    fn test_title_filename() {
+        assert_eq!(title_filename("My Note"), "My Note");
+        assert_eq!(title_filename("  Launch   plan: v2! "), "Launch plan v2");
+        assert_eq!(title_filename("?? !!"), "");
+    }
+
+    #[test]
+    fn test_sanitize_path() {
+        assert_eq!(sanitize_path("projects/ideas/my-note"), "projects/ideas/my-note");
+        assert_eq!(sanitize_path("/../projects//my  note\\x"), "projects/my note/x");
+        assert_eq!(sanitize_path("../.."), "");
+    }
+
     #[test]
     fn test_normalize_whitespace() {
         assert_eq!(normalize_whitespace("   hello    world   "), "hello world");
         assert_eq!(normalize_whitespace("singleword"), "singleword");
         assert_eq!(normalize_whitespace("multiple    spaces   here"), "multiple spaces here");
     }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Hello World"), "hello-world");
+        assert_eq!(slugify("What's new? (v2)"), "whats-new-v2");
+        assert_eq!(slugify("  Ünïcode_Title "), "ünïcode_title");
+    }
 }
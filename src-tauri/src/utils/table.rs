@@ -0,0 +1,301 @@
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::cmp::Ordering;
+use std::io::{self, Error, ErrorKind};
+
+use crate::utils::{frontmatter, message::Message};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Table {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub header: Vec<String>,
+    pub alignments: Vec<Alignment>,
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TableOp {
+    InsertRow { at: usize },
+    DeleteRow { index: usize },
+    InsertColumn { at: usize, header: String },
+    DeleteColumn { index: usize },
+    Sort { column: usize, descending: bool },
+    Align { column: usize, alignment: Alignment },
+}
+
+// Splits a table row into trimmed cells, honouring escaped pipes.
+fn split_row(line: &str) -> Vec<String> {
+    let trimmed = line.trim();
+    let trimmed = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let trimmed = if trimmed.ends_with('|') && !trimmed.ends_with("\\|") {
+        &trimmed[..trimmed.len() - 1]
+    } else {
+        trimmed
+    };
+
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut chars = trimmed.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                current.push_str("\\|");
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(c),
+        }
+    }
+    cells.push(current.trim().to_string());
+    cells
+}
+
+fn parse_alignment(cell: &str) -> Alignment {
+    match (cell.starts_with(':'), cell.ends_with(':')) {
+        (true, true) => Alignment::Center,
+        (true, false) => Alignment::Left,
+        (false, true) => Alignment::Right,
+        (false, false) => Alignment::None,
+    }
+}
+
+// Finds the GFM tables of a note, skipping frontmatter and fenced code blocks.
+pub fn find_tables(content: &str) -> Vec<Table> {
+    let delimiter_re = Regex::new(r"^\s*\|?\s*:?-+:?\s*(\|\s*:?-+:?\s*)*\|?\s*$").unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    let mut line = match frontmatter::split(content) {
+        (Some(_), body) => content[..content.len() - body.len()].lines().count(),
+        (None, _) => 0,
+    };
+
+    let mut tables = Vec::new();
+    let mut in_code = false;
+    while line < lines.len() {
+        let trimmed = lines[line].trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            line += 1;
+            continue;
+        }
+
+        let is_table = !in_code
+            && lines[line].contains('|')
+            && line + 1 < lines.len()
+            && delimiter_re.is_match(lines[line + 1])
+            && split_row(lines[line]).len() == split_row(lines[line + 1]).len();
+        if !is_table {
+            line += 1;
+            continue;
+        }
+
+        let mut header = split_row(lines[line]);
+        let mut alignments: Vec<Alignment> = split_row(lines[line + 1]).iter().map(|cell| parse_alignment(cell)).collect();
+        let mut end = line + 2;
+        let mut rows = Vec::new();
+        while end < lines.len() && lines[end].contains('|') && !lines[end].trim().is_empty() {
+            rows.push(split_row(lines[end]));
+            end += 1;
+        }
+        // Short rows are padded; rows with extra cells widen the header, so no cell is lost on rewrite
+        let width = rows.iter().map(Vec::len).chain([header.len()]).max().unwrap_or_default();
+        header.resize(width, String::new());
+        alignments.resize(width, Alignment::None);
+        for row in &mut rows {
+            row.resize(width, String::new());
+        }
+
+        tables.push(Table {
+            start_line: line,
+            end_line: end,
+            header,
+            alignments,
+            rows,
+        });
+        line = end;
+    }
+    tables
+}
+
+// Renders a table as Markdown with padded columns.
+pub fn render_table(table: &Table) -> String {
+    let widths: Vec<usize> = (0..table.header.len())
+        .map(|column| {
+            std::iter::once(&table.header)
+                .chain(&table.rows)
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(3)
+        })
+        .collect();
+
+    let render_row = |row: &Vec<String>| {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+            .collect();
+        format!("| {} |", cells.join(" | "))
+    };
+
+    let delimiter: Vec<String> = table
+        .alignments
+        .iter()
+        .zip(&widths)
+        .map(|(alignment, width)| match alignment {
+            Alignment::None => "-".repeat(*width),
+            Alignment::Left => format!(":{}", "-".repeat(width - 1)),
+            Alignment::Right => format!("{}:", "-".repeat(width - 1)),
+            Alignment::Center => format!(":{}:", "-".repeat(width - 2)),
+        })
+        .collect();
+
+    let mut lines = vec![render_row(&table.header), format!("| {} |", delimiter.join(" | "))];
+    lines.extend(table.rows.iter().map(render_row));
+    lines.join("\n")
+}
+
+fn out_of_range() -> Error {
+    Message::new("table.cell_out_of_range", "Table row or column out of range").error(ErrorKind::InvalidInput)
+}
+
+// Applies an operation to a table in place.
+pub fn apply(table: &mut Table, op: &TableOp) -> io::Result<()> {
+    let columns = table.header.len();
+    match op {
+        TableOp::InsertRow { at } => {
+            if *at > table.rows.len() {
+                return Err(out_of_range());
+            }
+            table.rows.insert(*at, vec![String::new(); columns]);
+        }
+        TableOp::DeleteRow { index } => {
+            if *index >= table.rows.len() {
+                return Err(out_of_range());
+            }
+            table.rows.remove(*index);
+        }
+        TableOp::InsertColumn { at, header } => {
+            if *at > columns {
+                return Err(out_of_range());
+            }
+            table.header.insert(*at, header.clone());
+            table.alignments.insert(*at, Alignment::None);
+            for row in &mut table.rows {
+                row.insert(*at, String::new());
+            }
+        }
+        TableOp::DeleteColumn { index } => {
+            if *index >= columns || columns == 1 {
+                return Err(out_of_range());
+            }
+            table.header.remove(*index);
+            table.alignments.remove(*index);
+            for row in &mut table.rows {
+                row.remove(*index);
+            }
+        }
+        TableOp::Sort { column, descending } => {
+            if *column >= columns {
+                return Err(out_of_range());
+            }
+            let numeric = table.rows.iter().all(|row| row[*column].parse::<f64>().is_ok());
+            table.rows.sort_by(|a, b| {
+                let ordering = if numeric {
+                    let (x, y) = (a[*column].parse::<f64>().unwrap(), b[*column].parse::<f64>().unwrap());
+                    x.partial_cmp(&y).unwrap_or(Ordering::Equal)
+                } else {
+                    a[*column].to_lowercase().cmp(&b[*column].to_lowercase())
+                };
+                if *descending { ordering.reverse() } else { ordering }
+            });
+        }
+        TableOp::Align { column, alignment } => {
+            if *column >= columns {
+                return Err(out_of_range());
+            }
+            table.alignments[*column] = *alignment;
+        }
+    }
+    Ok(())
+}
+
+// Applies an operation to the n-th table of a note and returns the rewritten note.
+pub fn edit_table(content: &str, table_index: usize, op: &TableOp) -> io::Result<String> {
+    let mut table = find_tables(content)
+        .into_iter()
+        .nth(table_index)
+        .ok_or_else(|| Message::new("table.not_found", "Table does not exist").error(ErrorKind::NotFound))?;
+    apply(&mut table, op)?;
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut rewritten: Vec<String> = lines[..table.start_line].iter().map(|line| line.to_string()).collect();
+    rewritten.push(render_table(&table));
+    rewritten.extend(lines[table.end_line..].iter().map(|line| line.to_string()));
+
+    let mut result = rewritten.join("\n");
+    if content.ends_with('\n') {
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "Intro\n\n| Name | Qty |\n|:-----|----:|\n| pear | 10 |\n| Apple | 2 |\n\nOutro\n";
+
+    #[test]
+    fn test_find_tables() {
+        let tables = find_tables(NOTE);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].header, vec!["Name", "Qty"]);
+        assert_eq!(tables[0].alignments, vec![Alignment::Left, Alignment::Right]);
+        assert_eq!(tables[0].rows[1], vec!["Apple", "2"]);
+        assert_eq!((tables[0].start_line, tables[0].end_line), (2, 6));
+        assert!(find_tables("```\n| a | b |\n|---|---|\n```").is_empty());
+        assert_eq!(split_row(r"| a \| b | c |"), vec![r"a \| b", "c"]);
+
+        let ragged = find_tables("| a | b |\n|---|---|\n| 1 |\n| 1 | 2 | 3 |").remove(0);
+        assert_eq!(ragged.header, vec!["a", "b", ""]);
+        assert_eq!(ragged.alignments.len(), 3);
+        assert_eq!(ragged.rows, vec![vec!["1", "", ""], vec!["1", "2", "3"]]);
+    }
+
+    #[test]
+    fn test_sort_and_rewrite() {
+        let sorted = edit_table(NOTE, 0, &TableOp::Sort { column: 1, descending: false }).unwrap();
+        assert_eq!(
+            sorted,
+            "Intro\n\n| Name  | Qty |\n| :---- | --: |\n| Apple | 2   |\n| pear  | 10  |\n\nOutro\n"
+        );
+    }
+
+    #[test]
+    fn test_row_and_column_operations() {
+        let mut table = find_tables(NOTE).remove(0);
+        apply(&mut table, &TableOp::InsertColumn { at: 1, header: "Color".to_string() }).unwrap();
+        apply(&mut table, &TableOp::InsertRow { at: 2 }).unwrap();
+        apply(&mut table, &TableOp::Align { column: 1, alignment: Alignment::Center }).unwrap();
+        assert_eq!(table.header, vec!["Name", "Color", "Qty"]);
+        assert_eq!(table.rows.len(), 3);
+        assert_eq!(table.alignments[1], Alignment::Center);
+
+        apply(&mut table, &TableOp::DeleteColumn { index: 1 }).unwrap();
+        apply(&mut table, &TableOp::DeleteRow { index: 0 }).unwrap();
+        assert_eq!(table.rows, vec![vec!["Apple", "2"], vec!["", ""]]);
+        assert!(apply(&mut table, &TableOp::DeleteRow { index: 5 }).is_err());
+    }
+}